@@ -1,10 +1,7 @@
 use criterion::BenchmarkId;
 use criterion::{criterion_group, criterion_main, Criterion};
 
-use ode_integrate::concepts::errors::CalcError;
-use ode_integrate::concepts::ode_def::OdeDefinition;
-use ode_integrate::methods::helper_functions::*;
-use ode_integrate::solvers::fixed_step::*;
+use ode_integrate::*;
 
 #[macro_export]
 macro_rules! bench_array {
@@ -1,10 +1,9 @@
+#![allow(clippy::ptr_arg)]
+
 use criterion::BenchmarkId;
 use criterion::{criterion_group, criterion_main, Criterion};
 
-use ode_integrate::concepts::errors::CalcError;
-use ode_integrate::concepts::ode_def::*;
-use ode_integrate::methods::helper_functions::*;
-use ode_integrate::solvers::fixed_step::*;
+use ode_integrate::*;
 
 pub fn rhs_vec(y: &Vec<f64>, dy: &mut Vec<f64>, _t: &f64, p: &f64) -> Result<(), CalcError> {
     for (yi, dyi) in y.iter().zip(dy) {
@@ -23,7 +22,7 @@ fn bench_vec(size: u32, iter: u32, solver: FixedStepSolvers) {
 
     let ode_def = OdeDefinition {
         y0: y.clone(),
-        t0: t.clone(),
+        t0: t,
         func: &rhs_vec,
     };
 
@@ -0,0 +1,618 @@
+use crate::checkpoint::AdaptiveIntegration;
+use crate::concepts::*;
+use crate::input::InputSignal;
+use crate::methods::{
+    equidistributed_step, integrate_fixed_step_observed, solve_adaptive_step_observed, Direction,
+    FixedStep, Substeps,
+};
+use crate::solvers::{AdaptiveStepSolvers, FixedStepSolvers};
+
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display};
+use core::ops::Mul;
+
+use alloc::vec::Vec;
+
+/// # Stepping method of a [Solver]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SolverMethod {
+    /// Steps of fixed size
+    FixedStep(FixedStepSolvers),
+    /// Steps controlled by an error estimate
+    AdaptiveStep(AdaptiveStepSolvers),
+}
+
+/// # Division of the intervals between time points by fixed steppers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Substepping {
+    /// Steps of size `dt` followed by a shorter final step
+    Maximal,
+    /// The smallest number of equal steps which are not larger than `dt`
+    Equidistributed,
+}
+
+/// Calls an [Observer] which is shared by all solves of a [Solver]
+pub type SharedObserver<'s, I, F> = &'s RefCell<dyn Observer<I, F> + 's>;
+
+/// Advances an [AdaptiveIntegration] to the given time while calling the [Observer]
+type ObservedAdaptiveStep<'s, 'a, I, F, P, E> = &'s dyn Fn(
+    &mut AdaptiveIntegration<'a, I, F, P, E>,
+    &F,
+    &P,
+    &mut dyn Observer<I, F>,
+) -> Result<ObserverAction, SolvingError>;
+
+/// # Validated configuration of a solver
+/// Combines all settings of the time-series drivers and solves ODEs with a single entry point
+/// for fixed and adaptive methods. Construct it with [Solver::builder].
+/// Decreasing time points integrate the ODE backward in time.
+///
+/// An [Observer] and [TStops] can be attached which are used by every solve. Since the solve
+/// methods only borrow the solver, the observer is shared via a [RefCell].
+/// Both are skipped when serializing the solver.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let t_series = vec![0.0, 0.5, 1.0];
+///
+/// // Adaptive Bulirsch-Stoer with default tolerances
+/// let solver = Solver::builder().build().unwrap();
+/// let y = solver.solve(&[1.0], &t_series, &rhs, &2.0).unwrap().y;
+/// assert!((y[2][0] - (-2.0_f64).exp()).abs() < 1e-6);
+///
+/// // Rk4 with steps of at most 0.01
+/// let solver = Solver::builder()
+///     .fixed_step(FixedStepSolvers::Rk4)
+///     .dt(0.01)
+///     .build()
+///     .unwrap();
+/// let y = solver.solve(&[1.0], &t_series, &rhs, &2.0).unwrap().y;
+/// assert_eq!(y.len(), 3);
+///
+/// // Tolerances have no meaning for fixed steps
+/// let invalid = Solver::<f64>::builder()
+///     .fixed_step(FixedStepSolvers::Euler)
+///     .tolerances(Tolerances { rtol: 1e-6, atol: 1e-6 })
+///     .build();
+/// assert!(invalid.is_err());
+///
+/// // Count the steps of equal size which land on the stop time
+/// let n_steps = core::cell::RefCell::new(0);
+/// let observer = core::cell::RefCell::new(
+///     |_t: &f64, _y: &mut [f64; 1], _dt: &f64, _err: Option<&f64>| {
+///         *n_steps.borrow_mut() += 1;
+///         ObserverAction::Continue
+///     },
+/// );
+/// let tstops = TStops { times: vec![0.25], jump: None };
+/// let solver = Solver::builder()
+///     .fixed_step(FixedStepSolvers::Rk4)
+///     .dt(0.2)
+///     .substepping(Substepping::Equidistributed)
+///     .observer(&observer)
+///     .tstops(&tstops)
+///     .build()
+///     .unwrap();
+/// let solution = solver.solve(&[1.0], &t_series, &rhs, &2.0).unwrap();
+/// assert_eq!(*n_steps.borrow(), 7);
+/// assert!(solution.stopped.is_none());
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "F: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Solver<'s, F, I = (), P = ()> {
+    /// Stepping method
+    method: SolverMethod,
+    /// Maximal step size of fixed methods or initial step size of adaptive methods
+    dt: Option<F>,
+    /// Tolerances of adaptive methods
+    tolerances: Option<Tolerances<F>>,
+    /// Maximal number of attempted steps
+    max_steps: Option<usize>,
+    /// Division of the intervals between time points by fixed steppers
+    substepping: Substepping,
+    /// Called after every accepted step
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observer: Option<SharedObserver<'s, I, F>>,
+    /// Known discontinuities of the ODE
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tstops: Option<&'s TStops<'s, I, F, P>>,
+    /// Input signals whose breakpoints are added to the stop times
+    #[cfg_attr(feature = "serde", serde(skip))]
+    inputs: Vec<&'s InputSignal<F>>,
+}
+
+/// # Builder of a [Solver]
+/// Unset options take default values when [SolverBuilder::build] is called.
+#[derive(Clone)]
+pub struct SolverBuilder<'s, F, I = (), P = ()> {
+    /// Stepping method
+    method: Option<SolverMethod>,
+    /// Step size
+    dt: Option<F>,
+    /// Tolerances of adaptive methods
+    tolerances: Option<Tolerances<F>>,
+    /// Maximal number of attempted steps
+    max_steps: Option<usize>,
+    /// Division of the intervals between time points by fixed steppers
+    substepping: Option<Substepping>,
+    /// Called after every accepted step
+    observer: Option<SharedObserver<'s, I, F>>,
+    /// Known discontinuities of the ODE
+    tstops: Option<&'s TStops<'s, I, F, P>>,
+    /// Input signals whose breakpoints are added to the stop times
+    inputs: Vec<&'s InputSignal<F>>,
+}
+
+impl<'s, F, I, P> Debug for Solver<'s, F, I, P>
+where
+    F: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solver")
+            .field("method", &self.method)
+            .field("dt", &self.dt)
+            .field("tolerances", &self.tolerances)
+            .field("max_steps", &self.max_steps)
+            .field("substepping", &self.substepping)
+            .field("observer", &self.observer.is_some())
+            .field("tstops", &self.tstops.map(|tstops| &tstops.times))
+            .field("inputs", &self.inputs)
+            .finish()
+    }
+}
+
+impl<'s, F, I, P> Debug for SolverBuilder<'s, F, I, P>
+where
+    F: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverBuilder")
+            .field("method", &self.method)
+            .field("dt", &self.dt)
+            .field("tolerances", &self.tolerances)
+            .field("max_steps", &self.max_steps)
+            .field("substepping", &self.substepping)
+            .field("observer", &self.observer.is_some())
+            .field("tstops", &self.tstops.map(|tstops| &tstops.times))
+            .field("inputs", &self.inputs)
+            .finish()
+    }
+}
+
+impl<'s, F, I, P> Solver<'s, F, I, P> {
+    /// Start the configuration of a solver
+    pub fn builder() -> SolverBuilder<'s, F, I, P> {
+        SolverBuilder {
+            method: None,
+            dt: None,
+            tolerances: None,
+            max_steps: None,
+            substepping: None,
+            observer: None,
+            tstops: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Stepping method
+    pub fn method(&self) -> &SolverMethod {
+        &self.method
+    }
+
+    /// Maximal step size of fixed methods or initial step size of adaptive methods
+    pub fn dt(&self) -> Option<&F> {
+        self.dt.as_ref()
+    }
+
+    /// Tolerances of adaptive methods
+    pub fn tolerances(&self) -> Option<&Tolerances<F>> {
+        self.tolerances.as_ref()
+    }
+
+    /// Maximal number of attempted steps
+    pub fn max_steps(&self) -> Option<usize> {
+        self.max_steps
+    }
+
+    /// Division of the intervals between time points by fixed steppers
+    pub fn substepping(&self) -> Substepping {
+        self.substepping
+    }
+
+    /// Known discontinuities of the ODE
+    pub fn tstops(&self) -> Option<&'s TStops<'s, I, F, P>> {
+        self.tstops
+    }
+
+    /// Input signals whose breakpoints are added to the stop times
+    pub fn inputs(&self) -> &[&'s InputSignal<F>] {
+        &self.inputs
+    }
+
+    /// Copy of the settings without [Observer], [TStops] and input signals which can be used for
+    /// other types of states and parameters
+    pub(crate) fn without_hooks<'t, J, Q>(&self) -> Solver<'t, F, J, Q>
+    where
+        F: Clone,
+    {
+        Solver {
+            method: self.method.clone(),
+            dt: self.dt.clone(),
+            tolerances: self.tolerances.clone(),
+            max_steps: self.max_steps,
+            substepping: self.substepping,
+            observer: None,
+            tstops: None,
+            inputs: Vec::new(),
+        }
+    }
+}
+
+/// Tolerances of adaptive steppers if none were specified
+fn default_tolerances<F: RealFloatLikeType>() -> Tolerances<F> {
+    Tolerances {
+        rtol: F::from_f64(1e-6),
+        atol: F::from_f64(1e-6),
+    }
+}
+
+impl<'s, F, I, P> SolverBuilder<'s, F, I, P>
+where
+    F: RealFloatLikeType,
+{
+    /// Use a fixed stepper
+    pub fn fixed_step(mut self, solver_type: FixedStepSolvers) -> Self {
+        self.method = Some(SolverMethod::FixedStep(solver_type));
+        self
+    }
+
+    /// Use an adaptive stepper. This is the default with [AdaptiveStepSolvers::BulirschStoer].
+    pub fn adaptive_step(mut self, solver_type: AdaptiveStepSolvers) -> Self {
+        self.method = Some(SolverMethod::AdaptiveStep(solver_type));
+        self
+    }
+
+    /// For fixed steppers, the maximal step size. Without it, a single step is taken between two
+    /// time points. For adaptive steppers, the initial step size which defaults to the first
+    /// interval between time points.
+    pub fn dt(mut self, dt: F) -> Self {
+        self.dt = Some(dt);
+        self
+    }
+
+    /// Tolerances of adaptive steppers. Defaults to \\(10^{-6}\\) for both.
+    pub fn tolerances(mut self, tolerances: Tolerances<F>) -> Self {
+        self.tolerances = Some(tolerances);
+        self
+    }
+
+    /// Fail if more steps are needed including rejected ones
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Division of the intervals between time points by fixed steppers with a step size.
+    /// Defaults to [Substepping::Maximal].
+    pub fn substepping(mut self, substepping: Substepping) -> Self {
+        self.substepping = Some(substepping);
+        self
+    }
+
+    /// Call the observer after every accepted step. If it stops the integration, the solution
+    /// only contains the time points which were reached.
+    pub fn observer(mut self, observer: SharedObserver<'s, I, F>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Land exactly on the stop times, apply the state jump and restart the stepper there
+    pub fn tstops(mut self, tstops: &'s TStops<'s, I, F, P>) -> Self {
+        self.tstops = Some(tstops);
+        self
+    }
+
+    /// Land exactly on the breakpoints of an input signal which is queried by the [RHS] and
+    /// restart the stepper there. The state does not jump at these additional stop times.
+    /// Can be called repeatedly to add several signals.
+    pub fn input(mut self, signal: &'s InputSignal<F>) -> Self {
+        self.inputs.push(signal);
+        self
+    }
+
+    /// Check the combination of options and fill in defaults
+    pub fn build(self) -> Result<Solver<'s, F, I, P>, SolvingError> {
+        let method = self.method.unwrap_or(SolverMethod::AdaptiveStep(
+            AdaptiveStepSolvers::BulirschStoer,
+        ));
+        if self
+            .dt
+            .is_some_and(|dt| dt <= F::from(0) || !dt.is_finite())
+        {
+            return Err(SolvingError::from("Step size needs to be positive"));
+        }
+        if self.max_steps == Some(0) {
+            return Err(SolvingError::from(
+                "Maximal number of steps needs to be positive",
+            ));
+        }
+        let tolerances = match (&method, self.tolerances) {
+            (SolverMethod::FixedStep(_), Some(_)) => {
+                return Err(SolvingError::from(
+                    "Tolerances can only be used with adaptive methods",
+                ))
+            }
+            (SolverMethod::FixedStep(_), None) => None,
+            (SolverMethod::AdaptiveStep(_), Some(tolerances)) => {
+                let zero = F::from(0);
+                if tolerances.rtol < zero
+                    || tolerances.atol < zero
+                    || (tolerances.rtol == zero && tolerances.atol == zero)
+                {
+                    return Err(SolvingError::from(
+                        "Tolerances need to be non-negative and not both zero",
+                    ));
+                }
+                Some(tolerances)
+            }
+            (SolverMethod::AdaptiveStep(_), None) => Some(default_tolerances()),
+        };
+        let substepping = self.substepping.unwrap_or(Substepping::Maximal);
+        if substepping == Substepping::Equidistributed
+            && !(matches!(method, SolverMethod::FixedStep(_)) && self.dt.is_some())
+        {
+            return Err(SolvingError::from(
+                "Equidistributed substeps need a fixed stepper with a step size",
+            ));
+        }
+        Ok(Solver {
+            method,
+            dt: self.dt,
+            tolerances,
+            max_steps: self.max_steps,
+            substepping,
+            observer: self.observer,
+            tstops: self.tstops,
+            inputs: self.inputs,
+        })
+    }
+}
+
+impl<'s, F, I, P> Solver<'s, F, I, P>
+where
+    F: RealFloatLikeType,
+{
+    /// Collect the time points and determine the direction of integration.
+    /// Fails if they are neither increasing nor decreasing.
+    fn time_points<V>(t_series: &V) -> Result<(Vec<F>, Direction), SolvingError>
+    where
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+    {
+        let times: Vec<F> = t_series.into_iter().copied().collect();
+        if times.is_empty() {
+            return Err(SolvingError::from("Did not supply enough time steps."));
+        }
+        let direction = Direction::of_time_series(t_series)?;
+        Ok((times, direction))
+    }
+
+    /// Initial step size of adaptive steppers pointing in the direction of integration
+    fn initial_dt(&self, times: &[F], direction: Direction) -> F {
+        match self.dt {
+            Some(dt) => direction.orient(dt),
+            None => times
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .find(|dt| *dt != F::from(0))
+                .unwrap_or(direction.orient(F::from(1))),
+        }
+    }
+
+    /// Stop times of the tstops merged with the breakpoints of the input signals between the
+    /// first and last time point, ordered in the direction of integration
+    fn stop_times(&self, times: &[F], direction: Direction) -> Vec<F> {
+        let (t_first, t_last) = (times[0], times[times.len() - 1]);
+        let (t_start, t_end) = if direction.before(&t_last, &t_first) {
+            (t_last, t_first)
+        } else {
+            (t_first, t_last)
+        };
+        let mut stop_times: Vec<F> = self
+            .inputs
+            .iter()
+            .flat_map(|signal| signal.breakpoints(&t_start, &t_end))
+            .chain(
+                self.tstops
+                    .iter()
+                    .flat_map(|tstops| tstops.times.iter().copied()),
+            )
+            .collect();
+        stop_times.sort_by(
+            |a, b| match (direction.before(a, b), direction.before(b, a)) {
+                (true, _) => Ordering::Less,
+                (_, true) => Ordering::Greater,
+                _ => Ordering::Equal,
+            },
+        );
+        stop_times.dedup();
+        stop_times
+    }
+
+    /// Integrate with the observer, tstops, substepping and step limit of the solver.
+    /// Every configuration counts attempted steps including rejected ones against `max_steps`.
+    #[allow(clippy::too_many_arguments)]
+    fn solve_observed<'a, E>(
+        &self,
+        y0: &I,
+        times: &Vec<F>,
+        direction: Direction,
+        rhs: RHS<'a, I, F, P, E>,
+        p: &P,
+        fixed_step: FixedStep<I, F, P, E>,
+        adaptive_step: ObservedAdaptiveStep<'_, 'a, I, F, P, E>,
+    ) -> Result<ObservedSolution<I, F>, SolvingError>
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        I: Clone,
+        P: Clone,
+        E: Display + Clone,
+    {
+        let mut continue_always =
+            |_t: &F, _y: &mut I, _dt: &F, _err: Option<&F>| ObserverAction::Continue;
+        let mut shared = match self.observer {
+            Some(observer) => Some(
+                observer
+                    .try_borrow_mut()
+                    .map_err(|_| SolvingError::from("Observer is already in use"))?,
+            ),
+            None => None,
+        };
+        let observer: &mut dyn Observer<I, F> = match shared.as_mut() {
+            Some(observer) => &mut **observer,
+            None => &mut continue_always,
+        };
+        // The state only jumps at the stop times of the tstops but not at input breakpoints
+        let jump_at_tstops = |t: &F, y: &mut I, p: &P| {
+            if let Some(tstops) = self.tstops.filter(|tstops| tstops.times.contains(t)) {
+                if let Some(jump) = tstops.jump {
+                    jump(t, y, p);
+                }
+            }
+        };
+        let with_inputs;
+        let tstops = if self.inputs.is_empty() {
+            self.tstops
+        } else {
+            with_inputs = TStops {
+                times: self.stop_times(times, direction),
+                jump: Some(&jump_at_tstops),
+            };
+            Some(&with_inputs)
+        };
+        match (&self.method, &self.dt) {
+            (SolverMethod::FixedStep(solver_type), dt) => {
+                let substeps = match (dt, self.substepping) {
+                    (None, _) => Substeps::Single,
+                    (Some(dt), Substepping::Maximal) => Substeps::Maximal(dt),
+                    (Some(dt), Substepping::Equidistributed) => {
+                        Substeps::Equidistributed(dt, equidistributed_step)
+                    }
+                };
+                let mut y_res = Vec::new();
+                let stopped = integrate_fixed_step_observed::<I, F, P, E, Vec<F>>(
+                    y0,
+                    times,
+                    rhs,
+                    p,
+                    solver_type.clone(),
+                    substeps,
+                    Some(observer),
+                    tstops,
+                    self.max_steps,
+                    fixed_step,
+                    &mut |_t, y| {
+                        y_res.push(y.clone());
+                        Ok(())
+                    },
+                )?;
+                Ok(ObservedSolution { y: y_res, stopped })
+            }
+            (SolverMethod::AdaptiveStep(solver_type), _) => {
+                solve_adaptive_step_observed::<I, F, P, E, Vec<F>>(
+                    y0,
+                    times,
+                    rhs,
+                    p,
+                    solver_type.clone(),
+                    &self.initial_dt(times, direction),
+                    &self.tolerances.clone().unwrap_or_else(default_tolerances),
+                    tstops,
+                    self.max_steps,
+                    &mut |integration, t_j| adaptive_step(integration, t_j, p, observer),
+                )
+            }
+        }
+    }
+
+    /// Solve an ODE with an iterable type for the time points in `t_series`.
+    /// Returns the solution at every time point which was reached and where the observer
+    /// stopped the integration.
+    pub fn solve<'a, E, V>(
+        &self,
+        y0: &I,
+        t_series: &V,
+        rhs: RHS<'a, I, F, P, E>,
+        p: &P,
+    ) -> Result<ObservedSolution<I, F>, SolvingError>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        I: Clone + 'a,
+        F: 'a,
+        P: Clone + 'a,
+        E: Display + Clone + 'a,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+    {
+        let (times, direction) = Self::time_points(t_series)?;
+        self.solve_observed(
+            y0,
+            &times,
+            direction,
+            rhs,
+            p,
+            &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+            &|integration, t_j, p, observer| {
+                integration.integrate_to_observed_iter(t_j, p, observer)
+            },
+        )
+    }
+
+    /// Same as [Solver::solve] for a type which can be added via [MathVecLikeType] instead of
+    /// being mutably iterable. Since adaptive steppers estimate errors component-wise, `&I`
+    /// needs to be iterable.
+    pub fn solve_add<'a, E, V>(
+        &self,
+        y0: &I,
+        t_series: &V,
+        rhs: RHS<'a, I, F, P, E>,
+        p: &P,
+    ) -> Result<ObservedSolution<I, F>, SolvingError>
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        I: MathVecLikeType<F> + 'a,
+        F: Mul<I, Output = I> + 'a,
+        P: Clone + 'a,
+        E: Display + Clone + 'a,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+    {
+        let (times, direction) = Self::time_points(t_series)?;
+        self.solve_observed(
+            y0,
+            &times,
+            direction,
+            rhs,
+            p,
+            &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+            &|integration, t_j, p, observer| {
+                integration.integrate_to_observed_add(t_j, p, observer)
+            },
+        )
+    }
+}
@@ -0,0 +1,332 @@
+use crate::builder::Solver;
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+use crate::solvers::{gather, scatter};
+
+use core::fmt::Display;
+
+use alloc::{vec, vec::Vec};
+
+/// Maximal number of times the Newton step is halved before giving up
+const MAX_HALVINGS: usize = 20;
+
+/// # Residual of the boundary conditions
+/// Function \\(r(y(a), y(b), p)\\) which writes one residual per component of the state into the
+/// slice. The boundary conditions are satisfied when all residuals vanish.
+pub type BoundaryResidual<'a, I, F, P> = &'a dyn Fn(&I, &I, &P, &mut [F]);
+
+/// # Two-point boundary value problem
+/// The ODE \\(y' = f(y, t, p)\\) on the interval \\([a, b]\\) together with boundary conditions
+/// \\(r(y(a), y(b), p) = 0\\) which replace the initial values of an [OdeDefinition].
+/// Collocation with [solve_bvp_collocation_iter](crate::solve_bvp_collocation_iter) remains
+/// stable for boundary layers where shooting amplifies errors of the initial values.
+#[derive(Clone)]
+pub struct BoundaryValueProblem<'a, I, F, P, Err> {
+    /// Left end of the interval
+    pub a: F,
+    /// Right end of the interval
+    pub b: F,
+    /// Right-hand side function to determine the ODE
+    pub func: RHS<'a, I, F, P, Err>,
+    /// Residual of the boundary conditions
+    pub residual: BoundaryResidual<'a, I, F, P>,
+}
+
+/// # Settings of the shooting method
+/// Fixed-step solvers make the end values a smooth function of the initial values such that the
+/// finite-difference Jacobian of the Newton iteration is accurate.
+/// The solver can not carry an [Observer](crate::Observer) or [TStops](crate::TStops) since it
+/// integrates every shooting interval separately.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShootingSettings<F: 'static> {
+    /// Solver of the initial value problems on every shooting interval
+    pub solver: Solver<'static, F>,
+    /// Maximal number of Newton iterations
+    pub max_iterations: usize,
+    /// The iteration stops once the maximum norm of all residuals falls below this value
+    pub tolerance: F,
+}
+
+impl<F> ShootingSettings<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Default settings which integrate with the given solver
+    pub fn new(solver: Solver<'static, F>) -> Self {
+        ShootingSettings {
+            solver,
+            max_iterations: 50,
+            tolerance: F::from_f64(1e-10),
+        }
+    }
+}
+
+/// # Solution of a [BoundaryValueProblem]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BvpSolution<I, F> {
+    /// Shooting nodes followed by the right end of the interval
+    pub times: Vec<F>,
+    /// Solution at every time point
+    pub y: Vec<I>,
+    /// Maximum norm of the continuity and boundary residuals
+    pub residual: F,
+    /// Number of Newton iterations
+    pub iterations: usize,
+}
+
+/// # Solve a boundary value problem by single shooting
+/// Determines the initial values \\(y(a)\\) such that the solution of the initial value problem
+/// satisfies the boundary conditions at \\(b\\). Starting from `y_guess`, the initial values are
+/// improved by a damped Newton iteration whose Jacobian is approximated by finite differences.
+/// Every evaluation integrates the ODE from \\(a\\) to \\(b\\) with the [Solver] of the
+/// [ShootingSettings].
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // y'' = -y written as a system of first order
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[1];
+///     dy[1] = -y[0];
+///     Ok(())
+/// }
+///
+/// // y(0) = 0 and y(pi/2) = 1
+/// let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &(), r: &mut [f64]| {
+///     r[0] = ya[0];
+///     r[1] = yb[0] - 1.0;
+/// };
+/// let bvp = BoundaryValueProblem {
+///     a: 0.0,
+///     b: core::f64::consts::FRAC_PI_2,
+///     func: &rhs,
+///     residual: &residual,
+/// };
+/// let solver = Solver::builder()
+///     .tolerances(Tolerances { rtol: 1e-10, atol: 1e-10 })
+///     .build()
+///     .unwrap();
+/// let settings = ShootingSettings::new(solver);
+///
+/// // The solution is sin(t) with slope 1 at the left end
+/// let solution = solve_bvp_single_shooting_iter(&bvp, &[0.0, 0.0], &(), &settings).unwrap();
+/// assert!((solution.y[0][1] - 1.0).abs() < 1e-8);
+/// ```
+pub fn solve_bvp_single_shooting_iter<'a, I, F, P, E>(
+    bvp: &BoundaryValueProblem<'a, I, F, P, E>,
+    y_guess: &I,
+    p: &P,
+    settings: &ShootingSettings<F>,
+) -> Result<BvpSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: Clone + 'a,
+    E: Display + Clone + 'a,
+{
+    solve_bvp_shooting(bvp, &[bvp.a], core::slice::from_ref(y_guess), p, settings)
+}
+
+/// # Solve a boundary value problem by multiple shooting
+/// Splits \\([a, b]\\) into shooting intervals which start at the increasing `nodes`, the first
+/// of which needs to be \\(a\\). The ODE is integrated on every interval from the unknown value
+/// at its node, starting from the corresponding entry of `guesses`. Newton's method then
+/// determines all node values simultaneously such that the solution is continuous at the nodes
+/// and satisfies the boundary conditions.
+/// Compared to [solve_bvp_single_shooting_iter], the short intervals keep unstable ODEs from
+/// amplifying errors of the initial values and allow to supply a guess of the whole solution.
+pub fn solve_bvp_multiple_shooting_iter<'a, I, F, P, E>(
+    bvp: &BoundaryValueProblem<'a, I, F, P, E>,
+    nodes: &[F],
+    guesses: &[I],
+    p: &P,
+    settings: &ShootingSettings<F>,
+) -> Result<BvpSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: Clone + 'a,
+    E: Display + Clone + 'a,
+{
+    solve_bvp_shooting(bvp, nodes, guesses, p, settings)
+}
+
+/// Maximum norm of the residuals
+fn max_norm<F>(res: &[F]) -> F
+where
+    F: RealFloatLikeType,
+{
+    res.iter().fold(F::from(0), |acc, r| acc.max(r.abs()))
+}
+
+/// Shared implementation of single and multiple shooting
+fn solve_bvp_shooting<'a, I, F, P, E>(
+    bvp: &BoundaryValueProblem<'a, I, F, P, E>,
+    nodes: &[F],
+    guesses: &[I],
+    p: &P,
+    settings: &ShootingSettings<F>,
+) -> Result<BvpSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: Clone + 'a,
+    E: Display + Clone + 'a,
+{
+    if nodes.is_empty() || nodes.len() != guesses.len() {
+        return Err(SolvingError::from(
+            "Every shooting node needs exactly one guess",
+        ));
+    }
+    if nodes[0] != bvp.a {
+        return Err(SolvingError::from(
+            "The first shooting node needs to be the left end of the interval",
+        ));
+    }
+    let mut times = nodes.to_vec();
+    times.push(bvp.b);
+    if times.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(SolvingError::from(
+            "Shooting nodes need to be strictly increasing and before the right end of the interval",
+        ));
+    }
+    let m = nodes.len();
+    let template = guesses[0].clone();
+    let n = template.into_iter().count();
+    let mut x = Vec::with_capacity(m * n);
+    let mut values = Vec::new();
+    for guess in guesses {
+        gather(guess, &mut values);
+        if values.len() != n {
+            return Err(SolvingError::from(
+                "All guesses need to have the same number of components",
+            ));
+        }
+        x.extend_from_slice(&values);
+    }
+
+    let to_state = |values: &[F]| {
+        let mut y = template.clone();
+        scatter(values, &mut y);
+        y
+    };
+    // Solution at the end of the k-th shooting interval
+    let shoot = |k: usize, x: &[F]| -> Result<I, SolvingError> {
+        let y0 = to_state(&x[k * n..(k + 1) * n]);
+        let t_interval = vec![times[k], times[k + 1]];
+        let mut solution = settings.solver.without_hooks::<I, P>().solve::<E, Vec<F>>(
+            &y0,
+            &t_interval,
+            bvp.func,
+            p,
+        )?;
+        solution
+            .y
+            .pop()
+            .ok_or_else(|| SolvingError::from("Integration returned no solution"))
+    };
+    // Continuity residuals at the interior nodes followed by the boundary residuals
+    let residuals = |x: &[F], ends: &[I]| {
+        let mut res = vec![F::from(0); m * n];
+        for k in 0..m - 1 {
+            for (i, yi) in ends[k].into_iter().enumerate() {
+                res[k * n + i] = *yi - x[(k + 1) * n + i];
+            }
+        }
+        (bvp.residual)(&to_state(&x[..n]), &ends[m - 1], p, &mut res[(m - 1) * n..]);
+        res
+    };
+    let evaluate = |x: &[F]| -> Result<(Vec<I>, Vec<F>), SolvingError> {
+        let ends = (0..m)
+            .map(|k| shoot(k, x))
+            .collect::<Result<Vec<I>, SolvingError>>()?;
+        let res = residuals(x, &ends);
+        if !max_norm(&res).is_finite() {
+            return Err(SolvingError::from("Residuals are not finite"));
+        }
+        Ok((ends, res))
+    };
+    let sum_of_squares = |res: &[F]| res.iter().fold(F::from(0), |acc, r| acc + *r * *r);
+
+    let (mut ends, mut res) = evaluate(&x)?;
+    let mut iterations = 0;
+    let sqrt_eps = F::epsilon().sqrt();
+    while max_norm(&res) > settings.tolerance {
+        if iterations == settings.max_iterations {
+            return Err(SolvingError::from(
+                "Newton iteration of the shooting method did not converge",
+            ));
+        }
+        iterations += 1;
+
+        // Finite-difference Jacobian where a node value only changes its own interval
+        let dim = m * n;
+        let mut jac = vec![F::from(0); dim * dim];
+        let mut xp = x.clone();
+        let mut ends_p = ends.clone();
+        for c in 0..dim {
+            let k = c / n;
+            let delta = sqrt_eps * x[c].abs().max(F::from(1));
+            xp[c] = x[c] + delta;
+            ends_p[k] = shoot(k, &xp)?;
+            for (i, (rp, r)) in residuals(&xp, &ends_p).iter().zip(res.iter()).enumerate() {
+                jac[i * dim + c] = (*rp - *r) / delta;
+            }
+            xp[c] = x[c];
+            ends_p[k] = ends[k].clone();
+        }
+        let lu = LuDecomposition::new(jac, dim)
+            .ok_or_else(|| SolvingError::from("Jacobian of the shooting method is singular"))?;
+        let mut step: Vec<F> = res.iter().map(|r| -*r).collect();
+        lu.solve(&mut step);
+
+        // Halve the Newton step until the residuals decrease
+        let current = sum_of_squares(&res);
+        let mut lambda = F::from(1);
+        let mut accepted = None;
+        for _ in 0..MAX_HALVINGS {
+            let x_new: Vec<F> = x
+                .iter()
+                .zip(step.iter())
+                .map(|(xi, si)| *xi + lambda * *si)
+                .collect();
+            if let Ok((ends_new, res_new)) = evaluate(&x_new) {
+                if sum_of_squares(&res_new) < current {
+                    accepted = Some((x_new, ends_new, res_new));
+                    break;
+                }
+            }
+            lambda = lambda / F::from(2);
+        }
+        match accepted {
+            Some((x_new, ends_new, res_new)) => {
+                x = x_new;
+                ends = ends_new;
+                res = res_new;
+            }
+            None => {
+                return Err(SolvingError::from(
+                    "Damped Newton iteration of the shooting method made no progress",
+                ))
+            }
+        }
+    }
+
+    let mut y: Vec<I> = (0..m).map(|k| to_state(&x[k * n..(k + 1) * n])).collect();
+    y.push(ends[m - 1].clone());
+    Ok(BvpSolution {
+        times,
+        y,
+        residual: max_norm(&res),
+        iterations,
+    })
+}
@@ -0,0 +1,291 @@
+use crate::concepts::*;
+use crate::methods::{get_adaptive_step_stepper, next_step_size, step_accepted, Direction};
+use crate::solvers::{AdaptiveStepSolvers, AdaptiveStepperState};
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::boxed::Box;
+
+/// Performs a single step of an [AdaptiveStepper] with one of its methods
+type AdaptiveStep<'s, I, F, P, E> =
+    &'s dyn Fn(&mut dyn AdaptiveStepper<I, F, P, E>, &mut I, &F, &F, &P) -> Result<Option<F>, E>;
+
+/// # Snapshot of an adaptive integration
+/// Contains everything besides the RHS and parameters which is needed to continue an
+/// [AdaptiveIntegration]. The snapshot is plain data and can be stored in any way, for example
+/// by enabling the `serde` feature.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegrationCheckpoint<I, F> {
+    /// Current time of the integration
+    pub t: F,
+    /// Current value of the solution
+    pub y: I,
+    /// Step size which is attempted next
+    pub dt: F,
+    /// Stepper used for the integration
+    pub solver_type: AdaptiveStepSolvers,
+    /// Tolerances of the stepper
+    pub tolerances: Tolerances<F>,
+    /// Internal state of the stepper such as its order and step-size control
+    pub stepper_state: Option<AdaptiveStepperState<F>>,
+}
+
+/// # Adaptive integration which can be interrupted and resumed
+/// Integrates an ODE with an [AdaptiveStepper] from one output time to the next.
+/// At every output time a [IntegrationCheckpoint] can be taken. Resuming from it with
+/// [AdaptiveIntegration::resume] continues bit-identically to an uninterrupted run over the
+/// same output times.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition { y0: [1.0], t0: 0.0, func: &rhs };
+/// let tolerances = Tolerances { rtol: 1e-10, atol: 1e-10 };
+/// let solver_type = AdaptiveStepSolvers::BulirschStoer;
+///
+/// let mut integration = AdaptiveIntegration::new(ode_def, solver_type, 0.1, tolerances);
+/// integration.integrate_to_iter(&1.0, &0.5).unwrap();
+/// let checkpoint = integration.checkpoint();
+/// let y = *integration.integrate_to_iter(&2.0, &0.5).unwrap();
+///
+/// let mut resumed = AdaptiveIntegration::resume(&rhs, checkpoint).unwrap();
+/// assert_eq!(*resumed.integrate_to_iter(&2.0, &0.5).unwrap(), y);
+/// ```
+pub struct AdaptiveIntegration<'a, I, F, P, E> {
+    /// Stepper which performs the individual steps
+    stepper: Box<dyn AdaptiveStepper<I, F, P, E> + 'a>,
+    /// RHS of the ODE which is needed to restart the stepper
+    rhs: RHS<'a, I, F, P, E>,
+    /// Type of the stepper
+    solver_type: AdaptiveStepSolvers,
+    /// Tolerances of the stepper
+    tolerances: Tolerances<F>,
+    /// Current time
+    t: F,
+    /// Current value of the solution
+    y: I,
+    /// Step size which is attempted next
+    dt: F,
+    /// Maximal number of attempted steps
+    max_steps: Option<usize>,
+    /// Number of steps attempted so far including rejected ones
+    n_attempted: usize,
+}
+
+impl<'a, I, F, P, E> AdaptiveIntegration<'a, I, F, P, E>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+{
+    /// Start a new integration at the initial values of the ODE with initial step size `dt`
+    pub fn new(
+        ode_def: OdeDefinition<'a, I, F, P, E>,
+        solver_type: AdaptiveStepSolvers,
+        dt: F,
+        tolerances: Tolerances<F>,
+    ) -> Self {
+        let t = ode_def.t0;
+        let y = ode_def.y0.clone();
+        AdaptiveIntegration {
+            rhs: ode_def.func,
+            stepper: get_adaptive_step_stepper(solver_type.clone(), ode_def, tolerances.clone()),
+            solver_type,
+            tolerances,
+            t,
+            y,
+            dt,
+            max_steps: None,
+            n_attempted: 0,
+        }
+    }
+
+    /// Fail once more than `max_steps` steps were attempted including rejected ones
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Continue an integration from a checkpoint
+    pub fn resume(
+        rhs: RHS<'a, I, F, P, E>,
+        checkpoint: IntegrationCheckpoint<I, F>,
+    ) -> Result<Self, SolvingError> {
+        let ode_def = OdeDefinition {
+            y0: checkpoint.y,
+            t0: checkpoint.t,
+            func: rhs,
+        };
+        let mut integration = Self::new(
+            ode_def,
+            checkpoint.solver_type,
+            checkpoint.dt,
+            checkpoint.tolerances,
+        );
+        if let Some(state) = checkpoint.stepper_state {
+            integration.stepper.set_state(state)?;
+        }
+        Ok(integration)
+    }
+
+    /// Take a snapshot of the current state of the integration
+    pub fn checkpoint(&self) -> IntegrationCheckpoint<I, F> {
+        IntegrationCheckpoint {
+            t: self.t,
+            y: self.y.clone(),
+            dt: self.dt,
+            solver_type: self.solver_type.clone(),
+            tolerances: self.tolerances.clone(),
+            stepper_state: self.stepper.get_state(),
+        }
+    }
+
+    /// Current time of the integration
+    pub fn t(&self) -> &F {
+        &self.t
+    }
+
+    /// Current value of the solution
+    pub fn y(&self) -> &I {
+        &self.y
+    }
+
+    /// Step size which is attempted next
+    pub fn dt(&self) -> &F {
+        &self.dt
+    }
+
+    /// Continue from the state `y` at the current time with a fresh stepper.
+    /// The internal state of the previous stepper such as its order and step-size control is
+    /// discarded while the step size which is attempted next is kept.
+    pub fn restart(&mut self, y: I) {
+        let ode_def = OdeDefinition {
+            y0: y.clone(),
+            t0: self.t,
+            func: self.rhs,
+        };
+        self.stepper =
+            get_adaptive_step_stepper(self.solver_type.clone(), ode_def, self.tolerances.clone());
+        self.y = y;
+    }
+
+    /// Advance until `t_end` is reached exactly or the observer terminates the integration.
+    /// If `t_end` lies before the current time, the integration proceeds backward in time.
+    /// `do_step` performs a single adaptive step.
+    fn integrate_to_generic(
+        &mut self,
+        t_end: &F,
+        p: &P,
+        mut observer: Option<&mut dyn Observer<I, F>>,
+        do_step: AdaptiveStep<I, F, P, E>,
+    ) -> Result<ObserverAction, SolvingError>
+    where
+        E: Display,
+    {
+        let direction = Direction::between(&self.t, t_end);
+        self.dt = direction.orient(self.dt);
+        while direction.before(&self.t, t_end) {
+            if self
+                .max_steps
+                .is_some_and(|max_steps| self.n_attempted >= max_steps)
+            {
+                return Err(SolvingError::from("Maximal number of steps exceeded"));
+            }
+            self.n_attempted += 1;
+            let (dtau, last) = if !direction.before(&self.dt, &(*t_end - self.t)) {
+                (*t_end - self.t, true)
+            } else {
+                (self.dt, false)
+            };
+            let err = match do_step(self.stepper.as_mut(), &mut self.y, &self.t, &dtau, p) {
+                Ok(err) => err,
+                Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
+            };
+            let accepted = step_accepted(err);
+            if accepted {
+                self.t = if last { *t_end } else { self.t + dtau };
+            }
+            self.dt = next_step_size(self.stepper.suggested_dt(), self.dt, accepted, &self.t)?;
+            if let (true, Some(observer)) = (accepted, observer.as_mut()) {
+                if observer.observe(&self.t, &mut self.y, &dtau, err.as_ref())
+                    == ObserverAction::Stop
+                {
+                    return Ok(ObserverAction::Stop);
+                }
+            }
+        }
+        Ok(ObserverAction::Continue)
+    }
+
+    /// Integrate an iterable type up to `t_end` and return the solution at this time
+    pub fn integrate_to_iter(&mut self, t_end: &F, p: &P) -> Result<&I, SolvingError>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        E: Display,
+    {
+        self.integrate_to_generic(t_end, p, None, &|stepper, y, t, dt, p| {
+            stepper.do_step_iter(y, t, dt, p)
+        })?;
+        Ok(&self.y)
+    }
+
+    /// Integrate a type which can be added via [MathVecLikeType] up to `t_end`
+    /// and return the solution at this time
+    pub fn integrate_to_add(&mut self, t_end: &F, p: &P) -> Result<&I, SolvingError>
+    where
+        I: MathVecLikeType<F>,
+        F: Mul<I, Output = I>,
+        E: Display,
+    {
+        self.integrate_to_generic(t_end, p, None, &|stepper, y, t, dt, p| {
+            stepper.do_step_add(y, t, dt, p)
+        })?;
+        Ok(&self.y)
+    }
+
+    /// Equivalent to [AdaptiveIntegration::integrate_to_iter] but calls the [Observer] after
+    /// every accepted step. If the observer returns [ObserverAction::Stop], the integration
+    /// ends before `t_end` and the current time and state describe the point of termination.
+    pub fn integrate_to_observed_iter(
+        &mut self,
+        t_end: &F,
+        p: &P,
+        observer: &mut dyn Observer<I, F>,
+    ) -> Result<ObserverAction, SolvingError>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        E: Display,
+    {
+        self.integrate_to_generic(t_end, p, Some(observer), &|stepper, y, t, dt, p| {
+            stepper.do_step_iter(y, t, dt, p)
+        })
+    }
+
+    /// Equivalent to [AdaptiveIntegration::integrate_to_add] but calls the [Observer] after
+    /// every accepted step.
+    pub fn integrate_to_observed_add(
+        &mut self,
+        t_end: &F,
+        p: &P,
+        observer: &mut dyn Observer<I, F>,
+    ) -> Result<ObserverAction, SolvingError>
+    where
+        I: MathVecLikeType<F>,
+        F: Mul<I, Output = I>,
+        E: Display,
+    {
+        self.integrate_to_generic(t_end, p, Some(observer), &|stepper, y, t, dt, p| {
+            stepper.do_step_add(y, t, dt, p)
+        })
+    }
+}
@@ -0,0 +1,623 @@
+use crate::bvp::BoundaryValueProblem;
+use crate::concepts::*;
+use crate::linalg::BandedLuDecomposition;
+use crate::solvers::{gather, newton_update_norm, scatter};
+
+use core::fmt::Display;
+
+use alloc::{vec, vec::Vec};
+
+/// Maximal number of times the Newton step is halved before giving up
+const MAX_HALVINGS: usize = 20;
+
+/// # Settings of the collocation method
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollocationSettings<F> {
+    /// Tolerance of the root-mean-square relative residual of the continuous solution on every
+    /// interval of the mesh
+    pub tolerance: F,
+    /// Maximal number of mesh nodes
+    pub max_nodes: usize,
+    /// Maximal number of Newton iterations on every mesh
+    pub max_iterations: usize,
+}
+
+impl<F> CollocationSettings<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Default settings for the given tolerance of the residual
+    pub fn new(tolerance: F) -> Self {
+        CollocationSettings {
+            tolerance,
+            max_nodes: 1000,
+            max_iterations: 50,
+        }
+    }
+}
+
+/// # Continuous solution of a [BoundaryValueProblem] obtained by collocation
+/// Between two nodes of the final mesh, the solution is the cubic Hermite polynomial matching
+/// the values and derivatives at both nodes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollocationSolution<I, F, P> {
+    /// Nodes of the final mesh
+    pub times: Vec<F>,
+    /// Solution at every node
+    pub y: Vec<I>,
+    /// Derivative of the solution at every node
+    pub dy: Vec<I>,
+    /// Unknown parameters
+    pub p: P,
+    /// Root-mean-square relative residual of the continuous solution on every interval
+    pub residuals: Vec<F>,
+    /// Total number of Newton iterations on all meshes
+    pub iterations: usize,
+}
+
+/// Coefficients of the cubic Hermite polynomial and its derivative at the relative position
+/// `s` within an interval of length `h`. The values and derivatives at both ends are weighted
+/// in the order \\(y_i, f_i, y_{i+1}, f_{i+1}\\).
+fn hermite_weights<F>(s: F, h: F) -> ([F; 4], [F; 4])
+where
+    F: RealFloatLikeType,
+{
+    let (one, two, three) = (F::from(1), F::from(2), F::from(3));
+    let s2 = s * s;
+    let s3 = s2 * s;
+    let value = [
+        two * s3 - three * s2 + one,
+        h * (s3 - two * s2 + s),
+        three * s2 - two * s3,
+        h * (s3 - s2),
+    ];
+    let derivative = [
+        F::from(6) * (s2 - s) / h,
+        three * s2 - F::from(4) * s + one,
+        F::from(6) * (s - s2) / h,
+        three * s2 - two * s,
+    ];
+    (value, derivative)
+}
+
+/// Combine the values and derivatives at both ends of an interval with the given weights
+fn hermite<F>(w: &[F; 4], yi: &[F], fi: &[F], yj: &[F], fj: &[F]) -> Vec<F>
+where
+    F: RealFloatLikeType,
+{
+    (0..yi.len())
+        .map(|l| w[0] * yi[l] + w[1] * fi[l] + w[2] * yj[l] + w[3] * fj[l])
+        .collect()
+}
+
+impl<I, F, P> CollocationSolution<I, F, P>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    /// Value or derivative of the continuous solution at `t`.
+    /// Outside of the mesh, the polynomial of the first or last interval is extrapolated.
+    fn interpolate(&self, t: &F, derivative: bool) -> I {
+        let last = self.times.len() - 2;
+        let i = self
+            .times
+            .partition_point(|ti| ti <= t)
+            .saturating_sub(1)
+            .min(last);
+        let h = self.times[i + 1] - self.times[i];
+        let (value, slope) = hermite_weights((*t - self.times[i]) / h, h);
+        let w = if derivative { slope } else { value };
+        let (mut yi, mut fi, mut yj, mut fj) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        gather(&self.y[i], &mut yi);
+        gather(&self.dy[i], &mut fi);
+        gather(&self.y[i + 1], &mut yj);
+        gather(&self.dy[i + 1], &mut fj);
+        let mut y = self.y[i].clone();
+        scatter(&hermite(&w, &yi, &fi, &yj, &fj), &mut y);
+        y
+    }
+
+    /// Value of the continuous solution at time `t`
+    pub fn value(&self, t: &F) -> I {
+        self.interpolate(t, false)
+    }
+
+    /// Derivative of the continuous solution at time `t`
+    pub fn derivative(&self, t: &F) -> I {
+        self.interpolate(t, true)
+    }
+}
+
+/// # Discretization of a [BoundaryValueProblem] by collocation
+/// The unknowns at node \\(i\\) are \\(z_i = (y_i, p_i, c_i)\\) where \\(p_i\\) are copies of
+/// the unknown parameters and \\(c_i\\) copies of \\(y(a)\\). With the additional equations
+/// \\(p_{i+1} = p_i\\), \\(c_{i+1} = c_i\\) and \\(c_0 = y_0\\), the boundary conditions only
+/// involve the last node and the Jacobian is banded.
+struct Collocation<'s, 'a, I, F, P, E> {
+    /// Problem which is discretized
+    bvp: &'s BoundaryValueProblem<'a, I, F, P, E>,
+    /// Number of components of the state
+    n: usize,
+    /// Number of unknown parameters
+    k: usize,
+    /// Storage for the state at which the RHS is evaluated
+    y: I,
+    /// Storage for the evaluated RHS
+    dy: I,
+    /// Storage for the parameters with which the RHS is evaluated
+    p: P,
+}
+
+impl<I, F, P, E> Collocation<'_, '_, I, F, P, E>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display,
+{
+    /// Number of unknowns per node
+    fn m(&self) -> usize {
+        2 * self.n + self.k
+    }
+
+    /// Evaluate the RHS at the state `y` and parameters `p`
+    fn rhs(&mut self, y: &[F], t: &F, p: &[F]) -> Result<Vec<F>, SolvingError> {
+        scatter(y, &mut self.y);
+        scatter(p, &mut self.p);
+        (self.bvp.func)(&self.y, &mut self.dy, t, &self.p)
+            .map_err(|error| SolvingError::from(alloc::format!("{error}")))?;
+        let mut f = Vec::with_capacity(self.n);
+        gather(&self.dy, &mut f);
+        Ok(f)
+    }
+
+    /// RHS at a node with the parameters stored at this node
+    fn node_rhs(&mut self, z: &[F], t: &F) -> Result<Vec<F>, SolvingError> {
+        let (n, k) = (self.n, self.k);
+        self.rhs(&z[..n], t, &z[n..n + k])
+    }
+
+    /// Residuals of the interval from `ti` to `tj` with the unknowns `zi`, `zj` and
+    /// derivatives `fi`, `fj` at its nodes. The collocation condition at the midpoint is
+    /// equivalent to Simpson's rule for the cubic Hermite polynomial.
+    #[allow(clippy::too_many_arguments)]
+    fn interval_residual(
+        &mut self,
+        ti: &F,
+        tj: &F,
+        zi: &[F],
+        zj: &[F],
+        fi: &[F],
+        fj: &[F],
+        res: &mut [F],
+    ) -> Result<(), SolvingError> {
+        let (n, k) = (self.n, self.k);
+        let h = *tj - *ti;
+        let (w, _) = hermite_weights(F::from(1) / F::from(2), h);
+        let y_mid = hermite(&w, &zi[..n], fi, &zj[..n], fj);
+        let f_mid = self.rhs(&y_mid, &(*ti + h / F::from(2)), &zi[n..n + k])?;
+        for l in 0..n {
+            res[l] = zj[l] - zi[l] - h / F::from(6) * (fi[l] + F::from(4) * f_mid[l] + fj[l]);
+        }
+        for l in n..self.m() {
+            res[l] = zj[l] - zi[l];
+        }
+        Ok(())
+    }
+
+    /// Boundary conditions with \\(c_0\\) and \\(y_{N-1}\\) stored in the last node `z`
+    fn right_residual(&mut self, z: &[F], res: &mut [F]) {
+        let (n, k) = (self.n, self.k);
+        let mut ya = self.y.clone();
+        scatter(&z[n + k..], &mut ya);
+        scatter(&z[..n], &mut self.y);
+        scatter(&z[n..n + k], &mut self.p);
+        (self.bvp.residual)(&ya, &self.y, &self.p, res);
+    }
+
+    /// All residuals for the unknowns `z` on the mesh `times` where `fs` holds the derivatives
+    /// at all nodes
+    fn residuals(&mut self, times: &[F], z: &[F], fs: &[Vec<F>]) -> Result<Vec<F>, SolvingError> {
+        let (n, m) = (self.n, self.m());
+        let nodes = times.len();
+        let mut res = vec![F::from(0); nodes * m];
+        for l in 0..n {
+            res[l] = z[n + self.k + l] - z[l];
+        }
+        for i in 0..nodes - 1 {
+            let (zi, zj) = (&z[i * m..(i + 1) * m], &z[(i + 1) * m..(i + 2) * m]);
+            let rows = n + i * m;
+            self.interval_residual(
+                &times[i],
+                &times[i + 1],
+                zi,
+                zj,
+                &fs[i],
+                &fs[i + 1],
+                &mut res[rows..rows + m],
+            )?;
+        }
+        let rows = n + (nodes - 1) * m;
+        self.right_residual(&z[(nodes - 1) * m..], &mut res[rows..]);
+        if !res.iter().all(|r| r.is_finite()) {
+            return Err(SolvingError::from("Residuals are not finite"));
+        }
+        Ok(res)
+    }
+
+    /// Derivatives at all nodes
+    fn node_derivatives(&mut self, times: &[F], z: &[F]) -> Result<Vec<Vec<F>>, SolvingError> {
+        let m = self.m();
+        times
+            .iter()
+            .enumerate()
+            .map(|(i, t)| self.node_rhs(&z[i * m..(i + 1) * m], t))
+            .collect()
+    }
+
+    /// Banded Jacobian of the residuals approximated by finite differences.
+    /// A perturbation of a node only changes the residuals of the adjacent intervals.
+    fn jacobian(
+        &mut self,
+        times: &[F],
+        z: &[F],
+        fs: &[Vec<F>],
+        res: &[F],
+    ) -> Result<BandedLuDecomposition<F>, SolvingError> {
+        let (n, k, m) = (self.n, self.k, self.m());
+        let nodes = times.len();
+        let dim = nodes * m;
+        let mut jac = BandedLuDecomposition::zeros(dim, n + m - 1, 2 * m - 1 - n);
+        let sqrt_eps = F::epsilon().sqrt();
+        let mut zp = z.to_vec();
+        let mut res_p = vec![F::from(0); m];
+        for i in 0..nodes {
+            for c in 0..m {
+                let col = i * m + c;
+                let delta = sqrt_eps * z[col].abs().max(F::from(1));
+                zp[col] = z[col] + delta;
+                let zi = &zp[i * m..(i + 1) * m];
+                let fi = if c < n + k {
+                    self.node_rhs(zi, &times[i])?
+                } else {
+                    fs[i].clone()
+                };
+                let set_rows = |jac: &mut BandedLuDecomposition<F>, rows: usize, values: &[F]| {
+                    for (l, value) in values.iter().enumerate() {
+                        jac.set(rows + l, col, (*value - res[rows + l]) / delta);
+                    }
+                };
+                if i == 0 {
+                    let left: Vec<F> = (0..n).map(|l| zi[n + k + l] - zi[l]).collect();
+                    set_rows(&mut jac, 0, &left);
+                }
+                if i > 0 {
+                    let zh = &zp[(i - 1) * m..i * m];
+                    self.interval_residual(
+                        &times[i - 1],
+                        &times[i],
+                        zh,
+                        zi,
+                        &fs[i - 1],
+                        &fi,
+                        &mut res_p,
+                    )?;
+                    set_rows(&mut jac, n + (i - 1) * m, &res_p);
+                }
+                if i < nodes - 1 {
+                    let zj = &zp[(i + 1) * m..(i + 2) * m];
+                    self.interval_residual(
+                        &times[i],
+                        &times[i + 1],
+                        zi,
+                        zj,
+                        &fi,
+                        &fs[i + 1],
+                        &mut res_p,
+                    )?;
+                    set_rows(&mut jac, n + i * m, &res_p);
+                } else {
+                    let mut right = vec![F::from(0); n + k];
+                    self.right_residual(zi, &mut right);
+                    set_rows(&mut jac, n + i * m, &right);
+                }
+                zp[col] = z[col];
+            }
+        }
+        jac.factorize()
+            .ok_or_else(|| SolvingError::from("Jacobian of the collocation equations is singular"))
+    }
+
+    /// Solve the collocation equations on the mesh `times` by a damped Newton iteration
+    /// starting from `z`. Returns the number of iterations.
+    fn solve(
+        &mut self,
+        times: &[F],
+        z: &mut Vec<F>,
+        settings: &CollocationSettings<F>,
+    ) -> Result<usize, SolvingError> {
+        let tol = F::from_f64(1e-3) * settings.tolerance;
+        let sum_of_squares = |res: &[F]| res.iter().fold(F::from(0), |acc, r| acc + *r * *r);
+        let mut fs = self.node_derivatives(times, z)?;
+        let mut res = self.residuals(times, z, &fs)?;
+        for iteration in 1..=settings.max_iterations {
+            let lu = self.jacobian(times, z, &fs, &res)?;
+            let mut step: Vec<F> = res.iter().map(|r| -*r).collect();
+            lu.solve(&mut step);
+            let converged = newton_update_norm(&step, z) <= tol;
+
+            // Halve the Newton step until the residuals decrease
+            let current = sum_of_squares(&res);
+            let mut lambda = F::from(1);
+            let mut accepted = None;
+            for _ in 0..MAX_HALVINGS {
+                let z_new: Vec<F> = z
+                    .iter()
+                    .zip(step.iter())
+                    .map(|(zi, si)| *zi + lambda * *si)
+                    .collect();
+                let trial = self.node_derivatives(times, &z_new).and_then(|fs_new| {
+                    let res_new = self.residuals(times, &z_new, &fs_new)?;
+                    Ok((fs_new, res_new))
+                });
+                if let Ok((fs_new, res_new)) = trial {
+                    if converged || sum_of_squares(&res_new) < current {
+                        accepted = Some((z_new, fs_new, res_new));
+                        break;
+                    }
+                }
+                lambda = lambda / F::from(2);
+            }
+            match accepted {
+                Some((z_new, fs_new, res_new)) => {
+                    *z = z_new;
+                    fs = fs_new;
+                    res = res_new;
+                }
+                None => {
+                    return Err(SolvingError::from(
+                        "Damped Newton iteration of the collocation method made no progress",
+                    ))
+                }
+            }
+            if converged {
+                return Ok(iteration);
+            }
+        }
+        Err(SolvingError::from(
+            "Newton iteration of the collocation method did not converge",
+        ))
+    }
+
+    /// Root-mean-square relative residual of the continuous solution on every interval,
+    /// approximated by Lobatto quadrature. The residual vanishes at the nodes.
+    fn interval_residuals(
+        &mut self,
+        times: &[F],
+        z: &[F],
+        fs: &[Vec<F>],
+    ) -> Result<Vec<F>, SolvingError> {
+        let (n, k, m) = (self.n, self.k, self.m());
+        let half = F::from(1) / F::from(2);
+        let offset = (F::from(3) / F::from(7)).sqrt() / F::from(2);
+        let points = [half - offset, half, half + offset];
+        let weights = [
+            F::from(49) / F::from(90),
+            F::from(32) / F::from(45),
+            F::from(49) / F::from(90),
+        ];
+        let mut residuals = Vec::with_capacity(times.len() - 1);
+        for i in 0..times.len() - 1 {
+            let h = times[i + 1] - times[i];
+            let (zi, zj) = (&z[i * m..(i + 1) * m], &z[(i + 1) * m..(i + 2) * m]);
+            let mut sum = F::from(0);
+            for (s, weight) in points.iter().zip(weights.iter()) {
+                let (w, dw) = hermite_weights(*s, h);
+                let y = hermite(&w, &zi[..n], &fs[i], &zj[..n], &fs[i + 1]);
+                let dy = hermite(&dw, &zi[..n], &fs[i], &zj[..n], &fs[i + 1]);
+                let f = self.rhs(&y, &(times[i] + *s * h), &zi[n..n + k])?;
+                for l in 0..n {
+                    let r = (dy[l] - f[l]) / (F::from(1) + f[l].abs());
+                    sum += *weight * r * r;
+                }
+            }
+            residuals.push((half * sum).sqrt());
+        }
+        Ok(residuals)
+    }
+}
+
+/// Insert nodes into all intervals whose residual exceeds the tolerance and interpolate the
+/// unknowns at the new nodes. Intervals with large residuals are split into three parts,
+/// all others into two.
+fn refine_mesh<F>(
+    times: &[F],
+    z: &[F],
+    fs: &[Vec<F>],
+    residuals: &[F],
+    m: usize,
+    n: usize,
+    tolerance: F,
+) -> (Vec<F>, Vec<F>)
+where
+    F: RealFloatLikeType,
+{
+    let mut new_times = vec![times[0]];
+    let mut new_z = z[..m].to_vec();
+    for (i, residual) in residuals.iter().enumerate() {
+        let parts = if *residual <= tolerance {
+            1
+        } else if *residual < F::from(100) * tolerance {
+            2
+        } else {
+            3
+        };
+        let h = times[i + 1] - times[i];
+        let (zi, zj) = (&z[i * m..(i + 1) * m], &z[(i + 1) * m..(i + 2) * m]);
+        for part in 1..parts {
+            let s = F::from_usize(part) / F::from_usize(parts);
+            let (w, _) = hermite_weights(s, h);
+            new_times.push(times[i] + s * h);
+            new_z.extend(hermite(&w, &zi[..n], &fs[i], &zj[..n], &fs[i + 1]));
+            new_z.extend_from_slice(&zi[n..]);
+        }
+        new_times.push(times[i + 1]);
+        new_z.extend_from_slice(zj);
+    }
+    (new_times, new_z)
+}
+
+/// # Solve a boundary value problem by collocation
+/// Approximates the solution by a continuously differentiable piecewise cubic polynomial which
+/// satisfies the ODE at the nodes and midpoints of a mesh, comparable to the method of
+/// `scipy.integrate.solve_bvp`. All nodes are coupled in one system of equations which is solved
+/// by a damped Newton iteration with a banded finite-difference Jacobian. In contrast to
+/// shooting, no initial value problem is integrated over long intervals, which keeps the method
+/// stable for boundary layers and other rapidly growing solutions.
+///
+/// After every solve, the root-mean-square residual of the continuous solution relative to
+/// \\(1 + |f|\\) is estimated on every interval. Intervals exceeding the tolerance are split,
+/// and the problem is solved again on the refined mesh until all residuals are within the
+/// tolerance or [CollocationSettings::max_nodes] would be exceeded.
+///
+/// The initial `mesh` needs to start at \\(a\\), end at \\(b\\) and be strictly increasing, and
+/// `guesses` contains one guess of the solution for every node.
+/// The parameters `p` of the RHS are treated as unknowns which are determined together with the
+/// solution, starting from `p_guess`. For \\(k\\) unknown parameters, the boundary residual
+/// needs to write \\(n + k\\) values. Problems without unknown parameters use an empty array
+/// such as `[f64; 0]` and pass known parameters by capturing them in the RHS.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Boundary layer at the left end: eps y'' = y with y(0) = 1 and y(1) = 0
+/// let eps = 1e-4;
+/// let rhs = |y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &[f64; 0]| -> Result<(), CalcError> {
+///     dy[0] = y[1];
+///     dy[1] = y[0] / eps;
+///     Ok(())
+/// };
+/// let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &[f64; 0], r: &mut [f64]| {
+///     r[0] = ya[0] - 1.0;
+///     r[1] = yb[0];
+/// };
+/// let bvp = BoundaryValueProblem { a: 0.0, b: 1.0, func: &rhs, residual: &residual };
+///
+/// let mesh: Vec<f64> = (0..=10).map(|i| 0.1 * i as f64).collect();
+/// let guesses = vec![[0.0, 0.0]; mesh.len()];
+/// let settings = CollocationSettings::new(1e-6);
+/// let solution = solve_bvp_collocation_iter(&bvp, &mesh, &guesses, &[], &settings).unwrap();
+///
+/// // The solution decays like exp(-t / sqrt(eps)) within the layer
+/// let y = solution.value(&0.02);
+/// assert!((y[0] - (-2.0_f64).exp()).abs() < 1e-4);
+/// assert!(solution.times.len() > mesh.len());
+/// ```
+pub fn solve_bvp_collocation_iter<'a, I, F, P, E>(
+    bvp: &BoundaryValueProblem<'a, I, F, P, E>,
+    mesh: &[F],
+    guesses: &[I],
+    p_guess: &P,
+    settings: &CollocationSettings<F>,
+) -> Result<CollocationSolution<I, F, P>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display,
+{
+    if mesh.len() < 2 || mesh.len() != guesses.len() {
+        return Err(SolvingError::from(
+            "The mesh needs at least two nodes with one guess for every node",
+        ));
+    }
+    if mesh[0] != bvp.a || mesh[mesh.len() - 1] != bvp.b {
+        return Err(SolvingError::from(
+            "The mesh needs to start and end at the boundaries of the interval",
+        ));
+    }
+    if mesh.windows(2).any(|w| w[1] <= w[0]) {
+        return Err(SolvingError::from(
+            "Mesh nodes need to be strictly increasing",
+        ));
+    }
+    if settings.tolerance <= F::from(0) {
+        return Err(SolvingError::from("Tolerance needs to be positive"));
+    }
+    let mut p0 = Vec::new();
+    gather(p_guess, &mut p0);
+    let mut ya = Vec::new();
+    gather(&guesses[0], &mut ya);
+    let (n, k) = (ya.len(), p0.len());
+
+    let mut z = Vec::with_capacity(mesh.len() * (2 * n + k));
+    let mut values = Vec::new();
+    for guess in guesses {
+        gather(guess, &mut values);
+        if values.len() != n {
+            return Err(SolvingError::from(
+                "All guesses need to have the same number of components",
+            ));
+        }
+        z.extend_from_slice(&values);
+        z.extend_from_slice(&p0);
+        z.extend_from_slice(&ya);
+    }
+
+    let mut collocation = Collocation {
+        bvp,
+        n,
+        k,
+        y: guesses[0].clone(),
+        dy: guesses[0].clone(),
+        p: p_guess.clone(),
+    };
+    let m = collocation.m();
+    let mut times = mesh.to_vec();
+    let mut iterations = 0;
+    loop {
+        iterations += collocation.solve(&times, &mut z, settings)?;
+        let fs = collocation.node_derivatives(&times, &z)?;
+        let residuals = collocation.interval_residuals(&times, &z, &fs)?;
+        if residuals.iter().all(|r| *r <= settings.tolerance) {
+            let to_state = |values: &[F]| {
+                let mut y = guesses[0].clone();
+                scatter(values, &mut y);
+                y
+            };
+            let mut p = p_guess.clone();
+            scatter(&z[n..n + k], &mut p);
+            return Ok(CollocationSolution {
+                y: (0..times.len())
+                    .map(|i| to_state(&z[i * m..i * m + n]))
+                    .collect(),
+                dy: fs.iter().map(|f| to_state(f)).collect(),
+                times,
+                p,
+                residuals,
+                iterations,
+            });
+        }
+        let (new_times, new_z) = refine_mesh(&times, &z, &fs, &residuals, m, n, settings.tolerance);
+        if new_times.len() > settings.max_nodes {
+            return Err(SolvingError::from(
+                "Maximal number of mesh nodes exceeded before the tolerance was reached",
+            ));
+        }
+        times = new_times;
+        z = new_z;
+    }
+}
@@ -2,6 +2,9 @@ use core::fmt;
 use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::solvers::AdaptiveStepperState;
 
 /// # Error while calculating RHS of ODE
 /// When the evaluation of the RHS of the ODE
@@ -10,6 +13,7 @@ use alloc::string::String;
 /// \end{equation}
 /// encounters an error, this one should be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CalcError(String);
 
 impl fmt::Display for CalcError {
@@ -32,7 +36,7 @@ impl From<&String> for CalcError {
 
 impl From<&str> for CalcError {
     fn from(string: &str) -> Self {
-        CalcError(alloc::format!("{}", string))
+        CalcError(string.into())
     }
 }
 
@@ -40,6 +44,7 @@ impl From<&str> for CalcError {
 /// When the solving process, which depends on the solver used does not produce
 /// a result, this error should be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SolvingError(String);
 
 impl fmt::Display for SolvingError {
@@ -68,7 +73,7 @@ impl From<&String> for SolvingError {
 
 impl From<&str> for SolvingError {
     fn from(string: &str) -> Self {
-        SolvingError(alloc::format!("{}", string))
+        SolvingError(string.into())
     }
 }
 
@@ -104,6 +109,103 @@ impl<T> FloatLikeType for T where
 {
 }
 
+/// # Floating point type with elementary functions
+/// Adaptive methods need to measure errors and derive new step sizes from them.
+/// This requires more than the arithmetic operations of [FloatLikeType].
+/// Every type implementing [num_traits::Float] such as `f32`, `f64` or `f128::f128` is
+/// automatically a [RealFloatLikeType].
+pub trait RealFloatLikeType: FloatLikeType {
+    /// Absolute value \\(|x|\\)
+    fn abs(self) -> Self;
+    /// Square root \\(\sqrt{x}\\)
+    fn sqrt(self) -> Self;
+    /// Raise to a floating point power \\(x^n\\)
+    fn powf(self, n: Self) -> Self;
+    /// Exponential function \\(e^x\\)
+    fn exp(self) -> Self;
+    /// Sine \\(\sin x\\)
+    fn sin(self) -> Self;
+    /// Cosine \\(\cos x\\)
+    fn cos(self) -> Self;
+    /// Larger of two numbers
+    fn max(self, other: Self) -> Self;
+    /// Smaller of two numbers
+    fn min(self, other: Self) -> Self;
+    /// Largest integer not greater than the number
+    fn floor(self) -> Self;
+    /// Smallest integer not less than the number
+    fn ceil(self) -> Self;
+    /// Returns `false` for infinite and `NaN` values
+    fn is_finite(self) -> bool;
+    /// Machine epsilon of the type
+    fn epsilon() -> Self;
+    /// Approximate a `f64` constant
+    fn from_f64(x: f64) -> Self;
+    /// Convert a count such as a number of steps
+    fn from_usize(n: usize) -> Self;
+}
+
+impl<T> RealFloatLikeType for T
+where
+    T: FloatLikeType + num_traits::Float,
+{
+    fn abs(self) -> Self {
+        num_traits::Float::abs(self)
+    }
+
+    fn sqrt(self) -> Self {
+        num_traits::Float::sqrt(self)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        num_traits::Float::powf(self, n)
+    }
+
+    fn exp(self) -> Self {
+        num_traits::Float::exp(self)
+    }
+
+    fn sin(self) -> Self {
+        num_traits::Float::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        num_traits::Float::cos(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        num_traits::Float::max(self, other)
+    }
+
+    fn min(self, other: Self) -> Self {
+        num_traits::Float::min(self, other)
+    }
+
+    fn floor(self) -> Self {
+        num_traits::Float::floor(self)
+    }
+
+    fn ceil(self) -> Self {
+        num_traits::Float::ceil(self)
+    }
+
+    fn is_finite(self) -> bool {
+        num_traits::Float::is_finite(self)
+    }
+
+    fn epsilon() -> Self {
+        num_traits::Float::epsilon()
+    }
+
+    fn from_f64(x: f64) -> Self {
+        <T as num_traits::NumCast>::from(x).unwrap_or_else(num_traits::Float::nan)
+    }
+
+    fn from_usize(n: usize) -> Self {
+        <T as num_traits::NumCast>::from(n).unwrap_or_else(num_traits::Float::infinity)
+    }
+}
+
 /// # Abstract mathematical additive (vector-like) object
 /// This type is ment to represent a mathematical type similar to a fixed-size vector in a vector space \\(\vec{v}\in\mathbb{R}^n\\)
 /// For a definition look at eg. <https://lyryx.com/first-course-linear-algebra/>
@@ -156,6 +258,21 @@ pub struct OdeDefinition<'a, I, F, P, Err> {
     pub func: RHS<'a, I, F, P, Err>,
 }
 
+/// # Tolerances of adaptive solvers
+/// Adaptive solvers accept a step if the estimated local error \\(e_i\\) of every component satisfies
+/// \begin{equation}
+///     |e_i| \leq \text{atol} + \text{rtol} \max\left(|y_i(t)|, |y_i(t+dt)|\right)
+/// \end{equation}
+/// in the root-mean-square sense.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tolerances<F> {
+    /// Relative tolerance
+    pub rtol: F,
+    /// Absolute tolerance
+    pub atol: F,
+}
+
 /// # Steppers
 /// This trait allows increasing the current value of an ODE to the next time step via differnt methods.
 /// If the inspected object is iterable, we can update the contents by iterating over individual elements.
@@ -182,6 +299,11 @@ pub trait Stepper<I, F, P, Err> {
 }
 
 /// Similar to [Stepper] but individual functions return error estimates.
+///
+/// The returned error is normalized with the [Tolerances] of the stepper such that a step is
+/// accepted if the error is not larger than one.
+/// When a step is rejected, the state `y` is left unchanged and the step should be repeated with
+/// the step size given by [AdaptiveStepper::suggested_dt].
 pub trait AdaptiveStepper<I, F, P, Err> {
     /// Similar to [Stepper::do_step_iter] but also returns an error approximation.
     fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err>
@@ -195,4 +317,640 @@ pub trait AdaptiveStepper<I, F, P, Err> {
     where
         I: MathVecLikeType<F>,
         F: FloatLikeType + Mul<I, Output = I>;
+
+    /// Step size which should be used for the next step as determined by the last call to
+    /// [AdaptiveStepper::do_step_iter] or [AdaptiveStepper::do_step_add].
+    fn suggested_dt(&self) -> Option<F> {
+        None
+    }
+
+    /// Copy the internal state which is needed to continue an integration bit-identically.
+    /// Steppers without such a state return `None`.
+    fn get_state(&self) -> Option<AdaptiveStepperState<F>> {
+        None
+    }
+
+    /// Restore a state previously obtained by [AdaptiveStepper::get_state].
+    /// Fails if the state belongs to a different stepper.
+    fn set_state(&mut self, state: AdaptiveStepperState<F>) -> Result<(), SolvingError> {
+        let _ = state;
+        Err(SolvingError::from(
+            "Stepper does not have a state which could be restored",
+        ))
+    }
+}
+
+/// # Decision of an [Observer] after a step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObserverAction {
+    /// Proceed with the integration
+    Continue,
+    /// Terminate the integration at the current time
+    Stop,
+}
+
+/// # Inspect and modify intermediate steps
+/// Drivers call the observer after every accepted step with the time `t` and state `y` at the
+/// end of the step, the size `dt` of the step and the normalized error estimate `err`.
+/// Fixed steppers do not estimate errors and pass `None`.
+///
+/// The state may be changed in place, for example to clamp values or to renormalize it.
+/// The integration continues from the changed state.
+/// Any closure `FnMut(&F, &mut I, &F, Option<&F>) -> ObserverAction` is an observer.
+pub trait Observer<I, F> {
+    /// Called after every accepted step
+    fn observe(&mut self, t: &F, y: &mut I, dt: &F, err: Option<&F>) -> ObserverAction;
+}
+
+impl<I, F, O> Observer<I, F> for O
+where
+    O: FnMut(&F, &mut I, &F, Option<&F>) -> ObserverAction,
+{
+    fn observe(&mut self, t: &F, y: &mut I, dt: &F, err: Option<&F>) -> ObserverAction {
+        self(t, y, dt, err)
+    }
+}
+
+/// # Solution of a driver with an [Observer]
+/// Contains the solution at every time point which was reached. If the observer stopped the
+/// integration before the last time point, the time and state at which it did so are stored
+/// additionally.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObservedSolution<I, F> {
+    /// Solution at the time points which were reached
+    pub y: Vec<I>,
+    /// Time and state at which the observer terminated the integration
+    pub stopped: Option<(F, I)>,
+}
+
+/// # Jump of the state at a stop time
+/// Changes the state `y` in place when the integration reaches the stop time `t`,
+/// for example to add a dose or to reset a valve.
+pub type StateJump<'a, I, F, P> = &'a dyn Fn(&F, &mut I, &P);
+
+/// # Known discontinuities of an ODE
+/// Drivers land exactly on every stop time and restart the stepper there, such that no step
+/// crosses a switch of the RHS and no internal state of the stepper is carried over.
+/// If a [StateJump] is given, it is applied to the state at every stop time before the
+/// integration continues. A solution stored at a stop time contains the state after the jump.
+///
+/// Stop times need to be ordered in the direction of integration, that is decreasing when
+/// integrating backward in time. Stops at or before the first time point are ignored.
+#[derive(Clone)]
+pub struct TStops<'a, I, F, P> {
+    /// Times at which the integration is stopped and restarted
+    pub times: Vec<F>,
+    /// Applied to the state at every stop time
+    pub jump: Option<StateJump<'a, I, F, P>>,
+}
+
+/// # Diffusion with general noise
+/// For \\(m\\) independent Wiener processes, the function has to write the column
+/// \\(g_j(y, t, p)\\) belonging to the \\(j\\)-th process into the \\(j\\)-th entry of the slice.
+pub type DiffusionMatrix<'a, I, F, P, Err> = &'a dyn Fn(&I, &mut [I], &F, &P) -> Result<(), Err>;
+
+/// # Diffusion term of a SDE
+/// Determines how the noise enters into the equation.
+#[derive(Clone)]
+pub enum Diffusion<'a, I, F, P, Err> {
+    /// Every component \\(y_i\\) is driven by its own Wiener process \\(W_i\\).
+    /// The function has the same form as the [RHS] and writes \\(g_i(y, t, p)\\) into the
+    /// corresponding component.
+    Diagonal(RHS<'a, I, F, P, Err>),
+    /// The system is driven by `n_noise` Wiener processes \\(W_1,\dots,W_m\\).
+    General {
+        /// Number \\(m\\) of Wiener processes
+        n_noise: usize,
+        /// Columns of the diffusion matrix
+        func: DiffusionMatrix<'a, I, F, P, Err>,
+    },
+}
+
+/// # SDE Definition
+/// A Stochastic Differential Equation (SDE) in the Itô sense is defined by
+/// \begin{align}
+///     dy &= f(y, t, p)dt + g(y, t, p)dW\\\\
+///     y(t_0) &= y_0
+/// \end{align}
+/// where \\(f\\) is the drift and \\(g\\) the [Diffusion].
+/// ```
+/// use ode_integrate::*;
+///
+/// fn drift(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &(f64, f64)) -> Result<(), CalcError> {
+///     dy[0] = p.0 * y[0];
+///     dy[1] = p.0 * y[1];
+///     Ok(())
+/// }
+///
+/// fn diffusion(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &(f64, f64)) -> Result<(), CalcError> {
+///     dy[0] = p.1 * y[0];
+///     dy[1] = p.1 * y[1];
+///     Ok(())
+/// }
+///
+/// let sde_def = SdeDefinition {
+///     y0: [1.0, 2.0],
+///     t0: 0.0,
+///     drift: &drift,
+///     diffusion: Diffusion::Diagonal(&diffusion),
+/// };
+/// ```
+#[derive(Clone)]
+pub struct SdeDefinition<'a, I, F, P, Err> {
+    /// Initial value of the SDE
+    pub y0: I,
+    /// Initial time point of the SDE
+    pub t0: F,
+    /// Deterministic part \\(f(y, t, p)\\)
+    pub drift: RHS<'a, I, F, P, Err>,
+    /// Stochastic part \\(g(y, t, p)\\)
+    pub diffusion: Diffusion<'a, I, F, P, Err>,
+}
+
+/// # Increments of the Wiener processes during one step
+/// Both `dw` and `dz` contain one entry per Wiener process which are independently
+/// normally distributed with mean \\(0\\) and variance \\(|dt|\\).
+/// The increment of the process itself is `dw` while `dz` is only needed by higher order methods
+/// to construct the iterated integral
+/// \begin{equation}
+///     I_{(1,0)} = \int_t^{t+dt}\int_t^s dW_u ds = \tfrac{1}{2}dt\left(dw + \tfrac{1}{\sqrt{3}}dz\right).
+/// \end{equation}
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrownianIncrements<F> {
+    /// Increments \\(\Delta W_j\\) of the Wiener processes
+    pub dw: Vec<F>,
+    /// Auxiliary independent increments
+    pub dz: Vec<F>,
+}
+
+impl<F> Default for BrownianIncrements<F> {
+    fn default() -> Self {
+        BrownianIncrements {
+            dw: Vec::new(),
+            dz: Vec::new(),
+        }
+    }
+}
+
+/// # Steppers for SDEs
+/// Similar to [Stepper] but the update additionally depends on increments of the Wiener processes.
+/// Since every component of the state may be driven by its own noise, only iterable types are
+/// supported. Negative step sizes integrate backward in time.
+pub trait SdeStepper<I, F, P, Err> {
+    /// Draw new [BrownianIncrements] for a step of size `dt` from the random number generator
+    /// of the stepper and update the components of an iterable type.
+    fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType;
+
+    /// Update the components of an iterable type with given increments.
+    /// This allows to integrate the same realization of the Wiener process with different
+    /// steppers or step sizes.
+    fn do_step_increments_iter(
+        &mut self,
+        y: &mut I,
+        t: &F,
+        dt: &F,
+        increments: &BrownianIncrements<F>,
+        p: &P,
+    ) -> Result<(), Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType;
+}
+
+/// # Access to past values of a DDE solution
+/// Passed to the RHS of a [DdeDefinition] to evaluate \\(y(t-\tau)\\).
+pub trait History<I, F> {
+    /// Value of the solution at a time point which lies in the past
+    fn at(&self, t: &F) -> I;
+}
+
+/// # Initial history of a DDE
+/// Function \\(\phi(t)\\) which defines the solution for \\(t < t_0\\).
+pub type InitialHistory<'a, I, F> = &'a dyn Fn(&F) -> I;
+
+/// # RHS of DDE
+/// Similar to the [RHS] of an ODE but with an additional [History] argument from which
+/// delayed values can be obtained.
+pub type DdeRHS<'a, I, F, P, Err> =
+    &'a dyn Fn(&I, &dyn History<I, F>, &mut I, &F, &P) -> Result<(), Err>;
+
+/// # DDE Definition
+/// A Delay Differential Equation (DDE) with constant delays \\(\tau_1,\dots,\tau_k\\) is defined by
+/// \begin{align}
+///     \frac{dy}{dt} &= f(y(t), y(t-\tau_1), \dots, y(t-\tau_k), t, p)\\\\
+///     y(t_0) &= y_0\\\\
+///     y(t) &= \phi(t) \hspace{1cm} t < t_0.
+/// \end{align}
+/// The delays are needed to restrict the step size and to track discontinuities in the
+/// derivatives of the solution which originate at \\(t_0\\) and propagate by the delays.
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(
+///     _y: &[f64; 1],
+///     history: &dyn History<[f64; 1], f64>,
+///     dy: &mut [f64; 1],
+///     t: &f64,
+///     p: &f64,
+/// ) -> Result<(), CalcError> {
+///     let y_delayed = history.at(&(t - p));
+///     dy[0] = -y_delayed[0];
+///     Ok(())
+/// }
+///
+/// let dde_def = DdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     history: &|_t: &f64| [1.0],
+///     delays: vec![1.0],
+///     func: &rhs,
+/// };
+/// ```
+#[derive(Clone)]
+pub struct DdeDefinition<'a, I, F, P, Err> {
+    /// Initial value of the DDE
+    pub y0: I,
+    /// Initial time point of the DDE
+    pub t0: F,
+    /// Solution before the initial time point
+    pub history: InitialHistory<'a, I, F>,
+    /// Constant delays which appear in the RHS
+    pub delays: Vec<F>,
+    /// Right-hand side function to determine the DDE
+    pub func: DdeRHS<'a, I, F, P, Err>,
+}
+
+/// # State-dependent mass matrix
+/// Writes the entries of \\(M(y, t, p)\\) in row-major order into the slice.
+pub type StateMassMatrix<'a, I, F, P> = &'a dyn Fn(&I, &F, &P, &mut [F]);
+
+/// # Mass matrix of a DAE
+/// Matrices are stored densely in row-major order, meaning the entry \\(M_{ij}\\) of a system with
+/// \\(n\\) components is found at index \\(i n + j\\).
+/// Functions receive a slice filled with zeros which they need to fill with the nonzero entries.
+#[derive(Clone)]
+pub enum MassMatrix<'a, I, F, P> {
+    /// The identity matrix which turns the DAE into an ODE
+    Identity,
+    /// Constant matrix \\(M\\)
+    Constant(Vec<F>),
+    /// Matrix \\(M(t, p)\\) depending on time and parameters
+    TimeDependent(&'a dyn Fn(&F, &P, &mut [F])),
+    /// Matrix \\(M(y, t, p)\\) depending additionally on the state
+    StateDependent(StateMassMatrix<'a, I, F, P>),
+}
+
+/// # DAE Definition
+/// A linearly implicit Differential Algebraic Equation (DAE) is given by
+/// \begin{align}
+///     M(y, t, p)\frac{dy}{dt} &= f(y, t, p)\\\\
+///     y(t_0) &= y_0
+/// \end{align}
+/// where the mass matrix \\(M\\) may be singular.
+/// Rows of \\(M\\) which vanish identically represent algebraic equations \\(0 = f_i(y, t, p)\\)
+/// which determine the components belonging to vanishing columns of \\(M\\) (index 1).
+/// Every [OdeDefinition] can be converted into a [DaeDefinition] with the identity as mass matrix.
+/// ```
+/// use ode_integrate::*;
+///
+/// // y0' = -y0 + y1
+/// //   0 = y1 - sin(t)
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -y[0] + y[1];
+///     dy[1] = y[1] - t.sin();
+///     Ok(())
+/// }
+///
+/// let dae_def = DaeDefinition {
+///     y0: [1.0, 0.0],
+///     t0: 0.0,
+///     func: &rhs,
+///     mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+/// };
+/// ```
+#[derive(Clone)]
+pub struct DaeDefinition<'a, I, F, P, Err> {
+    /// Initial value of the DAE
+    pub y0: I,
+    /// Initial time point of the DAE
+    pub t0: F,
+    /// Right-hand side \\(f(y, t, p)\\)
+    pub func: RHS<'a, I, F, P, Err>,
+    /// Mass matrix \\(M(y, t, p)\\) multiplying the derivative
+    pub mass: MassMatrix<'a, I, F, P>,
+}
+
+impl<'a, I, F, P, Err> From<OdeDefinition<'a, I, F, P, Err>> for DaeDefinition<'a, I, F, P, Err> {
+    fn from(ode_def: OdeDefinition<'a, I, F, P, Err>) -> Self {
+        DaeDefinition {
+            y0: ode_def.y0,
+            t0: ode_def.t0,
+            func: ode_def.func,
+            mass: MassMatrix::Identity,
+        }
+    }
+}
+
+/// # Linear operator of a semilinear ODE
+/// Matrices are stored in row-major order, meaning the entry \\(L_{ij}\\) of a system with
+/// \\(n\\) components is found at index \\(i n + j\\).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinearOperator<F> {
+    /// Diagonal matrix given by its \\(n\\) diagonal entries such as a PDE discretized in
+    /// Fourier space
+    Diagonal(Vec<F>),
+    /// Dense matrix with \\(n \times n\\) entries
+    Dense(Vec<F>),
+}
+
+/// # Semilinear ODE Definition
+/// A semilinear ODE separates a constant linear part, which is typically stiff, from a
+/// nonlinear remainder
+/// \begin{align}
+///     \frac{dy}{dt} &= L y + N(y, t, p)\\\\
+///     y(t_0) &= y_0.
+/// \end{align}
+/// Exponential integrators treat the linear part exactly and evaluate only \\(N\\) explicitly.
+/// ```
+/// use ode_integrate::*;
+///
+/// // y' = -100 y + 100 y^2
+/// fn nonlinear(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = 100.0 * y[0] * y[0];
+///     Ok(())
+/// }
+///
+/// let semi_def = SemilinearDefinition {
+///     y0: [0.5],
+///     t0: 0.0,
+///     linear: LinearOperator::Diagonal(vec![-100.0]),
+///     nonlinear: &nonlinear,
+/// };
+/// ```
+#[derive(Clone)]
+pub struct SemilinearDefinition<'a, I, F, P, Err> {
+    /// Initial value of the ODE
+    pub y0: I,
+    /// Initial time point of the ODE
+    pub t0: F,
+    /// Linear operator \\(L\\)
+    pub linear: LinearOperator<F>,
+    /// Nonlinear part \\(N(y, t, p)\\) of the RHS
+    pub nonlinear: RHS<'a, I, F, P, Err>,
+}
+
+/// # Additive ODE Definition
+/// The RHS of an additive ODE is split into a non-stiff part, which is treated explicitly, and a
+/// stiff part, which is treated implicitly,
+/// \begin{align}
+///     \frac{dy}{dt} &= f_E(y, t, p) + f_I(y, t, p)\\\\
+///     y(t_0) &= y_0.
+/// \end{align}
+/// A typical example are reaction-diffusion equations with explicit reactions and implicit
+/// diffusion.
+/// ```
+/// use ode_integrate::*;
+///
+/// // y' = y (1 - y) - 1000 y
+/// fn reaction(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[0] * (1.0 - y[0]);
+///     Ok(())
+/// }
+///
+/// fn decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -1000.0 * y[0];
+///     Ok(())
+/// }
+///
+/// let add_def = AdditiveDefinition {
+///     y0: [0.5],
+///     t0: 0.0,
+///     explicit: &reaction,
+///     implicit: &decay,
+/// };
+/// ```
+#[derive(Clone)]
+pub struct AdditiveDefinition<'a, I, F, P, Err> {
+    /// Initial value of the ODE
+    pub y0: I,
+    /// Initial time point of the ODE
+    pub t0: F,
+    /// Non-stiff part \\(f_E(y, t, p)\\) of the RHS
+    pub explicit: RHS<'a, I, F, P, Err>,
+    /// Stiff part \\(f_I(y, t, p)\\) of the RHS
+    pub implicit: RHS<'a, I, F, P, Err>,
+}
+
+/// # Jacobian of the RHS
+/// Writes the partial derivatives of \\(f(y, t, p)\\) with respect to the state or the parameters
+/// in row-major order into the slice. The slice is filled with zeros before.
+pub type Jacobian<'a, I, F, P, Err> = &'a dyn Fn(&I, &F, &P, &mut [F]) -> Result<(), Err>;
+
+/// # Definition of forward sensitivities
+/// Extends an [OdeDefinition] by the Jacobians needed for the sensitivities
+/// \\(S = \partial y / \partial p\\) which satisfy
+/// \begin{align}
+///     \frac{dS}{dt} &= \frac{\partial f}{\partial y}S + \frac{\partial f}{\partial p}\\\\
+///     S(t_0) &= 0.
+/// \end{align}
+/// The parameters need to be iterable such that the \\(n_p\\) individual parameters can be
+/// accessed. The Jacobian \\(\partial f/\partial y\\) has \\(n \times n\\) entries while
+/// \\(\partial f/\partial p\\) and \\(S\\) have \\(n \times n_p\\) entries, all stored in
+/// row-major order.
+/// Jacobians which are not supplied are approximated by finite differences.
+/// Every [OdeDefinition] can be converted into a [SensitivityDefinition] without Jacobians.
+pub struct SensitivityDefinition<'a, I, F, P, Err> {
+    /// Initial value of the ODE
+    pub y0: I,
+    /// Initial time point of the ODE
+    pub t0: F,
+    /// Right-hand side \\(f(y, t, p)\\)
+    pub func: RHS<'a, I, F, P, Err>,
+    /// Optional Jacobian \\(\partial f/\partial y\\)
+    pub jacobian_y: Option<Jacobian<'a, I, F, P, Err>>,
+    /// Optional Jacobian \\(\partial f/\partial p\\)
+    pub jacobian_p: Option<Jacobian<'a, I, F, P, Err>>,
+}
+
+impl<'a, I, F, P, Err> Clone for SensitivityDefinition<'a, I, F, P, Err>
+where
+    I: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        SensitivityDefinition {
+            y0: self.y0.clone(),
+            t0: self.t0.clone(),
+            func: self.func,
+            jacobian_y: self.jacobian_y,
+            jacobian_p: self.jacobian_p,
+        }
+    }
+}
+
+impl<'a, I, F, P, Err> From<OdeDefinition<'a, I, F, P, Err>>
+    for SensitivityDefinition<'a, I, F, P, Err>
+{
+    fn from(ode_def: OdeDefinition<'a, I, F, P, Err>) -> Self {
+        SensitivityDefinition {
+            y0: ode_def.y0,
+            t0: ode_def.t0,
+            func: ode_def.func,
+            jacobian_y: None,
+            jacobian_p: None,
+        }
+    }
+}
+
+/// # Scalar objective function
+/// Evaluates \\(g(y, t, p)\\) for adjoint sensitivities.
+pub type ObjectiveFunction<'a, I, F, P, Err> = &'a dyn Fn(&I, &F, &P) -> Result<F, Err>;
+
+/// # Gradient of a scalar objective function
+/// Writes \\(\partial g/\partial y\\) into the first and \\(\partial g/\partial p\\) into the
+/// second slice. Both slices are filled with zeros before.
+pub type ObjectiveGradient<'a, I, F, P, Err> =
+    &'a dyn Fn(&I, &F, &P, &mut [F], &mut [F]) -> Result<(), Err>;
+
+/// Describes how an [Objective] combines the values of its function
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObjectiveKind {
+    /// Time integral \\(G = \int_{t_0}^{T} g(y(t), t, p) dt\\) up to the last time point \\(T\\)
+    Integral,
+    /// Sum of pointwise losses \\(G = \sum_i g(y(t_i), t_i, p)\\) at all time points
+    Pointwise,
+}
+
+/// # Objective for adjoint sensitivities
+/// Scalar quantity \\(G\\) of the solution whose derivatives \\(dG/dp\\) and \\(dG/dy_0\\) are
+/// computed. If no gradient is supplied, it is approximated by finite differences.
+pub struct Objective<'a, I, F, P, Err> {
+    /// How the function values are combined
+    pub kind: ObjectiveKind,
+    /// Function \\(g(y, t, p)\\)
+    pub func: ObjectiveFunction<'a, I, F, P, Err>,
+    /// Optional gradient of \\(g\\)
+    pub gradient: Option<ObjectiveGradient<'a, I, F, P, Err>>,
+}
+
+impl<'a, I, F, P, Err> Clone for Objective<'a, I, F, P, Err> {
+    fn clone(&self) -> Self {
+        Objective {
+            kind: self.kind.clone(),
+            func: self.func,
+            gradient: self.gradient,
+        }
+    }
+}
+
+/// # RHS of a batch of ODEs
+/// Evaluates the RHS for all lanes of a batch at once. States are stored in structure-of-arrays
+/// layout, meaning that component `i` of lane `l` is found at index `i * n_lanes + l`.
+/// The third argument contains the current time of every lane.
+/// Parameters which differ between lanes can be stored in `P` in the same layout.
+pub type BatchedRHS<'a, F, P, Err> = &'a dyn Fn(&[F], &mut [F], &[F], &P) -> Result<(), Err>;
+
+/// # Batch of ODE Definitions
+/// Describes `n_lanes` independent copies of the same ODE which only differ in their initial
+/// values (and possibly parameters). The initial values are stored in structure-of-arrays layout
+/// as described in [BatchedRHS].
+/// Writing the RHS as loops over the lanes allows the compiler to vectorize its evaluation.
+/// An existing [OdeDefinition] can be batched without rewriting its RHS with
+/// [LaneWiseRHS](crate::LaneWiseRHS).
+/// ```
+/// use ode_integrate::*;
+///
+/// // y' = -p_l y for every lane l
+/// fn rhs(y: &[f64], dy: &mut [f64], _t: &[f64], p: &Vec<f64>) -> Result<(), CalcError> {
+///     for ((dyl, yl), pl) in dy.iter_mut().zip(y).zip(p) {
+///         *dyl = -pl * yl;
+///     }
+///     Ok(())
+/// }
+///
+/// let batch_def = BatchedOdeDefinition::from_lanes(&[[1.0], [2.0], [3.0]], 0.0, &rhs).unwrap();
+/// assert_eq!(batch_def.y0, vec![1.0, 2.0, 3.0]);
+/// assert_eq!(batch_def.n_states(), 1);
+///
+/// // All lanes need the same number of components
+/// assert!(BatchedOdeDefinition::from_lanes(&[vec![1.0], vec![2.0, 3.0]], 0.0, &rhs).is_err());
+/// ```
+#[derive(Clone)]
+pub struct BatchedOdeDefinition<'a, F, P, Err> {
+    /// Initial values of all lanes in structure-of-arrays layout
+    pub y0: Vec<F>,
+    /// Number of independent copies of the ODE
+    pub n_lanes: usize,
+    /// Initial time point of all lanes
+    pub t0: F,
+    /// Right-hand side evaluated for all lanes at once
+    pub func: BatchedRHS<'a, F, P, Err>,
+}
+
+impl<'a, F, P, Err> BatchedOdeDefinition<'a, F, P, Err>
+where
+    F: Copy,
+{
+    /// Construct the batch from the initial values of the individual lanes.
+    /// Fails if the lanes do not have the same number of components.
+    pub fn from_lanes<I>(
+        lanes: &[I],
+        t0: F,
+        func: BatchedRHS<'a, F, P, Err>,
+    ) -> Result<Self, SolvingError>
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+    {
+        let n_lanes = lanes.len();
+        let n_states = lanes.first().map_or(0, |y| y.into_iter().count());
+        if let Some(l) = lanes
+            .iter()
+            .position(|lane| lane.into_iter().count() != n_states)
+        {
+            return Err(SolvingError::from(alloc::format!(
+                "Lane {} has {} components but lane 0 has {}",
+                l,
+                lanes[l].into_iter().count(),
+                n_states
+            )));
+        }
+        let mut y0 = Vec::with_capacity(n_lanes * n_states);
+        for i in 0..n_states {
+            for lane in lanes {
+                if let Some(yi) = lane.into_iter().nth(i) {
+                    y0.push(*yi);
+                }
+            }
+        }
+        Ok(BatchedOdeDefinition {
+            y0,
+            n_lanes,
+            t0,
+            func,
+        })
+    }
+
+    /// Number of components of a single lane
+    pub fn n_states(&self) -> usize {
+        self.y0.len().checked_div(self.n_lanes).unwrap_or(0)
+    }
+
+    /// Copy the components of lane `l` out of a state in structure-of-arrays layout
+    pub fn lane(&self, y: &[F], l: usize) -> Vec<F> {
+        y.iter()
+            .skip(l)
+            .step_by(self.n_lanes.max(1))
+            .copied()
+            .collect()
+    }
 }
@@ -0,0 +1,191 @@
+use crate::concepts::*;
+use crate::methods::{
+    solve_ode_time_series_single_step_add, solve_ode_time_series_single_step_iter,
+};
+use crate::solvers::FixedStepSolvers;
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::vec::Vec;
+
+/// # Solutions of an ensemble
+/// Contains the result of every member in the order in which the problems were supplied.
+/// Failures of individual members do not affect the other members.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnsembleSolution<T> {
+    /// Result of every member of the ensemble
+    pub results: Vec<Result<T, SolvingError>>,
+}
+
+impl<T> EnsembleSolution<T> {
+    /// Solutions of all successful members together with their index
+    pub fn successes(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, res)| res.as_ref().ok().map(|solution| (i, solution)))
+    }
+
+    /// Errors of all failed members together with their index
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &SolvingError)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, res)| res.as_ref().err().map(|error| (i, error)))
+    }
+
+    /// Number of failed members
+    pub fn n_failed(&self) -> usize {
+        self.failures().count()
+    }
+}
+
+/// # Solve an ensemble of problems
+/// Calls `solve` for every pair \\((y_0, p)\\) of initial value and parameters and collects the
+/// results. This allows to use any of the time-series drivers for the individual members.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let t_series = vec![0.0, 0.5, 1.0];
+/// let problems = (1..=10).map(|i| ([i as f64], 0.1 * i as f64));
+/// let ensemble = solve_ensemble(problems, |y0, p| {
+///     solve_ode_time_series_single_step_iter(y0, &t_series, &rhs, p, FixedStepSolvers::Rk4)
+/// });
+/// assert_eq!(ensemble.results.len(), 10);
+/// assert_eq!(ensemble.n_failed(), 0);
+/// ```
+pub fn solve_ensemble<I, P, T, Q, S>(problems: Q, solve: S) -> EnsembleSolution<T>
+where
+    Q: IntoIterator<Item = (I, P)>,
+    S: Fn(&I, &P) -> Result<T, SolvingError>,
+{
+    EnsembleSolution {
+        results: problems.into_iter().map(|(y0, p)| solve(&y0, &p)).collect(),
+    }
+}
+
+/// # Solve an ensemble with single steps
+/// Solves every member with [solve_ode_time_series_single_step_iter].
+pub fn solve_ensemble_single_step_iter<'a, I, F, P, E, V, Q>(
+    problems: Q,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    solver_type: FixedStepSolvers,
+) -> EnsembleSolution<Vec<I>>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+    Q: IntoIterator<Item = (I, P)>,
+{
+    solve_ensemble(problems, |y0, p| {
+        solve_ode_time_series_single_step_iter(y0, t_series, rhs, p, solver_type.clone())
+    })
+}
+
+/// # Solve an ensemble with single steps
+/// Solves every member with [solve_ode_time_series_single_step_add].
+pub fn solve_ensemble_single_step_add<'a, I, F, P, E, V, Q>(
+    problems: Q,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    solver_type: FixedStepSolvers,
+) -> EnsembleSolution<Vec<I>>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+    Q: IntoIterator<Item = (I, P)>,
+{
+    solve_ensemble(problems, |y0, p| {
+        solve_ode_time_series_single_step_add(y0, t_series, rhs, p, solver_type.clone())
+    })
+}
+
+/// # RHS which can be shared between threads
+#[cfg(feature = "rayon")]
+pub type SyncRHS<'a, I, F, P, Err> = &'a (dyn Fn(&I, &mut I, &F, &P) -> Result<(), Err> + Sync);
+
+/// # Solve an ensemble of problems in parallel
+/// Parallel version of [solve_ensemble] using [rayon]. The order of the results is preserved.
+#[cfg(feature = "rayon")]
+pub fn solve_ensemble_par<I, P, T, Q, S>(problems: Q, solve: S) -> EnsembleSolution<T>
+where
+    Q: IntoIterator<Item = (I, P)>,
+    I: Send,
+    P: Send,
+    T: Send,
+    S: Fn(&I, &P) -> Result<T, SolvingError> + Sync,
+{
+    use rayon::prelude::*;
+    let problems: Vec<(I, P)> = problems.into_iter().collect();
+    EnsembleSolution {
+        results: problems
+            .into_par_iter()
+            .map(|(y0, p)| solve(&y0, &p))
+            .collect(),
+    }
+}
+
+/// # Solve an ensemble with single steps in parallel
+/// Parallel version of [solve_ensemble_single_step_iter].
+#[cfg(feature = "rayon")]
+pub fn solve_ensemble_single_step_iter_par<'a, I, F, P, E, V, Q>(
+    problems: Q,
+    t_series: &V,
+    rhs: SyncRHS<'a, I, F, P, E>,
+    solver_type: FixedStepSolvers,
+) -> EnsembleSolution<Vec<I>>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + Send,
+    F: FloatLikeType,
+    P: Clone + Send,
+    E: Display + Clone,
+    V: Sync,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+    Q: IntoIterator<Item = (I, P)>,
+{
+    solve_ensemble_par(problems, |y0, p| {
+        solve_ode_time_series_single_step_iter(y0, t_series, rhs, p, solver_type.clone())
+    })
+}
+
+/// # Solve an ensemble with single steps in parallel
+/// Parallel version of [solve_ensemble_single_step_add].
+#[cfg(feature = "rayon")]
+pub fn solve_ensemble_single_step_add_par<'a, I, F, P, E, V, Q>(
+    problems: Q,
+    t_series: &V,
+    rhs: SyncRHS<'a, I, F, P, E>,
+    solver_type: FixedStepSolvers,
+) -> EnsembleSolution<Vec<I>>
+where
+    I: MathVecLikeType<F> + Send,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone + Send,
+    E: Display + Clone,
+    V: Sync,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+    Q: IntoIterator<Item = (I, P)>,
+{
+    solve_ensemble_par(problems, |y0, p| {
+        solve_ode_time_series_single_step_add(y0, t_series, rhs, p, solver_type.clone())
+    })
+}
@@ -0,0 +1,382 @@
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+use crate::methods::solve_sensitivity_time_series_iter;
+use crate::solvers::SensitivityMethod;
+
+use core::fmt::Display;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Damping parameters above this value indicate that no further progress is possible
+const MAX_DAMPING: f64 = 1e16;
+
+/// # Observed data
+/// Measurements of some components of the state at given time points.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observations<F> {
+    /// Increasing time points of the measurements
+    pub times: Vec<F>,
+    /// Indices of the measured components of the state
+    pub components: Vec<usize>,
+    /// Measured values where `values[i][j]` belongs to `times[i]` and `components[j]`
+    pub values: Vec<Vec<F>>,
+}
+
+/// # Box constraints of the parameters
+/// Parameters are kept within \\(l_k \leq p_k \leq u_k\\) by projecting every trial step.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterBounds<F> {
+    /// Lower bounds of the parameters
+    pub lower: Vec<F>,
+    /// Upper bounds of the parameters
+    pub upper: Vec<F>,
+}
+
+/// # Settings of the Levenberg-Marquardt method
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitSettings<F> {
+    /// Maximal number of accepted iterations
+    pub max_iterations: usize,
+    /// The iteration stops once the gradient or the relative change of the parameters fall
+    /// below this value
+    pub tolerance: F,
+    /// Initial value of the damping parameter
+    pub initial_damping: F,
+    /// Maximal step size of the integration
+    pub dt: F,
+    /// Method used to integrate the sensitivities
+    pub method: SensitivityMethod,
+}
+
+impl<F> FitSettings<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Default settings for the given maximal step size of the integration
+    pub fn new(dt: F) -> Self {
+        FitSettings {
+            max_iterations: 100,
+            tolerance: F::from_f64(1e-10),
+            initial_damping: F::from_f64(1e-3),
+            dt,
+            method: SensitivityMethod::Simultaneous,
+        }
+    }
+}
+
+/// # Result of a parameter fit
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitResult<P, F> {
+    /// Best-fit parameters
+    pub parameters: P,
+    /// Residuals \\(y_j(t_i) - d_{ij}\\) of the best fit ordered by time point and component
+    pub residuals: Vec<F>,
+    /// Approximate covariance \\(\sigma^2 (J^T J)^{-1}\\) of the parameters in row-major order
+    /// where \\(\sigma^2\\) is the residual variance
+    /// or `None` if \(J^T J\) is singular because the parameters are not identifiable
+    pub covariance: Option<Vec<F>>,
+    /// Final cost \\(\tfrac{1}{2}\sum r_i^2\\)
+    pub cost: F,
+    /// Number of accepted iterations
+    pub iterations: usize,
+}
+
+/// Residuals and their Jacobian with respect to the parameters
+struct Linearization<F> {
+    /// Residuals of the model
+    residuals: Vec<F>,
+    /// Jacobian of the residuals in row-major order
+    jacobian: Vec<F>,
+    /// Half the sum of squared residuals
+    cost: F,
+}
+
+/// Solve the model with sensitivities and compare to the observations
+fn linearize<'a, I, F, P, E>(
+    sens_def: &SensitivityDefinition<'a, I, F, P, E>,
+    observations: &Observations<F>,
+    p: &P,
+    settings: &FitSettings<F>,
+) -> Result<Linearization<F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display + From<CalcError>,
+{
+    let n_p = p.into_iter().count();
+    let solution = solve_sensitivity_time_series_iter(
+        sens_def.clone(),
+        &observations.times,
+        p,
+        settings.method.clone(),
+        &settings.dt,
+    )?;
+    let mut residuals = Vec::new();
+    let mut jacobian = Vec::new();
+    for ((y, s), values) in solution.iter().zip(observations.values.iter()) {
+        for (component, value) in observations.components.iter().zip(values.iter()) {
+            let model = y.into_iter().nth(*component).copied().unwrap_or(F::from(0));
+            residuals.push(model - *value);
+            jacobian.extend_from_slice(&s[component * n_p..(component + 1) * n_p]);
+        }
+    }
+    let cost = residuals.iter().fold(F::from(0), |acc, r| acc + *r * *r) / F::from(2);
+    if !cost.is_finite() {
+        return Err(SolvingError::from("Residuals are not finite"));
+    }
+    Ok(Linearization {
+        residuals,
+        jacobian,
+        cost,
+    })
+}
+
+/// Normal matrix \\(J^T J\\) and gradient \\(J^T r\\) of the least-squares problem
+fn normal_equations<F>(lin: &Linearization<F>, n_p: usize) -> (Vec<F>, Vec<F>)
+where
+    F: RealFloatLikeType,
+{
+    let mut jtj = vec![F::from(0); n_p * n_p];
+    let mut jtr = vec![F::from(0); n_p];
+    for (row, r) in lin.jacobian.chunks(n_p.max(1)).zip(lin.residuals.iter()) {
+        for k in 0..n_p {
+            jtr[k] += row[k] * *r;
+            for l in 0..n_p {
+                jtj[k * n_p + l] += row[k] * row[l];
+            }
+        }
+    }
+    (jtj, jtr)
+}
+
+/// Check the dimensions of observations and bounds
+fn check_input<F>(
+    observations: &Observations<F>,
+    bounds: Option<&ParameterBounds<F>>,
+    n: usize,
+    n_p: usize,
+) -> Result<(), SolvingError>
+where
+    F: RealFloatLikeType,
+{
+    if observations.values.len() != observations.times.len()
+        || observations
+            .values
+            .iter()
+            .any(|v| v.len() != observations.components.len())
+    {
+        return Err(SolvingError::from(
+            "Observed values do not match the time points and components",
+        ));
+    }
+    if observations.components.iter().any(|c| *c >= n) {
+        return Err(SolvingError::from("Observed component exceeds the state"));
+    }
+    if let Some(bounds) = bounds {
+        if bounds.lower.len() != n_p || bounds.upper.len() != n_p {
+            return Err(SolvingError::from("Bounds need one entry per parameter"));
+        }
+        if bounds
+            .lower
+            .iter()
+            .zip(bounds.upper.iter())
+            .any(|(l, u)| l > u)
+        {
+            return Err(SolvingError::from("Lower bounds exceed upper bounds"));
+        }
+    }
+    Ok(())
+}
+
+/// Project the parameters onto the bounds
+fn project<F>(p: &mut [F], bounds: Option<&ParameterBounds<F>>)
+where
+    F: RealFloatLikeType,
+{
+    if let Some(bounds) = bounds {
+        for ((pk, l), u) in p
+            .iter_mut()
+            .zip(bounds.lower.iter())
+            .zip(bounds.upper.iter())
+        {
+            *pk = pk.max(*l).min(*u);
+        }
+    }
+}
+
+/// Write the values of a slice into the parameters
+fn to_parameters<P, F>(values: &[F], p: &mut P)
+where
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    F: Copy,
+{
+    for (pk, vk) in p.into_iter().zip(values.iter()) {
+        *pk = *vk;
+    }
+}
+
+/// # Fit parameters to observed data
+/// Minimizes the sum of squared residuals between the solution of the [SensitivityDefinition]
+/// and the [Observations] with the Levenberg-Marquardt method starting from `p0`.
+/// Every iteration solves
+/// \begin{equation}
+///     \left(J^T J + \lambda\\,\text{diag}(J^T J)\right)\delta = -J^T r
+/// \end{equation}
+/// where the Jacobian \\(J\\) of the residuals is obtained from the forward sensitivities.
+/// The damping \\(\lambda\\) is decreased after successful steps and increased otherwise.
+/// Trial parameters are projected onto the optional [ParameterBounds].
+/// Trial parameters for which the integration fails are rejected like steps which do not decrease
+/// the cost.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+///     dy[0] = -p[0] * y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     func: &rhs,
+/// };
+/// // Data generated with p = 0.7
+/// let times = vec![0.5, 1.0, 1.5, 2.0];
+/// let values = times.iter().map(|t: &f64| vec![(-0.7 * t).exp()]).collect();
+/// let observations = Observations {
+///     times,
+///     components: vec![0],
+///     values,
+/// };
+///
+/// let fit = fit_parameters(ode_def.into(), &observations, &[0.1], None,
+/// &FitSettings::new(0.01)).unwrap();
+/// assert!((fit.parameters[0] - 0.7).abs() < 1e-6);
+/// ```
+pub fn fit_parameters<'a, I, F, P, E>(
+    sens_def: SensitivityDefinition<'a, I, F, P, E>,
+    observations: &Observations<F>,
+    p0: &P,
+    bounds: Option<&ParameterBounds<F>>,
+    settings: &FitSettings<F>,
+) -> Result<FitResult<P, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display + From<CalcError>,
+{
+    let n = (&sens_def.y0).into_iter().count();
+    let mut values: Vec<F> = p0.into_iter().copied().collect();
+    let n_p = values.len();
+    check_input(observations, bounds, n, n_p)?;
+
+    let mut p = p0.clone();
+    project(&mut values, bounds);
+    to_parameters(&values, &mut p);
+    let mut lin = linearize(&sens_def, observations, &p, settings)?;
+    let mut damping = settings.initial_damping;
+    let mut iterations = 0;
+    let tol = settings.tolerance;
+    while iterations < settings.max_iterations {
+        let (jtj, jtr) = normal_equations(&lin, n_p);
+        let gradient_norm = jtr.iter().fold(F::from(0), |acc, g| acc.max(g.abs()));
+        if gradient_norm <= tol {
+            break;
+        }
+
+        // Increase the damping until the cost decreases
+        let mut accepted = None;
+        while damping <= F::from_f64(MAX_DAMPING) {
+            let mut matrix = jtj.clone();
+            for k in 0..n_p {
+                let diagonal = jtj[k * n_p + k].max(F::epsilon());
+                matrix[k * n_p + k] += damping * diagonal;
+            }
+            let mut step: Vec<F> = jtr.iter().map(|g| -*g).collect();
+            if let Some(lu) = LuDecomposition::new(matrix, n_p) {
+                lu.solve(&mut step);
+                let mut trial_values: Vec<F> = values
+                    .iter()
+                    .zip(step.iter())
+                    .map(|(v, s)| *v + *s)
+                    .collect();
+                project(&mut trial_values, bounds);
+                let mut trial = p.clone();
+                to_parameters(&trial_values, &mut trial);
+                if let Ok(trial_lin) = linearize(&sens_def, observations, &trial, settings) {
+                    if trial_lin.cost < lin.cost {
+                        accepted = Some((trial_values, trial, trial_lin));
+                        break;
+                    }
+                }
+            }
+            damping = damping * F::from(10);
+        }
+        let Some((trial_values, trial, trial_lin)) = accepted else {
+            break;
+        };
+
+        iterations += 1;
+        damping = (damping / F::from(10)).max(F::epsilon());
+        let change = trial_values
+            .iter()
+            .zip(values.iter())
+            .fold(F::from(0), |acc, (a, b)| {
+                acc.max((*a - *b).abs() / (b.abs() + tol))
+            });
+        let cost_change = (lin.cost - trial_lin.cost) / lin.cost.max(F::epsilon());
+        values = trial_values;
+        p = trial;
+        lin = trial_lin;
+        if change <= tol || cost_change <= tol {
+            break;
+        }
+    }
+
+    // Covariance from the linearization at the best fit
+    let (jtj, _) = normal_equations(&lin, n_p);
+    let m = lin.residuals.len();
+    let variance = if m > n_p {
+        F::from(2) * lin.cost / F::from_usize(m - n_p)
+    } else {
+        F::from(1)
+    };
+    let covariance = LuDecomposition::new(jtj, n_p).map(|lu| {
+        let mut covariance = vec![F::from(0); n_p * n_p];
+        let mut column = vec![F::from(0); n_p];
+        for l in 0..n_p {
+            column.iter_mut().for_each(|c| *c = F::from(0));
+            column[l] = F::from(1);
+            lu.solve(&mut column);
+            for k in 0..n_p {
+                covariance[k * n_p + l] = variance * column[k];
+            }
+        }
+        covariance
+    });
+
+    Ok(FitResult {
+        parameters: p,
+        residuals: lin.residuals,
+        covariance,
+        cost: lin.cost,
+        iterations,
+    })
+}
@@ -0,0 +1,396 @@
+use crate::checkpoint::AdaptiveIntegration;
+use crate::concepts::*;
+use crate::solvers::AdaptiveStepSolvers;
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::vec::Vec;
+
+/// # Guard of a transition
+/// Function \\(g(y, t, p)\\) of the state in the current mode. The transition is taken when the
+/// guard crosses zero from negative to non-negative values.
+pub type Guard<'a, I, F, P> = &'a dyn Fn(&I, &F, &P) -> F;
+
+/// # Transition between two modes of a [HybridAutomaton]
+#[derive(Clone)]
+pub struct Transition<'a, I, F, P> {
+    /// Index of the mode which is entered
+    pub target: usize,
+    /// Condition which triggers the transition
+    pub guard: Guard<'a, I, F, P>,
+    /// Reset map which is applied to the state when the transition is taken
+    pub reset: Option<StateJump<'a, I, F, P>>,
+}
+
+/// # Discrete mode of a [HybridAutomaton]
+/// Within a mode, the state evolves according to its RHS until one of its outgoing transitions
+/// is triggered.
+#[derive(Clone)]
+pub struct Mode<'a, I, F, P, Err> {
+    /// Right-hand side of the ODE in this mode
+    pub func: RHS<'a, I, F, P, Err>,
+    /// Outgoing transitions. If several guards trigger at the same time, the first one is taken.
+    pub transitions: Vec<Transition<'a, I, F, P>>,
+}
+
+/// # Hybrid automaton
+/// Switching system which follows the ODE of its current mode between transitions.
+/// Similar to an [OdeDefinition] but with one RHS per mode and the index of the initial mode.
+#[derive(Clone)]
+pub struct HybridAutomaton<'a, I, F, P, Err> {
+    /// Initial value of the state
+    pub y0: I,
+    /// Initial time point
+    pub t0: F,
+    /// Index of the mode at the initial time point
+    pub initial_mode: usize,
+    /// All discrete modes
+    pub modes: Vec<Mode<'a, I, F, P, Err>>,
+}
+
+/// # Settings of event detection for a [HybridAutomaton]
+/// Protects against Zeno behaviour, where infinitely many transitions accumulate in finite time,
+/// by failing once more than `max_transitions` transitions happen within a time window of
+/// length `window`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridSettings<F> {
+    /// Precision in time to which the triggering of guards is located
+    pub event_tolerance: F,
+    /// Maximal number of transitions within a window
+    pub max_transitions: usize,
+    /// Length of the window in which transitions are counted
+    pub window: F,
+}
+
+/// # Transition which happened during the integration
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModeTransition<F> {
+    /// Time at which the transition was taken. This is within the event tolerance before the
+    /// guard crossed zero.
+    pub t: F,
+    /// Index of the mode which was left
+    pub from: usize,
+    /// Index of the mode which was entered
+    pub to: usize,
+}
+
+/// # Solution of a [HybridAutomaton]
+/// Contains the state and mode at every time point together with the history of transitions.
+/// At a time point which coincides with a transition, the state after the reset and the new
+/// mode are stored.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HybridSolution<I, F> {
+    /// State at every time point
+    pub y: Vec<I>,
+    /// Index of the mode at every time point
+    pub modes: Vec<usize>,
+    /// All transitions in the order in which they happened
+    pub transitions: Vec<ModeTransition<F>>,
+}
+
+/// Integrates an [AdaptiveIntegration] up to the given time with an optional [Observer]
+type ModeIntegration<'s, 'a, I, F, P, E> = &'s dyn Fn(
+    &mut AdaptiveIntegration<'a, I, F, P, E>,
+    &F,
+    &P,
+    Option<&mut dyn Observer<I, F>>,
+) -> Result<ObserverAction, SolvingError>;
+
+/// # Solve a hybrid automaton for specified time points
+/// Starts at `t0` of the [HybridAutomaton] and stores the state and mode at every time point of
+/// `t_series` which need to be increasing and not before `t0`.
+/// Integrates the ODE of the current mode with an adaptive stepper and checks the guards of its
+/// transitions after every accepted step. When a guard triggers, the time of the crossing is
+/// located by bisection up to [HybridSettings::event_tolerance]. The transition is taken just
+/// before the crossing, where the reset map is applied and the integration continues in the
+/// target mode with a fresh stepper.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Thermostat which heats between 19 and 21 degrees
+/// fn heating(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = 30.0 - y[0];
+///     Ok(())
+/// }
+/// fn cooling(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = 10.0 - y[0];
+///     Ok(())
+/// }
+/// let too_warm = |y: &[f64; 1], _t: &f64, _p: &()| y[0] - 21.0;
+/// let too_cold = |y: &[f64; 1], _t: &f64, _p: &()| 19.0 - y[0];
+///
+/// let automaton = HybridAutomaton {
+///     y0: [20.0],
+///     t0: 0.0,
+///     initial_mode: 0,
+///     modes: vec![
+///         Mode {
+///             func: &heating,
+///             transitions: vec![Transition { target: 1, guard: &too_warm, reset: None }],
+///         },
+///         Mode {
+///             func: &cooling,
+///             transitions: vec![Transition { target: 0, guard: &too_cold, reset: None }],
+///         },
+///     ],
+/// };
+/// let settings = HybridSettings { event_tolerance: 1e-10, max_transitions: 10, window: 1.0 };
+/// let tolerances = Tolerances { rtol: 1e-10, atol: 1e-10 };
+/// let t_series: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+///
+/// let solution = solve_hybrid_time_series_iter(automaton, &t_series, &(),
+/// AdaptiveStepSolvers::BulirschStoer, &0.1, &tolerances, &settings).unwrap();
+///
+/// assert!(solution.transitions.len() > 5);
+/// for y in solution.y.iter() {
+///     assert!(y[0] > 19.0 - 1e-6 && y[0] < 21.0 + 1e-6);
+/// }
+/// ```
+pub fn solve_hybrid_time_series_iter<'a, I, F, P, E, V>(
+    automaton: HybridAutomaton<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    settings: &HybridSettings<F>,
+) -> Result<HybridSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: Display + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_hybrid(
+        automaton,
+        t_series,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        settings,
+        &|integration, t_end, p, observer| match observer {
+            Some(observer) => integration.integrate_to_observed_iter(t_end, p, observer),
+            None => integration
+                .integrate_to_iter(t_end, p)
+                .map(|_| ObserverAction::Continue),
+        },
+    )
+}
+
+/// # Solve a hybrid automaton for specified time points
+/// Equivalent to [solve_hybrid_time_series_iter] but for types which can be added via
+/// [MathVecLikeType].
+pub fn solve_hybrid_time_series_add<'a, I, F, P, E, V>(
+    automaton: HybridAutomaton<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    settings: &HybridSettings<F>,
+) -> Result<HybridSolution<I, F>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: MathVecLikeType<F> + 'a,
+    F: RealFloatLikeType + Mul<I, Output = I> + 'a,
+    P: 'a,
+    E: Display + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_hybrid(
+        automaton,
+        t_series,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        settings,
+        &|integration, t_end, p, observer| match observer {
+            Some(observer) => integration.integrate_to_observed_add(t_end, p, observer),
+            None => integration
+                .integrate_to_add(t_end, p)
+                .map(|_| ObserverAction::Continue),
+        },
+    )
+}
+
+/// Checks that all modes referenced by the automaton exist and that the settings are usable
+fn check_automaton<I, F, P, E>(
+    automaton: &HybridAutomaton<I, F, P, E>,
+    settings: &HybridSettings<F>,
+) -> Result<(), SolvingError>
+where
+    F: RealFloatLikeType,
+{
+    let n_modes = automaton.modes.len();
+    if automaton.initial_mode >= n_modes
+        || automaton
+            .modes
+            .iter()
+            .any(|mode| mode.transitions.iter().any(|tr| tr.target >= n_modes))
+    {
+        return Err(SolvingError::from(
+            "Hybrid automaton refers to a mode which does not exist",
+        ));
+    }
+    if settings.event_tolerance <= F::from(0)
+        || settings.window <= F::from(0)
+        || settings.max_transitions == 0
+    {
+        return Err(SolvingError::from(
+            "Event tolerance, window and maximal number of transitions need to be positive",
+        ));
+    }
+    Ok(())
+}
+
+/// Values of all guards of a mode
+fn guard_values<I, F, P, E>(mode: &Mode<I, F, P, E>, y: &I, t: &F, p: &P) -> Vec<F> {
+    mode.transitions
+        .iter()
+        .map(|transition| (transition.guard)(y, t, p))
+        .collect()
+}
+
+/// Index of the first guard which crossed zero from negative values.
+/// A guard which starts at zero, for example after a reset onto the boundary, triggers once it
+/// becomes positive.
+fn triggered<F>(before: &[F], after: &[F]) -> Option<usize>
+where
+    F: FloatLikeType,
+{
+    let zero = F::from(0);
+    before
+        .iter()
+        .zip(after.iter())
+        .position(|(g0, g1)| (*g0 < zero && *g1 >= zero) || (*g0 == zero && *g1 > zero))
+}
+
+/// Integrates the automaton over the time points of `t_series` with event detection
+#[allow(clippy::too_many_arguments)]
+fn solve_hybrid<'a, I, F, P, E, V>(
+    automaton: HybridAutomaton<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    settings: &HybridSettings<F>,
+    integrate_to: ModeIntegration<'_, 'a, I, F, P, E>,
+) -> Result<HybridSolution<I, F>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    check_automaton(&automaton, settings)?;
+    let modes = &automaton.modes;
+    let start = |mode: usize, y: I, t: F, dt: F| {
+        let ode_def = OdeDefinition {
+            y0: y,
+            t0: t,
+            func: modes[mode].func,
+        };
+        AdaptiveIntegration::new(ode_def, solver_type.clone(), dt, tolerances.clone())
+    };
+
+    let mut mode = automaton.initial_mode;
+    let mut integration = start(mode, automaton.y0.clone(), automaton.t0, *dt);
+    let mut solution = HybridSolution {
+        y: Vec::new(),
+        modes: Vec::new(),
+        transitions: Vec::new(),
+    };
+
+    for t_j in t_series.into_iter() {
+        if t_j < integration.t() {
+            return Err(SolvingError::from("Time steps need to be increasing"));
+        }
+        while integration.t() < t_j {
+            // Start of the last accepted step and the guards there
+            let mut t_last = *integration.t();
+            let mut y_last = integration.y().clone();
+            let mut g_last = guard_values(&modes[mode], &y_last, &t_last, p);
+            let mut observer = |t: &F, y: &mut I, _dt: &F, _err: Option<&F>| {
+                let g = guard_values(&modes[mode], y, t, p);
+                if triggered(&g_last, &g).is_some() {
+                    return ObserverAction::Stop;
+                }
+                t_last = *t;
+                y_last = y.clone();
+                g_last = g;
+                ObserverAction::Continue
+            };
+            if integrate_to(&mut integration, t_j, p, Some(&mut observer))?
+                == ObserverAction::Continue
+            {
+                break;
+            }
+
+            // Locate the crossing by integrating from the start of the step.
+            // The transition is taken just before the crossing such that the state has not yet
+            // passed the boundary described by the guard.
+            let (mut t_lo, mut t_hi) = (t_last, *integration.t());
+            let mut y_lo = y_last.clone();
+            let mut g_hi = guard_values(&modes[mode], integration.y(), &t_hi, p);
+            while t_hi - t_lo > settings.event_tolerance {
+                let t_mid = t_lo + (t_hi - t_lo) / F::from(2);
+                if t_mid <= t_lo || t_mid >= t_hi {
+                    break;
+                }
+                let mut bisection = start(mode, y_last.clone(), t_last, *integration.dt());
+                integrate_to(&mut bisection, &t_mid, p, None)?;
+                let g = guard_values(&modes[mode], bisection.y(), &t_mid, p);
+                if triggered(&g_last, &g).is_some() {
+                    t_hi = t_mid;
+                    g_hi = g;
+                } else {
+                    t_lo = t_mid;
+                    y_lo = bisection.y().clone();
+                }
+            }
+            let index = triggered(&g_last, &g_hi).unwrap_or(0);
+            let transition = &modes[mode].transitions[index];
+            if let Some(reset) = transition.reset {
+                reset(&t_lo, &mut y_lo, p);
+            }
+            solution.transitions.push(ModeTransition {
+                t: t_lo,
+                from: mode,
+                to: transition.target,
+            });
+            mode = transition.target;
+
+            // Zeno protection
+            let n_recent = solution
+                .transitions
+                .iter()
+                .rev()
+                .take_while(|tr| t_lo - tr.t < settings.window)
+                .count();
+            if n_recent > settings.max_transitions {
+                return Err(SolvingError::from(alloc::format!(
+                    "More than {} transitions within the time window indicate Zeno behaviour",
+                    settings.max_transitions
+                )));
+            }
+            integration = start(mode, y_lo, t_lo, *integration.dt());
+        }
+        solution.y.push(integration.y().clone());
+        solution.modes.push(mode);
+    }
+    Ok(solution)
+}
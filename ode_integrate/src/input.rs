@@ -0,0 +1,356 @@
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+
+use alloc::{vec, vec::Vec};
+
+/// # Interpolation between the samples of an [InputSignal]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    /// Keep the value of the last sample until the next one
+    ZeroOrderHold,
+    /// Connect samples by straight lines
+    Linear,
+    /// Twice continuously differentiable cubic spline through all samples
+    CubicSpline,
+}
+
+/// # Continuation of an [InputSignal] outside of its samples
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Extrapolation {
+    /// Keep the value of the first sample before and of the last sample after the grid
+    Hold,
+    /// Repeat the signal with the length of the grid as period.
+    /// The last sample closes the period and needs to equal the first one.
+    Periodic,
+}
+
+/// # Measured input \\(u(t)\\) sampled on a grid
+/// Interpolates samples \\(u_i = u(t_i)\\) to obtain the input at any time.
+/// Signals are not part of the [OdeDefinition]. They are passed to the [RHS] as part of the
+/// parameters `p` and queried there with [InputSignal::value].
+///
+/// Zero-order holds and linear interpolation are not smooth at the sample times. Steps which
+/// cross these breakpoints lose accuracy. A [Solver](crate::Solver) which gets the signal via
+/// [SolverBuilder::input](crate::SolverBuilder::input) lands exactly on its breakpoints. For the
+/// `_tstops_` drivers, collect them with [TStops::from_inputs].
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Tank which is filled by a measured inflow
+/// fn rhs(_y: &[f64; 1], dy: &mut [f64; 1], t: &f64, inflow: &InputSignal<f64>)
+///     -> Result<(), CalcError> {
+///     dy[0] = inflow.value(t);
+///     Ok(())
+/// }
+///
+/// let inflow = InputSignal::new(
+///     vec![0.0, 1.0, 3.0],
+///     vec![2.0, 0.5, 0.0],
+///     Interpolation::ZeroOrderHold,
+///     Extrapolation::Hold,
+/// ).unwrap();
+/// assert_eq!(inflow.value(&0.5), 2.0);
+/// assert_eq!(inflow.value(&2.0), 0.5);
+///
+/// let t_series = vec![0.0, 2.0, 4.0];
+/// let tstops = TStops::from_inputs(&[&inflow], &0.0, &4.0);
+/// let y = solve_ode_time_series_minimal_step_tstops_iter(&[0.0], &t_series, &rhs, &inflow,
+/// FixedStepSolvers::Euler, &0.25, &tstops).unwrap();
+/// assert_eq!(y, vec![[0.0], [2.5], [3.0]]);
+///
+/// let solver = Solver::builder()
+///     .fixed_step(FixedStepSolvers::Euler)
+///     .dt(0.25)
+///     .input(&inflow)
+///     .build()
+///     .unwrap();
+/// assert_eq!(solver.solve(&[0.0], &t_series, &rhs, &inflow).unwrap().y, y);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputSignal<F> {
+    /// Strictly increasing sample times
+    times: Vec<F>,
+    /// Sampled values
+    values: Vec<F>,
+    /// Second derivatives of the cubic spline at the sample times
+    curvatures: Vec<F>,
+    /// Interpolation between samples
+    interpolation: Interpolation,
+    /// Continuation outside of the samples
+    extrapolation: Extrapolation,
+}
+
+impl<F> InputSignal<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Interpolate the `values` sampled at strictly increasing `times`
+    pub fn new(
+        times: Vec<F>,
+        values: Vec<F>,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Result<Self, SolvingError> {
+        if times.len() != values.len() {
+            return Err(SolvingError::from(
+                "Input signal needs as many values as sample times",
+            ));
+        }
+        if times.is_empty() {
+            return Err(SolvingError::from("Input signal needs at least one sample"));
+        }
+        if times.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(SolvingError::from(
+                "Sample times of input signal need to be strictly increasing",
+            ));
+        }
+        if extrapolation == Extrapolation::Periodic
+            && (times.len() < 2 || values[0] != values[values.len() - 1])
+        {
+            return Err(SolvingError::from(
+                "Periodic input signal needs equal first and last values",
+            ));
+        }
+        let curvatures = match (interpolation, extrapolation) {
+            (Interpolation::CubicSpline, Extrapolation::Hold) => {
+                natural_spline_curvatures(&times, &values)
+            }
+            (Interpolation::CubicSpline, Extrapolation::Periodic) => {
+                periodic_spline_curvatures(&times, &values)?
+            }
+            _ => Vec::new(),
+        };
+        Ok(InputSignal {
+            times,
+            values,
+            curvatures,
+            interpolation,
+            extrapolation,
+        })
+    }
+
+    /// Sample times of the signal
+    pub fn times(&self) -> &[F] {
+        &self.times
+    }
+
+    /// Sampled values of the signal
+    pub fn values(&self) -> &[F] {
+        &self.values
+    }
+
+    /// Interpolation between samples
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Continuation outside of the samples
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    /// Length of the grid which is the period of periodic signals
+    fn period(&self) -> F {
+        self.times[self.times.len() - 1] - self.times[0]
+    }
+
+    /// Value of the input at time `t`
+    pub fn value(&self, t: &F) -> F {
+        let n = self.times.len();
+        let t_first = self.times[0];
+        let t_last = self.times[n - 1];
+        let t = match self.extrapolation {
+            Extrapolation::Hold if *t <= t_first => return self.values[0],
+            Extrapolation::Hold if *t >= t_last => return self.values[n - 1],
+            Extrapolation::Hold => *t,
+            Extrapolation::Periodic => {
+                let period = self.period();
+                let shifted = *t - ((*t - t_first) / period).floor() * period;
+                // Rounding can move the time out of the first period
+                if shifted < t_first || shifted >= t_last {
+                    t_first
+                } else {
+                    shifted
+                }
+            }
+        };
+        // Index of the interval [t_i, t_{i+1}) containing t
+        let i = (self.times.partition_point(|ti| *ti <= t) - 1).min(n - 2);
+        let (t_i, t_j) = (self.times[i], self.times[i + 1]);
+        let (u_i, u_j) = (self.values[i], self.values[i + 1]);
+        let h = t_j - t_i;
+        match self.interpolation {
+            Interpolation::ZeroOrderHold => u_i,
+            Interpolation::Linear => u_i + (u_j - u_i) * (t - t_i) / h,
+            Interpolation::CubicSpline => {
+                let (m_i, m_j) = (self.curvatures[i], self.curvatures[i + 1]);
+                let (s, r) = (t - t_i, t_j - t);
+                let six = F::from(6);
+                (m_i * r * r * r + m_j * s * s * s) / (six * h)
+                    + (u_i / h - m_i * h / six) * r
+                    + (u_j / h - m_j * h / six) * s
+            }
+        }
+    }
+
+    /// Times in `[t_start, t_end]` at which the signal or its derivative is not continuous
+    pub fn breakpoints(&self, t_start: &F, t_end: &F) -> Vec<F> {
+        let n = self.times.len();
+        let t_first = self.times[0];
+        let t_last = self.times[n - 1];
+        // Breakpoints within the grid
+        let inner: Vec<F> = match (self.interpolation, self.extrapolation) {
+            (Interpolation::CubicSpline, Extrapolation::Hold) => vec![t_first, t_last],
+            (Interpolation::CubicSpline, Extrapolation::Periodic) => Vec::new(),
+            (_, Extrapolation::Hold) => self.times.clone(),
+            (_, Extrapolation::Periodic) => self.times[..n - 1].to_vec(),
+        };
+        let in_range = |t: &F| *t >= *t_start && *t <= *t_end;
+        match self.extrapolation {
+            Extrapolation::Hold => inner.into_iter().filter(in_range).collect(),
+            Extrapolation::Periodic => {
+                if inner.is_empty() || *t_end < *t_start {
+                    return Vec::new();
+                }
+                let period = self.period();
+                let mut shift = ((*t_start - t_first) / period).floor() * period;
+                let mut breakpoints = Vec::new();
+                while t_first + shift <= *t_end {
+                    breakpoints.extend(inner.iter().map(|t| *t + shift).filter(in_range));
+                    shift += period;
+                }
+                breakpoints
+            }
+        }
+    }
+}
+
+impl<I, F, P> TStops<'_, I, F, P>
+where
+    F: RealFloatLikeType,
+{
+    /// Stop at all breakpoints of the input signals in `[t_start, t_end]` without jumps.
+    /// Signals with [Extrapolation::Periodic] have breakpoints in every period, so the range
+    /// needs to cover the whole integration.
+    pub fn from_inputs(signals: &[&InputSignal<F>], t_start: &F, t_end: &F) -> Self {
+        let mut times: Vec<F> = signals
+            .iter()
+            .flat_map(|signal| signal.breakpoints(t_start, t_end))
+            .collect();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+        times.dedup();
+        TStops { times, jump: None }
+    }
+}
+
+/// Solves a tridiagonal system with the Thomas algorithm.
+/// `sub[i]` and `sup[i]` are the entries left and right of the diagonal in row `i`.
+fn solve_tridiagonal<F>(sub: &[F], diag: &[F], sup: &[F], rhs: &[F]) -> Vec<F>
+where
+    F: RealFloatLikeType,
+{
+    let n = diag.len();
+    let mut diag = diag.to_vec();
+    let mut x = rhs.to_vec();
+    for i in 1..n {
+        let factor = sub[i] / diag[i - 1];
+        diag[i] -= factor * sup[i - 1];
+        let x_prev = x[i - 1];
+        x[i] -= factor * x_prev;
+    }
+    x[n - 1] = x[n - 1] / diag[n - 1];
+    for i in (0..n - 1).rev() {
+        x[i] = (x[i] - sup[i] * x[i + 1]) / diag[i];
+    }
+    x
+}
+
+/// Right-hand side \\(6\left(\frac{u_{i+1}-u_i}{h_i} - \frac{u_i-u_{i-1}}{h_{i-1}}\right)\\)
+/// of the equation for the second derivative at a sample
+fn spline_rhs<F>(u_prev: F, u: F, u_next: F, h_prev: F, h_next: F) -> F
+where
+    F: RealFloatLikeType,
+{
+    F::from(6) * ((u_next - u) / h_next - (u - u_prev) / h_prev)
+}
+
+/// Second derivatives of the natural cubic spline which vanish at both ends
+fn natural_spline_curvatures<F>(times: &[F], values: &[F]) -> Vec<F>
+where
+    F: RealFloatLikeType,
+{
+    let n = times.len();
+    if n < 3 {
+        return vec![F::from(0); n];
+    }
+    // Equations of the inner samples
+    let h = |i: usize| times[i + 1] - times[i];
+    let sub: Vec<F> = (1..n - 1).map(|i| h(i - 1)).collect();
+    let sup: Vec<F> = (1..n - 1).map(h).collect();
+    let diag: Vec<F> = (1..n - 1).map(|i| F::from(2) * (h(i - 1) + h(i))).collect();
+    let rhs: Vec<F> = (1..n - 1)
+        .map(|i| spline_rhs(values[i - 1], values[i], values[i + 1], h(i - 1), h(i)))
+        .collect();
+    let mut curvatures = vec![F::from(0)];
+    curvatures.extend(solve_tridiagonal(&sub, &diag, &sup, &rhs));
+    curvatures.push(F::from(0));
+    curvatures
+}
+
+/// Second derivatives of the periodic cubic spline whose first and second derivatives agree
+/// at both ends of the period
+fn periodic_spline_curvatures<F>(times: &[F], values: &[F]) -> Result<Vec<F>, SolvingError>
+where
+    F: RealFloatLikeType,
+{
+    // Unknowns M_0, ..., M_{m-1} with M_m = M_0 form a cyclic tridiagonal system
+    let m = times.len() - 1;
+    let h = |i: usize| times[i + 1] - times[i];
+    let prev = |i: usize| (i + m - 1) % m;
+    let sub: Vec<F> = (0..m).map(|i| h(prev(i))).collect();
+    let sup: Vec<F> = (0..m).map(h).collect();
+    let diag: Vec<F> = (0..m).map(|i| F::from(2) * (h(prev(i)) + h(i))).collect();
+    let rhs: Vec<F> = (0..m)
+        .map(|i| spline_rhs(values[prev(i)], values[i], values[i + 1], h(prev(i)), h(i)))
+        .collect();
+
+    let mut curvatures = if m < 3 {
+        // Corner entries coincide with the off-diagonals
+        let mut matrix = vec![F::from(0); m * m];
+        for i in 0..m {
+            matrix[i * m + i] += diag[i];
+            matrix[i * m + prev(i)] += sub[i];
+            matrix[i * m + (i + 1) % m] += sup[i];
+        }
+        let lu = LuDecomposition::new(matrix, m)
+            .ok_or_else(|| SolvingError::from("Could not determine periodic spline"))?;
+        let mut curvatures = rhs;
+        lu.solve(&mut curvatures);
+        curvatures
+    } else {
+        // Sherman-Morrison: split off the corners as a rank-one update
+        let (corner_top, corner_bottom) = (sub[0], sup[m - 1]);
+        let gamma = -diag[0];
+        let mut diag_modified = diag.clone();
+        diag_modified[0] -= gamma;
+        diag_modified[m - 1] -= corner_bottom * corner_top / gamma;
+        let x = solve_tridiagonal(&sub, &diag_modified, &sup, &rhs);
+        let mut u = vec![F::from(0); m];
+        u[0] = gamma;
+        u[m - 1] = corner_bottom;
+        let z = solve_tridiagonal(&sub, &diag_modified, &sup, &u);
+        let factor = (x[0] + corner_top * x[m - 1] / gamma)
+            / (F::from(1) + z[0] + corner_top * z[m - 1] / gamma);
+        x.iter()
+            .zip(z.iter())
+            .map(|(xi, zi)| *xi - factor * *zi)
+            .collect()
+    };
+    curvatures.push(curvatures[0]);
+    Ok(curvatures)
+}
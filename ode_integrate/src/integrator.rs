@@ -0,0 +1,404 @@
+use crate::concepts::*;
+use crate::methods::{
+    get_adaptive_step_stepper, get_fixed_step_stepper, next_step_size, step_accepted, Direction,
+};
+use crate::solvers::{AdaptiveStepSolvers, FixedStepSolvers};
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Performs a single step of a [Stepper] with one of its methods
+type FixedStepFn<I, F, P, E> =
+    fn(&mut dyn Stepper<I, F, P, E>, &mut I, &F, &F, &P) -> Result<(), E>;
+
+/// Performs a single step of an [AdaptiveStepper] with one of its methods
+type AdaptiveStepFn<I, F, P, E> =
+    fn(&mut dyn AdaptiveStepper<I, F, P, E>, &mut I, &F, &F, &P) -> Result<Option<F>, E>;
+
+/// Stepper of an [Integrator] together with the method used for stepping
+enum IntegratorStepper<'a, I, F, P, E> {
+    /// Steps of fixed size
+    Fixed(Box<dyn Stepper<I, F, P, E> + 'a>, FixedStepFn<I, F, P, E>),
+    /// Steps controlled by an error estimate
+    Adaptive(
+        Box<dyn AdaptiveStepper<I, F, P, E> + 'a>,
+        AdaptiveStepFn<I, F, P, E>,
+    ),
+}
+
+/// # Counters of an [Integrator]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegratorStatistics {
+    /// Number of accepted steps
+    pub n_accepted: usize,
+    /// Number of steps rejected by the error control of adaptive steppers
+    pub n_rejected: usize,
+}
+
+/// # Lazy integration as an iterator
+/// Wraps a [Stepper] or [AdaptiveStepper] and performs steps only when the next item is
+/// requested. Every item is a pair \\((t, y)\\) of time and state.
+/// By default, one item is produced after every accepted step. With
+/// [Integrator::with_output_times] items are instead produced exactly at the given times.
+/// The initial state is not produced in step mode.
+/// With a negative step size, the integration proceeds backward in time and the end time and
+/// output times need to lie before the initial time.
+///
+/// Between two items, the integration can be stopped with [Integrator::stop], the step size can
+/// be changed with [Integrator::set_dt] and counters can be obtained with
+/// [Integrator::statistics]. Iteration ends at the end time, after the last output time, after
+/// stopping or when an error occurs. Errors can be obtained with [Integrator::error] or handled
+/// directly by calling [Integrator::step] instead of [Iterator::next].
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition { y0: [1.0], t0: 0.0, func: &rhs };
+/// let tolerances = Tolerances { rtol: 1e-8, atol: 1e-8 };
+/// let mut integrator = Integrator::adaptive_step_iter(
+///     ode_def, AdaptiveStepSolvers::BulirschStoer, 0.1, tolerances, 1.0);
+///
+/// // Integrate until the solution has decayed below 1%
+/// for (t, y) in integrator.by_ref().take_while(|(_, y)| y[0] > 0.01) {
+///     assert!((y[0] - (-t).exp()).abs() < 1e-6);
+/// }
+/// assert!(*integrator.t() > 100.0_f64.ln());
+/// assert!(integrator.statistics().n_accepted > 0);
+/// assert!(integrator.error().is_none());
+/// ```
+pub struct Integrator<'a, I, F, P, E> {
+    /// Stepper which performs the individual steps
+    stepper: IntegratorStepper<'a, I, F, P, E>,
+    /// Parameters of the ODE
+    p: P,
+    /// Current time
+    t: F,
+    /// Current value of the solution
+    y: I,
+    /// Step size which is attempted next
+    dt: F,
+    /// Time at which the integration ends
+    t_end: Option<F>,
+    /// Times at which items are produced instead of after every step
+    output_times: Option<Vec<F>>,
+    /// Maximal number of attempted steps
+    max_steps: Option<usize>,
+    /// Index of the next output time
+    next_output: usize,
+    /// Counters of steps
+    statistics: IntegratorStatistics,
+    /// Error which ended the iteration
+    error: Option<SolvingError>,
+    /// No further items are produced
+    finished: bool,
+}
+
+impl<'a, I, F, P, E> Integrator<'a, I, F, P, E>
+where
+    I: Clone + 'a,
+    F: FloatLikeType + 'a,
+    P: Clone + 'a,
+    E: Clone + 'a,
+{
+    /// Construct an integrator from a stepper
+    fn new(stepper: IntegratorStepper<'a, I, F, P, E>, y0: I, t0: F, dt: F, p: P) -> Self {
+        Integrator {
+            stepper,
+            p,
+            t: t0,
+            y: y0,
+            dt,
+            t_end: None,
+            output_times: None,
+            max_steps: None,
+            next_output: 0,
+            statistics: IntegratorStatistics::default(),
+            error: None,
+            finished: false,
+        }
+    }
+
+    /// Integrate an iterable type with steps of size `dt`
+    pub fn fixed_step_iter(
+        ode_def: OdeDefinition<'a, I, F, P, E>,
+        solver_type: FixedStepSolvers,
+        dt: F,
+        p: P,
+    ) -> Self
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+    {
+        let (y0, t0) = (ode_def.y0.clone(), ode_def.t0);
+        let stepper = IntegratorStepper::Fixed(
+            get_fixed_step_stepper(solver_type, ode_def),
+            |stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+        );
+        Self::new(stepper, y0, t0, dt, p)
+    }
+
+    /// Integrate a type which can be added via [MathVecLikeType] with steps of size `dt`
+    pub fn fixed_step_add(
+        ode_def: OdeDefinition<'a, I, F, P, E>,
+        solver_type: FixedStepSolvers,
+        dt: F,
+        p: P,
+    ) -> Self
+    where
+        I: MathVecLikeType<F>,
+        F: Mul<I, Output = I>,
+    {
+        let (y0, t0) = (ode_def.y0.clone(), ode_def.t0);
+        let stepper = IntegratorStepper::Fixed(
+            get_fixed_step_stepper(solver_type, ode_def),
+            |stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+        );
+        Self::new(stepper, y0, t0, dt, p)
+    }
+
+    /// Integrate an iterable type with adaptive steps starting with step size `dt`
+    pub fn adaptive_step_iter(
+        ode_def: OdeDefinition<'a, I, F, P, E>,
+        solver_type: AdaptiveStepSolvers,
+        dt: F,
+        tolerances: Tolerances<F>,
+        p: P,
+    ) -> Self
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType,
+    {
+        let (y0, t0) = (ode_def.y0.clone(), ode_def.t0);
+        let stepper = IntegratorStepper::Adaptive(
+            get_adaptive_step_stepper(solver_type, ode_def, tolerances),
+            |stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+        );
+        Self::new(stepper, y0, t0, dt, p)
+    }
+
+    /// Integrate a type which can be added via [MathVecLikeType] with adaptive steps starting
+    /// with step size `dt`. Since the error estimate is computed component-wise, `&I` still needs
+    /// to be iterable.
+    pub fn adaptive_step_add(
+        ode_def: OdeDefinition<'a, I, F, P, E>,
+        solver_type: AdaptiveStepSolvers,
+        dt: F,
+        tolerances: Tolerances<F>,
+        p: P,
+    ) -> Self
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        I: MathVecLikeType<F>,
+        F: RealFloatLikeType + Mul<I, Output = I>,
+    {
+        let (y0, t0) = (ode_def.y0.clone(), ode_def.t0);
+        let stepper = IntegratorStepper::Adaptive(
+            get_adaptive_step_stepper(solver_type, ode_def, tolerances),
+            |stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+        );
+        Self::new(stepper, y0, t0, dt, p)
+    }
+
+    /// End the integration exactly at `t_end`
+    pub fn with_t_end(mut self, t_end: F) -> Self {
+        self.t_end = Some(t_end);
+        self
+    }
+
+    /// Produce items exactly at the given times instead of after every step.
+    /// The times need to be ordered in the direction of integration given by the sign of the
+    /// step size. Steps are shortened to land on these times.
+    pub fn with_output_times(mut self, output_times: Vec<F>) -> Self {
+        self.output_times = Some(output_times);
+        self.next_output = 0;
+        self
+    }
+
+    /// Fail once more than `max_steps` steps were attempted including rejected ones
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Current time of the integration
+    pub fn t(&self) -> &F {
+        &self.t
+    }
+
+    /// Current value of the solution
+    pub fn y(&self) -> &I {
+        &self.y
+    }
+
+    /// Parameters of the ODE
+    pub fn p(&self) -> &P {
+        &self.p
+    }
+
+    /// Step size which is attempted next
+    pub fn dt(&self) -> &F {
+        &self.dt
+    }
+
+    /// Change the step size of the following steps. For adaptive steppers, this only changes the
+    /// size of the next attempted step. Its sign determines the direction of integration.
+    pub fn set_dt(&mut self, dt: F) {
+        self.dt = dt;
+    }
+
+    /// Counters of the steps taken so far
+    pub fn statistics(&self) -> &IntegratorStatistics {
+        &self.statistics
+    }
+
+    /// Error which ended the iteration
+    pub fn error(&self) -> Option<&SolvingError> {
+        self.error.as_ref()
+    }
+
+    /// No further items will be produced
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// End the iteration. The current time and state are kept.
+    pub fn stop(&mut self) {
+        self.finished = true;
+    }
+
+    /// Direction of integration given by the sign of the step size
+    fn direction(&self) -> Direction {
+        Direction::between(&F::from(0), &self.dt)
+    }
+
+    /// Attempt a single step which ends at most at `limit`. Returns if the step was accepted.
+    fn attempt_step(&mut self, limit: Option<F>) -> Result<bool, SolvingError>
+    where
+        E: Display,
+    {
+        if self.dt == F::from(0) {
+            return Err(SolvingError::from("Step size needs to be nonzero"));
+        }
+        let n_steps = self.statistics.n_accepted + self.statistics.n_rejected;
+        if self.max_steps.is_some_and(|max_steps| n_steps >= max_steps) {
+            return Err(SolvingError::from("Maximal number of steps exceeded"));
+        }
+        let direction = self.direction();
+        let (dtau, t_next) = match limit {
+            Some(limit) if !direction.before(&self.dt, &(limit - self.t)) => {
+                (limit - self.t, limit)
+            }
+            _ => (self.dt, self.t + self.dt),
+        };
+        match &mut self.stepper {
+            IntegratorStepper::Fixed(stepper, do_step) => {
+                if let Err(error) = do_step(stepper.as_mut(), &mut self.y, &self.t, &dtau, &self.p)
+                {
+                    return Err(SolvingError::from(alloc::format!("{error}")));
+                }
+                self.t = t_next;
+                self.statistics.n_accepted += 1;
+                Ok(true)
+            }
+            IntegratorStepper::Adaptive(stepper, do_step) => {
+                let err = match do_step(stepper.as_mut(), &mut self.y, &self.t, &dtau, &self.p) {
+                    Ok(err) => err,
+                    Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
+                };
+                let accepted = step_accepted(err);
+                if accepted {
+                    self.t = t_next;
+                    self.statistics.n_accepted += 1;
+                } else {
+                    self.statistics.n_rejected += 1;
+                }
+                self.dt = next_step_size(stepper.suggested_dt(), self.dt, accepted, &self.t)?;
+                Ok(accepted)
+            }
+        }
+    }
+
+    /// Advance to the next item. Returns `None` once the iteration has ended.
+    /// In contrast to [Iterator::next], errors are returned instead of being stored.
+    pub fn step(&mut self) -> Result<Option<(F, I)>, SolvingError>
+    where
+        E: Display,
+    {
+        if self.finished {
+            return Ok(None);
+        }
+        let direction = self.direction();
+        let target = match &self.output_times {
+            Some(output_times) => match output_times.get(self.next_output) {
+                Some(t_out)
+                    if self
+                        .t_end
+                        .is_none_or(|t_end| !direction.before(&t_end, t_out)) =>
+                {
+                    Some(*t_out)
+                }
+                _ => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+            },
+            None => None,
+        };
+        match target {
+            Some(t_out) => {
+                if direction.before(&t_out, &self.t) {
+                    return Err(SolvingError::from(
+                        "Output times need to be ordered in the direction of integration",
+                    ));
+                }
+                while direction.before(&self.t, &t_out) {
+                    self.attempt_step(Some(t_out))?;
+                }
+                self.next_output += 1;
+            }
+            None => {
+                if self
+                    .t_end
+                    .is_some_and(|t_end| !direction.before(&self.t, &t_end))
+                {
+                    self.finished = true;
+                    return Ok(None);
+                }
+                while !self.attempt_step(self.t_end)? {}
+            }
+        }
+        Ok(Some((self.t, self.y.clone())))
+    }
+}
+
+impl<'a, I, F, P, E> Iterator for Integrator<'a, I, F, P, E>
+where
+    I: Clone + 'a,
+    F: FloatLikeType + 'a,
+    P: Clone + 'a,
+    E: Clone + Display + 'a,
+{
+    type Item = (F, I);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(item) => item,
+            Err(error) => {
+                self.error = Some(error);
+                self.finished = true;
+                None
+            }
+        }
+    }
+}
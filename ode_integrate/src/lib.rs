@@ -12,11 +12,55 @@
 //! *f(y, dy, t, p)*
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+/// Configuration of solvers with a builder and a single entry point
+mod builder;
+/// Boundary value problems solved by shooting
+mod bvp;
+/// Snapshots of running integrations which can be resumed later
+mod checkpoint;
+/// Boundary value problems solved by collocation with mesh refinement
+mod collocation;
+/// Traits, type definitions and errors shared by all solvers
 mod concepts;
+/// Solving many problems at once
+mod ensemble;
+/// Parameter estimation from observed data
+mod fitting;
+/// Switching systems with discrete modes and guarded transitions
+mod hybrid;
+/// Input signals sampled on a grid
+mod input;
+/// Lazy step-by-step integration as an iterator
+mod integrator;
+/// Dense linear algebra used by implicit solvers
+mod linalg;
+/// Driver functions which integrate an ODE over a series of time points
 mod methods;
+/// Streaming trajectories to CSV and NumPy files
+#[cfg(feature = "std")]
+mod output;
+/// Plotting solutions to image files
+#[cfg(feature = "plot")]
+mod plot;
+/// Implementations of individual steppers
 mod solvers;
 
+pub use builder::*;
+pub use bvp::*;
+pub use checkpoint::*;
+pub use collocation::*;
 pub use concepts::*;
+pub use ensemble::*;
+pub use fitting::*;
+pub use hybrid::*;
+pub use input::*;
+pub use integrator::*;
 pub use methods::*;
+#[cfg(feature = "std")]
+pub use output::*;
+#[cfg(feature = "plot")]
+pub use plot::*;
 pub use solvers::*;
@@ -0,0 +1,176 @@
+use crate::concepts::*;
+
+use alloc::{vec, vec::Vec};
+
+/// # LU decomposition with partial pivoting
+/// Factorizes a dense square matrix stored in row-major order as \\(PA = LU\\)
+/// such that linear systems \\(Ax = b\\) can be solved repeatedly with the same matrix.
+pub(crate) struct LuDecomposition<F> {
+    /// Dimension of the matrix
+    n: usize,
+    /// Factors \\(L\\) (strictly below the diagonal, unit diagonal omitted) and \\(U\\)
+    lu: Vec<F>,
+    /// Row which was swapped with row `k` in the `k`-th elimination step
+    pivots: Vec<usize>,
+}
+
+impl<F> LuDecomposition<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Factorize the `n` x `n` matrix `a`. Returns `None` if the matrix is singular.
+    pub(crate) fn new(mut a: Vec<F>, n: usize) -> Option<Self> {
+        let mut pivots = Vec::with_capacity(n);
+        for k in 0..n {
+            let mut p = k;
+            for i in k + 1..n {
+                if a[i * n + k].abs() > a[p * n + k].abs() {
+                    p = i;
+                }
+            }
+            let pivot = a[p * n + k];
+            if pivot == F::from(0) || !pivot.is_finite() {
+                return None;
+            }
+            if p != k {
+                for j in 0..n {
+                    a.swap(k * n + j, p * n + j);
+                }
+            }
+            pivots.push(p);
+            for i in k + 1..n {
+                let l = a[i * n + k] / pivot;
+                a[i * n + k] = l;
+                for j in k + 1..n {
+                    let u = a[k * n + j];
+                    a[i * n + j] -= l * u;
+                }
+            }
+        }
+        Some(LuDecomposition { n, lu: a, pivots })
+    }
+
+    /// Overwrite `b` with the solution \\(x\\) of \\(Ax = b\\)
+    pub(crate) fn solve(&self, b: &mut [F]) {
+        let n = self.n;
+        for (k, p) in self.pivots.iter().enumerate() {
+            b.swap(k, *p);
+        }
+        for i in 0..n {
+            for j in 0..i {
+                let bj = b[j];
+                b[i] -= self.lu[i * n + j] * bj;
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                let bj = b[j];
+                b[i] -= self.lu[i * n + j] * bj;
+            }
+            b[i] = b[i] / self.lu[i * n + i];
+        }
+    }
+}
+
+/// # Banded LU decomposition with partial pivoting
+/// Factorizes a square matrix whose entries vanish more than `kl` columns left and `ku` columns
+/// right of the diagonal. Every row stores the columns within the band together with the
+/// `kl` additional columns on the right which fill in through row interchanges.
+pub(crate) struct BandedLuDecomposition<F> {
+    /// Dimension of the matrix
+    n: usize,
+    /// Number of subdiagonals
+    kl: usize,
+    /// Number of superdiagonals including the fill-in
+    ku: usize,
+    /// Entries of row `i` from column `i - kl` up to column `i + ku`
+    band: Vec<F>,
+    /// Row which was swapped with row `k` in the `k`-th elimination step
+    pivots: Vec<usize>,
+}
+
+impl<F> BandedLuDecomposition<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Zero matrix of dimension `n` with `kl` subdiagonals and `ku` superdiagonals which is
+    /// filled with [BandedLuDecomposition::set] before calling [BandedLuDecomposition::factorize]
+    pub(crate) fn zeros(n: usize, kl: usize, ku: usize) -> Self {
+        let ku = ku + kl;
+        BandedLuDecomposition {
+            n,
+            kl,
+            ku,
+            band: vec![F::from(0); n * (kl + ku + 1)],
+            pivots: Vec::with_capacity(n),
+        }
+    }
+
+    /// Position of the entry in row `i` and column `j` within the band
+    fn index(&self, i: usize, j: usize) -> usize {
+        i * (self.kl + self.ku + 1) + j + self.kl - i
+    }
+
+    /// Set the entry in row `i` and column `j` which needs to lie within the band
+    pub(crate) fn set(&mut self, i: usize, j: usize, value: F) {
+        let index = self.index(i, j);
+        self.band[index] = value;
+    }
+
+    /// Factorize the matrix in place. Returns `None` if it is singular.
+    pub(crate) fn factorize(mut self) -> Option<Self> {
+        let n = self.n;
+        for k in 0..n {
+            let last_row = (k + self.kl).min(n - 1);
+            let last_col = (k + self.ku).min(n - 1);
+            let mut p = k;
+            for i in k + 1..=last_row {
+                if self.band[self.index(i, k)].abs() > self.band[self.index(p, k)].abs() {
+                    p = i;
+                }
+            }
+            let pivot = self.band[self.index(p, k)];
+            if pivot == F::from(0) || !pivot.is_finite() {
+                return None;
+            }
+            if p != k {
+                for j in k..=last_col {
+                    let (a, b) = (self.index(k, j), self.index(p, j));
+                    self.band.swap(a, b);
+                }
+            }
+            self.pivots.push(p);
+            for i in k + 1..=last_row {
+                let index = self.index(i, k);
+                let l = self.band[index] / pivot;
+                self.band[index] = l;
+                for j in k + 1..=last_col {
+                    let u = self.band[self.index(k, j)];
+                    let index = self.index(i, j);
+                    self.band[index] -= l * u;
+                }
+            }
+        }
+        Some(self)
+    }
+
+    /// Overwrite `b` with the solution \\(x\\) of \\(Ax = b\\)
+    pub(crate) fn solve(&self, b: &mut [F]) {
+        let n = self.n;
+        for (k, p) in self.pivots.iter().enumerate() {
+            b.swap(k, *p);
+            let bk = b[k];
+            let last_row = (k + self.kl).min(n - 1);
+            for (i, bi) in b.iter_mut().enumerate().take(last_row + 1).skip(k + 1) {
+                *bi -= self.band[self.index(i, k)] * bk;
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..=(i + self.ku).min(n - 1) {
+                let bj = b[j];
+                b[i] -= self.band[self.index(i, j)] * bj;
+            }
+            b[i] = b[i] / self.band[self.index(i, i)];
+        }
+    }
+}
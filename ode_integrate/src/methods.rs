@@ -1,11 +1,18 @@
 use core::fmt::Display;
 use core::ops::Mul;
 
+use crate::checkpoint::AdaptiveIntegration;
 use crate::concepts::*;
-use crate::solvers::{Euler, FixedStepSolvers, Rk4};
+use crate::solvers::{
+    consistent_initial_values, AdaptiveStepSolvers, AdjointResult, AdjointSensitivity, Ark324L2SA,
+    Ark436L2SA, BulirschStoer, Etd1, Etdrk4, Euler, EulerMaruyama, ExponentialSolvers,
+    FixedStepSolvers, ForwardSensitivity, ImexSolvers, ImplicitEuler, ImplicitSolvers, LawsonRk4,
+    MethodOfSteps, Milstein, Rk4, SdeSolvers, Sdirk2, SensitivityMethod, Sri,
+};
 
 use alloc::boxed::Box;
 use alloc::{vec, vec::Vec};
+use rand::Rng;
 
 /// # Solve ODE for specified time points and single steps in between
 /// Solves a ODE supplied via initial parameters and RHS function
@@ -13,7 +20,8 @@ use alloc::{vec, vec::Vec};
 /// \\(t_0,\dots,t_n\\),
 /// the corresponding time intervals will be \\(\textrm{d}t_i = t_{i+1} - t_i\\).
 /// This means, the solving routine will do exactly \\(n\\) steps to obtain the results.
-
+/// The time points may also be decreasing in which case the ODE is integrated backward in time.
+///
 /// ## Example
 /// First we define the RHS of the ODE \\(f(y, t, p) = \dots\\).
 /// Then specify initial values \\(y_0\\), parameters \\(p\\), and time points \\(t_i\\).
@@ -72,41 +80,23 @@ where
     E: Display + Clone,
     for<'m> &'m V: IntoIterator<Item = &'m F>,
 {
-    let t_i = t_series.into_iter().next();
-    let t0 = match t_i {
-        Some(t) => t,
-        None => return Err(SolvingError::from("Did not supply enough time steps.")),
-    };
-    let ode_def = OdeDefinition {
-        y0: y0.clone(),
-        t0: *t0,
-        func: rhs,
-    };
-
-    let mut stepper = get_fixed_step_stepper(solver_type, ode_def);
-    let mut y = y0.clone();
-
-    // TODO In the future use the method: with_capacity(t_series.len())
-    // This is currently not possible since len() is a function inherent to std::Vec and not any trait.
-    let mut y_res = vec![y0.clone()];
-
-    let mut dt: F;
-    let mut t_further = t_series.into_iter();
-    t_further.next();
-    for (t_i, t_j) in t_series.into_iter().zip(t_further) {
-        dt = *t_j - *t_i;
-        if dt < F::from(0) {
-            return Err(SolvingError::from("Time steps need to be increasing"));
-        }
-        match stepper.do_step_iter(&mut y, t_i, &dt, p) {
-            Ok(()) => (),
-            Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
-        }
-        y_res.push(y.clone());
-    }
-    Ok(y_res)
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
 }
 
+/// # Solve ODE for specified time points and single steps in between
+/// Equivalent to [solve_ode_time_series_single_step_iter] but for types which can be added
+/// via [MathVecLikeType].
 pub fn solve_ode_time_series_single_step_add<'a, I, F, P, E, V>(
     y0: &I,
     t_series: &V,
@@ -121,41 +111,27 @@ where
     E: Display + Clone,
     for<'m> &'m V: IntoIterator<Item = &'m F>,
 {
-    let t_i = t_series.into_iter().next();
-    let t0 = match t_i {
-        Some(t) => t,
-        None => return Err(SolvingError::from("Did not supply enough time steps.")),
-    };
-    let ode_def = OdeDefinition {
-        y0: y0.clone(),
-        t0: *t0,
-        func: rhs,
-    };
-
-    let mut stepper = get_fixed_step_stepper(solver_type, ode_def);
-    let mut y = y0.clone();
-
-    // TODO In the future use the method: with_capacity(t_series.len())
-    // This is currently not possible since len() is a function inherent to std::Vec and not any trait.
-    let mut y_res = vec![y0.clone()];
-
-    let mut dt: F;
-    let mut t_further = t_series.into_iter();
-    t_further.next();
-    for (t_i, t_j) in t_series.into_iter().zip(t_further) {
-        dt = *t_j - *t_i;
-        if dt < F::from(0) {
-            return Err(SolvingError::from("Time steps need to be increasing"));
-        }
-        match stepper.do_step_add(&mut y, t_i, &dt, p) {
-            Ok(()) => (),
-            Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
-        }
-        y_res.push(y.clone());
-    }
-    Ok(y_res)
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
 }
 
+/// # Solve ODE for specified time points with a maximal step size in between
+/// Solves a ODE supplied via initial parameters and RHS function
+/// for the given time points. In between two time points, steps of size `dt` are taken and the
+/// last step is shortened such that it ends exactly at the next time point.
+/// For decreasing time points, the ODE is integrated backward in time with negative steps of
+/// magnitude `dt`.
+///
 /// ## Example
 /// First we define the RHS of the ODE \\(f(y, t, p) = \dots\\).
 /// Then specify initial values \\(y_0\\), parameters \\(p\\), and time points \\(t_i\\).
@@ -212,6 +188,326 @@ where
     P: Clone,
     E: Display + Clone,
     for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE for specified time points with a maximal step size in between
+/// Equivalent to [solve_ode_time_series_minimal_step_iter] but for types which can be added
+/// via [MathVecLikeType].
+pub fn solve_ode_time_series_minimal_step_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE for specified time points with equal steps in between
+/// Equivalent to [solve_ode_time_series_minimal_step_iter] but the interval between two time
+/// points is divided into the smallest number of equal steps which are not larger than `dt`.
+/// This avoids a very short last step when the time points are no multiples of `dt`.
+pub fn solve_ode_time_series_equidistributed_step_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType + core::fmt::Debug,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Equidistributed(dt, equidistributed_step),
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE for specified time points with equal steps in between
+/// Equivalent to [solve_ode_time_series_equidistributed_step_iter] but for types which can be
+/// added via [MathVecLikeType].
+pub fn solve_ode_time_series_equidistributed_step_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: RealFloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Equidistributed(dt, equidistributed_step),
+        None,
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with single steps and observe every step
+/// Equivalent to [solve_ode_time_series_single_step_iter] but calls the [Observer] after every
+/// step. If the observer returns [ObserverAction::Stop], the integration ends and the time and
+/// state at this point are returned in [ObservedSolution::stopped].
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = p * y[0];
+///     Ok(())
+/// }
+///
+/// let t_series: Vec<f64> = (0..100).map(|i| 0.1 * i as f64).collect();
+///
+/// // Stop as soon as the solution exceeds 2
+/// let mut observer = |_t: &f64, y: &mut [f64; 1], _dt: &f64, _err: Option<&f64>| {
+///     if y[0] > 2.0 {
+///         ObserverAction::Stop
+///     } else {
+///         ObserverAction::Continue
+///     }
+/// };
+/// let solution = solve_ode_time_series_single_step_observed_iter(&[1.0], &t_series, &rhs,
+/// &1.0, FixedStepSolvers::Euler, &mut observer).unwrap();
+///
+/// let (t_stop, y_stop) = solution.stopped.unwrap();
+/// assert!(y_stop[0] > 2.0);
+/// assert_eq!(t_stop, t_series[solution.y.len() - 1]);
+/// ```
+pub fn solve_ode_time_series_single_step_observed_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        Some(observer),
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+}
+
+/// # Solve ODE with single steps and observe every step
+/// Equivalent to [solve_ode_time_series_single_step_observed_iter] but for types which can be
+/// added via [MathVecLikeType].
+pub fn solve_ode_time_series_single_step_observed_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        Some(observer),
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+}
+
+/// # Solve ODE with a maximal step size and observe every step
+/// Equivalent to [solve_ode_time_series_minimal_step_iter] but calls the [Observer] after every
+/// step. If the observer returns [ObserverAction::Stop], the integration ends and the time and
+/// state at this point are returned in [ObservedSolution::stopped].
+pub fn solve_ode_time_series_minimal_step_observed_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        Some(observer),
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+}
+
+/// # Solve ODE with a maximal step size and observe every step
+/// Equivalent to [solve_ode_time_series_minimal_step_observed_iter] but for types which can be
+/// added via [MathVecLikeType].
+pub fn solve_ode_time_series_minimal_step_observed_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        Some(observer),
+        None,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+}
+
+/// # Solve ODE for specified time points with adaptive steps in between
+/// Solves a ODE supplied via initial parameters and RHS function
+/// for the given time points. In between two time points, the step size is controlled by the
+/// chosen [AdaptiveStepper] such that the local error stays within the given [Tolerances].
+/// The argument `dt` is used as initial step size.
+/// Decreasing time points integrate the ODE backward in time with negative steps.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs_arr(y: &[f64; 3], dy: &mut [f64; 3], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     dy[1] = -p * y[1];
+///     dy[2] = -p * y[2];
+///     Ok(())
+/// }
+///
+/// let y0 = [1.0 ,2.0, 3.0];
+/// let p = 2.0;
+/// let t_series = vec![0.0, 0.5, 1.0, 1.5, 2.0];
+/// let tolerances = Tolerances { rtol: 1e-10, atol: 1e-12 };
+///
+/// let y_res = solve_ode_time_series_adaptive_step_iter(&y0, &t_series, &rhs_arr, &p,
+/// AdaptiveStepSolvers::BulirschStoer, &0.1, &tolerances).unwrap();
+///
+/// for (ti, yi) in t_series.iter().zip(y_res.iter()) {
+///     assert!((yi[0] - (-p * ti).exp()).abs() < 1e-9);
+/// }
+/// ```
+pub fn solve_ode_time_series_adaptive_step_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
 {
     let t_initial = t_series.into_iter().next();
     let t0 = match t_initial {
@@ -224,50 +520,35 @@ where
         func: rhs,
     };
 
-    let mut stepper = get_fixed_step_stepper(solver_type, ode_def);
-    let mut y = y0.clone();
-
-    // TODO In the future use the method: with_capacity(t_series.len())
-    // This is currently not possible since len() is a function inherent to std::Vec and not any trait.
+    Direction::of_time_series(t_series)?;
+    let mut integration = AdaptiveIntegration::new(ode_def, solver_type, *dt, tolerances.clone());
     let mut y_res = vec![y0.clone()];
 
-    let mut dtau: F;
     let mut t_further = t_series.into_iter();
     t_further.next();
-    for (t_i, t_j) in t_series.into_iter().zip(t_further) {
-        let mut t = *t_i;
-        while t < *t_j {
-            if *dt > *t_j - t {
-                dtau = *t_j - t;
-            } else {
-                dtau = *dt;
-            }
-            if dtau < F::from(0) {
-                return Err(SolvingError::from("Time steps need to be increasing"));
-            }
-            // Do step and save
-            match stepper.do_step_iter(&mut y, &t, &dt, p) {
-                Ok(()) => (),
-                Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
-            }
-            t += dtau;
-        }
-        y_res.push(y.clone());
+    for t_j in t_further {
+        y_res.push(integration.integrate_to_iter(t_j, p)?.clone());
     }
     Ok(y_res)
 }
 
-pub fn solve_ode_time_series_minimal_step_add<'a, I, F, P, E, V>(
+/// # Solve ODE for specified time points with adaptive steps in between
+/// Equivalent to [solve_ode_time_series_adaptive_step_iter] but for types which can be added
+/// via [MathVecLikeType].
+/// Since the error estimate is computed component-wise, `&I` still needs to be iterable.
+pub fn solve_ode_time_series_adaptive_step_add<'a, I, F, P, E, V>(
     y0: &I,
     t_series: &V,
     rhs: RHS<'a, I, F, P, E>,
     p: &P,
-    solver_type: FixedStepSolvers,
+    solver_type: AdaptiveStepSolvers,
     dt: &F,
+    tolerances: &Tolerances<F>,
 ) -> Result<Vec<I>, SolvingError>
 where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
     I: MathVecLikeType<F>,
-    F: FloatLikeType + Mul<I, Output = I>,
+    F: RealFloatLikeType + Mul<I, Output = I>,
     P: Clone,
     E: Display + Clone,
     for<'m> &'m V: IntoIterator<Item = &'m F>,
@@ -283,53 +564,1463 @@ where
         func: rhs,
     };
 
-    let mut stepper = get_fixed_step_stepper(solver_type, ode_def);
-    let mut y = y0.clone();
-
-    // TODO In the future use the method: with_capacity(t_series.len())
-    // This is currently not possible since len() is a function inherent to std::Vec and not any trait.
+    Direction::of_time_series(t_series)?;
+    let mut integration = AdaptiveIntegration::new(ode_def, solver_type, *dt, tolerances.clone());
     let mut y_res = vec![y0.clone()];
 
-    let mut dtau: F;
     let mut t_further = t_series.into_iter();
     t_further.next();
-    for (t_i, t_j) in t_series.into_iter().zip(t_further) {
-        let mut t = *t_i;
-        while t < *t_j {
-            if *dt > *t_j - t {
-                dtau = *t_j - t;
-            } else {
-                dtau = *dt;
-            }
-            if dtau < F::from(0) {
-                return Err(SolvingError::from("Time steps need to be increasing"));
-            }
-            // Do step and save
-            match stepper.do_step_add(&mut y, &t, &dt, p) {
-                Ok(()) => (),
-                Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
-            }
-            t += dtau;
-        }
-        y_res.push(y.clone());
+    for t_j in t_further {
+        y_res.push(integration.integrate_to_add(t_j, p)?.clone());
     }
     Ok(y_res)
 }
 
-/// # Initializes fixed size stepper from argument
-/// Helper function to obtain a Stepper Trait Object from the enum of steppers
-pub fn get_fixed_step_stepper<'a, I, F, P, E>(
-    solver_type: FixedStepSolvers,
-    ode_def: OdeDefinition<'a, I, F, P, E>,
-) -> Box<dyn Stepper<I, F, P, E> + 'a>
-where
-    I: Clone,
-    F: FloatLikeType,
-    P: Clone,
-    E: Clone,
-{
-    match solver_type {
-        FixedStepSolvers::Euler => Box::new(Euler::from(ode_def)) as Box<dyn Stepper<I, F, P, E>>,
-        FixedStepSolvers::Rk4 => Box::new(Rk4::from(ode_def)) as Box<dyn Stepper<I, F, P, E>>,
+/// # Solve ODE with adaptive steps and observe every accepted step
+/// Equivalent to [solve_ode_time_series_adaptive_step_iter] but calls the [Observer] after every
+/// accepted step together with the normalized error estimate of the stepper.
+/// If the observer returns [ObserverAction::Stop], the integration ends and the time and state at
+/// this point are returned in [ObservedSolution::stopped].
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[1];
+///     dy[1] = -y[0];
+///     Ok(())
+/// }
+///
+/// let t_series = vec![0.0, 1.0, 2.0, 3.0];
+/// let tolerances = Tolerances { rtol: 1e-8, atol: 1e-8 };
+///
+/// // Keep the oscillator on the unit circle
+/// let mut n_steps = 0;
+/// let mut observer = |_t: &f64, y: &mut [f64; 2], _dt: &f64, err: Option<&f64>| {
+///     assert!(*err.unwrap() <= 1.0);
+///     let norm = (y[0] * y[0] + y[1] * y[1]).sqrt();
+///     y.iter_mut().for_each(|yi| *yi /= norm);
+///     n_steps += 1;
+///     ObserverAction::Continue
+/// };
+/// let solution = solve_ode_time_series_adaptive_step_observed_iter(&[1.0, 0.0], &t_series,
+/// &rhs, &(), AdaptiveStepSolvers::BulirschStoer, &0.1, &tolerances, &mut observer).unwrap();
+///
+/// assert!(solution.stopped.is_none());
+/// assert!(n_steps >= 3);
+/// for y in solution.y.iter() {
+///     assert!((y[0] * y[0] + y[1] * y[1] - 1.0).abs() < 1e-14);
+/// }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn solve_ode_time_series_adaptive_step_observed_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_adaptive_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        None,
+        None,
+        &mut |integration, t_j| integration.integrate_to_observed_iter(t_j, p, observer),
+    )
+}
+
+/// # Solve ODE with adaptive steps and observe every accepted step
+/// Equivalent to [solve_ode_time_series_adaptive_step_observed_iter] but for types which can be
+/// added via [MathVecLikeType].
+#[allow(clippy::too_many_arguments)]
+pub fn solve_ode_time_series_adaptive_step_observed_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    observer: &mut dyn Observer<I, F>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: MathVecLikeType<F>,
+    F: RealFloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_adaptive_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        None,
+        None,
+        &mut |integration, t_j| integration.integrate_to_observed_add(t_j, p, observer),
+    )
+}
+
+/// # Solve ODE with single steps and known discontinuities
+/// Equivalent to [solve_ode_time_series_single_step_iter] but steps are split at the stop times
+/// of [TStops]. At every stop, the state jumps if a [StateJump] was given and the stepper is
+/// restarted.
+pub fn solve_ode_time_series_single_step_tstops_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        None,
+        Some(tstops),
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with single steps and known discontinuities
+/// Equivalent to [solve_ode_time_series_single_step_tstops_iter] but for types which can be
+/// added via [MathVecLikeType].
+pub fn solve_ode_time_series_single_step_tstops_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        None,
+        Some(tstops),
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with a maximal step size and known discontinuities
+/// Equivalent to [solve_ode_time_series_minimal_step_iter] but the integration lands exactly on
+/// the stop times of [TStops]. At every stop, the state jumps if a [StateJump] was given and the
+/// stepper is restarted.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Elimination of a drug with rate p
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// // Add a dose of 1 every 8 hours
+/// let dose = |_t: &f64, y: &mut [f64; 1], _p: &f64| y[0] += 1.0;
+/// let tstops = TStops {
+///     times: vec![8.0, 16.0],
+///     jump: Some(&dose),
+/// };
+///
+/// let t_series = vec![0.0, 12.0, 24.0];
+/// let y_res = solve_ode_time_series_minimal_step_tstops_iter(&[1.0], &t_series, &rhs, &0.1,
+/// FixedStepSolvers::Rk4, &0.01, &tstops).unwrap();
+///
+/// let y_12 = (-1.2_f64).exp() + (-0.4_f64).exp();
+/// assert!((y_res[1][0] - y_12).abs() < 1e-2);
+/// ```
+pub fn solve_ode_time_series_minimal_step_tstops_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        None,
+        Some(tstops),
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with a maximal step size and known discontinuities
+/// Equivalent to [solve_ode_time_series_minimal_step_tstops_iter] but for types which can be
+/// added via [MathVecLikeType].
+pub fn solve_ode_time_series_minimal_step_tstops_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        None,
+        Some(tstops),
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with adaptive steps and known discontinuities
+/// Equivalent to [solve_ode_time_series_adaptive_step_iter] but the integration lands exactly on
+/// the stop times of [TStops]. At every stop, the state jumps if a [StateJump] was given and the
+/// stepper is restarted with the current step size.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_ode_time_series_adaptive_step_tstops_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_adaptive_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        Some(tstops),
+        None,
+        &mut |integration, t_j| {
+            integration
+                .integrate_to_iter(t_j, p)
+                .map(|_| ObserverAction::Continue)
+        },
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve ODE with adaptive steps and known discontinuities
+/// Equivalent to [solve_ode_time_series_adaptive_step_tstops_iter] but for types which can be
+/// added via [MathVecLikeType].
+#[allow(clippy::too_many_arguments)]
+pub fn solve_ode_time_series_adaptive_step_tstops_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    tstops: &TStops<I, F, P>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: MathVecLikeType<F>,
+    F: RealFloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    solve_adaptive_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        dt,
+        tolerances,
+        Some(tstops),
+        None,
+        &mut |integration, t_j| {
+            integration
+                .integrate_to_add(t_j, p)
+                .map(|_| ObserverAction::Continue)
+        },
+    )
+    .map(|solution| solution.y)
+}
+
+/// # Solve SDE for specified time points with a maximal step size in between
+/// Integrates a single realization of the SDE starting from `t0` and `y0` of the [SdeDefinition]
+/// and stores the state at every time point of `t_series`.
+/// In between two time points, steps of size at most `dt` are taken.
+/// The Brownian increments are drawn from `rng` which makes results reproducible when a seeded
+/// generator is used.
+/// Time points before `t0` integrate the SDE backward in time. The scheme is applied with negative
+/// steps and independent Brownian increments of variance \\(|dt|\\), so a realization starts
+/// from `y0` at `t0` and is not the time reversal of a forward realization which ends there.
+/// The reverse-time SDE of a forward process has a different drift.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+/// use rand::SeedableRng;
+///
+/// fn drift(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &(f64, f64)) -> Result<(), CalcError> {
+///     dy[0] = p.0 * y[0];
+///     Ok(())
+/// }
+///
+/// fn diffusion(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &(f64, f64)) -> Result<(), CalcError> {
+///     dy[0] = p.1 * y[0];
+///     Ok(())
+/// }
+///
+/// let sde_def = SdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     drift: &drift,
+///     diffusion: Diffusion::Diagonal(&diffusion),
+/// };
+/// let t_series: Vec<f64> = (0..11).map(|i| i as f64 * 0.1).collect();
+/// let rng = rand::rngs::SmallRng::seed_from_u64(42);
+///
+/// let y_res = solve_sde_time_series_iter(sde_def, &t_series, &(0.5, 0.2), SdeSolvers::Sri,
+/// &0.01, rng).unwrap();
+/// assert_eq!(y_res.len(), t_series.len());
+/// ```
+pub fn solve_sde_time_series_iter<'a, I, F, P, E, V, R>(
+    sde_def: SdeDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: SdeSolvers,
+    dt: &F,
+    rng: R,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: Display + 'a,
+    R: Rng + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t = sde_def.t0;
+    let mut y = sde_def.y0.clone();
+    let direction = Direction::from_initial_time(&t, t_series)?;
+    let mut dt = nonzero_step(dt, direction)?;
+    let mut stepper = get_sde_stepper(solver_type, sde_def, rng)?;
+
+    let mut y_res = Vec::new();
+    for t_j in t_series.into_iter() {
+        integrate_substeps(&mut t, t_j, &mut dt, direction, &mut |t, dtau, _dt| {
+            stepper
+                .do_step_iter(&mut y, t, dtau, p)
+                .map(|()| true)
+                .map_err(|error| SolvingError::from(alloc::format!("{error}")))
+        })?;
+        y_res.push(y.clone());
+    }
+    Ok(y_res)
+}
+
+/// # Solve DDE for specified time points
+/// Integrates the [DdeDefinition] with the [MethodOfSteps] using steps of size at most `dt`
+/// and evaluates the dense output at every time point of `t_series`.
+/// All time points need to be increasing and not before `t0` of the definition.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(
+///     _y: &[f64; 1],
+///     history: &dyn History<[f64; 1], f64>,
+///     dy: &mut [f64; 1],
+///     t: &f64,
+///     tau: &f64,
+/// ) -> Result<(), CalcError> {
+///     dy[0] = -history.at(&(t - tau))[0];
+///     Ok(())
+/// }
+///
+/// let dde_def = DdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     history: &|_t: &f64| [1.0],
+///     delays: vec![1.0],
+///     func: &rhs,
+/// };
+/// let t_series = vec![0.0, 0.5, 1.0, 1.5];
+///
+/// let y_res = solve_dde_time_series_iter(dde_def, &t_series, &1.0, &0.01).unwrap();
+/// // The exact solution is y(t) = 1 - t on the interval [0, 1]
+/// assert!((y_res[1][0] - 0.5).abs() < 1e-12);
+/// ```
+pub fn solve_dde_time_series_iter<'a, I, F, P, E, V>(
+    dde_def: DdeDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    E: Display,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t_end = dde_def.t0;
+    for t in t_series.into_iter() {
+        if *t < t_end {
+            return Err(SolvingError::from(
+                "Time steps need to be increasing and not before the initial time point",
+            ));
+        }
+        t_end = *t;
+    }
+
+    let mut solver = MethodOfSteps::new(dde_def, dt, &t_end)?;
+    while !solver.is_finished() {
+        match solver.do_step_iter(p) {
+            Ok(()) => (),
+            Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
+        }
+    }
+    Ok(t_series
+        .into_iter()
+        .map(|t| solver.history().at(t))
+        .collect())
+}
+
+/// # Solve DAE for specified time points with a maximal step size in between
+/// Integrates the [DaeDefinition] with an implicit stepper and stores the state at every time
+/// point of `t_series`. In between two time points, steps of size at most `dt` are taken.
+/// Before the integration starts, the algebraic components of `y0` are made consistent with
+/// [consistent_initial_values].
+/// Time points before `t0` integrate the DAE backward in time with negative steps.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // y0' = -y0 + y1
+/// //   0 = y1 - sin(t)
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -y[0] + y[1];
+///     dy[1] = y[1] - t.sin();
+///     Ok(())
+/// }
+///
+/// let dae_def = DaeDefinition {
+///     y0: [1.0, 0.0],
+///     t0: 0.0,
+///     func: &rhs,
+///     mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+/// };
+/// let t_series = vec![0.0, 0.5, 1.0];
+///
+/// let y_res = solve_dae_time_series_iter(dae_def, &t_series, &(), ImplicitSolvers::Sdirk2,
+/// &0.01).unwrap();
+/// // The algebraic component is given exactly
+/// assert!((y_res[2][1] - 1f64.sin()).abs() < 1e-12);
+/// ```
+pub fn solve_dae_time_series_iter<'a, I, F, P, E, V>(
+    dae_def: DaeDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: ImplicitSolvers,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: Display + From<CalcError> + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t = dae_def.t0;
+    let direction = Direction::from_initial_time(&t, t_series)?;
+    let mut dt = nonzero_step(dt, direction)?;
+    let mut y = consistent_initial_values(&dae_def, p)?;
+    let mut stepper = get_implicit_stepper(solver_type, dae_def)?;
+
+    let mut y_res = Vec::new();
+    for t_j in t_series.into_iter() {
+        integrate_substeps(&mut t, t_j, &mut dt, direction, &mut |t, dtau, _dt| {
+            stepper
+                .do_step_iter(&mut y, t, dtau, p)
+                .map(|()| true)
+                .map_err(|error| SolvingError::from(alloc::format!("{error}")))
+        })?;
+        y_res.push(y.clone());
+    }
+    Ok(y_res)
+}
+
+/// # Solve semilinear ODE for specified time points with a maximal step size in between
+/// Integrates the [SemilinearDefinition] with an exponential integrator and stores the state at
+/// every time point of `t_series`. In between two time points, steps of size at most `dt` are
+/// taken. Since the linear part is treated exactly, the step size is only limited by the
+/// nonlinear part.
+/// Time points before `t0` integrate the ODE backward in time with negative steps.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // y' = -100 y + 100 with solution y = 1 - exp(-100 t)
+/// fn nonlinear(_y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = 100.0;
+///     Ok(())
+/// }
+///
+/// let semi_def = SemilinearDefinition {
+///     y0: [0.0],
+///     t0: 0.0,
+///     linear: LinearOperator::Diagonal(vec![-100.0]),
+///     nonlinear: &nonlinear,
+/// };
+/// let t_series = vec![0.0, 0.01, 0.5];
+///
+/// let y_res = solve_semilinear_time_series_iter(semi_def, &t_series, &(),
+/// ExponentialSolvers::Etdrk4, &0.5).unwrap();
+/// // Exponential integrators are exact for constant nonlinear parts
+/// assert!((y_res[1][0] - (1.0 - (-1f64).exp())).abs() < 1e-12);
+/// ```
+pub fn solve_semilinear_time_series_iter<'a, I, F, P, E, V>(
+    semi_def: SemilinearDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: ExponentialSolvers,
+    dt: &F,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: Display + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t = semi_def.t0;
+    let mut y = semi_def.y0.clone();
+    let direction = Direction::from_initial_time(&t, t_series)?;
+    let mut dt = nonzero_step(dt, direction)?;
+    let mut stepper = get_exponential_stepper(solver_type, semi_def)?;
+
+    let mut y_res = Vec::new();
+    for t_j in t_series.into_iter() {
+        integrate_substeps(&mut t, t_j, &mut dt, direction, &mut |t, dtau, _dt| {
+            stepper
+                .do_step_iter(&mut y, t, dtau, p)
+                .map(|()| true)
+                .map_err(|error| SolvingError::from(alloc::format!("{error}")))
+        })?;
+        y_res.push(y.clone());
+    }
+    Ok(y_res)
+}
+
+/// # Solve additive ODE for specified time points with adaptive steps in between
+/// Integrates the [AdditiveDefinition] with an implicit-explicit additive Runge-Kutta method and
+/// stores the state at every time point of `t_series`. The step size starts at `dt` and is
+/// adapted such that the embedded error estimate satisfies the tolerances.
+/// Only the stiff part needs to be solved implicitly, while the step size is limited by the
+/// non-stiff part and the accuracy.
+/// Time points before `t0` integrate the ODE backward in time with negative steps.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Logistic growth with fast linear decay
+/// fn reaction(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[0] * (1.0 - y[0]);
+///     Ok(())
+/// }
+///
+/// fn decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -1000.0 * y[0];
+///     Ok(())
+/// }
+///
+/// let add_def = AdditiveDefinition {
+///     y0: [0.5],
+///     t0: 0.0,
+///     explicit: &reaction,
+///     implicit: &decay,
+/// };
+/// let t_series = vec![0.0, 1.0, 2.0];
+/// let tolerances = Tolerances { rtol: 1e-6, atol: 1e-10 };
+///
+/// let y_res = solve_additive_time_series_iter(add_def, &t_series, &(), ImexSolvers::Ark436L2SA,
+/// &0.01, &tolerances).unwrap();
+/// assert!(y_res[2][0].abs() < 1e-10);
+/// ```
+pub fn solve_additive_time_series_iter<'a, I, F, P, E, V>(
+    add_def: AdditiveDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    solver_type: ImexSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+) -> Result<Vec<I>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: Display + 'a,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t = add_def.t0;
+    let mut y = add_def.y0.clone();
+    let direction = Direction::from_initial_time(&t, t_series)?;
+    let mut h = nonzero_step(dt, direction)?;
+    let mut stepper = get_imex_stepper(solver_type, add_def, tolerances.clone());
+
+    let mut y_res = Vec::new();
+    for t_j in t_series.into_iter() {
+        integrate_substeps(&mut t, t_j, &mut h, direction, &mut |t, dtau, h| {
+            let err = stepper
+                .do_step_iter(&mut y, t, dtau, p)
+                .map_err(|error| SolvingError::from(alloc::format!("{error}")))?;
+            let accepted = step_accepted(err);
+            *h = next_step_size(stepper.suggested_dt(), *h, accepted, t)?;
+            Ok(accepted)
+        })?;
+        y_res.push(y.clone());
+    }
+    Ok(y_res)
+}
+
+/// # Solve ODE and forward sensitivities for specified time points
+/// Integrates the state together with the sensitivities \\(S = \partial y/\partial p\\) of a
+/// [SensitivityDefinition] using the [ForwardSensitivity] stepper with steps of size at most
+/// `dt`. Returns the state and the \\(n \times n_p\\) sensitivity matrix in row-major order at
+/// every time point of `t_series`.
+/// Time points before `t0` integrate the ODE backward in time with negative steps.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Exponential decay y' = -p y with solution y = exp(-p t)
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+///     dy[0] = -p[0] * y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     func: &rhs,
+/// };
+/// let t_series = vec![0.0, 1.0];
+///
+/// let res = solve_sensitivity_time_series_iter(ode_def.into(), &t_series, &[0.5],
+/// SensitivityMethod::Simultaneous, &0.01).unwrap();
+/// // The sensitivity is dy/dp = -t exp(-p t)
+/// let (y, s) = &res[1];
+/// assert!((y[0] - (-0.5f64).exp()).abs() < 1e-8);
+/// assert!((s[0] + (-0.5f64).exp()).abs() < 1e-6);
+/// ```
+pub fn solve_sensitivity_time_series_iter<'a, I, F, P, E, V>(
+    sens_def: SensitivityDefinition<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    method: SensitivityMethod,
+    dt: &F,
+) -> Result<Vec<(I, Vec<F>)>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display + From<CalcError>,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let mut t = sens_def.t0;
+    let mut y = sens_def.y0.clone();
+    let direction = Direction::from_initial_time(&t, t_series)?;
+    let mut dt = nonzero_step(dt, direction)?;
+    let mut s = vec![F::from(0); (&y).into_iter().count() * p.into_iter().count()];
+    let mut stepper = ForwardSensitivity::new(sens_def, method)?;
+
+    let mut res = Vec::new();
+    for t_j in t_series.into_iter() {
+        integrate_substeps(&mut t, t_j, &mut dt, direction, &mut |t, dtau, _dt| {
+            stepper
+                .do_step_iter(&mut y, &mut s, t, dtau, p)
+                .map(|()| true)
+                .map_err(|error| SolvingError::from(alloc::format!("{error}")))
+        })?;
+        res.push((y.clone(), s.clone()));
+    }
+    Ok(res)
+}
+
+/// # Adjoint sensitivities of an objective for specified time points
+/// Computes the [Objective] \\(G\\) of the solution at the time points `t_series` together with
+/// \\(dG/dp\\) and \\(dG/dy_0\\) using [AdjointSensitivity] with steps of size at most `dt`.
+/// Every `checkpoint_every`-th state of the forward pass is stored for the backward pass.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Exponential decay y' = -p y with solution y = y0 exp(-p t)
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+///     dy[0] = -p[0] * y[0];
+///     Ok(())
+/// }
+///
+/// // Final value G = y(T)
+/// fn final_value(y: &[f64; 1], _t: &f64, _p: &[f64; 1]) -> Result<f64, CalcError> {
+///     Ok(y[0])
+/// }
+///
+/// let ode_def = OdeDefinition {
+///     y0: [1.0],
+///     t0: 0.0,
+///     func: &rhs,
+/// };
+/// let objective = Objective {
+///     kind: ObjectiveKind::Pointwise,
+///     func: &final_value,
+///     gradient: None,
+/// };
+///
+/// let res = solve_adjoint_sensitivity_iter(ode_def.into(), objective, &vec![2.0], &[0.5],
+/// &0.01, 20).unwrap();
+/// // dG/dp = -T exp(-p T) and dG/dy0 = exp(-p T)
+/// assert!((res.dg_dp[0] + 2.0 * (-1f64).exp()).abs() < 1e-6);
+/// assert!((res.dg_dy0[0] - (-1f64).exp()).abs() < 1e-6);
+/// ```
+pub fn solve_adjoint_sensitivity_iter<'a, I, F, P, E, V>(
+    sens_def: SensitivityDefinition<'a, I, F, P, E>,
+    objective: Objective<'a, I, F, P, E>,
+    t_series: &V,
+    p: &P,
+    dt: &F,
+    checkpoint_every: usize,
+) -> Result<AdjointResult<F>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    E: Display,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let times: Vec<F> = t_series.into_iter().copied().collect();
+    AdjointSensitivity::new(sens_def, objective, checkpoint_every)?.solve(&times, p, dt)
+}
+
+/// Performs a single step of a [Stepper] with one of its methods
+pub(crate) type FixedStep<'s, I, F, P, E> =
+    &'s dyn Fn(&mut dyn Stepper<I, F, P, E>, &mut I, &F, &F, &P) -> Result<(), E>;
+
+/// Integrates an [AdaptiveIntegration] up to the given time with an [Observer]
+type ObservedIntegration<'s, 'a, I, F, P, E> =
+    &'s mut dyn FnMut(
+        &mut AdaptiveIntegration<'a, I, F, P, E>,
+        &F,
+    ) -> Result<ObserverAction, SolvingError>;
+
+/// Direction in which an ODE is integrated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Direction {
+    /// Towards larger times with positive step sizes
+    Forward,
+    /// Towards smaller times with negative step sizes
+    Backward,
+}
+
+impl Direction {
+    /// Direction from `t_start` towards `t_end`
+    pub(crate) fn between<F>(t_start: &F, t_end: &F) -> Self
+    where
+        F: PartialOrd,
+    {
+        if t_end < t_start {
+            Direction::Backward
+        } else {
+            Direction::Forward
+        }
+    }
+
+    /// Direction of the time points of `t_series`. Fails if they are not monotonic.
+    pub(crate) fn of_time_series<F, V>(t_series: &V) -> Result<Self, SolvingError>
+    where
+        F: PartialOrd,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+    {
+        let mut direction = None;
+        let mut t_further = t_series.into_iter();
+        t_further.next();
+        for (t_i, t_j) in t_series.into_iter().zip(t_further) {
+            if t_i == t_j {
+                continue;
+            }
+            let step = Direction::between(t_i, t_j);
+            if direction.is_some_and(|direction| direction != step) {
+                return Err(SolvingError::from(
+                    "Time steps need to be either increasing or decreasing",
+                ));
+            }
+            direction = Some(step);
+        }
+        Ok(direction.unwrap_or(Direction::Forward))
+    }
+
+    /// Direction from `t0` through the time points of `t_series`.
+    /// Fails if they are not monotonic.
+    pub(crate) fn from_initial_time<F, V>(t0: &F, t_series: &V) -> Result<Self, SolvingError>
+    where
+        F: FloatLikeType,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+    {
+        let times: Vec<F> = core::iter::once(*t0)
+            .chain(t_series.into_iter().copied())
+            .collect();
+        Direction::of_time_series::<F, Vec<F>>(&times)
+    }
+
+    /// Checks if `t` comes strictly before `t_end` when integrating in this direction
+    pub(crate) fn before<F>(self, t: &F, t_end: &F) -> bool
+    where
+        F: PartialOrd,
+    {
+        match self {
+            Direction::Forward => t < t_end,
+            Direction::Backward => t > t_end,
+        }
+    }
+
+    /// Step size with the magnitude of `dt` which points in this direction
+    pub(crate) fn orient<F>(self, dt: F) -> F
+    where
+        F: FloatLikeType,
+    {
+        if (dt < F::from(0)) == (self == Direction::Backward) {
+            dt
+        } else {
+            -dt
+        }
+    }
+}
+
+/// Step size with the magnitude of `dt` which points in the direction of integration.
+/// Fails if it is zero.
+fn nonzero_step<F>(dt: &F, direction: Direction) -> Result<F, SolvingError>
+where
+    F: FloatLikeType,
+{
+    if *dt == F::from(0) {
+        return Err(SolvingError::from("Step size needs to be nonzero"));
+    }
+    Ok(direction.orient(*dt))
+}
+
+/// Advances `t` to `t_end` with steps of size `dt` followed by a shorter last step which ends
+/// exactly at `t_end`. `step` performs a step of the given size from the given time and returns
+/// if it was accepted. It may change `dt` for the next step. Rejected steps are repeated from the
+/// same time.
+fn integrate_substeps<F>(
+    t: &mut F,
+    t_end: &F,
+    dt: &mut F,
+    direction: Direction,
+    step: &mut dyn FnMut(&F, &F, &mut F) -> Result<bool, SolvingError>,
+) -> Result<(), SolvingError>
+where
+    F: FloatLikeType,
+{
+    while direction.before(t, t_end) {
+        let (dtau, last) = if !direction.before(dt, &(*t_end - *t)) {
+            (*t_end - *t, true)
+        } else {
+            (*dt, false)
+        };
+        if step(t, &dtau, dt)? {
+            *t = if last { *t_end } else { *t + dtau };
+        }
+    }
+    Ok(())
+}
+
+/// Stop times of `tstops` after `t0` in the direction of integration.
+/// Fails if they are not ordered in this direction.
+fn stop_times<'s, I, F, P>(
+    tstops: Option<&'s TStops<I, F, P>>,
+    t0: &F,
+    direction: Direction,
+) -> Result<&'s [F], SolvingError>
+where
+    F: PartialOrd,
+{
+    let times = match tstops {
+        Some(tstops) => tstops.times.as_slice(),
+        None => return Ok(&[]),
+    };
+    if times.windows(2).any(|w| direction.before(&w[1], &w[0])) {
+        return Err(SolvingError::from(
+            "Stop times need to be ordered in the direction of integration",
+        ));
+    }
+    let first = times
+        .iter()
+        .position(|t_stop| direction.before(t0, t_stop))
+        .unwrap_or(times.len());
+    Ok(&times[first..])
+}
+
+/// Next time at which the integration needs to land when heading for `t_end`
+/// and whether it is a stop time
+fn next_target<F>(stops: &[F], t_end: &F, direction: Direction) -> (F, bool)
+where
+    F: Copy + PartialOrd,
+{
+    match stops.first() {
+        Some(t_stop) if !direction.before(t_end, t_stop) => (*t_stop, true),
+        _ => (*t_end, false),
+    }
+}
+
+/// Division of the interval between two time points into steps of a fixed stepper
+#[derive(Clone, Copy)]
+pub(crate) enum Substeps<'s, F> {
+    /// A single step
+    Single,
+    /// Steps of size `dt` followed by a shorter final step
+    Maximal(&'s F),
+    /// The smallest number of equal steps which are not larger than `dt`.
+    /// Their size is determined by the given function, usually [equidistributed_step].
+    Equidistributed(&'s F, fn(F, F, Direction) -> F),
+}
+
+/// Size of equal steps which cover `length` with steps of size at most `dt`.
+/// Both `length` and `dt` point in the direction of integration.
+pub(crate) fn equidistributed_step<F>(length: F, dt: F, direction: Direction) -> F
+where
+    F: RealFloatLikeType,
+{
+    let mut n_steps = (length / dt).ceil().max(F::from(1));
+    // The quotient may be rounded up to the next integer
+    if n_steps > F::from(1) && !direction.before(&((n_steps - F::from(1)) * dt), &length) {
+        n_steps -= F::from(1);
+    }
+    length / n_steps
+}
+
+/// Receives the solution at every time point which was reached
+pub(crate) type Record<'s, I, F> = &'s mut dyn FnMut(&F, &I) -> Result<(), SolvingError>;
+
+/// Integrates with a fixed stepper over the time points of `t_series`.
+/// Between two time points, the steps are chosen according to `substeps` and the last step ends
+/// exactly at the next time point. The observer is called after every step.
+/// Steps end exactly at the stop times of `tstops` where the state jumps and the stepper is
+/// restarted.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn solve_fixed_step_observed<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    substeps: Substeps<F>,
+    observer: Option<&mut dyn Observer<I, F>>,
+    tstops: Option<&TStops<I, F, P>>,
+    do_step: FixedStep<I, F, P, E>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    // TODO In the future use the method: with_capacity(t_series.len())
+    // This is currently not possible since len() is a function inherent to std::Vec and not any trait.
+    let mut y_res = Vec::new();
+    let stopped = integrate_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        substeps,
+        observer,
+        tstops,
+        None,
+        do_step,
+        &mut |_t, y| {
+            y_res.push(y.clone());
+            Ok(())
+        },
+    )?;
+    Ok(ObservedSolution { y: y_res, stopped })
+}
+
+/// Same as [solve_fixed_step_observed] but passes the solution at every time point which was
+/// reached to `record` instead of collecting it. Fails once more than `max_steps` steps were
+/// taken.
+/// Returns the time and state at which the observer stopped the integration.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn integrate_fixed_step_observed<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    substeps: Substeps<F>,
+    mut observer: Option<&mut dyn Observer<I, F>>,
+    tstops: Option<&TStops<I, F, P>>,
+    max_steps: Option<usize>,
+    do_step: FixedStep<I, F, P, E>,
+    record: Record<I, F>,
+) -> Result<Option<(F, I)>, SolvingError>
+where
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let t_initial = t_series.into_iter().next();
+    let t0 = match t_initial {
+        Some(t) => t,
+        None => return Err(SolvingError::from("Did not supply enough time steps.")),
+    };
+    let direction = Direction::of_time_series(t_series)?;
+    let dt = match substeps {
+        Substeps::Maximal(dt) | Substeps::Equidistributed(dt, _) if *dt == F::from(0) => {
+            return Err(SolvingError::from("Step size needs to be nonzero"));
+        }
+        Substeps::Maximal(dt) | Substeps::Equidistributed(dt, _) => direction.orient(*dt),
+        Substeps::Single => F::from(0),
+    };
+    let mut stops = stop_times(tstops, t0, direction)?;
+    let ode_def = OdeDefinition {
+        y0: y0.clone(),
+        t0: *t0,
+        func: rhs,
+    };
+
+    let mut stepper = get_fixed_step_stepper(solver_type.clone(), ode_def);
+    let mut y = y0.clone();
+    let mut n_steps = 0;
+    record(t0, &y)?;
+
+    let mut observe = |t: &F, y: &mut I, dt: &F| match observer.as_mut() {
+        Some(observer) => observer.observe(t, y, dt, None) == ObserverAction::Stop,
+        None => false,
+    };
+
+    let mut t_further = t_series.into_iter();
+    t_further.next();
+    for (t_i, t_j) in t_series.into_iter().zip(t_further) {
+        let mut t = *t_i;
+        let mut stop = false;
+        while direction.before(&t, t_j) && !stop {
+            let (t_next, at_stop) = next_target(stops, t_j, direction);
+            let step = match substeps {
+                Substeps::Single => t_next - t,
+                Substeps::Maximal(_) => dt,
+                Substeps::Equidistributed(_, equidistribute) => {
+                    equidistribute(t_next - t, dt, direction)
+                }
+            };
+            while direction.before(&t, &t_next) && !stop {
+                let remaining = t_next - t;
+                // Equal steps do not add up exactly, so the last one takes the remainder
+                let last = match substeps {
+                    Substeps::Equidistributed(..) => {
+                        direction.before(&(F::from(2) * remaining), &(F::from(3) * step))
+                    }
+                    _ => !direction.before(&step, &remaining),
+                };
+                let dtau = if last { remaining } else { step };
+                if max_steps.is_some_and(|max_steps| n_steps >= max_steps) {
+                    return Err(SolvingError::from("Maximal number of steps exceeded"));
+                }
+                n_steps += 1;
+                // Do step and save
+                match do_step(stepper.as_mut(), &mut y, &t, &dtau, p) {
+                    Ok(()) => (),
+                    Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
+                }
+                t = if last { t_next } else { t + dtau };
+                stop = observe(&t, &mut y, &dtau);
+            }
+            if at_stop && !stop {
+                if let Some(jump) = tstops.and_then(|tstops| tstops.jump) {
+                    jump(&t, &mut y, p);
+                }
+                let ode_def = OdeDefinition {
+                    y0: y.clone(),
+                    t0: t,
+                    func: rhs,
+                };
+                stepper = get_fixed_step_stepper(solver_type.clone(), ode_def);
+                while stops
+                    .first()
+                    .is_some_and(|t_stop| !direction.before(&t, t_stop))
+                {
+                    stops = &stops[1..];
+                }
+            }
+        }
+        if !direction.before(&t, t_j) {
+            record(t_j, &y)?;
+        }
+        if stop {
+            return Ok(Some((t, y)));
+        }
+    }
+    Ok(None)
+}
+
+/// Integrates with an adaptive stepper over the time points of `t_series`.
+/// `integrate_to` advances the integration to the next time point while calling the observer.
+/// Fails once more than `max_steps` steps were attempted including rejected ones.
+/// The integration also lands on the stop times of `tstops` where the state jumps and the
+/// stepper is restarted.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn solve_adaptive_step_observed<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: AdaptiveStepSolvers,
+    dt: &F,
+    tolerances: &Tolerances<F>,
+    tstops: Option<&TStops<I, F, P>>,
+    max_steps: Option<usize>,
+    integrate_to: ObservedIntegration<'_, 'a, I, F, P, E>,
+) -> Result<ObservedSolution<I, F>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    let t_initial = t_series.into_iter().next();
+    let t0 = match t_initial {
+        Some(t) => t,
+        None => return Err(SolvingError::from("Did not supply enough time steps.")),
+    };
+    let direction = Direction::of_time_series(t_series)?;
+    let mut stops = stop_times(tstops, t0, direction)?;
+    let ode_def = OdeDefinition {
+        y0: y0.clone(),
+        t0: *t0,
+        func: rhs,
+    };
+
+    let mut integration = AdaptiveIntegration::new(ode_def, solver_type, *dt, tolerances.clone());
+    if let Some(max_steps) = max_steps {
+        integration = integration.with_max_steps(max_steps);
+    }
+    let mut y_res = vec![y0.clone()];
+
+    let mut t_further = t_series.into_iter();
+    t_further.next();
+    for t_j in t_further {
+        let mut action = ObserverAction::Continue;
+        while direction.before(integration.t(), t_j) && action == ObserverAction::Continue {
+            let (t_next, at_stop) = next_target(stops, t_j, direction);
+            action = integrate_to(&mut integration, &t_next)?;
+            if at_stop && action == ObserverAction::Continue {
+                let mut y = integration.y().clone();
+                if let Some(jump) = tstops.and_then(|tstops| tstops.jump) {
+                    jump(integration.t(), &mut y, p);
+                }
+                integration.restart(y);
+                while stops
+                    .first()
+                    .is_some_and(|t_stop| !direction.before(integration.t(), t_stop))
+                {
+                    stops = &stops[1..];
+                }
+            }
+        }
+        if !direction.before(integration.t(), t_j) {
+            y_res.push(integration.y().clone());
+        }
+        if action == ObserverAction::Stop {
+            return Ok(ObservedSolution {
+                y: y_res,
+                stopped: Some((*integration.t(), integration.y().clone())),
+            });
+        }
+    }
+    Ok(ObservedSolution {
+        y: y_res,
+        stopped: None,
+    })
+}
+
+/// Decides from the normalized error estimate if a step was accepted
+pub(crate) fn step_accepted<F>(err: Option<F>) -> bool
+where
+    F: FloatLikeType,
+{
+    match err {
+        Some(e) => e <= F::from(1),
+        None => true,
+    }
+}
+
+/// Determines the step size of the next step and checks that integration can proceed
+pub(crate) fn next_step_size<F>(
+    suggested: Option<F>,
+    h: F,
+    accepted: bool,
+    t: &F,
+) -> Result<F, SolvingError>
+where
+    F: FloatLikeType,
+{
+    let h_new = match (suggested, accepted) {
+        (Some(h_new), _) => h_new,
+        (None, true) => h,
+        (None, false) => {
+            return Err(SolvingError::from(
+                "Step was rejected but no new step size was suggested",
+            ))
+        }
+    };
+    if matches!(
+        (*t + h_new).partial_cmp(t),
+        None | Some(core::cmp::Ordering::Equal)
+    ) {
+        return Err(SolvingError::from("Step size became too small"));
+    }
+    Ok(h_new)
+}
+
+/// # Initializes fixed size stepper from argument
+/// Helper function to obtain a Stepper Trait Object from the enum of steppers
+pub fn get_fixed_step_stepper<'a, I, F, P, E>(
+    solver_type: FixedStepSolvers,
+    ode_def: OdeDefinition<'a, I, F, P, E>,
+) -> Box<dyn Stepper<I, F, P, E> + 'a>
+where
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Clone,
+{
+    match solver_type {
+        FixedStepSolvers::Euler => Box::new(Euler::from(ode_def)) as Box<dyn Stepper<I, F, P, E>>,
+        FixedStepSolvers::Rk4 => Box::new(Rk4::from(ode_def)) as Box<dyn Stepper<I, F, P, E>>,
+    }
+}
+
+/// # Initializes adaptive stepper from argument
+/// Helper function to obtain a AdaptiveStepper Trait Object from the enum of steppers
+pub fn get_adaptive_step_stepper<'a, I, F, P, E>(
+    solver_type: AdaptiveStepSolvers,
+    ode_def: OdeDefinition<'a, I, F, P, E>,
+    tolerances: Tolerances<F>,
+) -> Box<dyn AdaptiveStepper<I, F, P, E> + 'a>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+{
+    match solver_type {
+        AdaptiveStepSolvers::BulirschStoer => Box::new(BulirschStoer::new(ode_def, tolerances))
+            as Box<dyn AdaptiveStepper<I, F, P, E>>,
+    }
+}
+
+/// # Initializes SDE stepper from argument
+/// Helper function to obtain a SdeStepper Trait Object from the enum of steppers
+pub fn get_sde_stepper<'a, I, F, P, E, R>(
+    solver_type: SdeSolvers,
+    sde_def: SdeDefinition<'a, I, F, P, E>,
+    rng: R,
+) -> Result<Box<dyn SdeStepper<I, F, P, E> + 'a>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+    R: Rng + 'a,
+{
+    Ok(match solver_type {
+        SdeSolvers::EulerMaruyama => {
+            Box::new(EulerMaruyama::new(sde_def, rng)) as Box<dyn SdeStepper<I, F, P, E>>
+        }
+        SdeSolvers::Milstein => {
+            Box::new(Milstein::new(sde_def, rng)) as Box<dyn SdeStepper<I, F, P, E>>
+        }
+        SdeSolvers::Sri => Box::new(Sri::new(sde_def, rng)?) as Box<dyn SdeStepper<I, F, P, E>>,
+    })
+}
+
+/// # Initializes implicit stepper from argument
+/// Helper function to obtain a Stepper Trait Object from the enum of implicit steppers
+pub fn get_implicit_stepper<'a, I, F, P, E>(
+    solver_type: ImplicitSolvers,
+    dae_def: DaeDefinition<'a, I, F, P, E>,
+) -> Result<Box<dyn Stepper<I, F, P, E> + 'a>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: From<CalcError> + 'a,
+{
+    Ok(match solver_type {
+        ImplicitSolvers::ImplicitEuler => {
+            Box::new(ImplicitEuler::new(dae_def)?) as Box<dyn Stepper<I, F, P, E>>
+        }
+        ImplicitSolvers::Sdirk2 => Box::new(Sdirk2::new(dae_def)?) as Box<dyn Stepper<I, F, P, E>>,
+    })
+}
+
+/// # Initializes exponential integrator from argument
+/// Helper function to obtain a Stepper Trait Object from the enum of exponential integrators
+pub fn get_exponential_stepper<'a, I, F, P, E>(
+    solver_type: ExponentialSolvers,
+    semi_def: SemilinearDefinition<'a, I, F, P, E>,
+) -> Result<Box<dyn Stepper<I, F, P, E> + 'a>, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+{
+    Ok(match solver_type {
+        ExponentialSolvers::Etd1 => Box::new(Etd1::new(semi_def)?) as Box<dyn Stepper<I, F, P, E>>,
+        ExponentialSolvers::Etdrk4 => {
+            Box::new(Etdrk4::new(semi_def)?) as Box<dyn Stepper<I, F, P, E>>
+        }
+        ExponentialSolvers::LawsonRk4 => {
+            Box::new(LawsonRk4::new(semi_def)?) as Box<dyn Stepper<I, F, P, E>>
+        }
+    })
+}
+
+/// # Initializes IMEX stepper from argument
+/// Helper function to obtain an AdaptiveStepper Trait Object from the enum of IMEX methods
+pub fn get_imex_stepper<'a, I, F, P, E>(
+    solver_type: ImexSolvers,
+    add_def: AdditiveDefinition<'a, I, F, P, E>,
+    tolerances: Tolerances<F>,
+) -> Box<dyn AdaptiveStepper<I, F, P, E> + 'a>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+{
+    match solver_type {
+        ImexSolvers::Ark324L2SA => Box::new(Ark324L2SA::new(add_def, tolerances)),
+        ImexSolvers::Ark436L2SA => Box::new(Ark436L2SA::new(add_def, tolerances)),
     }
 }
@@ -0,0 +1,765 @@
+use crate::checkpoint::AdaptiveIntegration;
+use crate::concepts::*;
+use crate::methods::{integrate_fixed_step_observed, FixedStep, Substeps};
+use crate::solvers::FixedStepSolvers;
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use std::io::{Seek, SeekFrom, Write};
+
+/// Convert errors of the underlying writer
+fn io_error(error: std::io::Error) -> SolvingError {
+    SolvingError::from(format!("Could not write output: {error}"))
+}
+
+/// # Incremental output of trajectories
+/// Records \\((t, y)\\) are written one after another such that the whole solution never needs
+/// to be held in memory. Call [TrajectoryWriter::finish] once all records have been written.
+pub trait TrajectoryWriter<I, F> {
+    /// Append the record \\((t, y)\\)
+    fn write_record(&mut self, t: &F, y: &I) -> Result<(), SolvingError>;
+
+    /// Complete the output after the last record
+    fn finish(&mut self) -> Result<(), SolvingError> {
+        Ok(())
+    }
+}
+
+/// # CSV output
+/// Every record is written as one line containing the time followed by the components of the
+/// state. Unless disabled, a header line is written before the first record. Its default column
+/// names are `t,y0,y1,...`.
+/// ```
+/// use ode_integrate::*;
+///
+/// let mut csv = CsvWriter::new(Vec::new())
+///     .with_column_names(&["time", "x", "v"])
+///     .with_delimiter(';');
+/// csv.write_record(&0.0, &[1.0, 0.0]).unwrap();
+/// csv.write_record(&0.5, &[0.5, -1.0]).unwrap();
+/// csv.finish().unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv.into_inner()).unwrap(),
+///     "time;x;v\n0;1;0\n0.5;0.5;-1\n"
+/// );
+/// ```
+pub struct CsvWriter<W> {
+    /// Destination of the output
+    writer: W,
+    /// Names of all columns starting with the time
+    column_names: Option<Vec<String>>,
+    /// Write a header line before the first record
+    header: bool,
+    /// Separator between columns
+    delimiter: char,
+    /// Number of records written so far
+    n_records: usize,
+}
+
+impl<W> CsvWriter<W>
+where
+    W: Write,
+{
+    /// Construct a writer with default column names and `,` as delimiter
+    pub fn new(writer: W) -> Self {
+        CsvWriter {
+            writer,
+            column_names: None,
+            header: true,
+            delimiter: ',',
+            n_records: 0,
+        }
+    }
+
+    /// Change the names of the columns in the header. The first name belongs to the time.
+    pub fn with_column_names<S>(mut self, names: &[S]) -> Self
+    where
+        S: AsRef<str>,
+    {
+        self.column_names = Some(names.iter().map(|s| String::from(s.as_ref())).collect());
+        self
+    }
+
+    /// Do not write a header line
+    pub fn without_header(mut self) -> Self {
+        self.header = false;
+        self
+    }
+
+    /// Change the separator between columns
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Number of records written so far
+    pub fn n_records(&self) -> usize {
+        self.n_records
+    }
+
+    /// Flush the underlying writer
+    pub fn finish(&mut self) -> Result<(), SolvingError> {
+        self.writer.flush().map_err(io_error)
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write the header line for records with `n_states` components
+    fn write_header(&mut self, n_states: usize) -> Result<(), SolvingError> {
+        let names = match &self.column_names {
+            Some(names) if names.len() != n_states + 1 => {
+                return Err(SolvingError::from(format!(
+                    "Expected {} column names but {} were given",
+                    n_states + 1,
+                    names.len()
+                )))
+            }
+            Some(names) => names.clone(),
+            None => core::iter::once(String::from("t"))
+                .chain((0..n_states).map(|i| format!("y{i}")))
+                .collect(),
+        };
+        let mut delimiter = [0; 4];
+        let line = names.join(self.delimiter.encode_utf8(&mut delimiter));
+        writeln!(self.writer, "{line}").map_err(io_error)
+    }
+}
+
+impl<I, F, W> TrajectoryWriter<I, F> for CsvWriter<W>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Display,
+    W: Write,
+{
+    fn write_record(&mut self, t: &F, y: &I) -> Result<(), SolvingError> {
+        if self.n_records == 0 && self.header {
+            self.write_header(y.into_iter().count())?;
+        }
+        write!(self.writer, "{t}").map_err(io_error)?;
+        for yi in y {
+            write!(self.writer, "{}{yi}", self.delimiter).map_err(io_error)?;
+        }
+        writeln!(self.writer).map_err(io_error)?;
+        self.n_records += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SolvingError> {
+        CsvWriter::finish(self)
+    }
+}
+
+/// Length of the `.npy` header including the magic string. The header is padded to this length
+/// such that the final shape can be filled in once all records are known.
+const NPY_HEADER_LEN: usize = 128;
+
+/// Header of a `.npy` file containing a 2D array of little-endian `f64` values
+fn npy_header(n_rows: u64, n_columns: usize) -> [u8; NPY_HEADER_LEN] {
+    let dict =
+        format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({n_rows}, {n_columns}), }}");
+    let mut header = [b' '; NPY_HEADER_LEN];
+    header[..6].copy_from_slice(b"\x93NUMPY");
+    header[6] = 1;
+    header[7] = 0;
+    header[8..10].copy_from_slice(&((NPY_HEADER_LEN - 10) as u16).to_le_bytes());
+    header[10..10 + dict.len()].copy_from_slice(dict.as_bytes());
+    header[NPY_HEADER_LEN - 1] = b'\n';
+    header
+}
+
+/// # CRC-32 checksum as used by the zip format
+struct Crc32 {
+    /// Lookup table for every byte
+    table: [u32; 256],
+    /// Checksum of the bytes processed so far
+    value: u32,
+}
+
+impl Crc32 {
+    /// Reversed polynomial of the checksum
+    const POLYNOMIAL: u32 = 0xedb8_8320;
+
+    /// Start a new checksum
+    fn new() -> Self {
+        let mut table = [0; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    Self::POLYNOMIAL ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        Crc32 { table, value: 0 }
+    }
+
+    /// Process further bytes
+    fn update(&mut self, bytes: &[u8]) {
+        let mut c = !self.value;
+        for b in bytes {
+            c = self.table[((c ^ *b as u32) & 0xff) as usize] ^ (c >> 8);
+        }
+        self.value = !c;
+    }
+
+    /// Multiply the vector `v` with a matrix over GF(2)
+    fn gf2_times(matrix: &[u32; 32], mut v: u32) -> u32 {
+        let mut sum = 0;
+        let mut i = 0;
+        while v != 0 {
+            if v & 1 == 1 {
+                sum ^= matrix[i];
+            }
+            v >>= 1;
+            i += 1;
+        }
+        sum
+    }
+
+    /// Square a matrix over GF(2)
+    fn gf2_square(matrix: &[u32; 32]) -> [u32; 32] {
+        let mut square = [0; 32];
+        for (s, m) in square.iter_mut().zip(matrix) {
+            *s = Self::gf2_times(matrix, *m);
+        }
+        square
+    }
+
+    /// Checksum of the concatenation of two byte sequences from their individual checksums
+    /// and the length of the second sequence
+    fn combine(crc1: u32, crc2: u32, mut len2: u64) -> u32 {
+        // Operator which appends a single zero bit
+        let mut op = [0; 32];
+        op[0] = Self::POLYNOMIAL;
+        for (n, entry) in op.iter_mut().enumerate().skip(1) {
+            *entry = 1 << (n - 1);
+        }
+        // Operator which appends a single zero byte
+        for _ in 0..3 {
+            op = Self::gf2_square(&op);
+        }
+        let mut crc = crc1;
+        while len2 != 0 {
+            if len2 & 1 == 1 {
+                crc = Self::gf2_times(&op, crc);
+            }
+            len2 >>= 1;
+            op = Self::gf2_square(&op);
+        }
+        crc ^ crc2
+    }
+}
+
+/// # NumPy `.npy` output
+/// Records are stored as rows of a 2D array of `f64` values where the first column contains the
+/// time. The shape of the array is written by [TrajectoryWriter::finish] which therefore has to be
+/// called before the file can be read.
+/// ```
+/// use ode_integrate::*;
+///
+/// let mut npy = NpyWriter::new(std::io::Cursor::new(Vec::new())).unwrap();
+/// npy.write_record(&0.0, &[1.0, 2.0]).unwrap();
+/// npy.write_record(&0.1, &[1.5, 2.5]).unwrap();
+/// npy.finish().unwrap();
+/// let bytes = npy.into_inner().into_inner();
+/// assert_eq!(bytes.len(), 128 + 6 * 8);
+/// assert!(String::from_utf8_lossy(&bytes[..128]).contains("'shape': (2, 3)"));
+/// ```
+pub struct NpyWriter<W> {
+    /// Destination of the output
+    writer: W,
+    /// Position of the header in the output
+    start: u64,
+    /// Number of columns, known after the first record
+    n_columns: Option<usize>,
+    /// Number of records written so far
+    n_records: u64,
+    /// Checksum of the array data if needed by an enclosing archive
+    crc: Option<Crc32>,
+    /// Buffer for the bytes of a single record
+    buffer: Vec<u8>,
+}
+
+impl<W> NpyWriter<W>
+where
+    W: Write + Seek,
+{
+    /// Construct a writer which starts at the current position of `writer`
+    pub fn new(mut writer: W) -> Result<Self, SolvingError> {
+        let start = writer.stream_position().map_err(io_error)?;
+        writer.write_all(&npy_header(0, 0)).map_err(io_error)?;
+        Ok(NpyWriter {
+            writer,
+            start,
+            n_columns: None,
+            n_records: 0,
+            crc: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Number of records written so far
+    pub fn n_records(&self) -> u64 {
+        self.n_records
+    }
+
+    /// Write the final shape of the array into the header
+    pub fn finish(&mut self) -> Result<(), SolvingError> {
+        self.write_shape()?;
+        self.writer.flush().map_err(io_error)
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Fill in the final shape and return the complete header
+    fn write_shape(&mut self) -> Result<[u8; NPY_HEADER_LEN], SolvingError> {
+        let header = npy_header(self.n_records, self.n_columns.unwrap_or(0));
+        let end = self.writer.stream_position().map_err(io_error)?;
+        self.writer
+            .seek(SeekFrom::Start(self.start))
+            .map_err(io_error)?;
+        self.writer.write_all(&header).map_err(io_error)?;
+        self.writer.seek(SeekFrom::Start(end)).map_err(io_error)?;
+        Ok(header)
+    }
+}
+
+impl<I, F, W> TrajectoryWriter<I, F> for NpyWriter<W>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+    W: Write + Seek,
+{
+    fn write_record(&mut self, t: &F, y: &I) -> Result<(), SolvingError> {
+        self.buffer.clear();
+        self.buffer.extend((*t).into().to_le_bytes());
+        for yi in y {
+            self.buffer.extend((*yi).into().to_le_bytes());
+        }
+        let n_columns = self.buffer.len() / 8;
+        match self.n_columns {
+            None => self.n_columns = Some(n_columns),
+            Some(n) if n != n_columns => {
+                return Err(SolvingError::from(format!(
+                    "Record has {} columns but previous records had {}",
+                    n_columns, n
+                )))
+            }
+            Some(_) => (),
+        }
+        self.writer.write_all(&self.buffer).map_err(io_error)?;
+        if let Some(crc) = &mut self.crc {
+            crc.update(&self.buffer);
+        }
+        self.n_records += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SolvingError> {
+        NpyWriter::finish(self)
+    }
+}
+
+/// # NumPy `.npz` output
+/// Stores the records as described for [NpyWriter] in an uncompressed zip archive which can be
+/// read by `numpy.load`. The array is called `trajectory` unless specified otherwise.
+/// Archives are limited to a size of 4 GiB.
+/// ```
+/// use ode_integrate::*;
+///
+/// let mut npz = NpzWriter::new(std::io::Cursor::new(Vec::new()), "solution").unwrap();
+/// npz.write_record(&0.0, &[1.0]).unwrap();
+/// npz.finish().unwrap();
+/// let bytes = npz.into_inner().into_inner();
+/// assert_eq!(&bytes[..4], b"PK\x03\x04");
+/// assert_eq!(&bytes[30..42], b"solution.npy");
+/// ```
+pub struct NpzWriter<W> {
+    /// Writer of the array inside of the archive
+    npy: NpyWriter<W>,
+    /// Name of the file in the archive
+    file_name: String,
+    /// Position of the local file header
+    start: u64,
+    /// Archive is complete
+    finished: bool,
+}
+
+impl<W> NpzWriter<W>
+where
+    W: Write + Seek,
+{
+    /// Fixed part of the local file header and the central directory header:
+    /// version, flags, compression method, modification time and date
+    const VERSION_FLAGS_METHOD_TIME: [u8; 10] = [20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0];
+
+    /// Construct a writer for an array called `array_name` which starts at the current position
+    /// of `writer`
+    pub fn new(mut writer: W, array_name: &str) -> Result<Self, SolvingError> {
+        let start = writer.stream_position().map_err(io_error)?;
+        let file_name = format!("{array_name}.npy");
+        writer
+            .write_all(&Self::local_header(&file_name, 0, 0))
+            .map_err(io_error)?;
+        let mut npy = NpyWriter::new(writer)?;
+        npy.crc = Some(Crc32::new());
+        Ok(NpzWriter {
+            npy,
+            file_name,
+            start,
+            finished: false,
+        })
+    }
+
+    /// Complete the array and write the central directory of the archive
+    pub fn finish(&mut self) -> Result<(), SolvingError> {
+        if self.finished {
+            return Ok(());
+        }
+        let header = self.npy.write_shape()?;
+        let data_len = self.npy.n_records * self.npy.n_columns.unwrap_or(0) as u64 * 8;
+        let size = u32::try_from(NPY_HEADER_LEN as u64 + data_len)
+            .map_err(|_| SolvingError::from("Array is too large for an archive without zip64"))?;
+
+        let mut header_crc = Crc32::new();
+        header_crc.update(&header);
+        let data_crc = self.npy.crc.as_ref().map_or(0, |crc| crc.value);
+        let crc = Crc32::combine(header_crc.value, data_crc, data_len);
+
+        let writer = &mut self.npy.writer;
+        let end = writer.stream_position().map_err(io_error)?;
+        writer.seek(SeekFrom::Start(self.start)).map_err(io_error)?;
+        writer
+            .write_all(&Self::local_header(&self.file_name, crc, size))
+            .map_err(io_error)?;
+        writer.seek(SeekFrom::Start(end)).map_err(io_error)?;
+        let too_large = |_| SolvingError::from("Archive is too large without zip64");
+        let offset = u32::try_from(self.start).map_err(too_large)?;
+        let directory_offset = u32::try_from(end).map_err(too_large)?;
+        let directory = self.central_directory(crc, size, offset, directory_offset);
+        let writer = &mut self.npy.writer;
+        writer.write_all(&directory).map_err(io_error)?;
+        writer.flush().map_err(io_error)?;
+        self.finished = true;
+        Ok(())
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.npy.writer
+    }
+
+    /// Local file header preceding the data of the array
+    fn local_header(file_name: &str, crc: u32, size: u32) -> Vec<u8> {
+        let mut header = Vec::with_capacity(30 + file_name.len());
+        header.extend(b"PK\x03\x04");
+        header.extend(Self::VERSION_FLAGS_METHOD_TIME);
+        header.extend(crc.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend((file_name.len() as u16).to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(file_name.as_bytes());
+        header
+    }
+
+    /// Central directory with a single entry followed by the end of central directory record.
+    /// `offset` is the position of the local file header and `directory_offset` the position
+    /// of the central directory.
+    fn central_directory(
+        &self,
+        crc: u32,
+        size: u32,
+        offset: u32,
+        directory_offset: u32,
+    ) -> Vec<u8> {
+        let name = self.file_name.as_bytes();
+        let mut directory = Vec::with_capacity(46 + name.len() + 22);
+        directory.extend(b"PK\x01\x02");
+        directory.extend(20u16.to_le_bytes());
+        directory.extend(Self::VERSION_FLAGS_METHOD_TIME);
+        directory.extend(crc.to_le_bytes());
+        directory.extend(size.to_le_bytes());
+        directory.extend(size.to_le_bytes());
+        directory.extend((name.len() as u16).to_le_bytes());
+        // Extra field, comment, disk number, internal and external attributes
+        directory.extend([0; 12]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name);
+
+        let directory_size = directory.len() as u32;
+        directory.extend(b"PK\x05\x06");
+        directory.extend([0; 4]);
+        directory.extend(1u16.to_le_bytes());
+        directory.extend(1u16.to_le_bytes());
+        directory.extend(directory_size.to_le_bytes());
+        directory.extend(directory_offset.to_le_bytes());
+        directory.extend(0u16.to_le_bytes());
+        directory
+    }
+}
+
+impl<I, F, W> TrajectoryWriter<I, F> for NpzWriter<W>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+    W: Write + Seek,
+{
+    fn write_record(&mut self, t: &F, y: &I) -> Result<(), SolvingError> {
+        if self.finished {
+            return Err(SolvingError::from("Archive was already finished"));
+        }
+        self.npy.write_record(t, y)
+    }
+
+    fn finish(&mut self) -> Result<(), SolvingError> {
+        NpzWriter::finish(self)
+    }
+}
+
+impl<'a, I, F, P, E> AdaptiveIntegration<'a, I, F, P, E>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: RealFloatLikeType + 'a,
+    P: 'a,
+    E: 'a,
+{
+    /// Integrate an iterable type to every time point of `t_series` and write the solution at
+    /// these points with `writer`. Only the current state is kept in memory.
+    pub fn write_time_series_iter<V>(
+        &mut self,
+        t_series: &V,
+        p: &P,
+        writer: &mut dyn TrajectoryWriter<I, F>,
+    ) -> Result<(), SolvingError>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+        E: Display,
+    {
+        for t in t_series {
+            let y = self.integrate_to_iter(t, p)?;
+            writer.write_record(t, y)?;
+        }
+        writer.finish()
+    }
+
+    /// Equivalent to [AdaptiveIntegration::write_time_series_iter] but for types which can be
+    /// added via [MathVecLikeType].
+    pub fn write_time_series_add<V>(
+        &mut self,
+        t_series: &V,
+        p: &P,
+        writer: &mut dyn TrajectoryWriter<I, F>,
+    ) -> Result<(), SolvingError>
+    where
+        I: MathVecLikeType<F>,
+        F: Mul<I, Output = I>,
+        for<'m> &'m V: IntoIterator<Item = &'m F>,
+        E: Display,
+    {
+        for t in t_series {
+            let y = self.integrate_to_add(t, p)?;
+            writer.write_record(t, y)?;
+        }
+        writer.finish()
+    }
+}
+
+/// Integrates with a fixed stepper and writes the solution at every time point with `writer`
+#[allow(clippy::too_many_arguments)]
+fn write_fixed_step<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    substeps: Substeps<F>,
+    do_step: FixedStep<I, F, P, E>,
+    writer: &mut dyn TrajectoryWriter<I, F>,
+) -> Result<(), SolvingError>
+where
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    integrate_fixed_step_observed(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        substeps,
+        None,
+        None,
+        None,
+        do_step,
+        &mut |t, y| writer.write_record(t, y),
+    )?;
+    writer.finish()
+}
+
+/// # Write the solution of an ODE with single steps between the time points
+/// Equivalent to [solve_ode_time_series_single_step_iter](crate::solve_ode_time_series_single_step_iter)
+/// but the solution at every time point is written with `writer` instead of being collected.
+/// Only the current state is kept in memory.
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let mut csv = CsvWriter::new(Vec::new()).with_column_names(&["t", "y"]);
+/// write_ode_time_series_single_step_iter(
+///     &[1.0],
+///     &vec![0.0, 0.5, 1.0],
+///     &rhs,
+///     &0.0,
+///     FixedStepSolvers::Euler,
+///     &mut csv,
+/// )
+/// .unwrap();
+/// assert_eq!(String::from_utf8(csv.into_inner()).unwrap(), "t,y\n0,1\n0.5,1\n1,1\n");
+/// ```
+pub fn write_ode_time_series_single_step_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    writer: &mut dyn TrajectoryWriter<I, F>,
+) -> Result<(), SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    write_fixed_step(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+        writer,
+    )
+}
+
+/// # Write the solution of an ODE with single steps between the time points
+/// Equivalent to [write_ode_time_series_single_step_iter] but for types which can be added via
+/// [MathVecLikeType].
+pub fn write_ode_time_series_single_step_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    writer: &mut dyn TrajectoryWriter<I, F>,
+) -> Result<(), SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    write_fixed_step(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Single,
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+        writer,
+    )
+}
+
+/// # Write the solution of an ODE with a maximal step size between the time points
+/// Equivalent to [solve_ode_time_series_minimal_step_iter](crate::solve_ode_time_series_minimal_step_iter)
+/// but the solution at every time point is written with `writer` instead of being collected.
+/// Only the current state is kept in memory.
+pub fn write_ode_time_series_minimal_step_iter<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    writer: &mut dyn TrajectoryWriter<I, F>,
+) -> Result<(), SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: FloatLikeType,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    write_fixed_step(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        &|stepper, y, t, dt, p| stepper.do_step_iter(y, t, dt, p),
+        writer,
+    )
+}
+
+/// # Write the solution of an ODE with a maximal step size between the time points
+/// Equivalent to [write_ode_time_series_minimal_step_iter] but for types which can be added via
+/// [MathVecLikeType].
+pub fn write_ode_time_series_minimal_step_add<'a, I, F, P, E, V>(
+    y0: &I,
+    t_series: &V,
+    rhs: RHS<'a, I, F, P, E>,
+    p: &P,
+    solver_type: FixedStepSolvers,
+    dt: &F,
+    writer: &mut dyn TrajectoryWriter<I, F>,
+) -> Result<(), SolvingError>
+where
+    I: MathVecLikeType<F>,
+    F: FloatLikeType + Mul<I, Output = I>,
+    P: Clone,
+    E: Display + Clone,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+{
+    write_fixed_step(
+        y0,
+        t_series,
+        rhs,
+        p,
+        solver_type,
+        Substeps::Maximal(dt),
+        &|stepper, y, t, dt, p| stepper.do_step_add(y, t, dt, p),
+        writer,
+    )
+}
@@ -0,0 +1,339 @@
+use crate::concepts::*;
+
+use core::fmt::Display;
+use core::ops::Range;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use std::path::Path;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+/// Convert errors of the drawing backend
+fn plot_error<E: Display>(error: E) -> SolvingError {
+    SolvingError::from(format!("Could not draw plot: {error}"))
+}
+
+/// # Appearance of plots
+/// Plots are written as PNG or SVG depending on the extension of the file name.
+#[derive(Clone, Debug)]
+pub struct PlotSettings {
+    /// Caption above the plot
+    pub title: String,
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    /// Names of the components of the state. Components without name are called `y0, y1, ...`.
+    pub component_names: Vec<String>,
+}
+
+impl PlotSettings {
+    /// Settings for an image of 800x600 pixels with default component names
+    pub fn new(title: &str) -> Self {
+        PlotSettings {
+            title: String::from(title),
+            width: 800,
+            height: 600,
+            component_names: Vec::new(),
+        }
+    }
+
+    /// Name of the component with index `i`
+    fn component_name(&self, i: usize) -> String {
+        match self.component_names.get(i) {
+            Some(name) => name.clone(),
+            None => format!("y{i}"),
+        }
+    }
+}
+
+/// Image formats which can be written
+enum PlotFormat {
+    /// Portable network graphics
+    Png,
+    /// Scalable vector graphics
+    Svg,
+}
+
+impl PlotFormat {
+    /// Choose the format from the extension of the file name
+    fn from_path(path: &Path) -> Result<Self, SolvingError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => Ok(PlotFormat::Png),
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => Ok(PlotFormat::Svg),
+            _ => Err(SolvingError::from(format!(
+                "Can not plot to {}: expected extension png or svg",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Components of all states as `f64` where the outer index is the component
+fn components<I, F>(solution: &[I]) -> Result<Vec<Vec<f64>>, SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+{
+    let n_states = match solution.first() {
+        Some(y) => y.into_iter().count(),
+        None => return Err(SolvingError::from("Can not plot an empty solution")),
+    };
+    let mut columns = alloc::vec![Vec::with_capacity(solution.len()); n_states];
+    for y in solution {
+        if y.into_iter().count() != n_states {
+            return Err(SolvingError::from(
+                "All states need to have the same length",
+            ));
+        }
+        for (column, yi) in columns.iter_mut().zip(y) {
+            column.push((*yi).into());
+        }
+    }
+    Ok(columns)
+}
+
+/// Select a component by index
+fn component(columns: &[Vec<f64>], i: usize) -> Result<&Vec<f64>, SolvingError> {
+    columns.get(i).ok_or_else(|| {
+        SolvingError::from(format!(
+            "Component {i} does not exist in states with {} components",
+            columns.len()
+        ))
+    })
+}
+
+/// Range which contains all finite values. Constant values are widened to a range of length 2.
+fn value_range<'v>(values: impl IntoIterator<Item = &'v f64>) -> Range<f64> {
+    let (min, max) = values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    if min > max {
+        -1.0..1.0
+    } else if min == max {
+        min - 1.0..max + 1.0
+    } else {
+        let margin = 0.05 * (max - min);
+        min - margin..max + margin
+    }
+}
+
+/// Draw every component over time
+fn draw_time_series<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    t: &[f64],
+    columns: &[Vec<f64>],
+    settings: &PlotSettings,
+) -> Result<(), SolvingError> {
+    root.fill(&WHITE).map_err(plot_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&settings.title, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(value_range(t), value_range(columns.iter().flatten()))
+        .map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc("t")
+        .draw()
+        .map_err(plot_error)?;
+    for (i, column) in columns.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(
+                t.iter().copied().zip(column.iter().copied()),
+                color.stroke_width(2),
+            ))
+            .map_err(plot_error)?
+            .label(settings.component_name(i))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(plot_error)?;
+    root.present().map_err(plot_error)
+}
+
+/// Draw one component against another
+fn draw_phase_portrait<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    x: &[f64],
+    y: &[f64],
+    labels: (String, String),
+    settings: &PlotSettings,
+) -> Result<(), SolvingError> {
+    root.fill(&WHITE).map_err(plot_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&settings.title, ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(value_range(x), value_range(y))
+        .map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc(labels.0)
+        .y_desc(labels.1)
+        .draw()
+        .map_err(plot_error)?;
+    chart
+        .draw_series(LineSeries::new(
+            x.iter().copied().zip(y.iter().copied()),
+            Palette99::pick(0).stroke_width(2),
+        ))
+        .map_err(plot_error)?;
+    root.present().map_err(plot_error)
+}
+
+/// Draw three components as a curve in space with `z` on the vertical axis
+fn draw_trajectory_3d<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    x: &[f64],
+    y: &[f64],
+    z: &[f64],
+    settings: &PlotSettings,
+) -> Result<(), SolvingError> {
+    root.fill(&WHITE).map_err(plot_error)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&settings.title, ("sans-serif", 24))
+        .margin(10)
+        .build_cartesian_3d(value_range(x), value_range(z), value_range(y))
+        .map_err(plot_error)?;
+    chart.with_projection(|mut projection| {
+        projection.yaw = 0.5;
+        projection.pitch = 0.3;
+        projection.scale = 0.8;
+        projection.into_matrix()
+    });
+    chart
+        .configure_axes()
+        .light_grid_style(BLACK.mix(0.1))
+        .max_light_lines(3)
+        .draw()
+        .map_err(plot_error)?;
+    chart
+        .draw_series(LineSeries::new(
+            x.iter()
+                .zip(y.iter())
+                .zip(z.iter())
+                .map(|((x, y), z)| (*x, *z, *y)),
+            Palette99::pick(0).stroke_width(2),
+        ))
+        .map_err(plot_error)?;
+    root.present().map_err(plot_error)
+}
+
+/// Plot every component of the solution over time. The solution is given as returned by the
+/// drivers, i.e. one state for every time point in `t_series`.
+/// The image format is chosen by the extension of `path` which has to be `png` or `svg`.
+pub fn plot_time_series<I, F, V, Q>(
+    path: Q,
+    t_series: &V,
+    solution: &[I],
+    settings: &PlotSettings,
+) -> Result<(), SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    for<'m> &'m V: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+    Q: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let t: Vec<f64> = t_series.into_iter().map(|t| (*t).into()).collect();
+    if t.len() != solution.len() {
+        return Err(SolvingError::from(format!(
+            "Got {} time points but {} states",
+            t.len(),
+            solution.len()
+        )));
+    }
+    let columns = components(solution)?;
+    let size = (settings.width, settings.height);
+    match PlotFormat::from_path(path)? {
+        PlotFormat::Png => {
+            let root = BitMapBackend::new(path, size).into_drawing_area();
+            draw_time_series(root, &t, &columns, settings)
+        }
+        PlotFormat::Svg => {
+            let root = SVGBackend::new(path, size).into_drawing_area();
+            draw_time_series(root, &t, &columns, settings)
+        }
+    }
+}
+
+/// Plot the component with index `indices.1` against the one with index `indices.0`.
+/// The image format is chosen by the extension of `path` which has to be `png` or `svg`.
+pub fn plot_phase_portrait<I, F, Q>(
+    path: Q,
+    solution: &[I],
+    indices: (usize, usize),
+    settings: &PlotSettings,
+) -> Result<(), SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+    Q: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let columns = components(solution)?;
+    let x = component(&columns, indices.0)?;
+    let y = component(&columns, indices.1)?;
+    let labels = (
+        settings.component_name(indices.0),
+        settings.component_name(indices.1),
+    );
+    let size = (settings.width, settings.height);
+    match PlotFormat::from_path(path)? {
+        PlotFormat::Png => {
+            let root = BitMapBackend::new(path, size).into_drawing_area();
+            draw_phase_portrait(root, x, y, labels, settings)
+        }
+        PlotFormat::Svg => {
+            let root = SVGBackend::new(path, size).into_drawing_area();
+            draw_phase_portrait(root, x, y, labels, settings)
+        }
+    }
+}
+
+/// Plot the components with the given `indices` as a trajectory in 3D. The last component is
+/// shown on the vertical axis.
+/// The image format is chosen by the extension of `path` which has to be `png` or `svg`.
+pub fn plot_trajectory_3d<I, F, Q>(
+    path: Q,
+    solution: &[I],
+    indices: (usize, usize, usize),
+    settings: &PlotSettings,
+) -> Result<(), SolvingError>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy + Into<f64>,
+    Q: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let columns = components(solution)?;
+    let x = component(&columns, indices.0)?;
+    let y = component(&columns, indices.1)?;
+    let z = component(&columns, indices.2)?;
+    let size = (settings.width, settings.height);
+    match PlotFormat::from_path(path)? {
+        PlotFormat::Png => {
+            let root = BitMapBackend::new(path, size).into_drawing_area();
+            draw_trajectory_3d(root, x, y, z, settings)
+        }
+        PlotFormat::Svg => {
+            let root = SVGBackend::new(path, size).into_drawing_area();
+            draw_trajectory_3d(root, x, y, z, settings)
+        }
+    }
+}
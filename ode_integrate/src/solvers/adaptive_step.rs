@@ -0,0 +1,376 @@
+use crate::concepts::*;
+
+use core::ops::Mul;
+
+use alloc::vec::Vec;
+
+/// Contains all implementors of the [AdaptiveStepper] trait.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdaptiveStepSolvers {
+    /// Gragg-Bulirsch-Stoer extrapolation with adaptive order
+    BulirschStoer,
+}
+
+/// Internal states of all implementors of the [AdaptiveStepper] trait.
+/// See [AdaptiveStepper::get_state].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdaptiveStepperState<F> {
+    /// State of the [BulirschStoer] stepper
+    BulirschStoer(BulirschStoerState<F>),
+}
+
+/// # Scaled error norm
+/// Computes the root-mean-square norm of the error estimate `err` where every component is scaled by
+/// \begin{equation}
+///     sc_i = \text{atol} + \text{rtol} \max\left(|y_{0,i}|, |y_{1,i}|\right).
+/// \end{equation}
+pub(crate) fn scaled_error_norm<I, F>(y0: &I, y1: &I, err: &I, tolerances: &Tolerances<F>) -> F
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: RealFloatLikeType,
+{
+    let mut sum = F::from(0);
+    let mut n = 0;
+    for ((y0i, y1i), ei) in y0.into_iter().zip(y1).zip(err) {
+        let sc = tolerances.atol + tolerances.rtol * y0i.abs().max(y1i.abs());
+        let q = *ei / sc;
+        sum += q * q;
+        n += 1;
+    }
+    if n == 0 {
+        return F::from(0);
+    }
+    (sum / F::from_usize(n)).sqrt()
+}
+
+/// # Gragg-Bulirsch-Stoer extrapolation stepper
+/// A step of size \\(H\\) is computed by the modified midpoint rule with \\(n_k = 2k\\) substeps
+/// of size \\(h=H/n_k\\)
+/// \begin{equation}
+///     \begin{alignedat}{3}
+///         z_0 &= y_0\\\\
+///         z_1 &= z_0 + &&h f(t_0, z_0)\\\\
+///         z_{m+1} &= z_{m-1} + 2&&h f(t_0 + mh, z_m)\\\\
+///         T_{k,1} &= \tfrac{1}{2}\left(z_{n_k} + z_{n_k-1} + \right.&&\left.h f(t_0 + H, z_{n_k})\right).
+///     \end{alignedat}
+/// \end{equation}
+/// Since the error of \\(T_{k,1}\\) has an asymptotic expansion in even powers of \\(h\\), the
+/// results are combined by the Aitken-Neville scheme
+/// \begin{equation}
+///     T_{k,j+1} = T_{k,j} + \frac{T_{k,j} - T_{k-1,j}}{(n_k/n_{k-j})^2 - 1}
+/// \end{equation}
+/// to obtain an approximation \\(T_{k,k}\\) of order \\(2k\\).
+/// The difference \\(T_{k,k} - T_{k,k-1}\\) serves as error estimate.
+///
+/// The number of columns \\(k\\) and the step size are chosen such that the work per unit step
+/// is minimized (see Hairer, Nørsett, Wanner: Solving Ordinary Differential Equations I, Section II.9).
+/// Since the error norm needs access to the individual components of the state, `&I` has to be
+/// iterable for both [AdaptiveStepper::do_step_iter] and [AdaptiveStepper::do_step_add].
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[1];
+///     dy[1] = -y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition { y0: [1.0, 0.0], t0: 0.0, func: &rhs };
+/// let tolerances = Tolerances { rtol: 1e-10, atol: 1e-10 };
+/// let mut bs = BulirschStoer::new(ode_def, tolerances);
+///
+/// let mut y = [1.0, 0.0];
+/// let err = bs.do_step_iter(&mut y, &0.0, &0.1, &()).unwrap();
+/// assert!(err.unwrap() <= 1.0);
+/// assert!((y[0] - 0.1_f64.cos()).abs() < 1e-10);
+/// ```
+pub struct BulirschStoer<'a, I, F, P, Err> {
+    /// Definition of the ODE which is solved
+    ode_def: OdeDefinition<'a, I, F, P, Err>,
+    /// Tolerances used to normalize the error estimate
+    tolerances: Tolerances<F>,
+    /// Maximal number of columns of the extrapolation table
+    k_max: usize,
+    /// Number of columns which is currently considered optimal
+    k_opt: usize,
+    /// Step size proposed for the next step
+    dt_next: Option<F>,
+    /// Last computed row \\(T_{k,1},\dots,T_{k,k}\\) of the extrapolation table
+    table: Vec<I>,
+    /// Step sizes proposed by the individual columns
+    dt_k: Vec<F>,
+    /// Work per unit step of the individual columns
+    work_k: Vec<F>,
+    /// RHS evaluated at the beginning of the step
+    dy0: I,
+    /// Storage for the evaluated RHS
+    dy: I,
+    /// Previous value of the modified midpoint rule
+    z0: I,
+    /// Current value of the modified midpoint rule
+    z1: I,
+}
+
+/// # Internal state of the [BulirschStoer] stepper
+/// The extrapolation table is recomputed in every step such that only the order and step-size
+/// control needs to be stored.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BulirschStoerState<F> {
+    /// Maximal number of columns of the extrapolation table
+    pub k_max: usize,
+    /// Number of columns which is currently considered optimal
+    pub k_opt: usize,
+    /// Step size proposed for the next step
+    pub dt_next: Option<F>,
+}
+
+impl<'a, I, F, P, Err> BulirschStoer<'a, I, F, P, Err>
+where
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    /// Default for the maximal number of columns of the extrapolation table
+    const DEFAULT_K_MAX: usize = 8;
+
+    /// Construct a new stepper from the ODE and tolerances used for the error control
+    pub fn new(ode_def: OdeDefinition<'a, I, F, P, Err>, tolerances: Tolerances<F>) -> Self {
+        let y = ode_def.y0.clone();
+        BulirschStoer {
+            ode_def,
+            tolerances,
+            k_max: Self::DEFAULT_K_MAX,
+            k_opt: 4,
+            dt_next: None,
+            table: Vec::with_capacity(Self::DEFAULT_K_MAX),
+            dt_k: Vec::with_capacity(Self::DEFAULT_K_MAX),
+            work_k: Vec::with_capacity(Self::DEFAULT_K_MAX),
+            dy0: y.clone(),
+            dy: y.clone(),
+            z0: y.clone(),
+            z1: y,
+        }
+    }
+
+    /// Change the maximal number of columns of the extrapolation table.
+    /// The value is restricted to be at least 3.
+    pub fn with_max_columns(mut self, k_max: usize) -> Self {
+        self.k_max = k_max.max(3);
+        self.k_opt = self.k_opt.min(self.k_max - 1);
+        self
+    }
+
+    /// Copy the state of the order and step-size control
+    pub fn state(&self) -> BulirschStoerState<F> {
+        BulirschStoerState {
+            k_max: self.k_max,
+            k_opt: self.k_opt,
+            dt_next: self.dt_next,
+        }
+    }
+
+    /// Replace the state of the order and step-size control by a previously stored one
+    pub fn with_state(mut self, state: BulirschStoerState<F>) -> Self {
+        self.restore_state(state);
+        self
+    }
+
+    /// Overwrite the order and step-size control with the given state
+    fn restore_state(&mut self, state: BulirschStoerState<F>) {
+        self.k_max = state.k_max.max(3);
+        self.k_opt = state.k_opt.clamp(2, self.k_max - 1);
+        self.dt_next = state.dt_next;
+    }
+
+    /// Number of substeps \\(n_k=2k\\) of the modified midpoint rule in row `k` (starting at 1)
+    fn n_substeps(k: usize) -> usize {
+        2 * k
+    }
+
+    /// Number of RHS evaluations \\(A_k\\) needed to compute the first `k` rows
+    fn work(k: usize) -> usize {
+        1 + (1..=k).map(Self::n_substeps).sum::<usize>()
+    }
+
+    /// Modified midpoint rule with `n` substeps. The result is stored in `self.z1`.
+    /// The RHS at the initial point has to be precomputed in `self.dy0`.
+    fn modified_midpoint(
+        &mut self,
+        y: &I,
+        t: &F,
+        dt: &F,
+        p: &P,
+        n: usize,
+        axpy: &dyn Fn(&mut I, F, &I),
+    ) -> Result<(), Err> {
+        let h = *dt / F::from_usize(n);
+        self.z0 = y.clone();
+        self.z1 = y.clone();
+        axpy(&mut self.z1, h, &self.dy0);
+        for m in 1..n {
+            (self.ode_def.func)(&self.z1, &mut self.dy, &(*t + F::from_usize(m) * h), p)?;
+            axpy(&mut self.z0, F::from(2) * h, &self.dy);
+            core::mem::swap(&mut self.z0, &mut self.z1);
+        }
+        (self.ode_def.func)(&self.z1, &mut self.dy, &(*t + *dt), p)?;
+        // Smoothing step: z1 + 1/2 (z0 - z1 + h f(z1))
+        axpy(&mut self.z0, -F::from(1), &self.z1);
+        axpy(&mut self.z0, h, &self.dy);
+        axpy(&mut self.z1, F::from(1) / F::from(2), &self.z0);
+        Ok(())
+    }
+
+    /// Propose a step size for column `k` (starting at 2) given its normalized error
+    fn column_step_size(dt: &F, err: F, k: usize) -> F {
+        let fac_min = F::from_f64(0.02);
+        let fac_max = F::from(4);
+        let exponent = F::from(1) / F::from_usize(2 * k - 1);
+        let fac = if err.is_finite() {
+            F::from_f64(0.94) * (F::from_f64(0.65) / err).powf(exponent)
+        } else {
+            fac_min
+        };
+        *dt * fac.max(fac_min).min(fac_max)
+    }
+
+    /// Shared implementation of the step for both types of operations.
+    /// `axpy(y, a, x)` has to compute \\(y \leftarrow y + a x\\).
+    fn do_step_generic(
+        &mut self,
+        y: &mut I,
+        t: &F,
+        dt: &F,
+        p: &P,
+        axpy: &dyn Fn(&mut I, F, &I),
+    ) -> Result<Option<F>, Err>
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+    {
+        self.table.clear();
+        self.dt_k.clear();
+        self.work_k.clear();
+
+        (self.ode_def.func)(y, &mut self.dy0, t, p)?;
+
+        let k_end = (self.k_opt + 1).min(self.k_max);
+        let mut err = F::from(0);
+        let mut k_accepted = None;
+        for k in 1..=k_end {
+            let n_k = Self::n_substeps(k);
+            self.modified_midpoint(y, t, dt, p, n_k, axpy)?;
+
+            // Aitken-Neville extrapolation of the new row
+            let mut current = self.z1.clone();
+            let mut previous = self.z1.clone();
+            for j in 0..k - 1 {
+                let ratio = F::from_usize(n_k) / F::from_usize(Self::n_substeps(k - j - 1));
+                let mut diff = current.clone();
+                axpy(&mut diff, -F::from(1), &self.table[j]);
+                previous = current.clone();
+                axpy(
+                    &mut current,
+                    F::from(1) / (ratio * ratio - F::from(1)),
+                    &diff,
+                );
+                self.table[j] = previous.clone();
+            }
+            self.table.push(current);
+
+            if k >= 2 {
+                let mut diff = self.table[k - 1].clone();
+                axpy(&mut diff, -F::from(1), &previous);
+                err = scaled_error_norm(y, &self.table[k - 1], &diff, &self.tolerances);
+                let dt_col = Self::column_step_size(dt, err, k);
+                self.dt_k.push(dt_col);
+                self.work_k
+                    .push(F::from_usize(Self::work(k)) / dt_col.abs());
+                if k + 1 >= self.k_opt && err <= F::from(1) {
+                    k_accepted = Some(k);
+                    break;
+                }
+            }
+        }
+
+        match k_accepted {
+            Some(k) => {
+                *y = self.table[k - 1].clone();
+                // Choose the column with the least work per unit step
+                let mut k_new = 2;
+                for j in 3..=k {
+                    if self.work_k[j - 2] < self.work_k[k_new - 2] {
+                        k_new = j;
+                    }
+                }
+                let dt_new = if k_new == k && k < self.k_max {
+                    k_new = k + 1;
+                    self.dt_k[k - 2] * F::from_usize(Self::work(k + 1))
+                        / F::from_usize(Self::work(k))
+                } else {
+                    self.dt_k[k_new - 2]
+                };
+                self.k_opt = k_new.max(2).min(self.k_max - 1);
+                self.dt_next = Some(dt_new);
+            }
+            None => {
+                let k = self.k_opt.min(k_end);
+                self.k_opt = (self.k_opt - 1).max(2);
+                // The smaller step size in magnitude since steps are negative backward in time
+                let (dt_opt, dt_last) = (self.dt_k[k - 2], self.dt_k[self.dt_k.len() - 1]);
+                self.dt_next = Some(if dt_opt.abs() < dt_last.abs() {
+                    dt_opt
+                } else {
+                    dt_last
+                });
+            }
+        }
+        Ok(Some(err))
+    }
+}
+
+impl<'a, I, F, P, Err> AdaptiveStepper<I, F, P, Err> for BulirschStoer<'a, I, F, P, Err>
+where
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+{
+    fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: FloatLikeType,
+    {
+        let axpy = |y: &mut I, a: F, x: &I| {
+            for (yi, xi) in y.into_iter().zip(x) {
+                *yi += a * *xi;
+            }
+        };
+        self.do_step_generic(y, t, dt, p, &axpy)
+    }
+
+    fn do_step_add(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err>
+    where
+        I: MathVecLikeType<F>,
+        F: FloatLikeType + Mul<I, Output = I>,
+    {
+        let axpy = |y: &mut I, a: F, x: &I| {
+            *y += a * x.clone();
+        };
+        self.do_step_generic(y, t, dt, p, &axpy)
+    }
+
+    fn suggested_dt(&self) -> Option<F> {
+        self.dt_next
+    }
+
+    fn get_state(&self) -> Option<AdaptiveStepperState<F>> {
+        Some(AdaptiveStepperState::BulirschStoer(self.state()))
+    }
+
+    fn set_state(&mut self, state: AdaptiveStepperState<F>) -> Result<(), SolvingError> {
+        let AdaptiveStepperState::BulirschStoer(state) = state;
+        self.restore_state(state);
+        Ok(())
+    }
+}
@@ -0,0 +1,419 @@
+use crate::concepts::*;
+use crate::solvers::implicit::{gather, scatter};
+use crate::solvers::sensitivity::RhsJacobians;
+
+use core::fmt::Display;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// # Result of an adjoint sensitivity computation
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdjointResult<F> {
+    /// Value of the objective \\(G\\)
+    pub value: F,
+    /// Derivative \\(dG/dp\\) with respect to all parameters
+    pub dg_dp: Vec<F>,
+    /// Derivative \\(dG/dy_0\\) with respect to the initial value
+    pub dg_dy0: Vec<F>,
+}
+
+/// # Adjoint sensitivities
+/// Computes the derivatives of an [Objective] \\(G\\) by integrating the adjoint equations
+/// \begin{align}
+///     \frac{d\lambda}{dt} &= -\left(\frac{\partial f}{\partial y}\right)^T\lambda
+///         - \left(\frac{\partial g}{\partial y}\right)^T &
+///     \frac{d\mu}{dt} &= -\left(\frac{\partial f}{\partial p}\right)^T\lambda
+///         - \left(\frac{\partial g}{\partial p}\right)^T
+/// \end{align}
+/// backward in time from \\(\lambda(T) = 0\\), \\(\mu(T) = 0\\) such that
+/// \\(dG/dy_0 = \lambda(t_0)\\) and \\(dG/dp = \mu(t_0)\\).
+/// For [pointwise](ObjectiveKind::Pointwise) objectives, the terms of \\(g\\) are dropped from the
+/// equations and instead \\(\lambda\\) and \\(\mu\\) jump by the gradient of \\(g\\) at every
+/// time point.
+///
+/// Both the forward and the backward integration use the classical 4th order Runge-Kutta method.
+/// Only every `checkpoint_every`-th state of the forward pass is stored. During the backward
+/// pass, the states between two checkpoints are recomputed and interpolated by cubic Hermite
+/// polynomials. This reduces the memory from \\(N\\) to roughly
+/// \\(N / K + K\\) states for \\(N\\) steps and checkpoints every \\(K\\) steps.
+pub struct AdjointSensitivity<'a, I, F, P, Err> {
+    /// Definition of the ODE and evaluation of its Jacobians
+    rhs: RhsJacobians<'a, I, F, P, Err>,
+    /// Objective which is differentiated
+    objective: Objective<'a, I, F, P, Err>,
+    /// Number of steps between two stored states of the forward pass
+    checkpoint_every: usize,
+    /// Storage for states at which the objective is evaluated
+    buf: I,
+}
+
+impl<'a, I, F, P, Err> AdjointSensitivity<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    Err: Display,
+{
+    /// Prepare the computation. Fails if `checkpoint_every` is zero.
+    pub fn new(
+        sens_def: SensitivityDefinition<'a, I, F, P, Err>,
+        objective: Objective<'a, I, F, P, Err>,
+        checkpoint_every: usize,
+    ) -> Result<Self, SolvingError> {
+        if checkpoint_every == 0 {
+            return Err(SolvingError::from(
+                "Checkpoints need to be at least one step apart",
+            ));
+        }
+        let buf = sens_def.y0.clone();
+        Ok(AdjointSensitivity {
+            rhs: RhsJacobians::new(sens_def),
+            objective,
+            checkpoint_every,
+            buf,
+        })
+    }
+
+    /// Evaluate the objective function at a state given as slice
+    fn objective_value(&mut self, y: &[F], t: &F, p: &P) -> Result<F, Err> {
+        scatter(y, &mut self.buf);
+        (self.objective.func)(&self.buf, t, p)
+    }
+
+    /// Evaluate the gradient of the objective function at a state given as slice
+    fn objective_gradient(
+        &mut self,
+        y: &[F],
+        t: &F,
+        p: &P,
+        grad_y: &mut Vec<F>,
+        grad_p: &mut Vec<F>,
+    ) -> Result<(), Err> {
+        let n_p = p.into_iter().count();
+        grad_y.clear();
+        grad_y.resize(y.len(), F::from(0));
+        grad_p.clear();
+        grad_p.resize(n_p, F::from(0));
+        scatter(y, &mut self.buf);
+        if let Some(gradient) = self.objective.gradient {
+            return gradient(&self.buf, t, p, grad_y, grad_p);
+        }
+
+        let sqrt_eps = F::epsilon().sqrt();
+        let g0 = (self.objective.func)(&self.buf, t, p)?;
+        let mut y_pert = y.to_vec();
+        for (j, gj) in grad_y.iter_mut().enumerate() {
+            let delta = sqrt_eps * y[j].abs().max(F::from(1));
+            y_pert[j] = y[j] + delta;
+            scatter(&y_pert, &mut self.buf);
+            *gj = ((self.objective.func)(&self.buf, t, p)? - g0) / delta;
+            y_pert[j] = y[j];
+        }
+        scatter(y, &mut self.buf);
+        for (k, gk) in grad_p.iter_mut().enumerate() {
+            let mut p_pert = p.clone();
+            let mut delta = F::from(0);
+            if let Some(pk) = (&mut p_pert).into_iter().nth(k) {
+                delta = sqrt_eps * pk.abs().max(F::from(1));
+                *pk += delta;
+            }
+            *gk = ((self.objective.func)(&self.buf, t, &p_pert)? - g0) / delta;
+        }
+        Ok(())
+    }
+
+    /// Classical Runge-Kutta step of the state which also integrates the objective if required
+    fn forward_step(
+        &mut self,
+        y: &mut [F],
+        t: &F,
+        h: &F,
+        p: &P,
+        integral: Option<&mut F>,
+    ) -> Result<(), Err> {
+        let half = F::from(1) / F::from(2);
+        let mut k = vec![Vec::new(); 4];
+        let mut g = [F::from(0); 4];
+        let mut y_stage = y.to_vec();
+        for (stage, c) in [F::from(0), half, half, F::from(1)].into_iter().enumerate() {
+            if stage > 0 {
+                for (yi, (y0i, ki)) in y_stage.iter_mut().zip(y.iter().zip(&k[stage - 1])) {
+                    *yi = *y0i + c * *h * *ki;
+                }
+            }
+            let t_stage = *t + c * *h;
+            let mut f = Vec::new();
+            self.rhs.eval(&y_stage, &t_stage, p, &mut f)?;
+            k[stage] = f;
+            if integral.is_some() {
+                g[stage] = self.objective_value(&y_stage, &t_stage, p)?;
+            }
+        }
+        let sixth = *h / F::from(6);
+        let two = F::from(2);
+        for (i, yi) in y.iter_mut().enumerate() {
+            *yi += sixth * (k[0][i] + two * k[1][i] + two * k[2][i] + k[3][i]);
+        }
+        if let Some(value) = integral {
+            *value += sixth * (g[0] + two * g[1] + two * g[2] + g[3]);
+        }
+        Ok(())
+    }
+
+    /// Evaluate the RHS of the adjoint equations
+    fn adjoint_rhs(
+        &mut self,
+        y: &[F],
+        t: &F,
+        lambda: &[F],
+        p: &P,
+        d_lambda: &mut [F],
+        d_mu: &mut [F],
+    ) -> Result<(), Err> {
+        let n = y.len();
+        let n_p = d_mu.len();
+        let mut f = Vec::new();
+        let mut jac_y = Vec::new();
+        let mut jac_p = Vec::new();
+        self.rhs.eval(y, t, p, &mut f)?;
+        self.rhs.jacobians(y, t, p, &f, &mut jac_y, &mut jac_p)?;
+        let mut grad_y = vec![F::from(0); n];
+        let mut grad_p = vec![F::from(0); n_p];
+        if let ObjectiveKind::Integral = self.objective.kind {
+            self.objective_gradient(y, t, p, &mut grad_y, &mut grad_p)?;
+        }
+        for (j, dl) in d_lambda.iter_mut().enumerate() {
+            *dl = -grad_y[j];
+            for (i, li) in lambda.iter().enumerate() {
+                *dl -= jac_y[i * n + j] * *li;
+            }
+        }
+        for (k, dm) in d_mu.iter_mut().enumerate() {
+            *dm = -grad_p[k];
+            for (i, li) in lambda.iter().enumerate() {
+                *dm -= jac_p[i * n_p + k] * *li;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the gradient of a pointwise loss to the adjoint variables
+    fn add_pointwise_gradient(
+        &mut self,
+        y: &[F],
+        t: &F,
+        p: &P,
+        count: usize,
+        lambda: &mut [F],
+        mu: &mut [F],
+    ) -> Result<(), Err> {
+        if count == 0 {
+            return Ok(());
+        }
+        let mut grad_y = Vec::new();
+        let mut grad_p = Vec::new();
+        self.objective_gradient(y, t, p, &mut grad_y, &mut grad_p)?;
+        let count = F::from_usize(count);
+        for (li, gi) in lambda.iter_mut().zip(grad_y.iter()) {
+            *li += count * *gi;
+        }
+        for (mi, gi) in mu.iter_mut().zip(grad_p.iter()) {
+            *mi += count * *gi;
+        }
+        Ok(())
+    }
+
+    /// Classical Runge-Kutta step of the adjoint equations backward from `t1` to `t0`
+    fn backward_step(
+        &mut self,
+        states: [&[F]; 3],
+        t0: &F,
+        t1: &F,
+        p: &P,
+        lambda: &mut [F],
+        mu: &mut [F],
+    ) -> Result<(), Err> {
+        let h = *t1 - *t0;
+        let half = F::from(1) / F::from(2);
+        let t_mid = *t0 + half * h;
+        let n = lambda.len();
+        let n_p = mu.len();
+        let mut kl = vec![vec![F::from(0); n]; 4];
+        let mut km = vec![vec![F::from(0); n_p]; 4];
+        let mut l_stage = lambda.to_vec();
+        for (stage, (c, y, t)) in [
+            (F::from(0), states[2], *t1),
+            (half, states[1], t_mid),
+            (half, states[1], t_mid),
+            (F::from(1), states[0], *t0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if stage > 0 {
+                for (li, (l0i, ki)) in l_stage.iter_mut().zip(lambda.iter().zip(&kl[stage - 1])) {
+                    *li = *l0i - c * h * *ki;
+                }
+            }
+            let (kl_stage, km_stage) = (&mut kl[stage], &mut km[stage]);
+            self.adjoint_rhs(y, &t, &l_stage, p, kl_stage, km_stage)?;
+        }
+        let sixth = h / F::from(6);
+        let two = F::from(2);
+        for (i, li) in lambda.iter_mut().enumerate() {
+            *li -= sixth * (kl[0][i] + two * kl[1][i] + two * kl[2][i] + kl[3][i]);
+        }
+        for (k, mk) in mu.iter_mut().enumerate() {
+            *mk -= sixth * (km[0][k] + two * km[1][k] + two * km[2][k] + km[3][k]);
+        }
+        Ok(())
+    }
+
+    /// Compute the objective and its derivatives for the time points `t_series` using steps of
+    /// size at most `dt`
+    pub fn solve(
+        &mut self,
+        t_series: &[F],
+        p: &P,
+        dt: &F,
+    ) -> Result<AdjointResult<F>, SolvingError> {
+        let to_solving_error = |error: Err| SolvingError::from(alloc::format!("{error}"));
+        if t_series.is_empty() {
+            return Err(SolvingError::from("At least one time point is required"));
+        }
+        if *dt <= F::from(0) {
+            return Err(SolvingError::from("Step size needs to be positive"));
+        }
+
+        // Time grid of all steps and number of time points which coincide with every grid point
+        let t0 = self.rhs.sens_def.t0;
+        let mut grid = vec![t0];
+        let mut counts = vec![0];
+        let mut t = t0;
+        for t_j in t_series.iter() {
+            if *t_j < t {
+                return Err(SolvingError::from(
+                    "Time steps need to be increasing and not before the initial time point",
+                ));
+            }
+            while t < *t_j {
+                t = if *dt >= *t_j - t { *t_j } else { t + *dt };
+                grid.push(t);
+                counts.push(0);
+            }
+            if let Some(count) = counts.last_mut() {
+                *count += 1;
+            }
+        }
+        let n_steps = grid.len() - 1;
+
+        // Forward pass storing checkpoints
+        let mut y = Vec::new();
+        gather(&self.rhs.sens_def.y0, &mut y);
+        let n = y.len();
+        let n_p = p.into_iter().count();
+        let pointwise = matches!(self.objective.kind, ObjectiveKind::Pointwise);
+        let mut value = F::from(0);
+        let mut checkpoints = vec![(0, y.clone())];
+        for k in 0..n_steps {
+            if pointwise && counts[k] > 0 {
+                let g = self
+                    .objective_value(&y, &grid[k], p)
+                    .map_err(to_solving_error)?;
+                value += F::from_usize(counts[k]) * g;
+            }
+            let h = grid[k + 1] - grid[k];
+            let integral = if pointwise { None } else { Some(&mut value) };
+            self.forward_step(&mut y, &grid[k], &h, p, integral)
+                .map_err(to_solving_error)?;
+            if (k + 1) % self.checkpoint_every == 0 && k + 1 < n_steps {
+                checkpoints.push((k + 1, y.clone()));
+            }
+        }
+        if pointwise {
+            let g = self
+                .objective_value(&y, &grid[n_steps], p)
+                .map_err(to_solving_error)?;
+            value += F::from_usize(counts[n_steps]) * g;
+        }
+
+        // Backward pass recomputing the states between checkpoints
+        let mut lambda = vec![F::from(0); n];
+        let mut mu = vec![F::from(0); n_p];
+        let mut end = n_steps;
+        for (start, y_start) in checkpoints.into_iter().rev() {
+            let mut states = vec![y_start];
+            let mut derivatives = Vec::new();
+            for k in start..end {
+                let mut y_next = states[k - start].clone();
+                let mut f = Vec::new();
+                self.rhs
+                    .eval(&y_next, &grid[k], p, &mut f)
+                    .map_err(to_solving_error)?;
+                derivatives.push(f);
+                let h = grid[k + 1] - grid[k];
+                self.forward_step(&mut y_next, &grid[k], &h, p, None)
+                    .map_err(to_solving_error)?;
+                states.push(y_next);
+            }
+            let mut f_end = Vec::new();
+            self.rhs
+                .eval(&states[end - start], &grid[end], p, &mut f_end)
+                .map_err(to_solving_error)?;
+            derivatives.push(f_end);
+
+            for k in (start..end).rev() {
+                let (i0, i1) = (k - start, k + 1 - start);
+                if pointwise {
+                    self.add_pointwise_gradient(
+                        &states[i1],
+                        &grid[k + 1],
+                        p,
+                        counts[k + 1],
+                        &mut lambda,
+                        &mut mu,
+                    )
+                    .map_err(to_solving_error)?;
+                }
+                // Hermite interpolation at the midpoint of the step
+                let h = grid[k + 1] - grid[k];
+                let eighth = h / F::from(8);
+                let half = F::from(1) / F::from(2);
+                let y_mid: Vec<F> = (0..n)
+                    .map(|i| {
+                        half * (states[i0][i] + states[i1][i])
+                            + eighth * (derivatives[i0][i] - derivatives[i1][i])
+                    })
+                    .collect();
+                self.backward_step(
+                    [&states[i0], &y_mid, &states[i1]],
+                    &grid[k],
+                    &grid[k + 1],
+                    p,
+                    &mut lambda,
+                    &mut mu,
+                )
+                .map_err(to_solving_error)?;
+            }
+            end = start;
+        }
+        if pointwise {
+            let mut y0 = Vec::new();
+            gather(&self.rhs.sens_def.y0, &mut y0);
+            self.add_pointwise_gradient(&y0, &grid[0], p, counts[0], &mut lambda, &mut mu)
+                .map_err(to_solving_error)?;
+        }
+
+        Ok(AdjointResult {
+            value,
+            dg_dp: mu,
+            dg_dy0: lambda,
+        })
+    }
+}
@@ -0,0 +1,539 @@
+use crate::concepts::*;
+
+use core::cell::RefCell;
+use core::fmt::Display;
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Check the layout of a batch and return the number of states of a single lane
+fn batch_layout<F, P, Err>(
+    batch_def: &BatchedOdeDefinition<F, P, Err>,
+) -> Result<usize, SolvingError> {
+    if batch_def.n_lanes == 0 {
+        return Err(SolvingError::from("A batch needs at least one lane"));
+    }
+    if !batch_def.y0.len().is_multiple_of(batch_def.n_lanes) {
+        return Err(SolvingError::from(alloc::format!(
+            "Initial values of length {} can not be split into {} lanes",
+            batch_def.y0.len(),
+            batch_def.n_lanes
+        )));
+    }
+    Ok(batch_def.y0.len() / batch_def.n_lanes)
+}
+
+/// Return an error if the state does not match the size of the batch
+fn check_state_length<F, Err>(y: &[F], n: usize) -> Result<(), Err>
+where
+    Err: From<CalcError>,
+{
+    if y.len() != n {
+        return Err(CalcError::from(alloc::format!(
+            "State of the batch needs {} entries but has {}",
+            n,
+            y.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// RHS of a batch which is stored on the heap
+type BoxedBatchedRHS<'a, F, P, Err> = Box<dyn Fn(&[F], &mut [F], &[F], &P) -> Result<(), Err> + 'a>;
+
+/// # Batch of copies of an existing ODE
+/// Adapts the [RHS] of an [OdeDefinition] to a [BatchedRHS] such that the ODE does not need to be
+/// rewritten for batched steppers. Every evaluation gathers the state of each lane, calls the
+/// RHS with the time of the lane and scatters the derivative back. All lanes share the
+/// parameters. A [BatchedRHS] written as loops over the lanes avoids these copies and remains
+/// the faster choice.
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &f64) -> Result<(), CalcError> {
+///     dy[0] = y[1];
+///     dy[1] = -p * y[0];
+///     Ok(())
+/// }
+///
+/// let ode_def = OdeDefinition { y0: [1.0, 0.0], t0: 0.0, func: &rhs };
+/// let copies = LaneWiseRHS::new(ode_def);
+/// let batch_def = copies.batch(&[[1.0, 0.0], [0.0, 1.0]]).unwrap();
+/// let mut rk4 = BatchedRk4::new(batch_def).unwrap();
+///
+/// let mut y = vec![1.0, 0.0, 0.0, 1.0];
+/// for i in 0..100 {
+///     rk4.do_step(&mut y, &(0.01 * i as f64), &0.01, &1.0).unwrap();
+/// }
+/// assert!((y[0] - 1.0_f64.cos()).abs() < 1e-8);
+/// assert!((y[1] - 1.0_f64.sin()).abs() < 1e-8);
+/// ```
+pub struct LaneWiseRHS<'a, I, F, P, Err> {
+    /// Definition of a single copy of the ODE
+    ode_def: OdeDefinition<'a, I, F, P, Err>,
+    /// RHS of the batch which gathers and scatters the lanes
+    func: BoxedBatchedRHS<'a, F, P, Err>,
+}
+
+impl<'a, I, F, P, Err> LaneWiseRHS<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone + 'a,
+    F: Copy + 'a,
+    P: 'a,
+    Err: From<CalcError> + 'a,
+{
+    /// Adapt the RHS of the given ODE. Its initial value determines the size of every lane.
+    pub fn new(ode_def: OdeDefinition<'a, I, F, P, Err>) -> Self {
+        let rhs = ode_def.func;
+        let n_states = (&ode_def.y0).into_iter().count();
+        // States of a single lane which are reused by every evaluation
+        let buffers = RefCell::new((ode_def.y0.clone(), ode_def.y0.clone()));
+        let func = Box::new(
+            move |y: &[F], dy: &mut [F], t: &[F], p: &P| -> Result<(), Err> {
+                let n_lanes = t.len();
+                check_state_length(y, n_states * n_lanes)?;
+                check_state_length(dy, n_states * n_lanes)?;
+                let mut buffers = buffers.borrow_mut();
+                let (y_lane, dy_lane) = &mut *buffers;
+                for (l, t_l) in t.iter().enumerate() {
+                    for (i, yi) in (&mut *y_lane).into_iter().enumerate() {
+                        *yi = y[i * n_lanes + l];
+                    }
+                    rhs(y_lane, dy_lane, t_l, p)?;
+                    for (i, dyi) in (&*dy_lane).into_iter().enumerate() {
+                        dy[i * n_lanes + l] = *dyi;
+                    }
+                }
+                Ok(())
+            },
+        );
+        LaneWiseRHS { ode_def, func }
+    }
+
+    /// RHS of a batch of any number of lanes
+    pub fn rhs(&self) -> BatchedRHS<'_, F, P, Err> {
+        self.func.as_ref()
+    }
+
+    /// Batch which starts from the given initial values of every lane at the initial time of the
+    /// ODE. Fails if a lane does not have the size of the initial value of the ODE.
+    pub fn batch(&self, lanes: &[I]) -> Result<BatchedOdeDefinition<'_, F, P, Err>, SolvingError> {
+        let n_states = (&self.ode_def.y0).into_iter().count();
+        if let Some(lane) = lanes
+            .iter()
+            .find(|lane| lane.into_iter().count() != n_states)
+        {
+            return Err(SolvingError::from(alloc::format!(
+                "Lanes need {} components like the ODE but one has {}",
+                n_states,
+                lane.into_iter().count()
+            )));
+        }
+        BatchedOdeDefinition::from_lanes(lanes, self.ode_def.t0, self.rhs())
+    }
+}
+
+/// # Batched Runge-Kutta stepper of 4th order
+/// Integrates all lanes of a [BatchedOdeDefinition] with the classical Runge-Kutta method and a
+/// step size shared by all lanes. The stage arithmetic runs over contiguous arrays and can thus be
+/// vectorized by the compiler.
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64], dy: &mut [f64], _t: &[f64], p: &Vec<f64>) -> Result<(), CalcError> {
+///     for ((dyl, yl), pl) in dy.iter_mut().zip(y).zip(p) {
+///         *dyl = -pl * yl;
+///     }
+///     Ok(())
+/// }
+///
+/// let batch_def = BatchedOdeDefinition::from_lanes(&[[1.0], [1.0]], 0.0, &rhs).unwrap();
+/// let mut rk4 = BatchedRk4::new(batch_def).unwrap();
+///
+/// let p = vec![1.0, 2.0];
+/// let mut y = vec![1.0, 1.0];
+/// for i in 0..10 {
+///     rk4.do_step(&mut y, &(0.1 * i as f64), &0.1, &p).unwrap();
+/// }
+/// assert!((y[0] - (-1.0_f64).exp()).abs() < 1e-6);
+/// assert!((y[1] - (-2.0_f64).exp()).abs() < 1e-5);
+/// ```
+pub struct BatchedRk4<'a, F, P, Err> {
+    /// Definition of the batch which is solved
+    batch_def: BatchedOdeDefinition<'a, F, P, Err>,
+    /// Time of every lane at the evaluated stage
+    t_stage: Vec<F>,
+    /// State at the evaluated stage
+    y_stage: Vec<F>,
+    /// Sum of the weighted stages
+    y_sum: Vec<F>,
+    /// Storage for the evaluated RHS
+    dy: Vec<F>,
+}
+
+impl<'a, F, P, Err> BatchedRk4<'a, F, P, Err>
+where
+    F: FloatLikeType,
+    Err: From<CalcError>,
+{
+    /// Construct a new stepper. Fails if the initial values can not be split into the lanes.
+    pub fn new(batch_def: BatchedOdeDefinition<'a, F, P, Err>) -> Result<Self, SolvingError> {
+        batch_layout(&batch_def)?;
+        let n = batch_def.y0.len();
+        let n_lanes = batch_def.n_lanes;
+        Ok(BatchedRk4 {
+            t_stage: vec![batch_def.t0; n_lanes],
+            y_stage: vec![F::from(0); n],
+            y_sum: vec![F::from(0); n],
+            dy: vec![F::from(0); n],
+            batch_def,
+        })
+    }
+
+    /// Advance all lanes of the state `y` (in structure-of-arrays layout) from `t` by `dt`
+    pub fn do_step(&mut self, y: &mut [F], t: &F, dt: &F, p: &P) -> Result<(), Err> {
+        check_state_length(y, self.y_stage.len())?;
+        let half = F::from(1) / F::from(2);
+        let sixth = F::from(1) / F::from(6);
+        let third = F::from(1) / F::from(3);
+
+        // (offset of the stage, weight in the final sum, offset of the next stage)
+        let stages = [
+            (F::from(0), sixth, half),
+            (half, third, half),
+            (half, third, F::from(1)),
+            (F::from(1), sixth, F::from(0)),
+        ];
+        self.y_stage.copy_from_slice(y);
+        for (s, (c, b, a_next)) in stages.into_iter().enumerate() {
+            let t_s = *t + c * *dt;
+            self.t_stage.iter_mut().for_each(|ts| *ts = t_s);
+            (self.batch_def.func)(&self.y_stage, &mut self.dy, &self.t_stage, p)?;
+            let (w, a) = (b * *dt, a_next * *dt);
+            if s == 0 {
+                for (((ys, yl), sum), dyl) in self
+                    .y_stage
+                    .iter_mut()
+                    .zip(y.iter())
+                    .zip(self.y_sum.iter_mut())
+                    .zip(&self.dy)
+                {
+                    *sum = w * *dyl;
+                    *ys = *yl + a * *dyl;
+                }
+            } else {
+                for (((ys, yl), sum), dyl) in self
+                    .y_stage
+                    .iter_mut()
+                    .zip(y.iter())
+                    .zip(self.y_sum.iter_mut())
+                    .zip(&self.dy)
+                {
+                    *sum += w * *dyl;
+                    *ys = *yl + a * *dyl;
+                }
+            }
+        }
+        for (yl, sum) in y.iter_mut().zip(&self.y_sum) {
+            *yl += *sum;
+        }
+        Ok(())
+    }
+}
+
+/// Nodes \\(c_s\\) of the Dormand-Prince method
+const DOPRI5_C: [f64; 7] = [0.0, 0.2, 0.3, 0.8, 8.0 / 9.0, 1.0, 1.0];
+
+/// Coefficients \\(a_{sj}\\) of the Dormand-Prince method. The last row equals the weights of
+/// the solution of 5th order.
+const DOPRI5_A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [0.2, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+
+/// Difference between the weights of the 5th and 4th order solutions of the Dormand-Prince method
+const DOPRI5_E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+/// # Batched Dormand-Prince stepper with per-lane step sizes
+/// Integrates all lanes of a [BatchedOdeDefinition] with the embedded Runge-Kutta pair of
+/// Dormand and Prince of order 5(4). Every lane keeps its own time and step size which is
+/// controlled by the [Tolerances]. A step is always attempted for all lanes at once, lanes whose
+/// step is rejected or which already reached the final time are masked and left unchanged.
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64], dy: &mut [f64], _t: &[f64], p: &Vec<f64>) -> Result<(), CalcError> {
+///     for ((dyl, yl), pl) in dy.iter_mut().zip(y).zip(p) {
+///         *dyl = -pl * yl;
+///     }
+///     Ok(())
+/// }
+///
+/// let batch_def = BatchedOdeDefinition::from_lanes(&[[1.0], [1.0]], 0.0, &rhs).unwrap();
+/// let tolerances = Tolerances { rtol: 1e-8, atol: 1e-8 };
+/// let mut dopri = BatchedDopri5::new(batch_def, tolerances).unwrap();
+///
+/// let p = vec![1.0, 20.0];
+/// let mut y = vec![1.0, 1.0];
+/// let mut t = vec![0.0, 0.0];
+/// dopri.integrate_to(&mut y, &mut t, &1.0, &p).unwrap();
+/// assert_eq!(t, vec![1.0, 1.0]);
+/// assert!((y[0] - (-1.0_f64).exp()).abs() < 1e-7);
+/// assert!((y[1] - (-20.0_f64).exp()).abs() < 1e-7);
+/// ```
+pub struct BatchedDopri5<'a, F, P, Err> {
+    /// Definition of the batch which is solved
+    batch_def: BatchedOdeDefinition<'a, F, P, Err>,
+    /// Tolerances used to normalize the error estimate
+    tolerances: Tolerances<F>,
+    /// Maximal number of steps taken by [BatchedDopri5::integrate_to]
+    max_steps: usize,
+    /// Step size of every lane which is used for the next attempt
+    dt: Vec<F>,
+    /// Step size of every lane in the current attempt. Masked lanes use zero.
+    h: Vec<F>,
+    /// Normalized error of every lane in the last attempt
+    err: Vec<F>,
+    /// Time of every lane at the evaluated stage
+    t_stage: Vec<F>,
+    /// State at the evaluated stage
+    y_stage: Vec<F>,
+    /// Evaluated stages
+    k: [Vec<F>; 7],
+}
+
+impl<'a, F, P, Err> BatchedDopri5<'a, F, P, Err>
+where
+    F: RealFloatLikeType,
+    Err: From<CalcError>,
+{
+    /// Default for the initial step size of every lane
+    const DEFAULT_INITIAL_DT: f64 = 1e-2;
+    /// Default for the maximal number of steps of [BatchedDopri5::integrate_to]
+    const DEFAULT_MAX_STEPS: usize = 100_000;
+
+    /// Construct a new stepper. Fails if the initial values can not be split into the lanes.
+    pub fn new(
+        batch_def: BatchedOdeDefinition<'a, F, P, Err>,
+        tolerances: Tolerances<F>,
+    ) -> Result<Self, SolvingError> {
+        batch_layout(&batch_def)?;
+        let n = batch_def.y0.len();
+        let n_lanes = batch_def.n_lanes;
+        let zeros = vec![F::from(0); n];
+        Ok(BatchedDopri5 {
+            tolerances,
+            max_steps: Self::DEFAULT_MAX_STEPS,
+            dt: vec![F::from_f64(Self::DEFAULT_INITIAL_DT); n_lanes],
+            h: vec![F::from(0); n_lanes],
+            err: vec![F::from(0); n_lanes],
+            t_stage: vec![batch_def.t0; n_lanes],
+            y_stage: zeros.clone(),
+            k: [
+                zeros.clone(),
+                zeros.clone(),
+                zeros.clone(),
+                zeros.clone(),
+                zeros.clone(),
+                zeros.clone(),
+                zeros,
+            ],
+            batch_def,
+        })
+    }
+
+    /// Change the step size with which every lane starts
+    pub fn with_initial_dt(mut self, dt: F) -> Self {
+        self.dt.iter_mut().for_each(|dtl| *dtl = dt);
+        self
+    }
+
+    /// Change the maximal number of steps taken by [BatchedDopri5::integrate_to]
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Step sizes which every lane uses for its next attempt
+    pub fn suggested_dt(&self) -> &[F] {
+        &self.dt
+    }
+
+    /// Normalized errors of every lane in the last attempt. Masked lanes report zero.
+    pub fn errors(&self) -> &[F] {
+        &self.err
+    }
+
+    /// Attempt one step of every lane which has not yet reached `t_end`.
+    /// Lanes whose error is not larger than one advance their state `y` and time `t`,
+    /// all other lanes are left unchanged. Afterwards the step sizes of all active lanes are
+    /// adapted. Returns `true` once all lanes reached `t_end`.
+    pub fn do_step(&mut self, y: &mut [F], t: &mut [F], t_end: &F, p: &P) -> Result<bool, Err> {
+        let n_lanes = self.batch_def.n_lanes;
+        check_state_length(y, self.y_stage.len())?;
+        check_state_length(t, n_lanes)?;
+        let n_states = y.len() / n_lanes;
+
+        for ((h, dt), tl) in self.h.iter_mut().zip(&self.dt).zip(t.iter()) {
+            let remaining = *t_end - *tl;
+            *h = if remaining > F::from(0) {
+                dt.min(remaining)
+            } else {
+                F::from(0)
+            };
+        }
+        if self.h.iter().all(|h| *h == F::from(0)) {
+            return Ok(true);
+        }
+
+        (self.batch_def.func)(y, &mut self.k[0], t, p)?;
+        for s in 1..7 {
+            let a = DOPRI5_A[s].map(F::from_f64);
+            self.y_stage.copy_from_slice(y);
+            for (j, aj) in a.iter().enumerate().take(s) {
+                if *aj == F::from(0) {
+                    continue;
+                }
+                for i in 0..n_states {
+                    let range = i * n_lanes..(i + 1) * n_lanes;
+                    for ((ys, kj), h) in self.y_stage[range.clone()]
+                        .iter_mut()
+                        .zip(&self.k[j][range])
+                        .zip(&self.h)
+                    {
+                        *ys += *aj * *h * *kj;
+                    }
+                }
+            }
+            let c = F::from_f64(DOPRI5_C[s]);
+            for ((ts, tl), h) in self.t_stage.iter_mut().zip(t.iter()).zip(&self.h) {
+                *ts = *tl + c * *h;
+            }
+            (self.batch_def.func)(&self.y_stage, &mut self.k[s], &self.t_stage, p)?;
+        }
+
+        // The last stage was evaluated at the solution of 5th order which is stored in y_stage
+        let e = DOPRI5_E.map(F::from_f64);
+        self.err.iter_mut().for_each(|el| *el = F::from(0));
+        for i in 0..n_states {
+            for (l, err) in self.err.iter_mut().enumerate() {
+                let j = i * n_lanes + l;
+                let mut diff = F::from(0);
+                for (ej, kj) in e.iter().zip(&self.k) {
+                    diff += *ej * kj[j];
+                }
+                let sc = self.tolerances.atol
+                    + self.tolerances.rtol * y[j].abs().max(self.y_stage[j].abs());
+                let q = self.h[l] * diff / sc;
+                *err += q * q;
+            }
+        }
+
+        let n = F::from_usize(n_states.max(1));
+        let fac_min = F::from_f64(0.2);
+        let fac_max = F::from(5);
+        let exponent = F::from_f64(0.2);
+        for (l, err) in self.err.iter_mut().enumerate() {
+            let h = self.h[l];
+            if h == F::from(0) {
+                *err = F::from(0);
+                continue;
+            }
+            *err = (*err / n).sqrt();
+            let accepted = *err <= F::from(1);
+            let fac = if !err.is_finite() {
+                fac_min
+            } else if *err == F::from(0) {
+                fac_max
+            } else {
+                (F::from_f64(0.9) * (F::from(1) / *err).powf(exponent))
+                    .max(fac_min)
+                    .min(fac_max)
+            };
+            if accepted {
+                for i in 0..n_states {
+                    y[i * n_lanes + l] = self.y_stage[i * n_lanes + l];
+                }
+                let remaining = *t_end - t[l];
+                t[l] = if h >= remaining { *t_end } else { t[l] + h };
+                // A step shortened by the final time should not reduce the step size
+                self.dt[l] = if h < self.dt[l] {
+                    self.dt[l].max(h * fac)
+                } else {
+                    h * fac
+                };
+            } else {
+                self.dt[l] = h * fac.min(F::from(1));
+            }
+        }
+        Ok(t.iter().all(|tl| *tl >= *t_end))
+    }
+
+    /// Repeat [BatchedDopri5::do_step] until all lanes reached `t_end`.
+    /// Returns the number of attempted steps.
+    pub fn integrate_to(
+        &mut self,
+        y: &mut [F],
+        t: &mut [F],
+        t_end: &F,
+        p: &P,
+    ) -> Result<usize, SolvingError>
+    where
+        Err: Display,
+    {
+        for n_steps in 0..self.max_steps {
+            match self.do_step(y, t, t_end, p) {
+                Ok(true) => return Ok(n_steps + 1),
+                Ok(false) => (),
+                Err(error) => return Err(SolvingError::from(alloc::format!("{error}"))),
+            }
+            for (dt, tl) in self.dt.iter().zip(t.iter()) {
+                if *tl < *t_end && *dt <= F::epsilon() * tl.abs().max(F::from(1)) {
+                    return Err(SolvingError::from("Step size of a lane became too small"));
+                }
+            }
+        }
+        Err(SolvingError::from(alloc::format!(
+            "Lanes did not reach the final time within {} steps",
+            self.max_steps
+        )))
+    }
+}
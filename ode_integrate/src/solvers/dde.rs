@@ -0,0 +1,302 @@
+use crate::concepts::*;
+
+use core::cell::Cell;
+
+use alloc::vec::Vec;
+
+/// Discontinuities in derivatives of higher order than this do not affect the
+/// accuracy of the 4th order Runge-Kutta method and are not tracked.
+const DISCONTINUITY_ORDER: usize = 5;
+
+/// # Propagated discontinuities
+/// The derivative of the solution of a DDE is generally discontinuous at \\(t_0\\).
+/// This discontinuity propagates to all points \\(t_0 + \tau_{i_1} + \dots + \tau_{i_k}\\)
+/// where it appears in the \\(k\\)-th derivative.
+/// Returns all such points up to `t_end` in increasing order.
+pub fn propagated_discontinuities<F>(t0: &F, delays: &[F], t_end: &F) -> Vec<F>
+where
+    F: RealFloatLikeType,
+{
+    let mut level = alloc::vec![*t0];
+    let mut points = Vec::new();
+    for _ in 0..DISCONTINUITY_ORDER {
+        let mut next = Vec::new();
+        for t in level.iter() {
+            for tau in delays.iter() {
+                let s = *t + *tau;
+                if s <= *t_end {
+                    next.push(s);
+                }
+            }
+        }
+        sort_dedup(&mut next);
+        points.extend(next.iter().copied());
+        level = next;
+    }
+    sort_dedup(&mut points);
+    points
+}
+
+/// Sort the values and remove those which coincide up to rounding errors
+fn sort_dedup<F>(values: &mut Vec<F>)
+where
+    F: RealFloatLikeType,
+{
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal));
+    values.dedup_by(|b, a| (*b - *a).abs() <= F::from(16) * F::epsilon() * b.abs().max(F::from(1)));
+}
+
+/// # Dense history of a DDE solution
+/// Stores all accepted steps of the solution and evaluates it in between by cubic Hermite
+/// interpolation
+/// \begin{equation}
+///     y(t_k + \theta h) = (2\theta^3 - 3\theta^2 + 1)y_k + (\theta^3 - 2\theta^2 + \theta)h f_k
+///         + (-2\theta^3 + 3\theta^2)y_{k+1} + (\theta^3 - \theta^2)h f_{k+1}.
+/// \end{equation}
+/// Before the initial time point, the [InitialHistory] is used.
+pub struct DenseHistory<'a, I, F> {
+    /// Solution before the initial time point
+    initial: InitialHistory<'a, I, F>,
+    /// Time points of the accepted steps
+    times: Vec<F>,
+    /// Solution at the time points
+    values: Vec<I>,
+    /// Derivative at the beginning of every step
+    dy_start: Vec<I>,
+    /// Derivative at the end of every step evaluated as limit from within the step
+    dy_end: Vec<I>,
+    /// If set, the initial time point is evaluated as limit from the left
+    left_limit: Cell<bool>,
+}
+
+impl<'a, I, F> DenseHistory<'a, I, F>
+where
+    F: RealFloatLikeType,
+{
+    /// Time points of the accepted steps
+    pub fn times(&self) -> &[F] {
+        &self.times
+    }
+
+    /// Solution at the time points of the accepted steps
+    pub fn values(&self) -> &[I] {
+        &self.values
+    }
+}
+
+impl<'a, I, F> History<I, F> for DenseHistory<'a, I, F>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    fn at(&self, t: &F) -> I {
+        let t0 = self.times[0];
+        if *t < t0 || (*t == t0 && self.left_limit.get()) {
+            return (self.initial)(t);
+        }
+        let n = self.dy_end.len();
+        if n == 0 {
+            return self.values[0].clone();
+        }
+        let k = self.times.partition_point(|tk| tk <= t).clamp(1, n) - 1;
+        let h = self.times[k + 1] - self.times[k];
+        let theta = (*t - self.times[k]) / h;
+        let theta2 = theta * theta;
+        let theta3 = theta2 * theta;
+        let two = F::from(2);
+        let three = F::from(3);
+        let h00 = two * theta3 - three * theta2 + F::from(1);
+        let h10 = (theta3 - two * theta2 + theta) * h;
+        let h01 = three * theta2 - two * theta3;
+        let h11 = (theta3 - theta2) * h;
+
+        let mut y = self.values[k].clone();
+        for ((((yi, y0i), f0i), y1i), f1i) in (&mut y)
+            .into_iter()
+            .zip(&self.values[k])
+            .zip(&self.dy_start[k])
+            .zip(&self.values[k + 1])
+            .zip(&self.dy_end[k])
+        {
+            *yi = h00 * *y0i + h10 * *f0i + h01 * *y1i + h11 * *f1i;
+        }
+        y
+    }
+}
+
+/// # Method of steps for DDEs
+/// Integrates a [DdeDefinition] with the classical 4th order Runge-Kutta method (see [Rk4](crate::Rk4)).
+/// Delayed values are obtained from the [DenseHistory] of all previous steps.
+/// The step size is restricted to the smallest delay such that delayed arguments always lie in
+/// already computed steps. Steps are shortened to land exactly on the
+/// [propagated discontinuities](propagated_discontinuities) of the solution.
+pub struct MethodOfSteps<'a, I, F, P, Err> {
+    /// RHS of the DDE
+    func: DdeRHS<'a, I, F, P, Err>,
+    /// Dense output of all computed steps
+    history: DenseHistory<'a, I, F>,
+    /// Points at which the solution is not smooth
+    discontinuities: Vec<F>,
+    /// Index of the next discontinuity which has not been reached yet
+    next_discontinuity: usize,
+    /// Maximal step size
+    dt_max: F,
+    /// Final time point of the integration
+    t_end: F,
+    /// Current time point
+    t: F,
+    /// Current value of the solution
+    y: I,
+    /// Derivative at the current time point evaluated as limit from the right
+    dy: I,
+    /// Increments of the Runge-Kutta method
+    k: [I; 4],
+    /// Intermediate state at which the RHS is evaluated
+    ym: I,
+}
+
+impl<'a, I, F, P, Err> MethodOfSteps<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    /// Prepare the integration of the DDE until `t_end` with steps of size at most `dt`.
+    pub fn new(
+        dde_def: DdeDefinition<'a, I, F, P, Err>,
+        dt: &F,
+        t_end: &F,
+    ) -> Result<Self, SolvingError> {
+        let mut dt_max = *dt;
+        for tau in dde_def.delays.iter() {
+            if *tau <= F::from(0) {
+                return Err(SolvingError::from("Delays need to be positive"));
+            }
+            dt_max = dt_max.min(*tau);
+        }
+        if dt_max <= F::from(0) {
+            return Err(SolvingError::from("Step size needs to be positive"));
+        }
+        let discontinuities = propagated_discontinuities(&dde_def.t0, &dde_def.delays, t_end);
+        let y = dde_def.y0.clone();
+        Ok(MethodOfSteps {
+            func: dde_def.func,
+            history: DenseHistory {
+                initial: dde_def.history,
+                times: alloc::vec![dde_def.t0],
+                values: alloc::vec![y.clone()],
+                dy_start: Vec::new(),
+                dy_end: Vec::new(),
+                left_limit: Cell::new(false),
+            },
+            discontinuities,
+            next_discontinuity: 0,
+            dt_max,
+            t_end: *t_end,
+            t: dde_def.t0,
+            dy: y.clone(),
+            k: [y.clone(), y.clone(), y.clone(), y.clone()],
+            ym: y.clone(),
+            y,
+        })
+    }
+
+    /// Current time point
+    pub fn t(&self) -> &F {
+        &self.t
+    }
+
+    /// Current value of the solution
+    pub fn y(&self) -> &I {
+        &self.y
+    }
+
+    /// Dense output of all steps computed so far
+    pub fn history(&self) -> &DenseHistory<'a, I, F> {
+        &self.history
+    }
+
+    /// Returns `true` once the final time point has been reached
+    pub fn is_finished(&self) -> bool {
+        self.t >= self.t_end
+    }
+
+    /// Evaluate the RHS at the given point
+    fn eval(&self, y: &I, dy: &mut I, t: &F, p: &P) -> Result<(), Err> {
+        (self.func)(y, &self.history, dy, t, p)
+    }
+
+    /// Do a single step of the method.
+    /// The step is shortened to hit the next discontinuity or the final time point.
+    pub fn do_step_iter(&mut self, p: &P) -> Result<(), Err> {
+        if self.is_finished() {
+            return Ok(());
+        }
+        if self.history.dy_start.len() == self.history.dy_end.len() {
+            // Derivative at the start of the step as limit from the right
+            self.history.left_limit.set(false);
+            let mut dy = self.dy.clone();
+            self.eval(&self.y, &mut dy, &self.t, p)?;
+            self.dy = dy;
+            self.history.dy_start.push(self.dy.clone());
+        }
+
+        // Determine the step size
+        let mut t_next = (self.t + self.dt_max).min(self.t_end);
+        let mut hits_discontinuity = false;
+        if let Some(d) = self.discontinuities.get(self.next_discontinuity) {
+            if *d <= t_next {
+                t_next = *d;
+                hits_discontinuity = true;
+            }
+        }
+        let h = t_next - self.t;
+        let half = F::from(1) / F::from(2);
+
+        // Classical Runge-Kutta stages
+        self.k[0] = self.dy.clone();
+        for (stage, c) in [(1, half), (2, half), (3, F::from(1))] {
+            self.ym = self.y.clone();
+            for (ymi, ki) in (&mut self.ym).into_iter().zip(&self.k[stage - 1]) {
+                *ymi += c * h * *ki;
+            }
+            // The last stage lies at the end of the step and has to use values from within the step
+            self.history.left_limit.set(stage == 3);
+            let mut k = self.k[stage].clone();
+            self.eval(&self.ym, &mut k, &(self.t + c * h), p)?;
+            self.k[stage] = k;
+        }
+        let sixth = F::from(1) / F::from(6);
+        for ((((yi, k1), k2), k3), k4) in (&mut self.y)
+            .into_iter()
+            .zip(&self.k[0])
+            .zip(&self.k[1])
+            .zip(&self.k[2])
+            .zip(&self.k[3])
+        {
+            *yi += h * sixth * (*k1 + F::from(2) * *k2 + F::from(2) * *k3 + *k4);
+        }
+
+        // Derivative at the end of the step as limit from the left
+        let mut dy_end = self.dy.clone();
+        self.eval(&self.y, &mut dy_end, &t_next, p)?;
+        self.history.left_limit.set(false);
+
+        self.t = t_next;
+        self.history.times.push(self.t);
+        self.history.values.push(self.y.clone());
+        self.history.dy_end.push(dy_end.clone());
+
+        if hits_discontinuity {
+            self.next_discontinuity += 1;
+        } else {
+            // The derivative is continuous and can be reused for the next step
+            self.dy = dy_end.clone();
+            self.history.dy_start.push(dy_end);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+use crate::concepts::*;
+use crate::solvers::{gather, scatter};
+
+use core::ops::{Add, Div, Mul, Sub};
+
+use alloc::{vec, vec::Vec};
+
+/// Contains all exponential integrators which solve [SemilinearDefinition]s.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExponentialSolvers {
+    /// First-order exponential Euler method
+    Etd1,
+    /// Fourth-order exponential time differencing method of Cox and Matthews
+    Etdrk4,
+    /// Classical Runge-Kutta method in the integrating factor of the linear part
+    LawsonRk4,
+}
+
+/// Number of quadrature points on the upper half of the contour around every eigenvalue
+const CONTOUR_POINTS: usize = 32;
+
+/// Maximal number of terms of the Taylor series of the matrix exponential
+const MAX_TAYLOR_TERMS: usize = 30;
+
+/// Number of step sizes for which the functions of the linear operator are kept.
+/// Two entries cover the regular step and the shortened last step before an output time.
+const CACHED_STEP_SIZES: usize = 2;
+
+/// Complex number needed for the contour integrals of the φ-functions
+#[derive(Clone, Copy)]
+struct Complex<F> {
+    /// Real part
+    re: F,
+    /// Imaginary part
+    im: F,
+}
+
+impl<F> Complex<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Complex number with vanishing imaginary part
+    fn real(re: F) -> Self {
+        Complex { re, im: F::from(0) }
+    }
+
+    /// Complex exponential \\(e^{a + ib} = e^a(\cos b + i\sin b)\\)
+    fn exp(self) -> Self {
+        let r = self.re.exp();
+        Complex {
+            re: r * self.im.cos(),
+            im: r * self.im.sin(),
+        }
+    }
+}
+
+impl<F: RealFloatLikeType> Add for Complex<F> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Complex {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl<F: RealFloatLikeType> Sub for Complex<F> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Complex {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+impl<F: RealFloatLikeType> Mul for Complex<F> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Complex {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+impl<F: RealFloatLikeType> Div for Complex<F> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let norm = other.re * other.re + other.im * other.im;
+        Complex {
+            re: (self.re * other.re + self.im * other.im) / norm,
+            im: (self.im * other.re - self.re * other.im) / norm,
+        }
+    }
+}
+
+/// # φ-functions of a real number
+/// Computes \\(\varphi_0(z) = e^z\\) and \\(\varphi_{k+1}(z) = (\varphi_k(z) - 1/k!)/z\\) for
+/// \\(k < 3\\). The recursion cancels catastrophically for small \\(|z|\\), so the φ-functions
+/// are instead averaged over a circle of radius one around \\(z\\) as proposed by Kassam and
+/// Trefethen. Since the functions are real on the real axis, the upper half of the circle suffices.
+fn contour_phi_functions<F>(z: F) -> [F; 4]
+where
+    F: RealFloatLikeType,
+{
+    let one = Complex::real(F::from(1));
+    let half = Complex::real(F::from(1) / F::from(2));
+    let points = F::from_usize(CONTOUR_POINTS);
+    let pi = F::from_f64(core::f64::consts::PI);
+    let mut phi = [z.exp(), F::from(0), F::from(0), F::from(0)];
+    for j in 0..CONTOUR_POINTS {
+        let theta = pi * (F::from_usize(j) + F::from(1) / F::from(2)) / points;
+        let w = Complex {
+            re: z + theta.cos(),
+            im: theta.sin(),
+        };
+        let phi_1 = (w.exp() - one) / w;
+        let phi_2 = (phi_1 - one) / w;
+        let phi_3 = (phi_2 - half) / w;
+        phi[1] += phi_1.re / points;
+        phi[2] += phi_2.re / points;
+        phi[3] += phi_3.re / points;
+    }
+    phi
+}
+
+/// Product of two dense `m` x `m` matrices in row-major order
+fn matrix_product<F>(a: &[F], b: &[F], m: usize) -> Vec<F>
+where
+    F: RealFloatLikeType,
+{
+    let mut c = vec![F::from(0); m * m];
+    for i in 0..m {
+        for k in 0..m {
+            let a_ik = a[i * m + k];
+            if a_ik == F::from(0) {
+                continue;
+            }
+            for j in 0..m {
+                c[i * m + j] += a_ik * b[k * m + j];
+            }
+        }
+    }
+    c
+}
+
+/// Maximal absolute column sum of a dense `m` x `m` matrix
+fn one_norm<F>(a: &[F], m: usize) -> F
+where
+    F: RealFloatLikeType,
+{
+    (0..m).fold(F::from(0), |norm, j| {
+        norm.max((0..m).fold(F::from(0), |sum, i| sum + a[i * m + j].abs()))
+    })
+}
+
+/// # Matrix exponential by scaling and squaring
+/// Returns \\(e^B\\) and \\(e^{B/2}\\) of the dense `m` x `m` matrix `b`.
+/// The matrix is scaled by \\(2^{-s}\\), \\(s \geq 1\\), until its norm is at most one half.
+/// The exponential of the scaled matrix is given by its Taylor series and squared \\(s\\) times.
+fn exponential_with_half<F>(b: &[F], m: usize) -> (Vec<F>, Vec<F>)
+where
+    F: RealFloatLikeType,
+{
+    let half = F::from(1) / F::from(2);
+    let norm = one_norm(b, m);
+    let mut squarings = 1;
+    let mut scale = F::from(2);
+    while norm / scale > half {
+        squarings += 1;
+        scale = scale * F::from(2);
+    }
+    let x: Vec<F> = b.iter().map(|bij| *bij / scale).collect();
+
+    let mut sum = vec![F::from(0); m * m];
+    (0..m).for_each(|i| sum[i * m + i] = F::from(1));
+    let mut term = sum.clone();
+    for k in 1..=MAX_TAYLOR_TERMS {
+        let k = F::from_usize(k);
+        term = matrix_product(&term, &x, m)
+            .into_iter()
+            .map(|tij| tij / k)
+            .collect();
+        sum.iter_mut().zip(term.iter()).for_each(|(s, t)| *s += *t);
+        if one_norm(&term, m) <= F::epsilon() * one_norm(&sum, m) {
+            break;
+        }
+    }
+
+    let mut exp_half = sum;
+    for _ in 1..squarings {
+        exp_half = matrix_product(&exp_half, &exp_half, m);
+    }
+    (matrix_product(&exp_half, &exp_half, m), exp_half)
+}
+
+/// # φ-functions of a dense matrix
+/// Returns \\(\varphi_k(A)\\) and \\(\varphi_k(A/2)\\) for \\(k \leq\\) `order` of the `n` x `n`
+/// matrix \\(A\\).
+/// Contour integrals around the spectrum overflow for stiff dense matrices. Instead, the
+/// exponential of the augmented matrix
+/// \begin{equation}
+///     B = \begin{pmatrix}
+///         A & I & & \\\\
+///         & 0 & \ddots & \\\\
+///         & & \ddots & I \\\\
+///         & & & 0
+///     \end{pmatrix}
+/// \end{equation}
+/// is computed whose first block row of \\(e^{tB}\\) is given by
+/// \\(t^k\varphi_k(tA)\\) (Sidje).
+fn augmented_phi_functions<F>(a: &[F], n: usize, order: usize) -> (Vec<Vec<F>>, Vec<Vec<F>>)
+where
+    F: RealFloatLikeType,
+{
+    let m = (order + 1) * n;
+    let mut b = vec![F::from(0); m * m];
+    for i in 0..n {
+        b[i * m..i * m + n].copy_from_slice(&a[i * n..(i + 1) * n]);
+        for k in 0..order {
+            b[(k * n + i) * m + (k + 1) * n + i] = F::from(1);
+        }
+    }
+    let (exp_full, exp_half) = exponential_with_half(&b, m);
+    let block = |e: &[F], k: usize, scale: F| -> Vec<F> {
+        (0..n)
+            .flat_map(|i| {
+                e[i * m + k * n..i * m + (k + 1) * n]
+                    .iter()
+                    .map(move |v| *v * scale)
+            })
+            .collect()
+    };
+    let mut full = Vec::with_capacity(order + 1);
+    let mut half = Vec::with_capacity(order + 1);
+    let mut scale = F::from(1);
+    for k in 0..=order {
+        full.push(block(&exp_full, k, F::from(1)));
+        half.push(block(&exp_half, k, scale));
+        scale = scale * F::from(2);
+    }
+    (full, half)
+}
+
+/// # φ-functions of a linear operator
+/// Returns \\(\varphi_k(hL)\\) and \\(\varphi_k(hL/2)\\) for \\(k \leq\\) `order` of an operator
+/// acting on `n` components.
+fn phi_functions<F>(
+    linear: &LinearOperator<F>,
+    n: usize,
+    h: F,
+    order: usize,
+) -> (Vec<LinearOperator<F>>, Vec<LinearOperator<F>>)
+where
+    F: RealFloatLikeType,
+{
+    match linear {
+        LinearOperator::Diagonal(values) => {
+            let half = F::from(1) / F::from(2);
+            let full: Vec<[F; 4]> = values
+                .iter()
+                .map(|l| contour_phi_functions(h * *l))
+                .collect();
+            let halved: Vec<[F; 4]> = values
+                .iter()
+                .map(|l| contour_phi_functions(h * half * *l))
+                .collect();
+            let extract = |phi: &[[F; 4]], k: usize| {
+                LinearOperator::Diagonal(phi.iter().map(|phi_k| phi_k[k]).collect())
+            };
+            (
+                (0..=order).map(|k| extract(&full, k)).collect(),
+                (0..=order).map(|k| extract(&halved, k)).collect(),
+            )
+        }
+        LinearOperator::Dense(values) => {
+            let a: Vec<F> = values.iter().map(|l| h * *l).collect();
+            let (full, half) = augmented_phi_functions(&a, n, order);
+            (
+                full.into_iter().map(LinearOperator::Dense).collect(),
+                half.into_iter().map(LinearOperator::Dense).collect(),
+            )
+        }
+    }
+}
+
+impl<F> LinearOperator<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Entries of the operator regardless of its structure
+    fn entries(&self) -> &[F] {
+        match self {
+            LinearOperator::Diagonal(values) | LinearOperator::Dense(values) => values,
+        }
+    }
+
+    /// Linear combination \\(\sum_i c_i A_i\\) of operators with the same structure as `self`
+    fn combine(&self, terms: &[(F, &LinearOperator<F>)]) -> LinearOperator<F> {
+        let values = (0..self.entries().len())
+            .map(|l| {
+                terms
+                    .iter()
+                    .fold(F::from(0), |acc, (c, op)| acc + *c * op.entries()[l])
+            })
+            .collect();
+        match self {
+            LinearOperator::Diagonal(_) => LinearOperator::Diagonal(values),
+            LinearOperator::Dense(_) => LinearOperator::Dense(values),
+        }
+    }
+
+    /// Add \\(c A x\\) to `out`
+    fn apply_add(&self, c: F, x: &[F], out: &mut [F]) {
+        match self {
+            LinearOperator::Diagonal(values) => {
+                for ((oi, vi), xi) in out.iter_mut().zip(values.iter()).zip(x.iter()) {
+                    *oi += c * *vi * *xi;
+                }
+            }
+            LinearOperator::Dense(values) => {
+                let n = x.len();
+                for (i, oi) in out.iter_mut().enumerate() {
+                    let row = &values[i * n..(i + 1) * n];
+                    *oi += c * row
+                        .iter()
+                        .zip(x.iter())
+                        .fold(F::from(0), |acc, (aij, xj)| acc + *aij * *xj);
+                }
+            }
+        }
+    }
+
+    /// Check that the operator fits to a state with `n` components and has finite entries
+    fn check(&self, n: usize) -> Result<(), SolvingError> {
+        let (expected, kind) = match self {
+            LinearOperator::Diagonal(_) => (n, "Diagonal"),
+            LinearOperator::Dense(_) => (n * n, "Dense"),
+        };
+        let entries = self.entries();
+        if entries.len() != expected {
+            return Err(SolvingError::from(alloc::format!(
+                "{} linear operator needs {} entries but has {}",
+                kind,
+                expected,
+                entries.len()
+            )));
+        }
+        if !entries.iter().all(|l| l.is_finite()) {
+            return Err(SolvingError::from(
+                "Linear operator needs to have finite entries",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Functions of the linear operator needed by the method for a single step size
+struct Coefficients<F> {
+    /// Step size for which the coefficients were computed
+    h: F,
+    /// Coefficients in the order in which the method uses them
+    operators: Vec<LinearOperator<F>>,
+}
+
+/// # Exponential integrators
+/// Shared implementation of all exponential integrators for [SemilinearDefinition]s.
+/// The functions of the linear operator only depend on the step size and are kept for the two
+/// most recent step sizes.
+pub(crate) struct ExponentialCore<'a, I, F, P, Err> {
+    /// Definition of the semilinear ODE which is solved
+    semi_def: SemilinearDefinition<'a, I, F, P, Err>,
+    /// Method which is used
+    solver_type: ExponentialSolvers,
+    /// Number of components of the state
+    n: usize,
+    /// Functions of the linear operator for recently used step sizes
+    cache: Vec<Coefficients<F>>,
+    /// Storage for states at which the nonlinear part is evaluated
+    buf: I,
+    /// Storage for the evaluated nonlinear part
+    dy: I,
+}
+
+impl<'a, I, F, P, Err> ExponentialCore<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    /// Check that the linear operator fits to the state
+    pub(crate) fn new(
+        semi_def: SemilinearDefinition<'a, I, F, P, Err>,
+        solver_type: ExponentialSolvers,
+    ) -> Result<Self, SolvingError> {
+        let n = (&semi_def.y0).into_iter().count();
+        semi_def.linear.check(n)?;
+        let buf = semi_def.y0.clone();
+        let dy = semi_def.y0.clone();
+        Ok(ExponentialCore {
+            semi_def,
+            solver_type,
+            n,
+            cache: Vec::new(),
+            buf,
+            dy,
+        })
+    }
+
+    /// Compute the coefficients of the method for the step size `h`
+    fn coefficients(&self, h: F) -> Coefficients<F> {
+        let linear = &self.semi_def.linear;
+        let operators = match self.solver_type {
+            ExponentialSolvers::Etd1 => {
+                let (full, _) = phi_functions(linear, self.n, h, 1);
+                vec![full[0].clone(), linear.combine(&[(h, &full[1])])]
+            }
+            ExponentialSolvers::Etdrk4 => {
+                let (full, half) = phi_functions(linear, self.n, h, 3);
+                let (two, three, four) = (F::from(2), F::from(3), F::from(4));
+                vec![
+                    full[0].clone(),
+                    half[0].clone(),
+                    linear.combine(&[(h / two, &half[1])]),
+                    linear.combine(&[(h, &full[1]), (-three * h, &full[2]), (four * h, &full[3])]),
+                    linear.combine(&[(h, &full[2]), (-two * h, &full[3])]),
+                    linear.combine(&[(-h, &full[2]), (four * h, &full[3])]),
+                ]
+            }
+            ExponentialSolvers::LawsonRk4 => {
+                let (full, half) = phi_functions(linear, self.n, h, 0);
+                vec![full[0].clone(), half[0].clone()]
+            }
+        };
+        Coefficients { h, operators }
+    }
+
+    /// Position of the coefficients for the step size `h` in the cache
+    fn cached_coefficients(&mut self, h: F) -> usize {
+        if let Some(index) = self.cache.iter().position(|c| c.h == h) {
+            return index;
+        }
+        if self.cache.len() == CACHED_STEP_SIZES {
+            self.cache.remove(0);
+        }
+        let coefficients = self.coefficients(h);
+        self.cache.push(coefficients);
+        self.cache.len() - 1
+    }
+
+    /// Do a single step with all stages of the method
+    pub(crate) fn do_step(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err> {
+        let h = *dt;
+        let index = self.cached_coefficients(h);
+        let ExponentialCore {
+            semi_def,
+            solver_type,
+            cache,
+            buf,
+            n: _,
+            dy,
+        } = self;
+        let ops = &cache[index].operators;
+        let nonlinear = semi_def.nonlinear;
+        let mut eval = |x: &[F], t: &F, f: &mut Vec<F>| -> Result<(), Err> {
+            scatter(x, buf);
+            nonlinear(buf, dy, t, p)?;
+            gather(dy, f);
+            Ok(())
+        };
+
+        let one = F::from(1);
+        let two = F::from(2);
+        let t_half = *t + h / two;
+        let t_full = *t + h;
+        let mut u = Vec::new();
+        gather(y, &mut u);
+        let n = u.len();
+        let zeros = vec![F::from(0); n];
+        let mut k1 = Vec::new();
+        eval(&u, t, &mut k1)?;
+
+        let mut next = zeros.clone();
+        ops[0].apply_add(one, &u, &mut next);
+        match solver_type {
+            ExponentialSolvers::Etd1 => {
+                ops[1].apply_add(one, &k1, &mut next);
+            }
+            ExponentialSolvers::Etdrk4 => {
+                let (e2, q, f1, f2, f3) = (&ops[1], &ops[2], &ops[3], &ops[4], &ops[5]);
+                let mut e2u = zeros.clone();
+                e2.apply_add(one, &u, &mut e2u);
+
+                let mut a = e2u.clone();
+                q.apply_add(one, &k1, &mut a);
+                let mut k2 = Vec::new();
+                eval(&a, &t_half, &mut k2)?;
+
+                let mut b = e2u;
+                q.apply_add(one, &k2, &mut b);
+                let mut k3 = Vec::new();
+                eval(&b, &t_half, &mut k3)?;
+
+                let mut c = zeros.clone();
+                e2.apply_add(one, &a, &mut c);
+                let combined: Vec<F> = k3
+                    .iter()
+                    .zip(k1.iter())
+                    .map(|(k3i, k1i)| two * *k3i - *k1i)
+                    .collect();
+                q.apply_add(one, &combined, &mut c);
+                let mut k4 = Vec::new();
+                eval(&c, &t_full, &mut k4)?;
+
+                let k23: Vec<F> = k2.iter().zip(k3.iter()).map(|(a, b)| *a + *b).collect();
+                f1.apply_add(one, &k1, &mut next);
+                f2.apply_add(two, &k23, &mut next);
+                f3.apply_add(one, &k4, &mut next);
+            }
+            ExponentialSolvers::LawsonRk4 => {
+                let (e, e2) = (&ops[0], &ops[1]);
+                let h_half = h / two;
+                let shifted: Vec<F> = u
+                    .iter()
+                    .zip(k1.iter())
+                    .map(|(ui, ki)| *ui + h_half * *ki)
+                    .collect();
+                let mut a = zeros.clone();
+                e2.apply_add(one, &shifted, &mut a);
+                let mut k2 = Vec::new();
+                eval(&a, &t_half, &mut k2)?;
+
+                let mut b = zeros.clone();
+                e2.apply_add(one, &u, &mut b);
+                b.iter_mut()
+                    .zip(k2.iter())
+                    .for_each(|(bi, ki)| *bi += h_half * *ki);
+                let mut k3 = Vec::new();
+                eval(&b, &t_half, &mut k3)?;
+
+                let mut c = next.clone();
+                e2.apply_add(h, &k3, &mut c);
+                let mut k4 = Vec::new();
+                eval(&c, &t_full, &mut k4)?;
+
+                let h_sixth = h / F::from(6);
+                let k23: Vec<F> = k2.iter().zip(k3.iter()).map(|(a, b)| *a + *b).collect();
+                e.apply_add(h_sixth, &k1, &mut next);
+                e2.apply_add(two * h_sixth, &k23, &mut next);
+                next.iter_mut()
+                    .zip(k4.iter())
+                    .for_each(|(ni, ki)| *ni += h_sixth * *ki);
+            }
+        }
+        scatter(&next, y);
+        Ok(())
+    }
+}
+
+/// Implement the [Stepper] trait for a wrapper around [ExponentialCore]
+macro_rules! impl_exponential_stepper {
+    ($name: ident, $solver_type: expr) => {
+        impl<'a, I, F, P, Err> $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+        {
+            /// Create a new stepper. Fails if the linear operator does not fit to the state.
+            pub fn new(
+                semi_def: SemilinearDefinition<'a, I, F, P, Err>,
+            ) -> Result<Self, SolvingError> {
+                Ok($name {
+                    core: ExponentialCore::new(semi_def, $solver_type)?,
+                })
+            }
+        }
+
+        impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+        {
+            fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+            where
+                for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+                for<'m> &'m I: IntoIterator<Item = &'m F>,
+                F: FloatLikeType,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+
+            fn do_step_add(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+            where
+                I: MathVecLikeType<F>,
+                F: FloatLikeType + core::ops::Mul<I, Output = I>,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+        }
+    };
+}
+
+/// # Exponential Euler stepper
+/// First-order exponential time differencing method
+/// \begin{equation}
+///     y_1 = e^{hL}y_0 + h\varphi_1(hL)N(y_0, t_0)
+/// \end{equation}
+/// with \\(\varphi_1(z) = (e^z - 1)/z\\). It is exact if \\(N\\) is constant.
+pub struct Etd1<'a, I, F, P, Err> {
+    /// Definition of the ODE, coefficients and storage
+    core: ExponentialCore<'a, I, F, P, Err>,
+}
+
+impl_exponential_stepper!(Etd1, ExponentialSolvers::Etd1);
+
+/// # ETDRK4 stepper
+/// Fourth-order exponential time differencing method of Cox and Matthews.
+/// With \\(E_2 = e^{hL/2}\\) and \\(Q = \tfrac{h}{2}\varphi_1(hL/2)\\), the stages
+/// \begin{align}
+///     a &= E_2 y_0 + Q N(y_0, t_0)\\\\
+///     b &= E_2 y_0 + Q N(a, t_0 + h/2)\\\\
+///     c &= E_2 a + Q\left(2N(b, t_0 + h/2) - N(y_0, t_0)\right)
+/// \end{align}
+/// are combined to
+/// \begin{equation}
+///     y_1 = e^{hL}y_0 + f_1 N(y_0, t_0) + 2f_2\left(N(a, t_0 + h/2) + N(b, t_0 + h/2)\right)
+///         + f_3 N(c, t_0 + h)
+/// \end{equation}
+/// where \\(f_1 = h(\varphi_1 - 3\varphi_2 + 4\varphi_3)\\),
+/// \\(f_2 = h(\varphi_2 - 2\varphi_3)\\) and \\(f_3 = h(-\varphi_2 + 4\varphi_3)\\) are evaluated
+/// at \\(hL\\).
+/// The φ-functions of diagonal operators are computed by contour integrals following Kassam and
+/// Trefethen which avoids the cancellation of their explicit formulas for small arguments.
+pub struct Etdrk4<'a, I, F, P, Err> {
+    /// Definition of the ODE, coefficients and storage
+    core: ExponentialCore<'a, I, F, P, Err>,
+}
+
+impl_exponential_stepper!(Etdrk4, ExponentialSolvers::Etdrk4);
+
+/// # Lawson RK4 stepper
+/// Applies the classical Runge-Kutta method to \\(v = e^{-tL}y\\) which satisfies the ODE
+/// \\(v' = e^{-tL}N(e^{tL}v, t)\\) without stiff linear part. Only the matrix exponentials
+/// \\(e^{hL}\\) and \\(e^{hL/2}\\) are needed. Unlike [Etdrk4], the method is not exact for
+/// constant \\(N\\) and loses accuracy for stiff problems whose nonlinear part does not vanish.
+pub struct LawsonRk4<'a, I, F, P, Err> {
+    /// Definition of the ODE, coefficients and storage
+    core: ExponentialCore<'a, I, F, P, Err>,
+}
+
+impl_exponential_stepper!(LawsonRk4, ExponentialSolvers::LawsonRk4);
@@ -3,6 +3,8 @@ use crate::concepts::*;
 use core::ops::Mul;
 
 /// Contains all implementors of the [Stepper] trait for fixed step-sizes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FixedStepSolvers {
     /// First-order Euler solver
     Euler,
@@ -17,7 +19,9 @@ pub enum FixedStepSolvers {
 ///     y_1 = y_0 + dt f(y, t, p)
 /// \end{equation}
 pub struct Euler<'a, I, F, P, Err> {
+    /// Definition of the ODE which is solved
     ode_def: OdeDefinition<'a, I, F, P, Err>,
+    /// Storage for the evaluated RHS
     dy: I,
 }
 
@@ -34,6 +38,32 @@ where
     }
 }
 
+/// # Internal state of the [Euler] stepper
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EulerState<I> {
+    /// Storage for the evaluated RHS
+    pub dy: I,
+}
+
+impl<'a, I, F, P, Err> Euler<'a, I, F, P, Err>
+where
+    I: Clone,
+{
+    /// Copy the internal state of the stepper
+    pub fn state(&self) -> EulerState<I> {
+        EulerState {
+            dy: self.dy.clone(),
+        }
+    }
+
+    /// Replace the internal state of the stepper by a previously stored one
+    pub fn with_state(mut self, state: EulerState<I>) -> Self {
+        self.dy = state.dy;
+        self
+    }
+}
+
 impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for Euler<'a, I, F, P, Err> {
     fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
     where
@@ -42,7 +72,7 @@ impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for Euler<'a, I, F, P, Err> {
         F: FloatLikeType,
     {
         (self.ode_def.func)(y, &mut self.dy, t, p)?;
-        for (yi, dyi) in y.into_iter().zip(self.dy.into_iter()) {
+        for (yi, dyi) in y.into_iter().zip(&self.dy) {
             *yi += *dt * *dyi;
         }
         Ok(())
@@ -75,13 +105,20 @@ impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for Euler<'a, I, F, P, Err> {
 ///     y_1 = y_0 + \tfrac{1}{6} (k_1 + 2 k_2 + 2 k_3 + k_4).
 /// \end{equation}
 pub struct Rk4<'a, I, F, P, Err> {
+    /// Definition of the ODE which is solved
     ode_def: OdeDefinition<'a, I, F, P, Err>,
     // Helper variables
+    /// First increment \\(k_1\\)
     k1: I,
+    /// Second increment \\(k_2\\)
     k2: I,
+    /// Third increment \\(k_3\\)
     k3: I,
+    /// Fourth increment \\(k_4\\)
     k4: I,
+    /// Storage for the evaluated RHS
     dy: I,
+    /// Intermediate state at which the RHS is evaluated
     ym: I,
 }
 
@@ -106,6 +143,52 @@ where
     }
 }
 
+/// # Internal state of the [Rk4] stepper
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rk4State<I> {
+    /// First increment \\(k_1\\)
+    pub k1: I,
+    /// Second increment \\(k_2\\)
+    pub k2: I,
+    /// Third increment \\(k_3\\)
+    pub k3: I,
+    /// Fourth increment \\(k_4\\)
+    pub k4: I,
+    /// Storage for the evaluated RHS
+    pub dy: I,
+    /// Intermediate state at which the RHS is evaluated
+    pub ym: I,
+}
+
+impl<'a, I, F, P, Err> Rk4<'a, I, F, P, Err>
+where
+    I: Clone,
+{
+    /// Copy the internal state of the stepper
+    pub fn state(&self) -> Rk4State<I> {
+        Rk4State {
+            k1: self.k1.clone(),
+            k2: self.k2.clone(),
+            k3: self.k3.clone(),
+            k4: self.k4.clone(),
+            dy: self.dy.clone(),
+            ym: self.ym.clone(),
+        }
+    }
+
+    /// Replace the internal state of the stepper by a previously stored one
+    pub fn with_state(mut self, state: Rk4State<I>) -> Self {
+        self.k1 = state.k1;
+        self.k2 = state.k2;
+        self.k3 = state.k3;
+        self.k4 = state.k4;
+        self.dy = state.dy;
+        self.ym = state.ym;
+        self
+    }
+}
+
 // Implement the Rk4 stepper
 impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for Rk4<'a, I, F, P, Err> {
     fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
@@ -115,7 +198,7 @@ impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for Rk4<'a, I, F, P, Err> {
         F: FloatLikeType,
     {
         (self.ode_def.func)(y, &mut self.dy, t, p)?;
-        for (yi, dyi) in y.into_iter().zip(self.dy.into_iter()) {
+        for (yi, dyi) in y.into_iter().zip(&self.dy) {
             // TODO
             // This is not a Runge-Kutta solver yet!
             *yi += *dt * *dyi;
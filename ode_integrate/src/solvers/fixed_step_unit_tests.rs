@@ -15,6 +15,7 @@ enum Ethos {
     Bad,
 }
 
+/// Runs 100 steps of the given solver for a combination of [Operations] and [Ethos]
 #[macro_export]
 macro_rules! do_step {
     ($f: ty, $s: expr, $conf: expr) => {
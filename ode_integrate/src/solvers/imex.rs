@@ -0,0 +1,510 @@
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+use crate::solvers::{
+    default_newton_tolerance, finite_difference_jacobian, gather, newton_update_norm,
+    scaled_error_norm, scatter,
+};
+
+use core::ops::Mul;
+
+use alloc::{vec, vec::Vec};
+
+/// Contains all implicit-explicit additive Runge-Kutta methods which solve
+/// [AdditiveDefinition]s.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImexSolvers {
+    /// Four-stage method ARK3(2)4L\[2\]SA of Kennedy and Carpenter
+    Ark324L2SA,
+    /// Six-stage method ARK4(3)6L\[2\]SA of Kennedy and Carpenter
+    Ark436L2SA,
+}
+
+/// Maximal number of Newton iterations per stage
+const MAX_NEWTON_ITERATIONS: usize = 10;
+
+/// Quotient of two integers which are exactly representable as `f64`
+fn ratio<F>(numerator: f64, denominator: f64) -> F
+where
+    F: RealFloatLikeType,
+{
+    F::from_f64(numerator) / F::from_f64(denominator)
+}
+
+/// # Butcher tableau of an additive Runge-Kutta method
+/// Both parts share the weights and the nodes. The implicit part is singly diagonally implicit
+/// with an explicit first stage.
+struct ArkTableau<F> {
+    /// Entries below the diagonal of the explicit tableau for every stage
+    explicit: Vec<Vec<F>>,
+    /// Entries below the diagonal of the implicit tableau for every stage
+    implicit: Vec<Vec<F>>,
+    /// Diagonal entry of the implicit tableau
+    gamma: F,
+    /// Weights of the solution
+    b: Vec<F>,
+    /// Weights of the embedded solution of lower order
+    b_hat: Vec<F>,
+    /// Relative position of the stages in the step
+    c: Vec<F>,
+    /// Order of the embedded solution
+    embedded_order: usize,
+}
+
+impl<F> ArkTableau<F>
+where
+    F: RealFloatLikeType,
+{
+    /// Coefficients of the chosen method as given by Kennedy and Carpenter
+    fn new(solver_type: &ImexSolvers) -> Self {
+        match solver_type {
+            ImexSolvers::Ark324L2SA => {
+                let gamma = ratio(1767732205903.0, 4055673282236.0);
+                let b = vec![
+                    ratio(1471266399579.0, 7840856788654.0),
+                    ratio(-4482444167858.0, 7529755066697.0),
+                    ratio(11266239266428.0, 11593286722821.0),
+                    gamma,
+                ];
+                ArkTableau {
+                    explicit: vec![
+                        vec![],
+                        vec![ratio(1767732205903.0, 2027836641118.0)],
+                        vec![
+                            ratio(5535828885825.0, 10492691773637.0),
+                            ratio(788022342437.0, 10882634858940.0),
+                        ],
+                        vec![
+                            ratio(6485989280629.0, 16251701735622.0),
+                            ratio(-4246266847089.0, 9704473918619.0),
+                            ratio(10755448449292.0, 10357097424841.0),
+                        ],
+                    ],
+                    implicit: vec![
+                        vec![],
+                        vec![gamma],
+                        vec![
+                            ratio(2746238789719.0, 10658868560708.0),
+                            ratio(-640167445237.0, 6845629431997.0),
+                        ],
+                        b[..3].to_vec(),
+                    ],
+                    gamma,
+                    b,
+                    b_hat: vec![
+                        ratio(2756255671327.0, 12835298489170.0),
+                        ratio(-10771552573575.0, 22201958757719.0),
+                        ratio(9247589265047.0, 10645013368117.0),
+                        ratio(2193209047091.0, 5459859503100.0),
+                    ],
+                    c: vec![
+                        F::from(0),
+                        ratio(1767732205903.0, 2027836641118.0),
+                        ratio(3.0, 5.0),
+                        F::from(1),
+                    ],
+                    embedded_order: 2,
+                }
+            }
+            ImexSolvers::Ark436L2SA => {
+                let gamma = ratio(1.0, 4.0);
+                let b = vec![
+                    ratio(82889.0, 524892.0),
+                    F::from(0),
+                    ratio(15625.0, 83664.0),
+                    ratio(69875.0, 102672.0),
+                    ratio(-2260.0, 8211.0),
+                    gamma,
+                ];
+                ArkTableau {
+                    explicit: vec![
+                        vec![],
+                        vec![ratio(1.0, 2.0)],
+                        vec![ratio(13861.0, 62500.0), ratio(6889.0, 62500.0)],
+                        vec![
+                            ratio(-116923316275.0, 2393684061468.0),
+                            ratio(-2731218467317.0, 15368042101831.0),
+                            ratio(9408046702089.0, 11113171139209.0),
+                        ],
+                        vec![
+                            ratio(-451086348788.0, 2902428689909.0),
+                            ratio(-2682348792572.0, 7519795681897.0),
+                            ratio(12662868775082.0, 11960479115383.0),
+                            ratio(3355817975965.0, 11060851509271.0),
+                        ],
+                        vec![
+                            ratio(647845179188.0, 3216320057751.0),
+                            ratio(73281519250.0, 8382639484533.0),
+                            ratio(552539513391.0, 3454668386233.0),
+                            ratio(3354512671639.0, 8306763924573.0),
+                            ratio(4040.0, 17871.0),
+                        ],
+                    ],
+                    implicit: vec![
+                        vec![],
+                        vec![gamma],
+                        vec![ratio(8611.0, 62500.0), ratio(-1743.0, 31250.0)],
+                        vec![
+                            ratio(5012029.0, 34652500.0),
+                            ratio(-654441.0, 2922500.0),
+                            ratio(174375.0, 388108.0),
+                        ],
+                        vec![
+                            ratio(15267082809.0, 155376265600.0),
+                            ratio(-71443401.0, 120774400.0),
+                            ratio(730878875.0, 902184768.0),
+                            ratio(2285395.0, 8070912.0),
+                        ],
+                        b[..5].to_vec(),
+                    ],
+                    gamma,
+                    b,
+                    b_hat: vec![
+                        ratio(4586570599.0, 29645900160.0),
+                        F::from(0),
+                        ratio(178811875.0, 945068544.0),
+                        ratio(814220225.0, 1159782912.0),
+                        ratio(-3700637.0, 11593932.0),
+                        ratio(61727.0, 225920.0),
+                    ],
+                    c: vec![
+                        F::from(0),
+                        ratio(1.0, 2.0),
+                        ratio(83.0, 250.0),
+                        ratio(31.0, 50.0),
+                        ratio(17.0, 20.0),
+                        F::from(1),
+                    ],
+                    embedded_order: 3,
+                }
+            }
+        }
+    }
+}
+
+/// # Additive Runge-Kutta methods
+/// Shared implementation of all IMEX methods with explicit tableau \\((a^E_{ij})\\), implicit
+/// tableau \\((a^I_{ij})\\), \\(a^I_{ii} = \gamma\\) and common weights \\(b_i\\) and nodes
+/// \\(c_i\\). The stage values
+/// \begin{equation}
+///     Y_i = y_0 + h\sum_{j<i}\left(a^E_{ij}f_E(Y_j) + a^I_{ij}f_I(Y_j)\right)
+///         + h\gamma f_I(Y_i)
+/// \end{equation}
+/// are solved by a simplified Newton iteration with the matrix
+/// \\(I - h\gamma \partial f_I/\partial y\\) evaluated at the beginning of the step.
+/// The Jacobian is approximated by finite differences.
+/// The difference to the embedded solution with weights \\(\hat{b}_i\\) estimates the error.
+/// Steps in which the Newton iteration does not converge are rejected.
+pub(crate) struct ImexCore<'a, I, F, P, Err> {
+    /// Definition of the ODE which is solved
+    add_def: AdditiveDefinition<'a, I, F, P, Err>,
+    /// Tolerances used to normalize the error estimate
+    tolerances: Tolerances<F>,
+    /// Coefficients of the method
+    tableau: ArkTableau<F>,
+    /// Tolerance of the Newton iteration
+    newton_tol: F,
+    /// Step size proposed for the next step
+    dt_next: Option<F>,
+    /// Storage for states at which the RHS is evaluated
+    buf: I,
+    /// Storage for the evaluated RHS
+    dy: I,
+}
+
+impl<'a, I, F, P, Err> ImexCore<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+{
+    /// Store the tableau of the chosen method
+    pub(crate) fn new(
+        add_def: AdditiveDefinition<'a, I, F, P, Err>,
+        tolerances: Tolerances<F>,
+        solver_type: &ImexSolvers,
+    ) -> Self {
+        let buf = add_def.y0.clone();
+        let dy = add_def.y0.clone();
+        ImexCore {
+            add_def,
+            tolerances,
+            tableau: ArkTableau::new(solver_type),
+            newton_tol: default_newton_tolerance(),
+            dt_next: None,
+            buf,
+            dy,
+        }
+    }
+
+    /// Evaluate one part of the RHS at a state given as slice and write the result into `f`
+    fn eval(
+        &mut self,
+        func: RHS<'a, I, F, P, Err>,
+        y: &[F],
+        t: &F,
+        p: &P,
+        f: &mut Vec<F>,
+    ) -> Result<(), Err> {
+        scatter(y, &mut self.buf);
+        func(&self.buf, &mut self.dy, t, p)?;
+        gather(&self.dy, f);
+        Ok(())
+    }
+
+    /// Reject the step and retry with a quarter of the step size
+    fn reject(&mut self, dt: &F) -> Option<F> {
+        self.dt_next = Some(*dt / F::from(4));
+        Some(F::from_f64(f64::INFINITY))
+    }
+
+    /// Propose the next step size from the normalized error of the current step
+    fn propose_step_size(&mut self, dt: &F, err: F) {
+        let fac_min = F::from_f64(0.2);
+        let fac_max = if err <= F::from(1) {
+            F::from(5)
+        } else {
+            F::from(1)
+        };
+        let exponent = F::from(1) / F::from_usize(self.tableau.embedded_order + 1);
+        let fac = if err == F::from(0) {
+            fac_max
+        } else if err.is_finite() {
+            F::from_f64(0.9) * (F::from(1) / err).powf(exponent)
+        } else {
+            fac_min
+        };
+        self.dt_next = Some(*dt * fac.max(fac_min).min(fac_max));
+    }
+
+    /// Do a single step with all stages of the method and return the normalized error
+    pub(crate) fn do_step(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err> {
+        let h = *dt;
+        let h_gamma = h * self.tableau.gamma;
+        let (explicit, implicit) = (self.add_def.explicit, self.add_def.implicit);
+        let mut y0 = Vec::new();
+        gather(y, &mut y0);
+        let n = y0.len();
+
+        // Iteration matrix at the beginning of the step
+        let mut f = Vec::new();
+        let mut jac = Vec::new();
+        self.eval(implicit, &y0, t, p, &mut f)?;
+        finite_difference_jacobian(
+            implicit,
+            &y0,
+            t,
+            p,
+            &f,
+            &mut jac,
+            &mut self.buf,
+            &mut self.dy,
+        )?;
+        let mut iteration_matrix: Vec<F> = jac.iter().map(|jij| -h_gamma * *jij).collect();
+        (0..n).for_each(|i| iteration_matrix[i * n + i] += F::from(1));
+        let lu = match LuDecomposition::new(iteration_matrix, n) {
+            Some(lu) => lu,
+            None => return Ok(self.reject(dt)),
+        };
+
+        let n_stages = self.tableau.c.len();
+        let mut k_implicit: Vec<Vec<F>> = Vec::with_capacity(n_stages);
+        let mut k_explicit: Vec<Vec<F>> = Vec::with_capacity(n_stages);
+        k_implicit.push(f.clone());
+        self.eval(explicit, &y0, t, p, &mut f)?;
+        k_explicit.push(f.clone());
+
+        let mut stage = y0.clone();
+        let mut base = vec![F::from(0); n];
+        let mut residual = vec![F::from(0); n];
+        for i in 1..n_stages {
+            let t_i = *t + self.tableau.c[i] * h;
+            for (l, bl) in base.iter_mut().enumerate() {
+                *bl = y0[l];
+                for (j, (a_e, a_i)) in self.tableau.explicit[i]
+                    .iter()
+                    .zip(self.tableau.implicit[i].iter())
+                    .enumerate()
+                {
+                    *bl += h * (*a_e * k_explicit[j][l] + *a_i * k_implicit[j][l]);
+                }
+            }
+            // Predict the stage value with the implicit derivative of the previous stage
+            for ((si, bi), ki) in stage
+                .iter_mut()
+                .zip(base.iter())
+                .zip(k_implicit[i - 1].iter())
+            {
+                *si = *bi + h_gamma * *ki;
+            }
+
+            let mut converged = false;
+            for _ in 0..MAX_NEWTON_ITERATIONS {
+                self.eval(implicit, &stage, &t_i, p, &mut f)?;
+                for (((ri, bi), si), fi) in residual
+                    .iter_mut()
+                    .zip(base.iter())
+                    .zip(stage.iter())
+                    .zip(f.iter())
+                {
+                    *ri = *bi + h_gamma * *fi - *si;
+                }
+                lu.solve(&mut residual);
+                for (si, di) in stage.iter_mut().zip(residual.iter()) {
+                    *si += *di;
+                }
+                let norm = newton_update_norm(&residual, &stage);
+                if !norm.is_finite() {
+                    break;
+                }
+                if norm <= self.newton_tol {
+                    converged = true;
+                    break;
+                }
+            }
+            if !converged {
+                return Ok(self.reject(dt));
+            }
+            k_implicit.push(
+                stage
+                    .iter()
+                    .zip(base.iter())
+                    .map(|(si, bi)| (*si - *bi) / h_gamma)
+                    .collect(),
+            );
+            self.eval(explicit, &stage, &t_i, p, &mut f)?;
+            k_explicit.push(f.clone());
+        }
+
+        let mut y1 = y0.clone();
+        let mut err = vec![F::from(0); n];
+        for (j, (b, b_hat)) in self
+            .tableau
+            .b
+            .iter()
+            .zip(self.tableau.b_hat.iter())
+            .enumerate()
+        {
+            for (l, (y1l, el)) in y1.iter_mut().zip(err.iter_mut()).enumerate() {
+                let k = k_explicit[j][l] + k_implicit[j][l];
+                *y1l += h * *b * k;
+                *el += h * (*b - *b_hat) * k;
+            }
+        }
+
+        let start = y.clone();
+        scatter(&y1, &mut self.buf);
+        scatter(&err, &mut self.dy);
+        let err = scaled_error_norm(&start, &self.buf, &self.dy, &self.tolerances);
+        self.propose_step_size(dt, err);
+        if err <= F::from(1) {
+            scatter(&y1, y);
+        }
+        Ok(Some(err))
+    }
+}
+
+/// Implement the [AdaptiveStepper] trait for a wrapper around [ImexCore]
+macro_rules! impl_imex_stepper {
+    ($name: ident, $solver_type: expr) => {
+        impl<'a, I, F, P, Err> $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+        {
+            /// Construct a new stepper from the ODE and tolerances used for the error control
+            pub fn new(
+                add_def: AdditiveDefinition<'a, I, F, P, Err>,
+                tolerances: Tolerances<F>,
+            ) -> Self {
+                $name {
+                    core: ImexCore::new(add_def, tolerances, &$solver_type),
+                }
+            }
+
+            /// Set the tolerance of the Newton iteration which defaults to \\(\epsilon^{3/4}\\)
+            pub fn with_newton_tolerance(mut self, tol: F) -> Self {
+                self.core.newton_tol = tol;
+                self
+            }
+        }
+
+        impl<'a, I, F, P, Err> AdaptiveStepper<I, F, P, Err> for $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+        {
+            fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err>
+            where
+                for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+                for<'m> &'m I: IntoIterator<Item = &'m F>,
+                F: FloatLikeType,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+
+            fn do_step_add(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<Option<F>, Err>
+            where
+                I: MathVecLikeType<F>,
+                F: FloatLikeType + Mul<I, Output = I>,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+
+            fn suggested_dt(&self) -> Option<F> {
+                self.core.dt_next
+            }
+        }
+    };
+}
+
+/// # ARK3(2)4L\[2\]SA stepper
+/// Third-order IMEX method of Kennedy and Carpenter with four stages and an embedded method of
+/// second order. The implicit part is L-stable and stiffly accurate with
+/// \\(\gamma \approx 0.4359\\).
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// // Together, both parts give y' = -y^2 with solution y = y0 / (1 + y0 t)
+/// fn reaction(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = y[0] * (1.0 - y[0]);
+///     Ok(())
+/// }
+///
+/// fn decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -y[0];
+///     Ok(())
+/// }
+///
+/// let add_def = AdditiveDefinition { y0: [0.5], t0: 0.0, explicit: &reaction, implicit: &decay };
+/// let tolerances = Tolerances { rtol: 1e-6, atol: 1e-8 };
+/// let mut ark = Ark324L2SA::new(add_def, tolerances);
+///
+/// let mut y = [0.5];
+/// let err = ark.do_step_iter(&mut y, &0.0, &0.01, &()).unwrap();
+/// assert!(err.unwrap() <= 1.0);
+/// assert!((y[0] - 0.5 / 1.005).abs() < 1e-8);
+/// ```
+pub struct Ark324L2SA<'a, I, F, P, Err> {
+    /// Definition of the ODE, tableau and storage
+    core: ImexCore<'a, I, F, P, Err>,
+}
+
+impl_imex_stepper!(Ark324L2SA, ImexSolvers::Ark324L2SA);
+
+/// # ARK4(3)6L\[2\]SA stepper
+/// Fourth-order IMEX method of Kennedy and Carpenter with six stages and an embedded method of
+/// third order. The implicit part is L-stable and stiffly accurate with \\(\gamma = 1/4\\).
+pub struct Ark436L2SA<'a, I, F, P, Err> {
+    /// Definition of the ODE, tableau and storage
+    core: ImexCore<'a, I, F, P, Err>,
+}
+
+impl_imex_stepper!(Ark436L2SA, ImexSolvers::Ark436L2SA);
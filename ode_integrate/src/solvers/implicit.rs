@@ -0,0 +1,534 @@
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+
+use core::fmt::Display;
+use core::ops::Mul;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Contains all implicit implementors of the [Stepper] trait which can solve [DaeDefinition]s.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImplicitSolvers {
+    /// First-order implicit Euler method
+    ImplicitEuler,
+    /// L-stable and stiffly accurate 2nd order SDIRK method of Alexander
+    Sdirk2,
+}
+
+/// Maximal number of Newton iterations per stage
+const MAX_NEWTON_ITERATIONS: usize = 10;
+
+/// Maximal number of Newton iterations for the computation of consistent initial values
+const MAX_INITIALIZATION_ITERATIONS: usize = 50;
+
+/// Copy the components of an iterable type into a vector
+pub(crate) fn gather<I, F>(y: &I, v: &mut Vec<F>)
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: Copy,
+{
+    v.clear();
+    v.extend(y.into_iter().copied());
+}
+
+/// Copy the entries of a slice into the components of an iterable type
+pub(crate) fn scatter<I, F>(v: &[F], y: &mut I)
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    F: Copy,
+{
+    for (yi, vi) in y.into_iter().zip(v) {
+        *yi = *vi;
+    }
+}
+
+/// Default tolerance of Newton iterations \\(\epsilon^{3/4}\\)
+pub(crate) fn default_newton_tolerance<F>() -> F
+where
+    F: RealFloatLikeType,
+{
+    F::epsilon().powf(F::from(3) / F::from(4))
+}
+
+/// Weighted maximum norm \\(\max_i |\Delta_i| / (1 + |y_i|)\\) of a Newton update
+pub(crate) fn newton_update_norm<F>(delta: &[F], y: &[F]) -> F
+where
+    F: RealFloatLikeType,
+{
+    delta.iter().zip(y).fold(F::from(0), |acc, (d, yi)| {
+        acc.max(d.abs() / (F::from(1) + yi.abs()))
+    })
+}
+
+/// Evaluate the mass matrix at the given point.
+/// The matrix `m` is resized to `n` x `n` entries and filled in row-major order.
+pub(crate) fn eval_mass_matrix<I, F, P>(
+    mass: &MassMatrix<I, F, P>,
+    y: &I,
+    t: &F,
+    p: &P,
+    n: usize,
+    m: &mut Vec<F>,
+) where
+    F: RealFloatLikeType,
+{
+    m.clear();
+    m.resize(n * n, F::from(0));
+    match mass {
+        MassMatrix::Identity => (0..n).for_each(|i| m[i * n + i] = F::from(1)),
+        MassMatrix::Constant(values) => m.copy_from_slice(values),
+        MassMatrix::TimeDependent(func) => func(t, p, m),
+        MassMatrix::StateDependent(func) => func(y, t, p, m),
+    }
+}
+
+/// Check that a constant mass matrix matches the dimension of the state
+fn check_mass_matrix<I, F, P>(mass: &MassMatrix<I, F, P>, n: usize) -> Result<(), SolvingError> {
+    match mass {
+        MassMatrix::Constant(values) if values.len() != n * n => {
+            Err(SolvingError::from(alloc::format!(
+                "Mass matrix needs {} entries but has {}",
+                n * n,
+                values.len()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// # Jacobian by finite differences
+/// Approximates \\(J_{ij} = \partial f_i / \partial y_j\\) column by column with forward
+/// differences. The RHS `f0` at `y` needs to be known already. The matrix is stored in
+/// row-major order in `jac`. The types `buf` and `dy` are used as storage.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn finite_difference_jacobian<I, F, P, Err>(
+    func: RHS<I, F, P, Err>,
+    y: &[F],
+    t: &F,
+    p: &P,
+    f0: &[F],
+    jac: &mut Vec<F>,
+    buf: &mut I,
+    dy: &mut I,
+) -> Result<(), Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    F: RealFloatLikeType,
+{
+    let n = y.len();
+    jac.clear();
+    jac.resize(n * n, F::from(0));
+    let sqrt_eps = F::epsilon().sqrt();
+    let mut yp = y.to_vec();
+    for j in 0..n {
+        let delta = sqrt_eps * y[j].abs().max(F::from(1));
+        yp[j] = y[j] + delta;
+        scatter(&yp, buf);
+        func(buf, dy, t, p)?;
+        for (i, (fi, f0i)) in (&*dy).into_iter().zip(f0).enumerate() {
+            jac[i * n + j] = (*fi - *f0i) / delta;
+        }
+        yp[j] = y[j];
+    }
+    Ok(())
+}
+
+/// # Consistent initial values
+/// Computes initial values which satisfy the algebraic equations of a [DaeDefinition] of
+/// semi-explicit index-1 form.
+/// Algebraic equations are given by the rows of the mass matrix at \\((y_0, t_0)\\) which
+/// vanish, the algebraic components by its vanishing columns.
+/// The differential components of \\(y_0\\) are kept fixed while the algebraic components are
+/// determined by Newton's method starting from the supplied values.
+///
+/// ## Example
+/// ```
+/// use ode_integrate::*;
+///
+/// fn rhs(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+///     dy[0] = -y[1];
+///     dy[1] = y[1] - y[0] * y[0];
+///     Ok(())
+/// }
+///
+/// let dae_def = DaeDefinition {
+///     y0: [2.0, 0.0],
+///     t0: 0.0,
+///     func: &rhs,
+///     mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+/// };
+/// let y0 = consistent_initial_values(&dae_def, &()).unwrap();
+/// assert_eq!(y0[0], 2.0);
+/// assert!((y0[1] - 4.0).abs() < 1e-12);
+/// ```
+pub fn consistent_initial_values<I, F, P, Err>(
+    dae_def: &DaeDefinition<I, F, P, Err>,
+    p: &P,
+) -> Result<I, SolvingError>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    Err: Display,
+{
+    let to_solving_error = |error: Err| SolvingError::from(alloc::format!("{error}"));
+    let mut y = dae_def.y0.clone();
+    let mut yv = Vec::new();
+    gather(&y, &mut yv);
+    let n = yv.len();
+    check_mass_matrix(&dae_def.mass, n)?;
+
+    let mut m = Vec::new();
+    eval_mass_matrix(&dae_def.mass, &y, &dae_def.t0, p, n, &mut m);
+    let zero = F::from(0);
+    let rows: Vec<usize> = (0..n)
+        .filter(|i| m[i * n..(i + 1) * n].iter().all(|mij| *mij == zero))
+        .collect();
+    let cols: Vec<usize> = (0..n)
+        .filter(|j| (0..n).all(|i| m[i * n + j] == zero))
+        .collect();
+    if rows.len() != cols.len() {
+        return Err(SolvingError::from(
+            "The DAE is not of semi-explicit form: vanishing rows and columns of the mass matrix differ",
+        ));
+    }
+    if rows.is_empty() {
+        return Ok(y);
+    }
+
+    let mut buf = y.clone();
+    let mut dy = y.clone();
+    let mut fv = Vec::new();
+    let mut jac = Vec::new();
+    let tol = default_newton_tolerance::<F>();
+    for _ in 0..MAX_INITIALIZATION_ITERATIONS {
+        (dae_def.func)(&y, &mut dy, &dae_def.t0, p).map_err(to_solving_error)?;
+        gather(&dy, &mut fv);
+        finite_difference_jacobian(
+            dae_def.func,
+            &yv,
+            &dae_def.t0,
+            p,
+            &fv,
+            &mut jac,
+            &mut buf,
+            &mut dy,
+        )
+        .map_err(to_solving_error)?;
+
+        let k = rows.len();
+        let mut sub = Vec::with_capacity(k * k);
+        for i in rows.iter() {
+            sub.extend(cols.iter().map(|j| jac[i * n + j]));
+        }
+        let lu = LuDecomposition::new(sub, k).ok_or_else(|| {
+            SolvingError::from("Algebraic equations are singular, the DAE is not of index 1")
+        })?;
+        let mut delta: Vec<F> = rows.iter().map(|i| -fv[*i]).collect();
+        lu.solve(&mut delta);
+
+        let y_alg: Vec<F> = cols.iter().map(|j| yv[*j]).collect();
+        for (j, d) in cols.iter().zip(delta.iter()) {
+            yv[*j] += *d;
+        }
+        scatter(&yv, &mut y);
+        let norm = newton_update_norm(&delta, &y_alg);
+        if !norm.is_finite() {
+            break;
+        }
+        if norm <= tol {
+            return Ok(y);
+        }
+    }
+    Err(SolvingError::from(
+        "Newton iteration for consistent initial values did not converge",
+    ))
+}
+
+/// # Singly diagonally implicit Runge-Kutta methods
+/// Shared implementation of all SDIRK methods with Butcher tableau
+/// \\((a_{ij})\\), \\(a_{ii} = \gamma\\), \\(c_i\\) which are stiffly accurate, meaning the last
+/// stage is the result of the step.
+/// For the mass matrix \\(M\\), the stage derivatives \\(k_i\\) and stage values
+/// \\(Y_i = y_0 + h\sum_{j\leq i} a_{ij} k_j\\) satisfy
+/// \begin{equation}
+///     M(Y_i, t_0 + c_i h)k_i = f(Y_i, t_0 + c_i h, p)
+/// \end{equation}
+/// which is solved for \\(Y_i\\) by a simplified Newton iteration with the matrix
+/// \\(M - h\gamma \partial f/\partial y\\) evaluated at the beginning of the step.
+/// The Jacobian is approximated by finite differences.
+pub(crate) struct SdirkCore<'a, I, F, P, Err> {
+    /// Definition of the DAE which is solved
+    dae_def: DaeDefinition<'a, I, F, P, Err>,
+    /// Diagonal entry of the tableau
+    pub(crate) gamma: F,
+    /// Entries below the diagonal of the tableau for every stage
+    pub(crate) a: Vec<Vec<F>>,
+    /// Relative position of the stages in the step
+    pub(crate) c: Vec<F>,
+    /// Stage values of the last step
+    pub(crate) stages: Vec<Vec<F>>,
+    /// Tolerance of the Newton iteration
+    newton_tol: F,
+    /// Storage for states at which the RHS is evaluated
+    buf: I,
+    /// Storage for the evaluated RHS
+    dy: I,
+}
+
+impl<'a, I, F, P, Err> SdirkCore<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    Err: From<CalcError>,
+{
+    /// Check the DAE and store the tableau of the chosen method
+    pub(crate) fn new(
+        dae_def: DaeDefinition<'a, I, F, P, Err>,
+        solver_type: &ImplicitSolvers,
+    ) -> Result<Self, SolvingError> {
+        let one = F::from(1);
+        let (gamma, a, c) = match solver_type {
+            ImplicitSolvers::ImplicitEuler => (one, vec![vec![]], vec![one]),
+            ImplicitSolvers::Sdirk2 => {
+                let gamma = one - one / F::from(2).sqrt();
+                (gamma, vec![vec![], vec![one - gamma]], vec![gamma, one])
+            }
+        };
+        let n = (&dae_def.y0).into_iter().count();
+        check_mass_matrix(&dae_def.mass, n)?;
+        let buf = dae_def.y0.clone();
+        let dy = dae_def.y0.clone();
+        Ok(SdirkCore {
+            dae_def,
+            gamma,
+            a,
+            c,
+            stages: Vec::new(),
+            newton_tol: default_newton_tolerance(),
+            buf,
+            dy,
+        })
+    }
+
+    /// Evaluate the RHS at a state given as slice and write the result into `f`
+    fn eval(&mut self, y: &[F], t: &F, p: &P, f: &mut Vec<F>) -> Result<(), Err> {
+        scatter(y, &mut self.buf);
+        (self.dae_def.func)(&self.buf, &mut self.dy, t, p)?;
+        gather(&self.dy, f);
+        Ok(())
+    }
+
+    /// Do a single step with all stages of the method
+    pub(crate) fn do_step(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err> {
+        let h = *dt;
+        let h_gamma = h * self.gamma;
+        let mut y0 = Vec::new();
+        gather(y, &mut y0);
+        let n = y0.len();
+
+        // Iteration matrix at the beginning of the step
+        let mut f = Vec::new();
+        let mut jac = Vec::new();
+        let mut m = Vec::new();
+        self.eval(&y0, t, p, &mut f)?;
+        finite_difference_jacobian(
+            self.dae_def.func,
+            &y0,
+            t,
+            p,
+            &f,
+            &mut jac,
+            &mut self.buf,
+            &mut self.dy,
+        )?;
+        eval_mass_matrix(&self.dae_def.mass, y, t, p, n, &mut m);
+        let iteration_matrix = m
+            .iter()
+            .zip(jac.iter())
+            .map(|(mij, jij)| *mij - h_gamma * *jij)
+            .collect();
+        let lu = LuDecomposition::new(iteration_matrix, n).ok_or_else(|| {
+            CalcError::from("Iteration matrix of the implicit method is singular")
+        })?;
+
+        let n_stages = self.c.len();
+        let mut k: Vec<Vec<F>> = Vec::with_capacity(n_stages);
+        self.stages.clear();
+        let mut stage = y0.clone();
+        let mut base = vec![F::from(0); n];
+        let mut residual = vec![F::from(0); n];
+        for i in 0..n_stages {
+            let t_i = *t + self.c[i] * h;
+            for (l, bl) in base.iter_mut().enumerate() {
+                *bl = y0[l];
+                for (a_ij, k_j) in self.a[i].iter().zip(k.iter()) {
+                    *bl += h * *a_ij * k_j[l];
+                }
+            }
+            // Predict the stage value with the derivative of the previous stage
+            if let Some(k_prev) = k.last() {
+                for ((si, bi), ki) in stage.iter_mut().zip(base.iter()).zip(k_prev.iter()) {
+                    *si = *bi + h_gamma * *ki;
+                }
+            }
+
+            let mut converged = false;
+            for _ in 0..MAX_NEWTON_ITERATIONS {
+                self.eval(&stage, &t_i, p, &mut f)?;
+                // The storage still contains the stage value after evaluating the RHS
+                eval_mass_matrix(&self.dae_def.mass, &self.buf, &t_i, p, n, &mut m);
+                for (r, ri) in residual.iter_mut().enumerate() {
+                    let mut mz = F::from(0);
+                    for l in 0..n {
+                        mz += m[r * n + l] * (stage[l] - base[l]);
+                    }
+                    *ri = h_gamma * f[r] - mz;
+                }
+                lu.solve(&mut residual);
+                for (si, di) in stage.iter_mut().zip(residual.iter()) {
+                    *si += *di;
+                }
+                let norm = newton_update_norm(&residual, &stage);
+                if !norm.is_finite() {
+                    break;
+                }
+                if norm <= self.newton_tol {
+                    converged = true;
+                    break;
+                }
+            }
+            if !converged {
+                return Err(CalcError::from(
+                    "Newton iteration of the implicit method did not converge",
+                )
+                .into());
+            }
+            k.push(
+                stage
+                    .iter()
+                    .zip(base.iter())
+                    .map(|(si, bi)| (*si - *bi) / h_gamma)
+                    .collect(),
+            );
+            self.stages.push(stage.clone());
+        }
+        scatter(&stage, y);
+        Ok(())
+    }
+}
+
+/// Implement the [Stepper] trait for a wrapper around [SdirkCore]
+macro_rules! impl_sdirk_stepper {
+    ($name: ident) => {
+        impl<'a, I, F, P, Err> $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+            Err: From<CalcError>,
+        {
+            /// Set the tolerance of the Newton iteration which defaults to \\(\epsilon^{3/4}\\)
+            pub fn with_newton_tolerance(mut self, tol: F) -> Self {
+                self.core.newton_tol = tol;
+                self
+            }
+        }
+
+        impl<'a, I, F, P, Err> Stepper<I, F, P, Err> for $name<'a, I, F, P, Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            I: Clone,
+            F: RealFloatLikeType,
+            Err: From<CalcError>,
+        {
+            fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+            where
+                for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+                for<'m> &'m I: IntoIterator<Item = &'m F>,
+                F: FloatLikeType,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+
+            fn do_step_add(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+            where
+                I: MathVecLikeType<F>,
+                F: FloatLikeType + Mul<I, Output = I>,
+            {
+                self.core.do_step(y, t, dt, p)
+            }
+        }
+    };
+}
+
+/// # Implicit Euler stepper
+/// First-order L-stable method for stiff ODEs and index-1 DAEs
+/// \begin{equation}
+///     M(y_1, t_1)(y_1 - y_0) = dt f(y_1, t_1, p).
+/// \end{equation}
+pub struct ImplicitEuler<'a, I, F, P, Err> {
+    /// Definition of the DAE, tableau and storage
+    core: SdirkCore<'a, I, F, P, Err>,
+}
+
+impl<'a, I, F, P, Err> ImplicitEuler<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    Err: From<CalcError>,
+{
+    /// Create a new stepper. Fails if the mass matrix does not fit to the state.
+    pub fn new(dae_def: DaeDefinition<'a, I, F, P, Err>) -> Result<Self, SolvingError> {
+        Ok(ImplicitEuler {
+            core: SdirkCore::new(dae_def, &ImplicitSolvers::ImplicitEuler)?,
+        })
+    }
+}
+
+impl_sdirk_stepper!(ImplicitEuler);
+
+/// # SDIRK stepper of 2nd order
+/// Two-stage method of Alexander with \\(\gamma = 1 - \tfrac{1}{\sqrt{2}}\\) and Butcher tableau
+/// \begin{equation}
+///     \begin{array}{c|cc}
+///         \gamma & \gamma & 0\\\\
+///         1 & 1 - \gamma & \gamma\\\\
+///         \hline
+///         & 1 - \gamma & \gamma
+///     \end{array}
+/// \end{equation}
+/// The method is L-stable and stiffly accurate such that it converges with order 2 also for the
+/// algebraic components of index-1 DAEs.
+pub struct Sdirk2<'a, I, F, P, Err> {
+    /// Definition of the DAE, tableau and storage
+    core: SdirkCore<'a, I, F, P, Err>,
+}
+
+impl<'a, I, F, P, Err> Sdirk2<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    Err: From<CalcError>,
+{
+    /// Create a new stepper. Fails if the mass matrix does not fit to the state.
+    pub fn new(dae_def: DaeDefinition<'a, I, F, P, Err>) -> Result<Self, SolvingError> {
+        Ok(Sdirk2 {
+            core: SdirkCore::new(dae_def, &ImplicitSolvers::Sdirk2)?,
+        })
+    }
+}
+
+impl_sdirk_stepper!(Sdirk2);
@@ -1,6 +1,34 @@
+/// Steppers with adaptive step-size
+mod adaptive_step;
+/// Adjoint sensitivities of scalar objectives
+mod adjoint;
+/// Steppers for batches of independent ODEs in structure-of-arrays layout
+mod batched;
+/// Solvers for delay differential equations
+mod dde;
+/// Exponential integrators for semilinear ODEs
+mod exponential;
+/// Steppers with fixed step-size
 mod fixed_step;
+/// Implicit-explicit additive Runge-Kutta methods
+mod imex;
+/// Implicit steppers for stiff ODEs and DAEs
+mod implicit;
+/// Steppers for stochastic differential equations
+mod sde;
+/// Forward sensitivities with respect to parameters
+mod sensitivity;
 
 #[cfg(test)]
 mod fixed_step_unit_tests;
 
+pub use adaptive_step::*;
+pub use adjoint::*;
+pub use batched::*;
+pub use dde::*;
+pub use exponential::*;
 pub use fixed_step::*;
+pub use imex::*;
+pub use implicit::*;
+pub use sde::*;
+pub use sensitivity::*;
@@ -0,0 +1,518 @@
+use crate::concepts::*;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::Rng;
+
+/// Contains all implementors of the [SdeStepper] trait.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SdeSolvers {
+    /// Euler-Maruyama method of strong order 0.5
+    EulerMaruyama,
+    /// Derivative-free Milstein method of strong order 1.0
+    Milstein,
+    /// Stochastic Runge-Kutta method SRI1 of strong order 1.5
+    Sri,
+}
+
+/// # Standard normal random number
+/// Draws a sample of \\(\mathcal{N}(0, 1)\\) with the Box-Muller transform.
+pub(crate) fn sample_standard_normal<F, R>(rng: &mut R) -> F
+where
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    // Shift the sample to (0, 1] such that the logarithm is finite
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen::<f64>();
+    let r = num_traits::Float::sqrt(-2.0 * num_traits::Float::ln(u1));
+    F::from_f64(r * num_traits::Float::cos(2.0 * core::f64::consts::PI * u2))
+}
+
+/// Increment of the Wiener process driving component `i` in the diagonal case.
+/// For scalar noise, all components are driven by the same increment.
+fn increment_at<F: Copy>(increments: &[F], i: usize) -> F {
+    if increments.len() == 1 {
+        increments[0]
+    } else {
+        increments[i]
+    }
+}
+
+/// Shared state of all SDE steppers
+struct SdeCore<'a, I, F, P, Err, R> {
+    /// Definition of the SDE which is solved
+    sde_def: SdeDefinition<'a, I, F, P, Err>,
+    /// Random number generator used to draw the increments
+    rng: R,
+    /// Storage for the increments of the last step
+    increments: BrownianIncrements<F>,
+    /// Number of independent Wiener processes
+    n_noise: usize,
+}
+
+impl<'a, I, F, P, Err, R> SdeCore<'a, I, F, P, Err, R>
+where
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    /// Determine the number of Wiener processes from the diffusion
+    fn new(sde_def: SdeDefinition<'a, I, F, P, Err>, rng: R) -> Self
+    where
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+    {
+        let n_noise = match sde_def.diffusion {
+            Diffusion::Diagonal(_) => (&sde_def.y0).into_iter().count(),
+            Diffusion::General { n_noise, .. } => n_noise,
+        };
+        SdeCore {
+            sde_def,
+            rng,
+            increments: BrownianIncrements::default(),
+            n_noise,
+        }
+    }
+
+    /// Returns `true` if every component is driven by at most one Wiener process
+    fn is_diagonal(&self) -> bool {
+        match self.sde_def.diffusion {
+            Diffusion::Diagonal(_) => true,
+            Diffusion::General { n_noise, .. } => n_noise == 1,
+        }
+    }
+
+    /// Draw new increments for a step of size `dt` which have variance \\(|dt|\\).
+    /// The auxiliary increments `dz` are only drawn if `with_dz` is set.
+    fn draw(&mut self, increments: &mut BrownianIncrements<F>, dt: &F, with_dz: bool) {
+        let sqrt_dt = dt.abs().sqrt();
+        increments.dw.clear();
+        increments.dz.clear();
+        for _ in 0..self.n_noise {
+            let dw: F = sample_standard_normal(&mut self.rng);
+            increments.dw.push(sqrt_dt * dw);
+            if with_dz {
+                let dz: F = sample_standard_normal(&mut self.rng);
+                increments.dz.push(sqrt_dt * dz);
+            }
+        }
+    }
+
+    /// Evaluate the diffusion in the diagonal (or scalar noise) case
+    fn eval_diagonal(&self, y: &I, g: &mut I, t: &F, p: &P) -> Result<(), Err> {
+        match self.sde_def.diffusion {
+            Diffusion::Diagonal(func) => func(y, g, t, p),
+            Diffusion::General { func, .. } => func(y, core::slice::from_mut(g), t, p),
+        }
+    }
+
+    /// Evaluate all columns of the diffusion
+    fn eval_columns(&self, y: &I, g: &mut [I], t: &F, p: &P) -> Result<(), Err> {
+        match self.sde_def.diffusion {
+            Diffusion::Diagonal(func) => func(y, &mut g[0], t, p),
+            Diffusion::General { func, .. } => func(y, g, t, p),
+        }
+    }
+}
+
+/// Implements [SdeStepper::do_step_iter] by drawing increments and delegating to
+/// [SdeStepper::do_step_increments_iter].
+macro_rules! impl_sde_do_step_iter {
+    ($with_dz: expr) => {
+        fn do_step_iter(&mut self, y: &mut I, t: &F, dt: &F, p: &P) -> Result<(), Err>
+        where
+            for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+            for<'m> &'m I: IntoIterator<Item = &'m F>,
+            F: RealFloatLikeType,
+        {
+            let mut increments = core::mem::take(&mut self.core.increments);
+            self.core.draw(&mut increments, dt, $with_dz);
+            let res = self.do_step_increments_iter(y, t, dt, &increments, p);
+            self.core.increments = increments;
+            res
+        }
+    };
+}
+
+/// # Euler-Maruyama stepper
+/// The simplest method for SDEs with strong order 0.5
+/// \begin{equation}
+///     y_1 = y_0 + f(y_0, t_0, p)dt + \sum_j g_j(y_0, t_0, p)\Delta W_j.
+/// \end{equation}
+pub struct EulerMaruyama<'a, I, F, P, Err, R> {
+    /// Definition of the SDE, random number generator and increments
+    core: SdeCore<'a, I, F, P, Err, R>,
+    /// Storage for the evaluated drift
+    dy: I,
+    /// Storage for the evaluated columns of the diffusion
+    g: Vec<I>,
+}
+
+impl<'a, I, F, P, Err, R> EulerMaruyama<'a, I, F, P, Err, R>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    /// Create a new stepper which draws the increments from `rng`
+    pub fn new(sde_def: SdeDefinition<'a, I, F, P, Err>, rng: R) -> Self {
+        let dy = sde_def.y0.clone();
+        let core = SdeCore::new(sde_def, rng);
+        let n_columns = if core.is_diagonal() { 1 } else { core.n_noise };
+        EulerMaruyama {
+            g: vec![dy.clone(); n_columns],
+            dy,
+            core,
+        }
+    }
+}
+
+impl<'a, I, F, P, Err, R> SdeStepper<I, F, P, Err> for EulerMaruyama<'a, I, F, P, Err, R>
+where
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    impl_sde_do_step_iter!(false);
+
+    fn do_step_increments_iter(
+        &mut self,
+        y: &mut I,
+        t: &F,
+        dt: &F,
+        increments: &BrownianIncrements<F>,
+        p: &P,
+    ) -> Result<(), Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType,
+    {
+        (self.core.sde_def.drift)(y, &mut self.dy, t, p)?;
+        if self.core.is_diagonal() {
+            self.core.eval_diagonal(y, &mut self.g[0], t, p)?;
+            for (i, ((yi, dyi), gi)) in y.into_iter().zip(&self.dy).zip(&self.g[0]).enumerate() {
+                *yi += *dt * *dyi + *gi * increment_at(&increments.dw, i);
+            }
+        } else {
+            self.core.eval_columns(y, &mut self.g, t, p)?;
+            for (yi, dyi) in y.into_iter().zip(&self.dy) {
+                *yi += *dt * *dyi;
+            }
+            for (gj, dwj) in self.g.iter().zip(&increments.dw) {
+                for (yi, gji) in y.into_iter().zip(gj) {
+                    *yi += *gji * *dwj;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// # Milstein stepper
+/// Derivative-free variant of the Milstein method with strong order 1.0.
+/// With the supporting values \\(\hat{y}_j = y_0 + f dt + g_j\sqrt{dt}\\), a step is given by
+/// \begin{equation}
+///     y_1 = y_0 + f dt + \sum_j g_j \Delta W_j
+///         + \frac{1}{2\sqrt{dt}}\sum_{j,k}\left(g_k(\hat{y}_j) - g_k(y_0)\right)
+///         \left(\Delta W_j \Delta W_k - \delta_{jk}dt\right).
+/// \end{equation}
+/// For general noise with more than one Wiener process, the noise is assumed to be commutative
+/// since the method does not approximate the Lévy areas.
+pub struct Milstein<'a, I, F, P, Err, R> {
+    /// Definition of the SDE, random number generator and increments
+    core: SdeCore<'a, I, F, P, Err, R>,
+    /// Storage for the evaluated drift
+    dy: I,
+    /// Storage for the evaluated columns of the diffusion
+    g: Vec<I>,
+    /// Columns of the diffusion at the supporting value
+    g_hat: Vec<I>,
+    /// Supporting value \\(\hat{y}_j\\)
+    y_hat: I,
+    /// Accumulated correction term
+    correction: I,
+}
+
+impl<'a, I, F, P, Err, R> Milstein<'a, I, F, P, Err, R>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    /// Create a new stepper which draws the increments from `rng`
+    pub fn new(sde_def: SdeDefinition<'a, I, F, P, Err>, rng: R) -> Self {
+        let dy = sde_def.y0.clone();
+        let core = SdeCore::new(sde_def, rng);
+        let n_columns = if core.is_diagonal() { 1 } else { core.n_noise };
+        Milstein {
+            g: vec![dy.clone(); n_columns],
+            g_hat: vec![dy.clone(); n_columns],
+            y_hat: dy.clone(),
+            correction: dy.clone(),
+            dy,
+            core,
+        }
+    }
+}
+
+impl<'a, I, F, P, Err, R> SdeStepper<I, F, P, Err> for Milstein<'a, I, F, P, Err, R>
+where
+    I: Clone,
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    impl_sde_do_step_iter!(false);
+
+    fn do_step_increments_iter(
+        &mut self,
+        y: &mut I,
+        t: &F,
+        dt: &F,
+        increments: &BrownianIncrements<F>,
+        p: &P,
+    ) -> Result<(), Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType,
+    {
+        // Backward steps have Brownian increments of variance |dt|
+        let h = dt.abs();
+        let sqrt_dt = h.sqrt();
+        let half = F::from(1) / F::from(2);
+        (self.core.sde_def.drift)(y, &mut self.dy, t, p)?;
+
+        if self.core.is_diagonal() {
+            self.core.eval_diagonal(y, &mut self.g[0], t, p)?;
+            self.y_hat = y.clone();
+            for ((yh, dyi), gi) in (&mut self.y_hat).into_iter().zip(&self.dy).zip(&self.g[0]) {
+                *yh += *dt * *dyi + sqrt_dt * *gi;
+            }
+            self.core
+                .eval_diagonal(&self.y_hat, &mut self.g_hat[0], t, p)?;
+            for (i, (((yi, dyi), gi), ghi)) in y
+                .into_iter()
+                .zip(&self.dy)
+                .zip(&self.g[0])
+                .zip(&self.g_hat[0])
+                .enumerate()
+            {
+                let dw = increment_at(&increments.dw, i);
+                *yi += *dt * *dyi + *gi * dw + half * (*ghi - *gi) * (dw * dw - h) / sqrt_dt;
+            }
+        } else {
+            self.core.eval_columns(y, &mut self.g, t, p)?;
+            self.correction = y.clone();
+            for ci in &mut self.correction {
+                *ci = F::from(0);
+            }
+            for (j, gj) in self.g.iter().enumerate() {
+                self.y_hat = y.clone();
+                for ((yh, dyi), gji) in (&mut self.y_hat).into_iter().zip(&self.dy).zip(gj) {
+                    *yh += *dt * *dyi + sqrt_dt * *gji;
+                }
+                self.core.eval_columns(&self.y_hat, &mut self.g_hat, t, p)?;
+                for (k, (gk, ghk)) in self.g.iter().zip(&self.g_hat).enumerate() {
+                    let mut iterated = increments.dw[j] * increments.dw[k];
+                    if j == k {
+                        iterated -= h;
+                    }
+                    let factor = half * iterated / sqrt_dt;
+                    for ((ci, gki), ghki) in (&mut self.correction).into_iter().zip(gk).zip(ghk) {
+                        *ci += factor * (*ghki - *gki);
+                    }
+                }
+            }
+            for ((yi, dyi), ci) in y.into_iter().zip(&self.dy).zip(&self.correction) {
+                *yi += *dt * *dyi + *ci;
+            }
+            for (gj, dwj) in self.g.iter().zip(&increments.dw) {
+                for (yi, gji) in y.into_iter().zip(gj) {
+                    *yi += *gji * *dwj;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// # Stochastic Runge-Kutta stepper SRI1
+/// Method of Rößler (SIAM J. Numer. Anal. 48, 2010) with strong order 1.5 for diagonal and
+/// scalar noise. With the iterated integrals
+/// \begin{equation}
+///     \chi_1 = \frac{\Delta W^2 - dt}{2\sqrt{dt}},\quad
+///     \chi_2 = \frac{1}{2}\left(\Delta W + \frac{\Delta Z}{\sqrt{3}}\right),\quad
+///     \chi_3 = \frac{\Delta W^3 - 3\Delta W dt}{6 dt}
+/// \end{equation}
+/// and the stages
+/// \begin{equation}
+///     \begin{alignedat}{2}
+///         H^{(0)} &= y_0 + \tfrac{3}{4}f_1 dt + \tfrac{3}{2}\chi_2 g_1 &&\\\\
+///         H^{(1)}_2 &= y_0 + \tfrac{1}{4}f_1 dt + \tfrac{1}{2}\sqrt{dt}g_1, &\quad g_2 &= g(H^{(1)}_2, t_0 + \tfrac{1}{4}dt)\\\\
+///         H^{(1)}_3 &= y_0 + f_1 dt - \sqrt{dt}g_1, &\quad g_3 &= g(H^{(1)}_3, t_0 + dt)\\\\
+///         H^{(1)}_4 &= y_0 + \tfrac{1}{4}f_1 dt + \sqrt{dt}\left(-5g_1 + 3g_2 + \tfrac{1}{2}g_3\right), &\quad g_4 &= g(H^{(1)}_4, t_0 + \tfrac{1}{4}dt)
+///     \end{alignedat}
+/// \end{equation}
+/// the new value is obtained by
+/// \begin{equation}
+///     \begin{aligned}
+///         y_1 = y_0 &+ \tfrac{1}{3}\left(f_1 + 2f(H^{(0)}, t_0 + \tfrac{3}{4}dt)\right)dt
+///             + \Delta W\left(-g_1 + \tfrac{4}{3}g_2 + \tfrac{2}{3}g_3\right)
+///             + \chi_1\left(-g_1 + \tfrac{4}{3}g_2 - \tfrac{1}{3}g_3\right)\\\\
+///             &+ \chi_2\left(2g_1 - \tfrac{4}{3}g_2 - \tfrac{2}{3}g_3\right)
+///             + \chi_3\left(-2g_1 + \tfrac{5}{3}g_2 - \tfrac{2}{3}g_3 + g_4\right).
+///     \end{aligned}
+/// \end{equation}
+/// All products are taken component-wise.
+pub struct Sri<'a, I, F, P, Err, R> {
+    /// Definition of the SDE, random number generator and increments
+    core: SdeCore<'a, I, F, P, Err, R>,
+    /// Drift at the initial point
+    f1: I,
+    /// Drift at the stage \\(H^{(0)}\\)
+    f2: I,
+    /// Diffusion at the initial point
+    g1: I,
+    /// Diffusion at the stage \\(H^{(1)}_2\\)
+    g2: I,
+    /// Diffusion at the stage \\(H^{(1)}_3\\)
+    g3: I,
+    /// Diffusion at the stage \\(H^{(1)}_4\\)
+    g4: I,
+    /// Storage for the current stage value
+    h: I,
+}
+
+impl<'a, I, F, P, Err, R> Sri<'a, I, F, P, Err, R>
+where
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    /// Create a new stepper which draws the increments from `rng`.
+    /// Fails if the SDE has general noise with more than one Wiener process.
+    pub fn new(sde_def: SdeDefinition<'a, I, F, P, Err>, rng: R) -> Result<Self, SolvingError> {
+        let y = sde_def.y0.clone();
+        let core = SdeCore::new(sde_def, rng);
+        if !core.is_diagonal() {
+            return Err(SolvingError::from(
+                "The SRI method is only applicable for diagonal or scalar noise",
+            ));
+        }
+        Ok(Sri {
+            core,
+            f1: y.clone(),
+            f2: y.clone(),
+            g1: y.clone(),
+            g2: y.clone(),
+            g3: y.clone(),
+            g4: y.clone(),
+            h: y,
+        })
+    }
+}
+
+impl<'a, I, F, P, Err, R> SdeStepper<I, F, P, Err> for Sri<'a, I, F, P, Err, R>
+where
+    I: Clone,
+    F: RealFloatLikeType,
+    R: Rng,
+{
+    impl_sde_do_step_iter!(true);
+
+    fn do_step_increments_iter(
+        &mut self,
+        y: &mut I,
+        t: &F,
+        dt: &F,
+        increments: &BrownianIncrements<F>,
+        p: &P,
+    ) -> Result<(), Err>
+    where
+        for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+        for<'m> &'m I: IntoIterator<Item = &'m F>,
+        F: RealFloatLikeType,
+    {
+        let one = F::from(1);
+        let two = F::from(2);
+        let three = F::from(3);
+        let four = F::from(4);
+        let five = F::from(5);
+        // Backward steps have Brownian increments of variance |dt|
+        let h = dt.abs();
+        let sqrt_dt = h.sqrt();
+        let sqrt_3 = three.sqrt();
+        let chi2 = |i: usize| {
+            (increment_at(&increments.dw, i) + increment_at(&increments.dz, i) / sqrt_3) / two
+        };
+
+        (self.core.sde_def.drift)(y, &mut self.f1, t, p)?;
+        self.core.eval_diagonal(y, &mut self.g1, t, p)?;
+
+        // H^(0) and the drift evaluated there
+        self.h = y.clone();
+        for (i, ((hi, f1i), g1i)) in (&mut self.h)
+            .into_iter()
+            .zip(&self.f1)
+            .zip(&self.g1)
+            .enumerate()
+        {
+            *hi += three / four * *f1i * *dt + three / two * chi2(i) * *g1i;
+        }
+        (self.core.sde_def.drift)(&self.h, &mut self.f2, &(*t + three / four * *dt), p)?;
+
+        // H^(1)_2
+        self.h = y.clone();
+        for ((hi, f1i), g1i) in (&mut self.h).into_iter().zip(&self.f1).zip(&self.g1) {
+            *hi += *f1i * *dt / four + sqrt_dt * *g1i / two;
+        }
+        self.core
+            .eval_diagonal(&self.h, &mut self.g2, &(*t + *dt / four), p)?;
+
+        // H^(1)_3
+        self.h = y.clone();
+        for ((hi, f1i), g1i) in (&mut self.h).into_iter().zip(&self.f1).zip(&self.g1) {
+            *hi += *f1i * *dt - sqrt_dt * *g1i;
+        }
+        self.core
+            .eval_diagonal(&self.h, &mut self.g3, &(*t + *dt), p)?;
+
+        // H^(1)_4
+        self.h = y.clone();
+        for ((((hi, f1i), g1i), g2i), g3i) in (&mut self.h)
+            .into_iter()
+            .zip(&self.f1)
+            .zip(&self.g1)
+            .zip(&self.g2)
+            .zip(&self.g3)
+        {
+            *hi += *f1i * *dt / four + sqrt_dt * (-five * *g1i + three * *g2i + *g3i / two);
+        }
+        self.core
+            .eval_diagonal(&self.h, &mut self.g4, &(*t + *dt / four), p)?;
+
+        for (i, ((((((yi, f1i), f2i), g1i), g2i), g3i), g4i)) in y
+            .into_iter()
+            .zip(&self.f1)
+            .zip(&self.f2)
+            .zip(&self.g1)
+            .zip(&self.g2)
+            .zip(&self.g3)
+            .zip(&self.g4)
+            .enumerate()
+        {
+            let dw = increment_at(&increments.dw, i);
+            let chi1 = (dw * dw - h) / (two * sqrt_dt);
+            let chi3 = (dw * dw * dw - three * dw * h) / (F::from(6) * h);
+            *yi += (*f1i + two * *f2i) * *dt / three
+                + dw * (-*g1i + four / three * *g2i + two / three * *g3i)
+                + chi1 * (-*g1i + four / three * *g2i - one / three * *g3i)
+                + chi2(i) * (two * *g1i - four / three * *g2i - two / three * *g3i)
+                + chi3 * (-two * *g1i + five / three * *g2i - two / three * *g3i + *g4i);
+        }
+        Ok(())
+    }
+}
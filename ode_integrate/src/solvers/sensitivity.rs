@@ -0,0 +1,333 @@
+use crate::concepts::*;
+use crate::linalg::LuDecomposition;
+use crate::solvers::implicit::{
+    finite_difference_jacobian, gather, scatter, ImplicitSolvers, SdirkCore,
+};
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Strategies to integrate the forward sensitivities together with the state
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SensitivityMethod {
+    /// State and sensitivities are combined into one system which is integrated with the
+    /// classical 4th order Runge-Kutta method
+    Simultaneous,
+    /// The state is advanced with the implicit method first. Afterwards the linear sensitivity
+    /// equations are solved for every stage with the Jacobians at the converged stage values.
+    Staggered(ImplicitSolvers),
+}
+
+/// # Forward sensitivity stepper
+/// Integrates the state \\(y\\) of a [SensitivityDefinition] together with the sensitivities
+/// \\(S = \partial y/\partial p\\) which are stored as \\(n \times n_p\\) matrix in row-major order.
+/// For the [staggered](SensitivityMethod::Staggered) method, the sensitivities are the exact
+/// result of the implicit method applied to the combined system while only the state requires
+/// a Newton iteration.
+pub struct ForwardSensitivity<'a, I, F, P, Err> {
+    /// Definition of the ODE and evaluation of its Jacobians
+    rhs: RhsJacobians<'a, I, F, P, Err>,
+    /// Implicit method used to advance the state for the staggered method
+    implicit: Option<SdirkCore<'a, I, F, P, Err>>,
+}
+
+/// Evaluates the RHS of a [SensitivityDefinition] and its Jacobians at states given as slices
+pub(crate) struct RhsJacobians<'a, I, F, P, Err> {
+    /// Definition of the ODE and its Jacobians
+    pub(crate) sens_def: SensitivityDefinition<'a, I, F, P, Err>,
+    /// Storage for states at which the RHS is evaluated
+    buf: I,
+    /// Storage for the evaluated RHS
+    dy: I,
+}
+
+impl<'a, I, F, P, Err> RhsJacobians<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+{
+    /// Allocate the storage for the evaluation
+    pub(crate) fn new(sens_def: SensitivityDefinition<'a, I, F, P, Err>) -> Self {
+        let buf = sens_def.y0.clone();
+        let dy = sens_def.y0.clone();
+        RhsJacobians { sens_def, buf, dy }
+    }
+
+    /// Evaluate the RHS at a state given as slice and write the result into `f`
+    pub(crate) fn eval(&mut self, y: &[F], t: &F, p: &P, f: &mut Vec<F>) -> Result<(), Err> {
+        scatter(y, &mut self.buf);
+        (self.sens_def.func)(&self.buf, &mut self.dy, t, p)?;
+        gather(&self.dy, f);
+        Ok(())
+    }
+
+    /// Evaluate both Jacobians at the given state where `f0` is the RHS at this state
+    pub(crate) fn jacobians(
+        &mut self,
+        y: &[F],
+        t: &F,
+        p: &P,
+        f0: &[F],
+        jac_y: &mut Vec<F>,
+        jac_p: &mut Vec<F>,
+    ) -> Result<(), Err> {
+        let n = y.len();
+        let n_p = p.into_iter().count();
+        match self.sens_def.jacobian_y {
+            Some(jacobian) => {
+                jac_y.clear();
+                jac_y.resize(n * n, F::from(0));
+                scatter(y, &mut self.buf);
+                jacobian(&self.buf, t, p, jac_y)?;
+            }
+            None => finite_difference_jacobian(
+                self.sens_def.func,
+                y,
+                t,
+                p,
+                f0,
+                jac_y,
+                &mut self.buf,
+                &mut self.dy,
+            )?,
+        }
+
+        jac_p.clear();
+        jac_p.resize(n * n_p, F::from(0));
+        scatter(y, &mut self.buf);
+        match self.sens_def.jacobian_p {
+            Some(jacobian) => jacobian(&self.buf, t, p, jac_p)?,
+            None => {
+                let sqrt_eps = F::epsilon().sqrt();
+                let mut p_pert = p.clone();
+                for k in 0..n_p {
+                    let mut delta = F::from(0);
+                    if let Some(pk) = (&mut p_pert).into_iter().nth(k) {
+                        delta = sqrt_eps * pk.abs().max(F::from(1));
+                        *pk += delta;
+                    }
+                    (self.sens_def.func)(&self.buf, &mut self.dy, t, &p_pert)?;
+                    for (i, (fi, f0i)) in (&self.dy).into_iter().zip(f0).enumerate() {
+                        jac_p[i * n_p + k] = (*fi - *f0i) / delta;
+                    }
+                    p_pert = p.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, I, F, P, Err> ForwardSensitivity<'a, I, F, P, Err>
+where
+    for<'m> &'m mut I: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m I: IntoIterator<Item = &'m F>,
+    I: Clone,
+    F: RealFloatLikeType,
+    for<'m> &'m mut P: IntoIterator<Item = &'m mut F>,
+    for<'m> &'m P: IntoIterator<Item = &'m F>,
+    P: Clone,
+    Err: From<CalcError>,
+{
+    /// Create a new stepper which uses the given method
+    pub fn new(
+        sens_def: SensitivityDefinition<'a, I, F, P, Err>,
+        method: SensitivityMethod,
+    ) -> Result<Self, SolvingError> {
+        let implicit = match method {
+            SensitivityMethod::Simultaneous => None,
+            SensitivityMethod::Staggered(solver_type) => {
+                let dae_def = DaeDefinition {
+                    y0: sens_def.y0.clone(),
+                    t0: sens_def.t0,
+                    func: sens_def.func,
+                    mass: MassMatrix::Identity,
+                };
+                Some(SdirkCore::new(dae_def, &solver_type)?)
+            }
+        };
+        Ok(ForwardSensitivity {
+            rhs: RhsJacobians::new(sens_def),
+            implicit,
+        })
+    }
+
+    /// Evaluate the RHS of state and sensitivities
+    fn combined_rhs(
+        &mut self,
+        y: &[F],
+        s: &[F],
+        t: &F,
+        p: &P,
+        f: &mut Vec<F>,
+        ds: &mut [F],
+    ) -> Result<(), Err> {
+        let n = y.len();
+        let n_p = ds.len() / n.max(1);
+        let mut jac_y = Vec::new();
+        let mut jac_p = Vec::new();
+        self.rhs.eval(y, t, p, f)?;
+        self.rhs.jacobians(y, t, p, f, &mut jac_y, &mut jac_p)?;
+        for i in 0..n {
+            for k in 0..n_p {
+                let mut value = jac_p[i * n_p + k];
+                for l in 0..n {
+                    value += jac_y[i * n + l] * s[l * n_p + k];
+                }
+                ds[i * n_p + k] = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// Advance the state `y` and the sensitivities `s` by a single step
+    pub fn do_step_iter(
+        &mut self,
+        y: &mut I,
+        s: &mut [F],
+        t: &F,
+        dt: &F,
+        p: &P,
+    ) -> Result<(), Err> {
+        let mut y0 = Vec::new();
+        gather(y, &mut y0);
+        let n = y0.len();
+        let n_p = p.into_iter().count();
+        if s.len() != n * n_p {
+            return Err(CalcError::from(alloc::format!(
+                "Sensitivity matrix needs {} entries but has {}",
+                n * n_p,
+                s.len()
+            ))
+            .into());
+        }
+        match self.implicit.take() {
+            None => {
+                self.do_step_simultaneous(&mut y0, s, t, dt, p)?;
+                scatter(&y0, y);
+                Ok(())
+            }
+            Some(mut core) => {
+                let res = self.do_step_staggered(&mut core, y, &y0, s, t, dt, p);
+                self.implicit = Some(core);
+                res
+            }
+        }
+    }
+
+    /// Classical Runge-Kutta step for the combined system
+    fn do_step_simultaneous(
+        &mut self,
+        y: &mut [F],
+        s: &mut [F],
+        t: &F,
+        dt: &F,
+        p: &P,
+    ) -> Result<(), Err> {
+        let h = *dt;
+        let half = F::from(1) / F::from(2);
+        let mut ky = vec![Vec::new(); 4];
+        let mut ks = vec![vec![F::from(0); s.len()]; 4];
+        let mut y_stage = y.to_vec();
+        let mut s_stage = s.to_vec();
+        for (stage, c) in [F::from(0), half, half, F::from(1)].into_iter().enumerate() {
+            if stage > 0 {
+                for (yi, (y0i, ki)) in y_stage.iter_mut().zip(y.iter().zip(&ky[stage - 1])) {
+                    *yi = *y0i + c * h * *ki;
+                }
+                for (si, (s0i, ki)) in s_stage.iter_mut().zip(s.iter().zip(&ks[stage - 1])) {
+                    *si = *s0i + c * h * *ki;
+                }
+            }
+            let mut f = Vec::new();
+            self.combined_rhs(&y_stage, &s_stage, &(*t + c * h), p, &mut f, &mut ks[stage])?;
+            ky[stage] = f;
+        }
+        let sixth = h / F::from(6);
+        let two = F::from(2);
+        for (i, yi) in y.iter_mut().enumerate() {
+            *yi += sixth * (ky[0][i] + two * ky[1][i] + two * ky[2][i] + ky[3][i]);
+        }
+        for (i, si) in s.iter_mut().enumerate() {
+            *si += sixth * (ks[0][i] + two * ks[1][i] + two * ks[2][i] + ks[3][i]);
+        }
+        Ok(())
+    }
+
+    /// Implicit step of the state followed by the linear stage equations of the sensitivities
+    /// \begin{equation}
+    ///     \left(I - h\gamma\frac{\partial f}{\partial y}(Y_i)\right)S_i
+    ///         = S_0 + h\sum_{j<i}a_{ij}K_j + h\gamma\frac{\partial f}{\partial p}(Y_i)
+    /// \end{equation}
+    #[allow(clippy::too_many_arguments)]
+    fn do_step_staggered(
+        &mut self,
+        core: &mut SdirkCore<'a, I, F, P, Err>,
+        y: &mut I,
+        y0: &[F],
+        s: &mut [F],
+        t: &F,
+        dt: &F,
+        p: &P,
+    ) -> Result<(), Err> {
+        core.do_step(y, t, dt, p)?;
+        let n = y0.len();
+        let n_p = s.len() / n.max(1);
+        let h = *dt;
+        let h_gamma = h * core.gamma;
+        let s0 = s.to_vec();
+        let mut k: Vec<Vec<F>> = Vec::with_capacity(core.c.len());
+        let mut f = Vec::new();
+        let mut jac_y = Vec::new();
+        let mut jac_p = Vec::new();
+        for (i, stage) in core.stages.iter().enumerate() {
+            let t_i = *t + core.c[i] * h;
+            self.rhs.eval(stage, &t_i, p, &mut f)?;
+            self.rhs
+                .jacobians(stage, &t_i, p, &f, &mut jac_y, &mut jac_p)?;
+            let matrix = (0..n * n)
+                .map(|idx| {
+                    let identity = if idx / n == idx % n {
+                        F::from(1)
+                    } else {
+                        F::from(0)
+                    };
+                    identity - h_gamma * jac_y[idx]
+                })
+                .collect();
+            let lu = LuDecomposition::new(matrix, n).ok_or_else(|| {
+                CalcError::from("Iteration matrix of the sensitivity equations is singular")
+            })?;
+
+            let mut base = s0.clone();
+            for (a_ij, k_j) in core.a[i].iter().zip(k.iter()) {
+                for (bl, kl) in base.iter_mut().zip(k_j.iter()) {
+                    *bl += h * *a_ij * *kl;
+                }
+            }
+            let mut column = vec![F::from(0); n];
+            for col in 0..n_p {
+                for (r, cr) in column.iter_mut().enumerate() {
+                    *cr = base[r * n_p + col] + h_gamma * jac_p[r * n_p + col];
+                }
+                lu.solve(&mut column);
+                for (r, cr) in column.iter().enumerate() {
+                    s[r * n_p + col] = *cr;
+                }
+            }
+            k.push(
+                s.iter()
+                    .zip(base.iter())
+                    .map(|(si, bi)| (*si - *bi) / h_gamma)
+                    .collect(),
+            );
+        }
+        Ok(())
+    }
+}
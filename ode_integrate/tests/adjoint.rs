@@ -0,0 +1,221 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Exponential decay \\(y' = -p_0 y\\)
+fn rhs_decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+    dy[0] = -p[0] * y[0];
+    Ok(())
+}
+
+/// Integrand \\(g = y^2\\)
+fn square(y: &[f64; 1], _t: &f64, _p: &[f64; 1]) -> Result<f64, CalcError> {
+    Ok(y[0] * y[0])
+}
+
+#[test]
+fn integral_objective() {
+    let ode_def = OdeDefinition {
+        y0: [1.5],
+        t0: 0.0,
+        func: &rhs_decay,
+    };
+    let objective = Objective {
+        kind: ObjectiveKind::Integral,
+        func: &square,
+        gradient: None,
+    };
+    let (y0, p, t_end) = (1.5, 0.8, 2.0);
+    let res =
+        solve_adjoint_sensitivity_iter(ode_def.into(), objective, &vec![t_end], &[p], &0.01, 16)
+            .unwrap();
+
+    // G = y0^2 (1 - exp(-2 p T)) / (2 p)
+    let e = (-2.0 * p * t_end).exp();
+    let value = y0 * y0 * (1.0 - e) / (2.0 * p);
+    let dg_dp = y0 * y0 * (2.0 * t_end * e * p - (1.0 - e)) / (2.0 * p * p);
+    let dg_dy0 = y0 * (1.0 - e) / p;
+    assert_abs_diff_eq!(res.value, value, epsilon = 1e-8);
+    assert_abs_diff_eq!(res.dg_dp[0], dg_dp, epsilon = 1e-6);
+    assert_abs_diff_eq!(res.dg_dy0[0], dg_dy0, epsilon = 1e-6);
+}
+
+/// Logistic growth \\(y' = p_0 y (1 - y / p_1)\\) of two populations coupled by \\(p_2\\)
+fn rhs_logistic(y: &Vec<f64>, dy: &mut Vec<f64>, _t: &f64, p: &Vec<f64>) -> Result<(), CalcError> {
+    dy[0] = p[0] * y[0] * (1.0 - y[0] / p[1]) - p[2] * y[0] * y[1];
+    dy[1] = p[0] * y[1] * (1.0 - y[1] / p[1]) + p[2] * y[0] * y[1];
+    Ok(())
+}
+
+/// Measured data which is compared to the first population
+fn data(t: &f64) -> f64 {
+    0.2 + 0.3 * t
+}
+
+/// Squared deviation of the first population from the data
+fn loss(y: &Vec<f64>, t: &f64, _p: &Vec<f64>) -> Result<f64, CalcError> {
+    Ok((y[0] - data(t)).powi(2))
+}
+
+fn loss_gradient(
+    y: &Vec<f64>,
+    t: &f64,
+    _p: &Vec<f64>,
+    grad_y: &mut [f64],
+    _grad_p: &mut [f64],
+) -> Result<(), CalcError> {
+    grad_y[0] = 2.0 * (y[0] - data(t));
+    Ok(())
+}
+
+fn logistic_definition<'a>() -> SensitivityDefinition<'a, Vec<f64>, f64, Vec<f64>, CalcError> {
+    OdeDefinition {
+        y0: vec![0.1, 0.3],
+        t0: 0.0,
+        func: &rhs_logistic,
+    }
+    .into()
+}
+
+#[test]
+fn pointwise_objective_agrees_with_forward_sensitivities() {
+    let t_series = vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0];
+    let p = vec![1.2, 2.0, 0.4];
+    let dt = 0.01;
+    let forward = solve_sensitivity_time_series_iter(
+        logistic_definition(),
+        &t_series,
+        &p,
+        SensitivityMethod::Simultaneous,
+        &dt,
+    )
+    .unwrap();
+    let mut value = 0.0;
+    let mut dg_dp = [0.0; 3];
+    for (t, (y, s)) in t_series.iter().zip(forward.iter()) {
+        value += (y[0] - data(t)).powi(2);
+        for (k, dk) in dg_dp.iter_mut().enumerate() {
+            *dk += 2.0 * (y[0] - data(t)) * s[k];
+        }
+    }
+
+    for gradient in [
+        None,
+        Some(&loss_gradient as ObjectiveGradient<Vec<f64>, f64, Vec<f64>, CalcError>),
+    ] {
+        let objective = Objective {
+            kind: ObjectiveKind::Pointwise,
+            func: &loss,
+            gradient,
+        };
+        let res = solve_adjoint_sensitivity_iter(
+            logistic_definition(),
+            objective,
+            &t_series,
+            &p,
+            &dt,
+            25,
+        )
+        .unwrap();
+        assert_abs_diff_eq!(res.value, value, epsilon = 1e-12);
+        for (a, b) in res.dg_dp.iter().zip(dg_dp.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+        }
+    }
+}
+
+#[test]
+fn initial_value_derivative() {
+    let t_series = vec![1.0, 2.0, 3.0];
+    let p = vec![1.2, 2.0, 0.4];
+    let objective = Objective {
+        kind: ObjectiveKind::Pointwise,
+        func: &loss,
+        gradient: Some(&loss_gradient),
+    };
+    let res = solve_adjoint_sensitivity_iter(
+        logistic_definition(),
+        objective.clone(),
+        &t_series,
+        &p,
+        &0.01,
+        10,
+    )
+    .unwrap();
+
+    let delta = 1e-6;
+    for i in 0..2 {
+        let value = |shift: f64| {
+            let mut sens_def = logistic_definition();
+            sens_def.y0[i] += shift;
+            solve_adjoint_sensitivity_iter(sens_def, objective.clone(), &t_series, &p, &0.01, 10)
+                .unwrap()
+                .value
+        };
+        let central = (value(delta) - value(-delta)) / (2.0 * delta);
+        assert_abs_diff_eq!(res.dg_dy0[i], central, epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn checkpointing_does_not_change_results() {
+    let t_series = vec![0.0, 1.3, 2.9];
+    let p = vec![1.2, 2.0, 0.4];
+    let objective = Objective {
+        kind: ObjectiveKind::Pointwise,
+        func: &loss,
+        gradient: Some(&loss_gradient),
+    };
+    let solve = |checkpoint_every: usize| {
+        solve_adjoint_sensitivity_iter(
+            logistic_definition(),
+            objective.clone(),
+            &t_series,
+            &p,
+            &0.05,
+            checkpoint_every,
+        )
+        .unwrap()
+    };
+    let reference = solve(1000);
+    for checkpoint_every in [1, 7, 58] {
+        let res = solve(checkpoint_every);
+        assert_eq!(res.value, reference.value);
+        for (a, b) in res.dg_dp.iter().zip(reference.dg_dp.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-14);
+        }
+        for (a, b) in res.dg_dy0.iter().zip(reference.dg_dy0.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-14);
+        }
+    }
+}
+
+#[test]
+fn invalid_input() {
+    let objective = Objective {
+        kind: ObjectiveKind::Integral,
+        func: &loss,
+        gradient: None,
+    };
+    let p = vec![1.2, 2.0, 0.4];
+    assert!(solve_adjoint_sensitivity_iter(
+        logistic_definition(),
+        objective.clone(),
+        &vec![1.0],
+        &p,
+        &0.1,
+        0
+    )
+    .is_err());
+    assert!(solve_adjoint_sensitivity_iter(
+        logistic_definition(),
+        objective,
+        &vec![1.0, 0.5],
+        &p,
+        &0.1,
+        10
+    )
+    .is_err());
+}
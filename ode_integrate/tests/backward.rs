@@ -0,0 +1,410 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Exponential decay \\(y' = -p y\\)
+fn rhs_decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+    dy[0] = -p * y[0];
+    Ok(())
+}
+
+/// Exponential decay \\(y' = -p y\\) as a vector which can be added
+fn rhs_decay_vec(
+    y: &nalgebra::Vector1<f64>,
+    dy: &mut nalgebra::Vector1<f64>,
+    _t: &f64,
+    p: &f64,
+) -> Result<(), CalcError> {
+    dy[0] = -p * y[0];
+    Ok(())
+}
+
+fn tolerances() -> Tolerances<f64> {
+    Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    }
+}
+
+#[test]
+fn fixed_step_drivers() {
+    let p = 2.0;
+    let t_series = vec![1.0, 0.77, 0.5, 0.1, 0.0];
+    let y0 = nalgebra::Vector1::new((-p).exp());
+    let minimal = solve_ode_time_series_minimal_step_add(
+        &y0,
+        &t_series,
+        &rhs_decay_vec,
+        &p,
+        FixedStepSolvers::Rk4,
+        &0.01,
+    )
+    .unwrap();
+    // The sign of the step size does not matter
+    let equidistributed = solve_ode_time_series_equidistributed_step_add(
+        &y0,
+        &t_series,
+        &rhs_decay_vec,
+        &p,
+        FixedStepSolvers::Rk4,
+        &-0.01,
+    )
+    .unwrap();
+    for (t, (y_min, y_equi)) in t_series
+        .iter()
+        .zip(minimal.iter().zip(equidistributed.iter()))
+    {
+        let exact = (-p * t).exp();
+        assert_abs_diff_eq!(y_min[0], exact, epsilon = 1e-8);
+        assert_abs_diff_eq!(y_equi[0], exact, epsilon = 1e-8);
+    }
+
+    // A single backward Euler step with negative step size
+    let y = solve_ode_time_series_single_step_iter(
+        &[1.0],
+        &vec![1.0, 0.5],
+        &rhs_decay,
+        &p,
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[1][0], 2.0, epsilon = 1e-14);
+
+    let mut steps = Vec::new();
+    let mut observer = |_t: &f64, _y: &mut [f64; 1], dt: &f64, _e: Option<&f64>| {
+        steps.push(*dt);
+        ObserverAction::Continue
+    };
+    solve_ode_time_series_minimal_step_observed_iter(
+        &[1.0],
+        &vec![0.0, -0.25],
+        &rhs_decay,
+        &p,
+        FixedStepSolvers::Euler,
+        &0.1,
+        &mut observer,
+    )
+    .unwrap();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0], -0.1);
+    assert_abs_diff_eq!(steps[2], -0.05, epsilon = 1e-14);
+}
+
+#[test]
+fn adaptive_step_drivers() {
+    let p = 2.0;
+    let t_series = vec![2.0, 1.3, 0.5, 0.0];
+    let y0 = [(-2.0 * p).exp()];
+    let y_iter = solve_ode_time_series_adaptive_step_iter(
+        &y0,
+        &t_series,
+        &rhs_decay,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+    )
+    .unwrap();
+    let y_add = solve_ode_time_series_adaptive_step_add(
+        &nalgebra::Vector1::new(y0[0]),
+        &t_series,
+        &rhs_decay_vec,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+    )
+    .unwrap();
+    for (t, (y_iter, y_add)) in t_series.iter().zip(y_iter.iter().zip(y_add.iter())) {
+        let exact = (-p * t).exp();
+        assert_abs_diff_eq!(y_iter[0], exact, epsilon = 1e-8 * exact);
+        assert_abs_diff_eq!(y_add[0], exact, epsilon = 1e-8 * exact);
+    }
+
+    // Observed steps are all negative
+    let mut observer = |_t: &f64, _y: &mut [f64; 1], dt: &f64, _e: Option<&f64>| {
+        assert!(*dt < 0.0);
+        ObserverAction::Continue
+    };
+    let solution = solve_ode_time_series_adaptive_step_observed_iter(
+        &y0,
+        &t_series,
+        &rhs_decay,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+        &mut observer,
+    )
+    .unwrap();
+    assert_eq!(solution.y, y_iter);
+}
+
+#[test]
+fn time_reversal() {
+    // Integrating forward and back again returns to the initial values
+    let ode_def = OdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        func: &rhs_decay,
+    };
+    let mut integration = AdaptiveIntegration::new(
+        ode_def,
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances(),
+    );
+    let y = *integration.integrate_to_iter(&3.0, &1.0).unwrap();
+    assert_abs_diff_eq!(y[0], (-3.0_f64).exp(), epsilon = 1e-10);
+    assert!(*integration.dt() > 0.0);
+    let y = *integration.integrate_to_iter(&0.0, &1.0).unwrap();
+    assert_abs_diff_eq!(y[0], 1.0, epsilon = 1e-8);
+    assert!(*integration.dt() < 0.0);
+}
+
+#[test]
+fn tstops_backward() {
+    // Halve the state whenever a stop is passed
+    let halve = |_t: &f64, y: &mut [f64; 1], _p: &f64| y[0] *= 0.5;
+    let tstops = TStops {
+        times: vec![0.75, 0.25],
+        jump: Some(&halve),
+    };
+    let t_series = vec![1.0, 0.5, 0.0];
+    let y = solve_ode_time_series_adaptive_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &0.0,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+        &tstops,
+    )
+    .unwrap();
+    assert_eq!(y, vec![[1.0], [0.5], [0.25]]);
+    let y = solve_ode_time_series_minimal_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &0.0,
+        FixedStepSolvers::Euler,
+        &0.1,
+        &tstops,
+    )
+    .unwrap();
+    assert_eq!(y, vec![[1.0], [0.5], [0.25]]);
+
+    // Stop times in increasing order do not fit the backward integration
+    let increasing = TStops {
+        times: vec![0.25, 0.75],
+        jump: Some(&halve),
+    };
+    assert!(solve_ode_time_series_single_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &0.0,
+        FixedStepSolvers::Euler,
+        &increasing,
+    )
+    .is_err());
+}
+
+#[test]
+fn non_monotonic_time_series() {
+    let p = 1.0;
+    for t_series in [vec![0.0, 1.0, 0.5], vec![1.0, 0.5, 0.5, 2.0]] {
+        assert!(solve_ode_time_series_single_step_iter(
+            &[1.0],
+            &t_series,
+            &rhs_decay,
+            &p,
+            FixedStepSolvers::Euler,
+        )
+        .is_err());
+        assert!(solve_ode_time_series_minimal_step_iter(
+            &[1.0],
+            &t_series,
+            &rhs_decay,
+            &p,
+            FixedStepSolvers::Euler,
+            &0.1,
+        )
+        .is_err());
+        assert!(solve_ode_time_series_adaptive_step_iter(
+            &[1.0],
+            &t_series,
+            &rhs_decay,
+            &p,
+            AdaptiveStepSolvers::BulirschStoer,
+            &0.1,
+            &tolerances(),
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn solver_and_integrator() {
+    let p = 2.0;
+    let t_series = vec![1.0, 0.6, 0.0];
+    let y0 = [(-p).exp()];
+    // The fixed steppers are only first order accurate
+    for (solver, epsilon) in [
+        (Solver::builder().build().unwrap(), 1e-6),
+        (
+            Solver::builder()
+                .fixed_step(FixedStepSolvers::Rk4)
+                .dt(0.001)
+                .build()
+                .unwrap(),
+            1e-2,
+        ),
+    ] {
+        let y = solver.solve(&y0, &t_series, &rhs_decay, &p).unwrap().y;
+        assert_eq!(y.len(), 3);
+        for (t, y) in t_series.iter().zip(y.iter()) {
+            assert_abs_diff_eq!(y[0], (-p * t).exp(), epsilon = epsilon);
+        }
+    }
+
+    // Negative step sizes produce items at decreasing output times
+    let ode_def = OdeDefinition {
+        y0,
+        t0: 1.0,
+        func: &rhs_decay,
+    };
+    let integrator = Integrator::fixed_step_iter(ode_def.clone(), FixedStepSolvers::Rk4, -0.001, p)
+        .with_output_times(vec![0.75, 0.5, 0.0]);
+    let items: Vec<_> = integrator.collect();
+    assert_eq!(items.len(), 3);
+    for ((t, y), t_out) in items.iter().zip([0.75, 0.5, 0.0]) {
+        assert_eq!(*t, t_out);
+        assert_abs_diff_eq!(y[0], (-p * t).exp(), epsilon = 1e-2);
+    }
+
+    // Steps end at the end time and output times after it are not produced
+    let mut integrator = Integrator::adaptive_step_iter(
+        ode_def.clone(),
+        AdaptiveStepSolvers::BulirschStoer,
+        -0.1,
+        tolerances(),
+        p,
+    )
+    .with_t_end(0.5);
+    let items: Vec<_> = integrator.by_ref().collect();
+    assert_eq!(items.last().unwrap().0, 0.5);
+    assert!(items.windows(2).all(|w| w[1].0 < w[0].0));
+    assert!(integrator.error().is_none());
+
+    // Output times need to follow the direction of integration
+    let mut integrator = Integrator::fixed_step_iter(ode_def, FixedStepSolvers::Rk4, -0.01, p)
+        .with_output_times(vec![0.5, 0.75]);
+    assert!(integrator.step().unwrap().is_some());
+    assert!(integrator.step().is_err());
+}
+
+#[test]
+fn dae_sensitivity_and_sde_drivers() {
+    let p = 2.0;
+    let t_series = vec![1.0, 0.5, 0.0];
+
+    // y0' = -y0 + y1, 0 = y1 - sin(t) integrated forward and back again
+    let rhs = |y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = -y[0] + y[1];
+        dy[1] = y[1] - t.sin();
+        Ok(())
+    };
+    let dae_def = |y0: [f64; 2], t0: f64| DaeDefinition {
+        y0,
+        t0,
+        func: &rhs,
+        mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+    };
+    let forward = solve_dae_time_series_iter(
+        dae_def([1.0, 0.0], 0.0),
+        &vec![0.0, 1.0],
+        &(),
+        ImplicitSolvers::Sdirk2,
+        &1e-3,
+    )
+    .unwrap();
+    let backward = solve_dae_time_series_iter(
+        dae_def(forward[1], 1.0),
+        &t_series,
+        &(),
+        ImplicitSolvers::Sdirk2,
+        &1e-3,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(backward[2][0], 1.0, epsilon = 1e-6);
+    assert_abs_diff_eq!(backward[2][1], 0.0, epsilon = 1e-12);
+
+    // Starting at t = 1 gives y = y0 exp(-p (t - 1)) and dy/dp = -(t - 1) y
+    let rhs_param = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]| {
+        dy[0] = -p[0] * y[0];
+        Ok::<(), CalcError>(())
+    };
+    let ode_def = OdeDefinition {
+        y0: [1.0],
+        t0: 1.0,
+        func: &rhs_param,
+    };
+    let res = solve_sensitivity_time_series_iter(
+        ode_def.into(),
+        &t_series,
+        &[p],
+        SensitivityMethod::Simultaneous,
+        &0.01,
+    )
+    .unwrap();
+    for (t, (y, s)) in t_series.iter().zip(res.iter()) {
+        let exact = (-p * (t - 1.0)).exp();
+        assert_abs_diff_eq!(y[0], exact, epsilon = 1e-8 * exact);
+        assert_abs_diff_eq!(s[0], -(t - 1.0) * exact, epsilon = 1e-6 * exact);
+    }
+
+    // Without diffusion, the SDE steppers reduce to deterministic methods
+    let drift = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64| -> Result<(), CalcError> {
+        dy[0] = -p * y[0];
+        Ok(())
+    };
+    for sigma in [0.0, 0.5] {
+        let diffusion = move |y: &[f64; 1], g: &mut [f64; 1], _t: &f64, _p: &f64| {
+            g[0] = sigma * y[0];
+            Ok::<(), CalcError>(())
+        };
+        for solver in [
+            SdeSolvers::EulerMaruyama,
+            SdeSolvers::Milstein,
+            SdeSolvers::Sri,
+        ] {
+            let sde_def = SdeDefinition {
+                y0: [1.0],
+                t0: 1.0,
+                drift: &drift,
+                diffusion: Diffusion::Diagonal(&diffusion),
+            };
+            let y = solve_sde_time_series_iter(
+                sde_def,
+                &t_series,
+                &p,
+                solver,
+                &1e-3,
+                ChaCha8Rng::seed_from_u64(3),
+            )
+            .unwrap();
+            assert_eq!(y.len(), 3);
+            if sigma == 0.0 {
+                assert_abs_diff_eq!(y[2][0], p.exp(), epsilon = 1e-2 * p.exp());
+            } else {
+                assert!(y.iter().all(|y| y[0].is_finite()));
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Harmonic oscillators \\(y_0' = y_1, y_1' = -\omega_l^2 y_0\\) with one frequency per lane
+fn rhs_oscillators(
+    y: &[f64],
+    dy: &mut [f64],
+    _t: &[f64],
+    omega: &Vec<f64>,
+) -> Result<(), CalcError> {
+    let n = omega.len();
+    let (x, v) = y.split_at(n);
+    let (dx, dv) = dy.split_at_mut(n);
+    for l in 0..n {
+        dx[l] = v[l];
+        dv[l] = -omega[l] * omega[l] * x[l];
+    }
+    Ok(())
+}
+
+/// Lanes of the oscillators which all start at \\(x=1, v=0\\)
+fn oscillators<'a>(omega: &[f64]) -> BatchedOdeDefinition<'a, f64, Vec<f64>, CalcError> {
+    let lanes: Vec<[f64; 2]> = omega.iter().map(|_| [1.0, 0.0]).collect();
+    BatchedOdeDefinition::from_lanes(&lanes, 0.0, &rhs_oscillators).unwrap()
+}
+
+/// Maximal deviation of the first component from \\(\cos(\omega_l t)\\) after integrating with RK4
+fn rk4_error(omega: &Vec<f64>, dt: f64, n_steps: usize) -> f64 {
+    let batch_def = oscillators(omega);
+    let mut y = batch_def.y0.clone();
+    let mut rk4 = BatchedRk4::new(batch_def.clone()).unwrap();
+    for i in 0..n_steps {
+        rk4.do_step(&mut y, &(i as f64 * dt), &dt, omega).unwrap();
+    }
+    let t = n_steps as f64 * dt;
+    omega
+        .iter()
+        .enumerate()
+        .map(|(l, w)| (batch_def.lane(&y, l)[0] - (w * t).cos()).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn fixed_step_order() {
+    let omega: Vec<f64> = (1..=8).map(|i| 0.5 * i as f64).collect();
+    let e1 = rk4_error(&omega, 0.02, 50);
+    let e2 = rk4_error(&omega, 0.01, 100);
+    assert!(e1 < 1e-5);
+    assert_abs_diff_eq!(e1 / e2, 16.0, epsilon = 1.0);
+}
+
+#[test]
+fn adaptive_lanes_take_individual_steps() {
+    let omega = vec![1.0, 4.0, 16.0];
+    let batch_def = oscillators(&omega);
+    let tolerances = Tolerances {
+        rtol: 1e-9,
+        atol: 1e-9,
+    };
+    let mut dopri = BatchedDopri5::new(batch_def.clone(), tolerances).unwrap();
+    let mut y = batch_def.y0.clone();
+    let mut t = vec![0.0; 3];
+    let n_steps = dopri.integrate_to(&mut y, &mut t, &2.0, &omega).unwrap();
+    assert!(n_steps > 0);
+    assert_eq!(t, vec![2.0; 3]);
+    for (l, w) in omega.iter().enumerate() {
+        let lane = batch_def.lane(&y, l);
+        assert_abs_diff_eq!(lane[0], (2.0 * w).cos(), epsilon = 1e-6);
+        assert_abs_diff_eq!(lane[1], -w * (2.0 * w).sin(), epsilon = 1e-5);
+    }
+
+    // Faster oscillations need smaller steps
+    let dt = dopri.suggested_dt();
+    assert!(dt[0] > dt[1] && dt[1] > dt[2]);
+}
+
+#[test]
+fn finished_lanes_are_masked() {
+    let omega = vec![1.0, 2.0];
+    let batch_def = oscillators(&omega);
+    let tolerances = Tolerances {
+        rtol: 1e-8,
+        atol: 1e-8,
+    };
+    let mut dopri = BatchedDopri5::new(batch_def.clone(), tolerances)
+        .unwrap()
+        .with_initial_dt(0.1);
+    let mut y = batch_def.y0.clone();
+    let mut t = vec![0.0, 1.0];
+    let done = dopri.do_step(&mut y, &mut t, &1.0, &omega).unwrap();
+    assert!(!done);
+    assert_abs_diff_eq!(t[0], 0.1, epsilon = 1e-15);
+    assert_eq!(t[1], 1.0);
+    assert_eq!(batch_def.lane(&y, 1), vec![1.0, 0.0]);
+    assert_eq!(dopri.errors()[1], 0.0);
+    assert!(dopri.errors()[0] <= 1.0);
+
+    dopri.integrate_to(&mut y, &mut t, &1.0, &omega).unwrap();
+    assert_eq!(t, vec![1.0, 1.0]);
+    assert_abs_diff_eq!(y[0], 1.0_f64.cos(), epsilon = 1e-7);
+    assert_eq!(batch_def.lane(&y, 1), vec![1.0, 0.0]);
+}
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -\omega^2 y_0\\) of a single lane
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, omega: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -omega * omega * y[0];
+    Ok(())
+}
+
+#[test]
+fn lanes_of_an_ode_definition() {
+    let ode_def = OdeDefinition {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        func: &rhs_oscillator,
+    };
+    let copies = LaneWiseRHS::new(ode_def);
+    let lanes = [[1.0, 0.0], [1.0, 0.0], [1.0, 0.0]];
+    let batch_def = copies.batch(&lanes).unwrap();
+    assert_eq!(batch_def.n_states(), 2);
+
+    // Gathering and scattering gives the same steps as the structure-of-arrays RHS
+    let omega = 1.5;
+    let dt = 0.01;
+    let mut y = batch_def.y0.clone();
+    let mut rk4 = BatchedRk4::new(batch_def).unwrap();
+    let reference = oscillators(&[omega; 3]);
+    let mut y_ref = reference.y0.clone();
+    let mut rk4_ref = BatchedRk4::new(reference).unwrap();
+    for i in 0..100 {
+        let t = i as f64 * dt;
+        rk4.do_step(&mut y, &t, &dt, &omega).unwrap();
+        rk4_ref
+            .do_step(&mut y_ref, &t, &dt, &vec![omega; 3])
+            .unwrap();
+    }
+    assert_eq!(y, y_ref);
+    assert_abs_diff_eq!(y[0], (omega * 1.0_f64).cos(), epsilon = 1e-8);
+
+    // States of the batch and the lanes need the size of the ODE
+    let mut dy = vec![0.0; 3];
+    assert!((copies.rhs())(&[1.0, 0.0, 0.0], &mut dy, &[0.0], &omega).is_err());
+    let decay = |y: &Vec<f64>, dy: &mut Vec<f64>, _t: &f64, p: &f64| -> Result<(), CalcError> {
+        dy[0] = -p * y[0];
+        Ok(())
+    };
+    let copies = LaneWiseRHS::new(OdeDefinition {
+        y0: vec![1.0],
+        t0: 0.0,
+        func: &decay,
+    });
+    assert!(copies.batch(&[vec![1.0], vec![2.0]]).is_ok());
+    assert!(copies.batch(&[vec![1.0, 0.0], vec![2.0, 0.0]]).is_err());
+}
+
+#[test]
+fn invalid_layout() {
+    let mut batch_def = oscillators(&[1.0, 2.0]);
+    assert_eq!(batch_def.y0, vec![1.0, 1.0, 0.0, 0.0]);
+    assert_eq!(batch_def.n_states(), 2);
+
+    let mut rk4 = BatchedRk4::new(batch_def.clone()).unwrap();
+    let mut y = vec![1.0; 3];
+    assert!(rk4.do_step(&mut y, &0.0, &0.1, &vec![1.0, 2.0]).is_err());
+
+    batch_def.n_lanes = 3;
+    assert!(BatchedRk4::new(batch_def.clone()).is_err());
+    batch_def.n_lanes = 0;
+    let tolerances = Tolerances {
+        rtol: 1e-8,
+        atol: 1e-8,
+    };
+    assert!(BatchedDopri5::new(batch_def, tolerances).is_err());
+
+    // Lanes of different lengths are rejected instead of being truncated
+    let lanes = vec![vec![1.0], vec![2.0, 3.0]];
+    assert!(BatchedOdeDefinition::from_lanes(&lanes, 0.0, &rhs_oscillators).is_err());
+}
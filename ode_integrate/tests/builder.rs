@@ -0,0 +1,331 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Exponential decay \\(y' = -p y\\)
+fn rhs_decay(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &f64) -> Result<(), CalcError> {
+    dy[0] = -p * y[0];
+    dy[1] = -p * y[1];
+    Ok(())
+}
+
+/// Exponential decay \\(y' = -p y\\) as a vector which can be added
+fn rhs_decay_vec(
+    y: &nalgebra::Vector2<f64>,
+    dy: &mut nalgebra::Vector2<f64>,
+    _t: &f64,
+    p: &f64,
+) -> Result<(), CalcError> {
+    *dy = -p * y;
+    Ok(())
+}
+
+fn t_series() -> Vec<f64> {
+    (0..=10).map(|i| 0.2 * i as f64).collect()
+}
+
+#[test]
+fn defaults() {
+    let solver = Solver::builder().build().unwrap();
+    assert!(matches!(
+        solver.method(),
+        SolverMethod::AdaptiveStep(AdaptiveStepSolvers::BulirschStoer)
+    ));
+    assert!(solver.dt().is_none());
+    assert!(solver.max_steps().is_none());
+    let tolerances = solver.tolerances().unwrap();
+    assert_eq!(tolerances.rtol, 1e-6);
+    assert_eq!(tolerances.atol, 1e-6);
+
+    let y = solver
+        .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.5)
+        .unwrap()
+        .y;
+    for (t, yi) in t_series().iter().zip(y.iter()) {
+        assert_abs_diff_eq!(yi[0], (-1.5 * t).exp(), epsilon = 1e-5);
+        assert_abs_diff_eq!(yi[1], 2.0 * (-1.5 * t).exp(), epsilon = 1e-5);
+    }
+}
+
+#[test]
+fn invalid_combinations() {
+    let tolerances = Tolerances {
+        rtol: 1e-6,
+        atol: 1e-6,
+    };
+    assert!(Solver::<f64>::builder()
+        .fixed_step(FixedStepSolvers::Rk4)
+        .tolerances(tolerances.clone())
+        .build()
+        .is_err());
+    assert!(Solver::<f64>::builder().dt(0.0).build().is_err());
+    assert!(Solver::<f64>::builder().dt(-0.1).build().is_err());
+    assert!(Solver::<f64>::builder().max_steps(0).build().is_err());
+    assert!(Solver::<f64>::builder()
+        .tolerances(Tolerances {
+            rtol: 0.0,
+            atol: 0.0
+        })
+        .build()
+        .is_err());
+    assert!(Solver::<f64>::builder()
+        .tolerances(Tolerances {
+            rtol: -1e-6,
+            atol: 1e-6
+        })
+        .build()
+        .is_err());
+    assert!(Solver::<f64>::builder()
+        .adaptive_step(AdaptiveStepSolvers::BulirschStoer)
+        .tolerances(tolerances)
+        .dt(0.1)
+        .max_steps(100)
+        .build()
+        .is_ok());
+
+    // Time points are checked when solving
+    let solver = Solver::builder().build().unwrap();
+    assert!(solver
+        .solve(&[1.0, 2.0], &vec![0.0, 1.0, 0.5], &rhs_decay, &1.0)
+        .is_err());
+    assert!(solver
+        .solve(&[1.0, 2.0], &Vec::new(), &rhs_decay, &1.0)
+        .is_err());
+}
+
+#[test]
+fn fixed_steps_match_drivers() {
+    let solver = Solver::builder()
+        .fixed_step(FixedStepSolvers::Euler)
+        .build()
+        .unwrap();
+    let y = solver
+        .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+        .unwrap()
+        .y;
+    let reference = solve_ode_time_series_single_step_iter(
+        &[1.0, 2.0],
+        &t_series(),
+        &rhs_decay,
+        &1.0,
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+    assert_eq!(y, reference);
+
+    // Steps of at most dt land on every time point
+    let solver = Solver::builder()
+        .fixed_step(FixedStepSolvers::Euler)
+        .dt(0.05)
+        .build()
+        .unwrap();
+    let y = solver
+        .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+        .unwrap()
+        .y;
+    assert_eq!(y.len(), t_series().len());
+    let error_fine = (y[10][0] - (-2.0_f64).exp()).abs();
+    let error_coarse = (reference[10][0] - (-2.0_f64).exp()).abs();
+    assert!(error_fine < 0.3 * error_coarse);
+}
+
+#[test]
+fn max_steps() {
+    let t_quarters: Vec<f64> = (0..=8).map(|i| 0.25 * i as f64).collect();
+    let limited = |max_steps| {
+        Solver::builder()
+            .fixed_step(FixedStepSolvers::Rk4)
+            .dt(0.125)
+            .max_steps(max_steps)
+            .build()
+            .unwrap()
+            .solve(&[1.0, 2.0], &t_quarters, &rhs_decay, &1.0)
+    };
+    assert!(limited(16).is_ok());
+    assert!(limited(15).is_err());
+
+    let single_steps = |max_steps| {
+        Solver::builder()
+            .fixed_step(FixedStepSolvers::Rk4)
+            .max_steps(max_steps)
+            .build()
+            .unwrap()
+            .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+    };
+    assert!(single_steps(10).is_ok());
+    assert!(single_steps(9).is_err());
+
+    let adaptive = Solver::builder()
+        .tolerances(Tolerances {
+            rtol: 1e-12,
+            atol: 1e-12,
+        })
+        .max_steps(3)
+        .build()
+        .unwrap();
+    assert!(adaptive
+        .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+        .is_err());
+
+    // Rejected steps count the same with and without an observer
+    let observer = core::cell::RefCell::new(
+        |_t: &f64, _y: &mut [f64; 2], _dt: &f64, _err: Option<&f64>| ObserverAction::Continue,
+    );
+    let t_stiff = vec![0.0, 3.0];
+    let mut n_ok = 0;
+    for max_steps in 40..60 {
+        let builder = || {
+            Solver::builder()
+                .tolerances(Tolerances {
+                    rtol: 1e-12,
+                    atol: 1e-12,
+                })
+                .max_steps(max_steps)
+        };
+        let plain = builder()
+            .build()
+            .unwrap()
+            .solve(&[1.0, 2.0], &t_stiff, &rhs_decay, &50.0);
+        let observed = builder().observer(&observer).build().unwrap().solve(
+            &[1.0, 2.0],
+            &t_stiff,
+            &rhs_decay,
+            &50.0,
+        );
+        assert_eq!(plain.is_ok(), observed.is_ok());
+        if let (Ok(plain), Ok(observed)) = (plain, observed) {
+            assert_eq!(plain.y, observed.y);
+            n_ok += 1;
+        }
+    }
+    assert!(n_ok > 0 && n_ok < 20);
+}
+
+#[test]
+fn additive_types() {
+    let y0 = nalgebra::Vector2::new(1.0, 2.0);
+    for solver in [
+        Solver::builder().build().unwrap(),
+        Solver::builder()
+            .fixed_step(FixedStepSolvers::Rk4)
+            .dt(0.01)
+            .build()
+            .unwrap(),
+    ] {
+        let y = solver
+            .solve_add(&y0, &t_series(), &rhs_decay_vec, &1.0)
+            .unwrap()
+            .y;
+        assert_eq!(y.len(), 11);
+        assert_abs_diff_eq!(y[10][1], 2.0 * (-2.0_f64).exp(), epsilon = 1e-5);
+    }
+}
+
+#[test]
+fn observer_tstops_and_substepping() {
+    assert!(Solver::<f64>::builder()
+        .substepping(Substepping::Equidistributed)
+        .build()
+        .is_err());
+    assert!(Solver::<f64>::builder()
+        .fixed_step(FixedStepSolvers::Rk4)
+        .substepping(Substepping::Equidistributed)
+        .build()
+        .is_err());
+
+    // Equal steps of size 0.1 between the time points spaced by 0.2
+    let step_sizes = core::cell::RefCell::new(Vec::new());
+    let observer = core::cell::RefCell::new(
+        |_t: &f64, _y: &mut [f64; 2], dt: &f64, _err: Option<&f64>| {
+            step_sizes.borrow_mut().push(*dt);
+            ObserverAction::Continue
+        },
+    );
+    let solver = Solver::builder()
+        .fixed_step(FixedStepSolvers::Rk4)
+        .dt(0.15)
+        .substepping(Substepping::Equidistributed)
+        .observer(&observer)
+        .build()
+        .unwrap();
+    let y = solver
+        .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+        .unwrap()
+        .y;
+    assert_eq!(y.len(), 11);
+    assert_eq!(step_sizes.borrow().len(), 20);
+    for dt in step_sizes.borrow().iter() {
+        assert_abs_diff_eq!(*dt, 0.1, epsilon = 1e-12);
+    }
+
+    // The state is halved at the stop time
+    let jump = |_t: &f64, y: &mut [f64; 2], _p: &f64| {
+        y[0] *= 0.5;
+        y[1] *= 0.5;
+    };
+    let tstops = TStops {
+        times: vec![0.9],
+        jump: Some(&jump),
+    };
+    for (solver, epsilon) in [
+        (Solver::builder().tstops(&tstops).build().unwrap(), 1e-5),
+        (
+            Solver::builder()
+                .fixed_step(FixedStepSolvers::Rk4)
+                .dt(0.001)
+                .tstops(&tstops)
+                .build()
+                .unwrap(),
+            1e-3,
+        ),
+    ] {
+        let y = solver
+            .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+            .unwrap()
+            .y;
+        assert_abs_diff_eq!(y[4][0], (-0.8_f64).exp(), epsilon = epsilon);
+        assert_abs_diff_eq!(y[5][0], 0.5 * (-1.0_f64).exp(), epsilon = epsilon);
+    }
+
+    // Stopping the adaptive integration returns the time points which were reached
+    let stopping = core::cell::RefCell::new(
+        |t: &f64, _y: &mut nalgebra::Vector2<f64>, _dt: &f64, _err: Option<&f64>| {
+            if *t > 1.0 {
+                ObserverAction::Stop
+            } else {
+                ObserverAction::Continue
+            }
+        },
+    );
+    let solver = Solver::builder().observer(&stopping).build().unwrap();
+    let solution = solver
+        .solve_add(
+            &nalgebra::Vector2::new(1.0, 2.0),
+            &t_series(),
+            &rhs_decay_vec,
+            &1.0,
+        )
+        .unwrap();
+    let y = &solution.y;
+    assert!(y.len() > 1 && y.len() < 11);
+    assert_abs_diff_eq!(y[1][1], 2.0 * (-0.2_f64).exp(), epsilon = 1e-5);
+    let (t_stop, y_stop) = solution.stopped.unwrap();
+    assert!(t_stop > 1.0 && t_stop < 2.0);
+    assert_abs_diff_eq!(y_stop[0], (-t_stop).exp(), epsilon = 1e-5);
+
+    // Equal substeps count like any other steps
+    let limited = |max_steps| {
+        Solver::builder()
+            .fixed_step(FixedStepSolvers::Rk4)
+            .dt(0.15)
+            .substepping(Substepping::Equidistributed)
+            .max_steps(max_steps)
+            .build()
+            .unwrap()
+            .solve(&[1.0, 2.0], &t_series(), &rhs_decay, &1.0)
+    };
+    assert!(limited(20).is_ok());
+    assert!(limited(19).is_err());
+}
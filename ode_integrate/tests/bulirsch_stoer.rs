@@ -0,0 +1,156 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+use f128::f128;
+use nalgebra::Vector2;
+
+/// Harmonic oscillator \\(\ddot{x} = -p^2 x\\) written as first order system
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -p * p * y[0];
+    Ok(())
+}
+
+#[test]
+fn oscillator_f64_iter() {
+    let p = 1.3;
+    let y0 = [1.0, 0.0];
+    let t_series: Vec<f64> = (0..21).map(|i| i as f64 * 0.5).collect();
+    let tolerances = Tolerances {
+        rtol: 1e-12,
+        atol: 1e-12,
+    };
+
+    let y_res = solve_ode_time_series_adaptive_step_iter(
+        &y0,
+        &t_series,
+        &rhs_oscillator,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.01,
+        &tolerances,
+    )
+    .unwrap();
+
+    assert_eq!(y_res.len(), t_series.len());
+    for (t, y) in t_series.iter().zip(y_res.iter()) {
+        assert_abs_diff_eq!(y[0], (p * t).cos(), epsilon = 1e-9);
+        assert_abs_diff_eq!(y[1], -p * (p * t).sin(), epsilon = 1e-9);
+    }
+}
+
+/// Exponential decay for a type which can be added
+fn rhs_decay_add(
+    y: &Vector2<f64>,
+    dy: &mut Vector2<f64>,
+    _t: &f64,
+    p: &f64,
+) -> Result<(), CalcError> {
+    *dy = -*p * y;
+    Ok(())
+}
+
+#[test]
+fn decay_nalgebra_add() {
+    let p = 0.7;
+    let y0 = Vector2::new(1.0, -2.0);
+    let t_series = vec![0.0, 0.3, 1.1, 2.0, 5.0];
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+
+    let y_res = solve_ode_time_series_adaptive_step_add(
+        &y0,
+        &t_series,
+        &rhs_decay_add,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.5,
+        &tolerances,
+    )
+    .unwrap();
+
+    for (t, y) in t_series.iter().zip(y_res.iter()) {
+        let exact = y0 * (-p * t).exp();
+        assert_abs_diff_eq!(y[0], exact[0], epsilon = 1e-8);
+        assert_abs_diff_eq!(y[1], exact[1], epsilon = 1e-8);
+    }
+}
+
+/// Exponential decay with quadruple precision
+fn rhs_decay_f128(y: &Vec<f128>, dy: &mut Vec<f128>, _t: &f128, p: &f128) -> Result<(), CalcError> {
+    for (yi, dyi) in y.iter().zip(dy.iter_mut()) {
+        *dyi = -*p * *yi;
+    }
+    Ok(())
+}
+
+#[test]
+fn decay_f128_reference() {
+    let p = f128::from(1.5);
+    let y0 = vec![f128::from(1.0)];
+    let t_series = vec![f128::from(0.0), f128::from(1.0), f128::from(2.0)];
+    let tolerances = Tolerances {
+        rtol: f128::from(1e-25),
+        atol: f128::from(1e-25),
+    };
+
+    let y_res = solve_ode_time_series_adaptive_step_iter(
+        &y0,
+        &t_series,
+        &rhs_decay_f128,
+        &p,
+        AdaptiveStepSolvers::BulirschStoer,
+        &f128::from(0.1),
+        &tolerances,
+    )
+    .unwrap();
+
+    for (t, y) in t_series.iter().zip(y_res.iter()) {
+        let exact = (-p * *t).exp();
+        assert!((y[0] - exact).abs() < f128::from(1e-22));
+    }
+}
+
+#[test]
+fn step_rejection_keeps_state() {
+    let p = 1.0;
+    let tolerances = Tolerances {
+        rtol: 1e-14,
+        atol: 1e-14,
+    };
+    let ode_def = OdeDefinition {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        func: &rhs_oscillator,
+    };
+    let mut bs = BulirschStoer::new(ode_def, tolerances).with_max_columns(3);
+
+    // A huge step cannot be accurate with only 3 columns
+    let mut y = [1.0, 0.0];
+    let err = bs.do_step_iter(&mut y, &0.0, &5.0, &p).unwrap().unwrap();
+    assert!(err > 1.0);
+    assert_eq!(y, [1.0, 0.0]);
+    assert!(bs.suggested_dt().unwrap() < 5.0);
+}
+
+#[test]
+fn decreasing_time_series() {
+    let tolerances = Tolerances {
+        rtol: 1e-6,
+        atol: 1e-6,
+    };
+    let res = solve_ode_time_series_adaptive_step_iter(
+        &[1.0, 0.0],
+        &vec![0.0, 1.0, 0.5],
+        &rhs_oscillator,
+        &1.0,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+    );
+    assert!(res.is_err());
+}
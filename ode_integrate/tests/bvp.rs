@@ -0,0 +1,130 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Harmonic oscillator \\(y'' = -y\\)
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+/// Nonlinear ODE \\(y'' = \tfrac{3}{2} y^2\\) which has the solution \\(4 / (1 + t)^2\\)
+fn rhs_quadratic(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = 1.5 * y[0] * y[0];
+    Ok(())
+}
+
+/// Bulirsch-Stoer with tight tolerances
+fn settings() -> ShootingSettings<f64> {
+    let solver = Solver::builder()
+        .tolerances(Tolerances {
+            rtol: 1e-12,
+            atol: 1e-12,
+        })
+        .build()
+        .unwrap();
+    ShootingSettings::new(solver)
+}
+
+#[test]
+fn single_shooting() {
+    // y(0) = 0 and y(pi/2) = 1 is solved by sin(t)
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &(), r: &mut [f64]| {
+        r[0] = ya[0];
+        r[1] = yb[0] - 1.0;
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: core::f64::consts::FRAC_PI_2,
+        func: &rhs_oscillator,
+        residual: &residual,
+    };
+    let solution = solve_bvp_single_shooting_iter(&bvp, &[0.0, 0.0], &(), &settings()).unwrap();
+    assert_eq!(solution.times, vec![0.0, core::f64::consts::FRAC_PI_2]);
+    assert!(solution.residual <= 1e-10);
+    assert_abs_diff_eq!(solution.y[0][0], 0.0, epsilon = 1e-10);
+    assert_abs_diff_eq!(solution.y[0][1], 1.0, epsilon = 1e-10);
+    assert_abs_diff_eq!(solution.y[1][0], 1.0, epsilon = 1e-10);
+    assert_abs_diff_eq!(solution.y[1][1], 0.0, epsilon = 1e-10);
+    // A linear problem is solved by a single Newton step up to the finite differences
+    assert!(solution.iterations <= 2);
+
+    // Fixed steps of Euler solve the discretized problem
+    let solver = Solver::builder()
+        .fixed_step(FixedStepSolvers::Euler)
+        .dt(0.001)
+        .build()
+        .unwrap();
+    let solution =
+        solve_bvp_single_shooting_iter(&bvp, &[0.0, 0.0], &(), &ShootingSettings::new(solver))
+            .unwrap();
+    assert!(solution.residual <= 1e-10);
+    assert_abs_diff_eq!(solution.y[0][1], 1.0, epsilon = 1e-2);
+}
+
+#[test]
+fn multiple_shooting() {
+    // y(0) = 4 and y(1) = 1
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &(), r: &mut [f64]| {
+        r[0] = ya[0] - 4.0;
+        r[1] = yb[0] - 1.0;
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: 1.0,
+        func: &rhs_quadratic,
+        residual: &residual,
+    };
+    // Guess the straight line between the boundary values
+    let nodes = vec![0.0, 0.25, 0.5, 0.75];
+    let guesses: Vec<[f64; 2]> = nodes.iter().map(|t| [4.0 - 3.0 * t, -3.0]).collect();
+    let solution =
+        solve_bvp_multiple_shooting_iter(&bvp, &nodes, &guesses, &(), &settings()).unwrap();
+    assert_eq!(solution.times, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    for (t, y) in solution.times.iter().zip(solution.y.iter()) {
+        assert_abs_diff_eq!(y[0], 4.0 / (1.0 + t).powi(2), epsilon = 1e-9);
+        assert_abs_diff_eq!(y[1], -8.0 / (1.0 + t).powi(3), epsilon = 1e-9);
+    }
+
+    // Single shooting from the same guess finds the same solution
+    let single = solve_bvp_single_shooting_iter(&bvp, &guesses[0], &(), &settings()).unwrap();
+    assert_abs_diff_eq!(single.y[0][1], -8.0, epsilon = 1e-8);
+}
+
+#[test]
+fn invalid_problems() {
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &(), r: &mut [f64]| {
+        r[0] = ya[0];
+        r[1] = yb[0] - 1.0;
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: 1.0,
+        func: &rhs_oscillator,
+        residual: &residual,
+    };
+    let solve = |nodes: &[f64], guesses: &[[f64; 2]]| {
+        solve_bvp_multiple_shooting_iter(&bvp, nodes, guesses, &(), &settings())
+    };
+    assert!(solve(&[0.0, 0.5], &[[0.0, 1.0], [0.5, 1.0]]).is_ok());
+    assert!(solve(&[0.0, 0.5], &[[0.0, 1.0]]).is_err());
+    assert!(solve(&[0.1, 0.5], &[[0.0, 1.0], [0.5, 1.0]]).is_err());
+    assert!(solve(&[0.0, 0.0], &[[0.0, 1.0], [0.5, 1.0]]).is_err());
+    assert!(solve(&[0.0, 1.0], &[[0.0, 1.0], [0.5, 1.0]]).is_err());
+    assert!(solve(&[], &[]).is_err());
+
+    // Every solution with y(0) = 0 vanishes at pi, so y(pi) = 1 can not be satisfied
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: core::f64::consts::PI,
+        func: &rhs_oscillator,
+        residual: &residual,
+    };
+    let mut few_iterations = settings();
+    few_iterations.max_iterations = 5;
+    assert!(solve_bvp_single_shooting_iter(&bvp, &[0.0, 0.0], &(), &few_iterations).is_err());
+}
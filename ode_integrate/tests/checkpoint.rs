@@ -0,0 +1,117 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Van der Pol oscillator \\(y_0' = y_1, y_1' = \mu (1 - y_0^2) y_1 - y_0\\)
+fn rhs_van_der_pol(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, mu: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = mu * (1.0 - y[0] * y[0]) * y[1] - y[0];
+    Ok(())
+}
+
+fn tolerances() -> Tolerances<f64> {
+    Tolerances {
+        rtol: 1e-8,
+        atol: 1e-8,
+    }
+}
+
+fn t_series() -> Vec<f64> {
+    (0..=12).map(|i| 0.75 * i as f64).collect()
+}
+
+#[test]
+fn resume_at_every_output_time() {
+    let y0 = [2.0, 0.0];
+    let mu = 1.5;
+    let reference = solve_ode_time_series_adaptive_step_iter(
+        &y0,
+        &t_series(),
+        &rhs_van_der_pol,
+        &mu,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+    )
+    .unwrap();
+
+    // Interrupt the integration after every output time and continue from the checkpoint
+    let ode_def = OdeDefinition {
+        y0,
+        t0: 0.0,
+        func: &rhs_van_der_pol,
+    };
+    let mut checkpoint = AdaptiveIntegration::new(
+        ode_def,
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances(),
+    )
+    .checkpoint();
+    for (t, y_ref) in t_series().iter().zip(reference.iter()).skip(1) {
+        let mut integration = AdaptiveIntegration::resume(&rhs_van_der_pol, checkpoint).unwrap();
+        let y = *integration.integrate_to_iter(t, &mu).unwrap();
+        assert_eq!(y, *y_ref);
+        assert_eq!(integration.t(), t);
+        checkpoint = integration.checkpoint();
+    }
+    assert!(checkpoint.stepper_state.is_some());
+}
+
+#[test]
+fn checkpoint_contents() {
+    let ode_def = OdeDefinition {
+        y0: [2.0, 0.0],
+        t0: 1.0,
+        func: &rhs_van_der_pol,
+    };
+    let mut integration = AdaptiveIntegration::new(
+        ode_def,
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances(),
+    );
+    integration.integrate_to_iter(&2.0, &1.0).unwrap();
+    let checkpoint = integration.checkpoint();
+    assert_eq!(checkpoint.t, 2.0);
+    assert_eq!(checkpoint.y, *integration.y());
+    assert_eq!(checkpoint.dt, *integration.dt());
+    assert_abs_diff_eq!(checkpoint.tolerances.rtol, 1e-8);
+    match checkpoint.stepper_state {
+        Some(AdaptiveStepperState::BulirschStoer(state)) => {
+            assert!(state.k_opt >= 2 && state.k_opt < state.k_max);
+            assert_eq!(state.dt_next, Some(checkpoint.dt));
+        }
+        None => panic!("Bulirsch-Stoer stepper has a state"),
+    }
+
+    // Integration continues backward to the initial values
+    let y = *integration.integrate_to_iter(&1.0, &1.0).unwrap();
+    assert_abs_diff_eq!(y[0], 2.0, epsilon = 1e-6);
+    assert_abs_diff_eq!(y[1], 0.0, epsilon = 1e-6);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn resume_from_serialized_checkpoint() {
+    let ode_def = OdeDefinition {
+        y0: [2.0, 0.0],
+        t0: 0.0,
+        func: &rhs_van_der_pol,
+    };
+    let mut integration = AdaptiveIntegration::new(
+        ode_def,
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances(),
+    );
+    integration.integrate_to_iter(&3.3, &2.0).unwrap();
+    let json = serde_json::to_string(&integration.checkpoint()).unwrap();
+    let y = *integration.integrate_to_iter(&7.1, &2.0).unwrap();
+
+    let checkpoint: IntegrationCheckpoint<[f64; 2], f64> = serde_json::from_str(&json).unwrap();
+    let mut resumed = AdaptiveIntegration::resume(&rhs_van_der_pol, checkpoint).unwrap();
+    assert_eq!(*resumed.integrate_to_iter(&7.1, &2.0).unwrap(), y);
+}
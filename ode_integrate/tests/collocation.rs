@@ -0,0 +1,159 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Nonlinear ODE \\(y'' = \tfrac{3}{2} y^2\\) which has the solution \\(4 / (1 + t)^2\\)
+fn rhs_quadratic(
+    y: &[f64; 2],
+    dy: &mut [f64; 2],
+    _t: &f64,
+    _p: &[f64; 0],
+) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = 1.5 * y[0] * y[0];
+    Ok(())
+}
+
+/// Eigenvalue problem \\(y'' = -\lambda y\\)
+fn rhs_eigen(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -p[0] * y[0];
+    Ok(())
+}
+
+fn uniform_mesh(a: f64, b: f64, n: usize) -> Vec<f64> {
+    (0..=n).map(|i| a + (b - a) * i as f64 / n as f64).collect()
+}
+
+#[test]
+fn boundary_layer() {
+    // eps y'' = y with y(0) = 1 and y(1) = 0 has a layer of width sqrt(eps) at the left end
+    let eps = 1e-4;
+    let rhs = |y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &[f64; 0]| -> Result<(), CalcError> {
+        dy[0] = y[1];
+        dy[1] = y[0] / eps;
+        Ok(())
+    };
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &[f64; 0], r: &mut [f64]| {
+        r[0] = ya[0] - 1.0;
+        r[1] = yb[0];
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: 1.0,
+        func: &rhs,
+        residual: &residual,
+    };
+    let mesh = uniform_mesh(0.0, 1.0, 10);
+    let guesses = vec![[0.0, 0.0]; mesh.len()];
+    let settings = CollocationSettings::new(1e-6);
+    let solution = solve_bvp_collocation_iter(&bvp, &mesh, &guesses, &[], &settings).unwrap();
+    assert!(solution.residuals.iter().all(|r| *r <= 1e-6));
+    assert!(solution.times.len() <= settings.max_nodes);
+    // Nodes are concentrated within the layer
+    let in_layer = solution.times.iter().filter(|t| **t < 0.1).count();
+    assert!(2 * in_layer > solution.times.len());
+
+    let sqrt_eps = f64::sqrt(eps);
+    let exact = |t: f64| ((1.0 - t) / sqrt_eps).sinh() / (1.0 / sqrt_eps).sinh();
+    let exact_slope = |t: f64| -((1.0 - t) / sqrt_eps).cosh() / (1.0 / sqrt_eps).sinh() / sqrt_eps;
+    for i in 0..=200 {
+        let t = 0.005 * i as f64;
+        assert_abs_diff_eq!(solution.value(&t)[0], exact(t), epsilon = 1e-5);
+        assert_abs_diff_eq!(
+            solution.derivative(&t)[0],
+            exact_slope(t),
+            epsilon = 1e-5 / sqrt_eps
+        );
+    }
+}
+
+#[test]
+fn nonlinear_problem() {
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &[f64; 0], r: &mut [f64]| {
+        r[0] = ya[0] - 4.0;
+        r[1] = yb[0] - 1.0;
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: 1.0,
+        func: &rhs_quadratic,
+        residual: &residual,
+    };
+    // Guess the straight line between the boundary values
+    let mesh = uniform_mesh(0.0, 1.0, 5);
+    let guesses: Vec<[f64; 2]> = mesh.iter().map(|t| [4.0 - 3.0 * t, -3.0]).collect();
+    let settings = CollocationSettings::new(1e-8);
+    let solution = solve_bvp_collocation_iter(&bvp, &mesh, &guesses, &[], &settings).unwrap();
+    assert!(solution.iterations > 1);
+    for i in 0..=20 {
+        let t = 0.05 * i as f64;
+        let y = solution.value(&t);
+        assert_abs_diff_eq!(y[0], 4.0 / (1.0 + t).powi(2), epsilon = 1e-6);
+        assert_abs_diff_eq!(y[1], -8.0 / (1.0 + t).powi(3), epsilon = 1e-5);
+    }
+    for (y, dy) in solution.y.iter().zip(solution.dy.iter()) {
+        assert_abs_diff_eq!(dy[0], y[1], epsilon = 1e-14);
+    }
+}
+
+#[test]
+fn unknown_parameter() {
+    // y(0) = 0, y'(0) = 1 and y(pi) = 0 determine the smallest eigenvalue 1 with solution sin(t)
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &[f64; 1], r: &mut [f64]| {
+        r[0] = ya[0];
+        r[1] = ya[1] - 1.0;
+        r[2] = yb[0];
+    };
+    let pi = core::f64::consts::PI;
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: pi,
+        func: &rhs_eigen,
+        residual: &residual,
+    };
+    let mesh = uniform_mesh(0.0, pi, 10);
+    let guesses: Vec<[f64; 2]> = mesh
+        .iter()
+        .map(|t| [t * (pi - t) / pi, 1.0 - 2.0 * t / pi])
+        .collect();
+    let settings = CollocationSettings::new(1e-8);
+    let solution = solve_bvp_collocation_iter(&bvp, &mesh, &guesses, &[1.5], &settings).unwrap();
+    assert_abs_diff_eq!(solution.p[0], 1.0, epsilon = 1e-7);
+    for i in 0..=20 {
+        let t = pi * i as f64 / 20.0;
+        assert_abs_diff_eq!(solution.value(&t)[0], t.sin(), epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn invalid_problems() {
+    let residual = |ya: &[f64; 2], yb: &[f64; 2], _p: &[f64; 0], r: &mut [f64]| {
+        r[0] = ya[0] - 4.0;
+        r[1] = yb[0] - 1.0;
+    };
+    let bvp = BoundaryValueProblem {
+        a: 0.0,
+        b: 1.0,
+        func: &rhs_quadratic,
+        residual: &residual,
+    };
+    let settings = CollocationSettings::new(1e-6);
+    let solve = |mesh: &[f64], settings: &CollocationSettings<f64>| {
+        let guesses = vec![[1.0, -1.0]; mesh.len()];
+        solve_bvp_collocation_iter(&bvp, mesh, &guesses, &[], settings)
+    };
+    assert!(solve(&[0.0, 0.5, 1.0], &settings).is_ok());
+    assert!(solve(&[0.0], &settings).is_err());
+    assert!(solve(&[0.1, 0.5, 1.0], &settings).is_err());
+    assert!(solve(&[0.0, 0.5, 0.9], &settings).is_err());
+    assert!(solve(&[0.0, 0.5, 0.5, 1.0], &settings).is_err());
+    assert!(solve_bvp_collocation_iter(&bvp, &[0.0, 1.0], &[[1.0, -1.0]], &[], &settings).is_err());
+
+    // The tolerance can not be reached with few nodes
+    let mut few_nodes = CollocationSettings::new(1e-10);
+    few_nodes.max_nodes = 10;
+    assert!(solve(&[0.0, 0.5, 1.0], &few_nodes).is_err());
+}
@@ -0,0 +1,186 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Stiff Prothero-Robinson problem \\(y' = -\lambda(y - \cos t) - \sin t\\) with solution \\(\cos t\\)
+fn rhs_prothero_robinson(
+    y: &Vec<f64>,
+    dy: &mut Vec<f64>,
+    t: &f64,
+    lambda: &f64,
+) -> Result<(), CalcError> {
+    dy[0] = -lambda * (y[0] - t.cos()) - t.sin();
+    Ok(())
+}
+
+#[test]
+fn stiff_ode() {
+    let ode_def = OdeDefinition {
+        y0: vec![1.0],
+        t0: 0.0,
+        func: &rhs_prothero_robinson,
+    };
+    let t_series: Vec<f64> = (0..11).map(|i| i as f64 * 0.2).collect();
+    for solver in [
+        || ImplicitSolvers::ImplicitEuler,
+        || ImplicitSolvers::Sdirk2,
+    ] {
+        // The step size is far beyond the stability limit of explicit methods
+        let y_res =
+            solve_dae_time_series_iter(ode_def.clone().into(), &t_series, &1e6, solver(), &0.1)
+                .unwrap();
+        for (t, y) in t_series.iter().zip(y_res.iter()) {
+            assert_abs_diff_eq!(y[0], t.cos(), epsilon = 1e-6);
+        }
+    }
+}
+
+/// Semi-explicit DAE \\(y_0' = -y_0 + y_1\\), \\(0 = y_1 - \sin t\\)
+fn rhs_semi_explicit(y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = -y[0] + y[1];
+    dy[1] = y[1] - t.sin();
+    Ok(())
+}
+
+/// Maximal error of the semi-explicit DAE on the interval \\([0, 2]\\)
+fn semi_explicit_error(solver: ImplicitSolvers, dt: f64) -> f64 {
+    let dae_def = DaeDefinition {
+        // The algebraic component is inconsistent
+        y0: [1.0, 5.0],
+        t0: 0.0,
+        func: &rhs_semi_explicit,
+        mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+    };
+    let t_series: Vec<f64> = (0..11).map(|i| i as f64 * 0.2).collect();
+    let y_res = solve_dae_time_series_iter(dae_def, &t_series, &(), solver, &dt).unwrap();
+    t_series
+        .iter()
+        .zip(y_res.iter())
+        .map(|(t, y)| {
+            let exact = 1.5 * (-t).exp() + 0.5 * (t.sin() - t.cos());
+            (y[0] - exact).abs().max((y[1] - t.sin()).abs())
+        })
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn semi_explicit_convergence() {
+    let order = (semi_explicit_error(ImplicitSolvers::ImplicitEuler, 0.02)
+        / semi_explicit_error(ImplicitSolvers::ImplicitEuler, 0.01))
+    .log2();
+    assert!(order > 0.9 && order < 1.1, "order {order}");
+
+    let order = (semi_explicit_error(ImplicitSolvers::Sdirk2, 0.02)
+        / semi_explicit_error(ImplicitSolvers::Sdirk2, 0.01))
+    .log2();
+    assert!(order > 1.9 && order < 2.1, "order {order}");
+}
+
+#[test]
+fn consistent_initialization() {
+    let dae_def = DaeDefinition {
+        y0: [1.0, 5.0],
+        t0: 1.0,
+        func: &rhs_semi_explicit,
+        mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0, 0.0]),
+    };
+    let y0 = consistent_initial_values(&dae_def, &()).unwrap();
+    assert_eq!(y0[0], 1.0);
+    assert_abs_diff_eq!(y0[1], 1f64.sin(), epsilon = 1e-14);
+
+    // Differential components are not changed for ODEs
+    let ode_def = DaeDefinition {
+        mass: MassMatrix::Identity,
+        ..dae_def.clone()
+    };
+    assert_eq!(
+        consistent_initial_values(&ode_def, &()).unwrap(),
+        [1.0, 5.0]
+    );
+
+    // Vanishing rows and columns need to match
+    let not_semi_explicit = DaeDefinition {
+        mass: MassMatrix::Constant(vec![1.0, 1.0, 0.0, 0.0]),
+        ..dae_def.clone()
+    };
+    assert!(consistent_initial_values(&not_semi_explicit, &()).is_err());
+
+    // The mass matrix needs to fit to the state
+    let wrong_size = DaeDefinition {
+        mass: MassMatrix::Constant(vec![1.0, 0.0, 0.0]),
+        ..dae_def
+    };
+    assert!(consistent_initial_values(&wrong_size, &()).is_err());
+    assert!(get_implicit_stepper(ImplicitSolvers::Sdirk2, wrong_size).is_err());
+}
+
+/// DAE \\((1 + t)y_0' = -y_0\\), \\(0 = y_1 - y_0^2\\) with solution \\(y_0 = 1/(1 + t)\\)
+fn rhs_time_dependent(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = -y[0];
+    dy[1] = y[1] - y[0] * y[0];
+    Ok(())
+}
+
+fn mass_time_dependent(t: &f64, _p: &(), m: &mut [f64]) {
+    m[0] = 1.0 + t;
+}
+
+#[test]
+fn time_dependent_mass_matrix() {
+    let error = |dt: f64| {
+        let dae_def = DaeDefinition {
+            y0: [1.0, 0.0],
+            t0: 0.0,
+            func: &rhs_time_dependent,
+            mass: MassMatrix::TimeDependent(&mass_time_dependent),
+        };
+        let y_res =
+            solve_dae_time_series_iter(dae_def, &vec![0.0, 1.0], &(), ImplicitSolvers::Sdirk2, &dt)
+                .unwrap();
+        assert_abs_diff_eq!(y_res[0][1], 1.0, epsilon = 1e-14);
+        (y_res[1][0] - 0.5).abs().max((y_res[1][1] - 0.25).abs())
+    };
+    let order = (error(0.02) / error(0.01)).log2();
+    assert!(order > 1.9 && order < 2.1, "order {order}");
+}
+
+/// ODE \\(2y_0 y_0' = -1\\), \\(y_1' = 1\\) with solution \\(y_0 = \sqrt{4 - t}\\)
+fn rhs_state_dependent(
+    _y: &Vec<f64>,
+    dy: &mut Vec<f64>,
+    _t: &f64,
+    _p: &(),
+) -> Result<(), CalcError> {
+    dy[0] = -1.0;
+    dy[1] = 1.0;
+    Ok(())
+}
+
+fn mass_state_dependent(y: &Vec<f64>, _t: &f64, _p: &(), m: &mut [f64]) {
+    m[0] = 2.0 * y[0];
+    m[3] = 1.0;
+}
+
+#[test]
+fn state_dependent_mass_matrix() {
+    let error = |solver: ImplicitSolvers, dt: f64| {
+        let dae_def = DaeDefinition {
+            y0: vec![2.0, 0.0],
+            t0: 0.0,
+            func: &rhs_state_dependent,
+            mass: MassMatrix::StateDependent(&mass_state_dependent),
+        };
+        let y_res = solve_dae_time_series_iter(dae_def, &vec![3.0], &(), solver, &dt).unwrap();
+        assert_abs_diff_eq!(y_res[0][1], 3.0, epsilon = 1e-12);
+        (y_res[0][0] - 1.0).abs()
+    };
+    let order = (error(ImplicitSolvers::ImplicitEuler, 0.02)
+        / error(ImplicitSolvers::ImplicitEuler, 0.01))
+    .log2();
+    assert!(order > 0.9 && order < 1.1, "order {order}");
+    let order =
+        (error(ImplicitSolvers::Sdirk2, 0.02) / error(ImplicitSolvers::Sdirk2, 0.01)).log2();
+    assert!(order > 1.9 && order < 2.1, "order {order}");
+}
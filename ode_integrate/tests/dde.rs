@@ -0,0 +1,168 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Linear DDE \\(y'(t) = -y(t - \tau)\\) with a single delay given as parameter
+fn rhs_delayed_decay(
+    _y: &[f64; 1],
+    history: &dyn History<[f64; 1], f64>,
+    dy: &mut [f64; 1],
+    t: &f64,
+    tau: &f64,
+) -> Result<(), CalcError> {
+    dy[0] = -history.at(&(t - tau))[0];
+    Ok(())
+}
+
+/// Exact solution for \\(\phi(t)=1\\) and \\(\tau = 1\\) obtained by the method of steps
+fn exact_constant_history(t: f64) -> f64 {
+    if t <= 1.0 {
+        1.0 - t
+    } else if t <= 2.0 {
+        1.0 - t + (t - 1.0).powi(2) / 2.0
+    } else {
+        1.0 - t + (t - 1.0).powi(2) / 2.0 - (t - 2.0).powi(3) / 6.0
+    }
+}
+
+fn max_error(dt: f64) -> f64 {
+    let dde_def = DdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        history: &|_t: &f64| [1.0],
+        delays: vec![1.0],
+        func: &rhs_delayed_decay,
+    };
+    let t_series: Vec<f64> = (0..31).map(|i| i as f64 * 0.1).collect();
+    let y_res = solve_dde_time_series_iter(dde_def, &t_series, &1.0, &dt).unwrap();
+    t_series
+        .iter()
+        .zip(y_res.iter())
+        .map(|(t, y)| (y[0] - exact_constant_history(*t)).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn constant_history() {
+    // The step size does not divide the delay such that discontinuities need to be hit explicitly
+    assert!(max_error(0.03) < 1e-8);
+}
+
+/// Solution for the smooth history \\(\phi(t)=\cos(t)\\) at the time points 0.0, 0.1, ..., 3.0
+fn solve_cos_history(dt: f64) -> Vec<[f64; 1]> {
+    let dde_def = DdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        history: &|t: &f64| [t.cos()],
+        delays: vec![1.0],
+        func: &rhs_delayed_decay,
+    };
+    let t_series: Vec<f64> = (0..31).map(|i| i as f64 * 0.1).collect();
+    solve_dde_time_series_iter(dde_def, &t_series, &1.0, &dt).unwrap()
+}
+
+#[test]
+fn convergence_order() {
+    let reference = solve_cos_history(0.001);
+    let error = |dt: f64| {
+        solve_cos_history(dt)
+            .iter()
+            .zip(reference.iter())
+            .map(|(y, r)| (y[0] - r[0]).abs())
+            .fold(0.0, f64::max)
+    };
+    let order = (error(0.08) / error(0.04)).log2();
+    assert!(order > 3.5, "order {order}");
+}
+
+#[test]
+fn jump_at_initial_time() {
+    // The history vanishes but the initial value is one
+    let dde_def = DdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        history: &|_t: &f64| [0.0],
+        delays: vec![1.0],
+        func: &rhs_delayed_decay,
+    };
+    let t_series = vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0];
+    let y_res = solve_dde_time_series_iter(dde_def, &t_series, &1.0, &0.1).unwrap();
+
+    let exact = |t: f64| {
+        if t <= 1.0 {
+            1.0
+        } else if t <= 2.0 {
+            2.0 - t
+        } else {
+            -(t - 2.0) + (t - 2.0).powi(2) / 2.0
+        }
+    };
+    for (t, y) in t_series.iter().zip(y_res.iter()) {
+        assert_abs_diff_eq!(y[0], exact(*t), epsilon = 1e-12);
+    }
+}
+
+/// Two delays acting on a two-component system
+fn rhs_two_delays(
+    y: &Vec<f64>,
+    history: &dyn History<Vec<f64>, f64>,
+    dy: &mut Vec<f64>,
+    t: &f64,
+    p: &[f64; 2],
+) -> Result<(), CalcError> {
+    let y1 = history.at(&(t - p[0]));
+    let y2 = history.at(&(t - p[1]));
+    dy[0] = -y1[1];
+    dy[1] = y2[0] - 0.5 * y[1];
+    Ok(())
+}
+
+#[test]
+fn discontinuities_are_propagated() {
+    let points = propagated_discontinuities(&0.0, &[0.3, 0.5], &1.0);
+    let expected = [0.3, 0.5, 0.6, 0.8, 0.9, 1.0];
+    assert_eq!(points.len(), expected.len());
+    for (a, b) in points.iter().zip(expected.iter()) {
+        assert_abs_diff_eq!(a, b, epsilon = 1e-14);
+    }
+
+    // Every discontinuity is a step of the solver
+    let dde_def = DdeDefinition {
+        y0: vec![1.0, 0.0],
+        t0: 0.0,
+        history: &|t: &f64| vec![1.0 + t, 0.0],
+        delays: vec![0.3, 0.5],
+        func: &rhs_two_delays,
+    };
+    let mut solver = MethodOfSteps::new(dde_def, &0.07, &1.0).unwrap();
+    while !solver.is_finished() {
+        solver.do_step_iter(&[0.3, 0.5]).unwrap();
+    }
+    let times = solver.history().times();
+    for d in points.iter() {
+        assert!(times.iter().any(|t| (t - d).abs() < 1e-14));
+    }
+    assert!(times
+        .windows(2)
+        .all(|w| w[1] - w[0] <= 0.07 + 1e-14 && w[1] > w[0]));
+}
+
+#[test]
+fn invalid_input() {
+    let dde_def = DdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        history: &|_t: &f64| [1.0],
+        delays: vec![0.0],
+        func: &rhs_delayed_decay,
+    };
+    assert!(solve_dde_time_series_iter(dde_def.clone(), &vec![0.0, 1.0], &0.0, &0.1).is_err());
+
+    let dde_def = DdeDefinition {
+        delays: vec![1.0],
+        ..dde_def
+    };
+    assert!(solve_dde_time_series_iter(dde_def, &vec![1.0, 0.5], &1.0, &0.1).is_err());
+}
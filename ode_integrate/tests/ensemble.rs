@@ -0,0 +1,102 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Exponential decay \\(y' = -p y\\) which is only defined for non-negative rates
+fn rhs_decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+    if *p < 0.0 {
+        return Err(CalcError::from("Negative decay rate"));
+    }
+    dy[0] = -p * y[0];
+    Ok(())
+}
+
+/// Initial values and decay rates of the ensemble where the third member is invalid
+fn problems() -> Vec<([f64; 1], f64)> {
+    vec![
+        ([1.0], 0.5),
+        ([2.0], 1.0),
+        ([3.0], -1.0),
+        ([4.0], 0.0),
+        ([5.0], 2.0),
+    ]
+}
+
+#[test]
+fn failures_do_not_abort_the_ensemble() {
+    let t_series: Vec<f64> = (0..=20).map(|i| 0.05 * i as f64).collect();
+    let ensemble =
+        solve_ensemble_single_step_iter(problems(), &t_series, &rhs_decay, FixedStepSolvers::Rk4);
+    assert_eq!(ensemble.results.len(), 5);
+    assert_eq!(ensemble.n_failed(), 1);
+    assert_eq!(ensemble.failures().next().unwrap().0, 2);
+
+    let problems = problems();
+    let mut n_successes = 0;
+    for (i, solution) in ensemble.successes() {
+        let (y0, p) = problems[i];
+        let single = solve_ode_time_series_single_step_iter(
+            &y0,
+            &t_series,
+            &rhs_decay,
+            &p,
+            FixedStepSolvers::Rk4,
+        )
+        .unwrap();
+        assert_eq!(solution, &single);
+        assert_abs_diff_eq!(solution[20][0], y0[0] * (-p).exp(), epsilon = 0.1);
+        n_successes += 1;
+    }
+    assert_eq!(n_successes, 4);
+}
+
+#[test]
+fn generic_ensemble_with_other_drivers() {
+    let t_series = vec![0.0, 1.0, 2.0];
+    let ensemble = solve_ensemble(problems(), |y0, p| {
+        solve_ode_time_series_single_step_iter(
+            y0,
+            &t_series,
+            &rhs_decay,
+            p,
+            FixedStepSolvers::Euler,
+        )
+    });
+    let reference =
+        solve_ensemble_single_step_iter(problems(), &t_series, &rhs_decay, FixedStepSolvers::Euler);
+    assert_eq!(ensemble.n_failed(), 1);
+    for (res, ref_res) in ensemble.results.iter().zip(reference.results.iter()) {
+        match (res, ref_res) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(_), Err(_)) => (),
+            _ => panic!("Sequential ensembles disagree"),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_ensemble_agrees_with_sequential() {
+    let t_series = vec![0.0, 0.25, 0.5, 1.0];
+    let problems: Vec<([f64; 1], f64)> = (0..64)
+        .map(|i| ([1.0 + i as f64], 0.1 * (i as f64 - 3.0)))
+        .collect();
+    let sequential = solve_ensemble_single_step_iter(
+        problems.clone(),
+        &t_series,
+        &rhs_decay,
+        FixedStepSolvers::Rk4,
+    );
+    let parallel =
+        solve_ensemble_single_step_iter_par(problems, &t_series, &rhs_decay, FixedStepSolvers::Rk4);
+    assert_eq!(parallel.n_failed(), 3);
+    for (a, b) in parallel.results.iter().zip(sequential.results.iter()) {
+        match (a, b) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(_), Err(_)) => (),
+            _ => panic!("Parallel and sequential ensembles disagree"),
+        }
+    }
+}
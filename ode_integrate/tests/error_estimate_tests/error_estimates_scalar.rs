@@ -24,7 +24,7 @@ fn rhs_exp_decay(y: &f64, dy: &mut f64, _t: &f64, p: &f64) -> Result<(), CalcErr
 fn exponential_decay_f64() {
     // Define initial parameters for exponential decay
     let x0 = 93.3477397479;
-    let mut x = x0.clone();
+    let mut x = x0;
 
     // Dummy variables to store previous results and variables for error estimate
     let mut x_prev = 0.0;
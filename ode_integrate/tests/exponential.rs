@@ -0,0 +1,251 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Linear part of the manufactured problem
+const L: [f64; 4] = [-10.0, 1.0, 1.0, -20.0];
+
+/// Exact solution of the manufactured problem
+fn exact(t: f64) -> [f64; 2] {
+    [t.cos(), (2.0 * t).sin()]
+}
+
+/// Nonlinear part \\(N(y, t) = y_e' - L y_e + y^2 - y_e^2\\) with solution \\(y_e\\)
+fn manufactured(
+    y: &[f64; 2],
+    dy: &mut [f64; 2],
+    t: &f64,
+    linear: &[f64; 4],
+) -> Result<(), CalcError> {
+    let ye = exact(*t);
+    let dye = [-t.sin(), 2.0 * (2.0 * t).cos()];
+    for i in 0..2 {
+        let lye = linear[2 * i] * ye[0] + linear[2 * i + 1] * ye[1];
+        dy[i] = dye[i] - lye + y[i] * y[i] - ye[i] * ye[i];
+    }
+    Ok(())
+}
+
+/// Error at \\(t = 1\\) of the manufactured problem with step size `dt`
+fn manufactured_error(
+    linear: LinearOperator<f64>,
+    matrix: [f64; 4],
+    solver: ExponentialSolvers,
+    dt: f64,
+) -> f64 {
+    let nonlinear =
+        |y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()| manufactured(y, dy, t, &matrix);
+    let semi_def = SemilinearDefinition {
+        y0: exact(0.0),
+        t0: 0.0,
+        linear,
+        nonlinear: &nonlinear,
+    };
+    let y = solve_semilinear_time_series_iter(semi_def, &vec![0.0, 1.0], &(), solver, &dt).unwrap();
+    let ye = exact(1.0);
+    (y[1][0] - ye[0]).abs().max((y[1][1] - ye[1]).abs())
+}
+
+#[test]
+fn constant_nonlinear_part() {
+    // The eigenvalue zero and tiny eigenvalues would spoil the explicit φ-functions
+    let linear = vec![-1e4, -1.0, -1e-9, 0.0];
+    let c = [1.0, 2.0, 3.0, 4.0];
+    let nonlinear =
+        |_y: &[f64; 4], dy: &mut [f64; 4], _t: &f64, _p: &()| -> Result<(), CalcError> {
+            *dy = c;
+            Ok(())
+        };
+    let t_series = vec![0.0, 0.3, 1.0];
+    for solver in [ExponentialSolvers::Etd1, ExponentialSolvers::Etdrk4] {
+        for operator in [
+            LinearOperator::Diagonal(linear.clone()),
+            LinearOperator::Dense(
+                (0..16)
+                    .map(|k| if k % 5 == 0 { linear[k / 5] } else { 0.0 })
+                    .collect(),
+            ),
+        ] {
+            let semi_def = SemilinearDefinition {
+                y0: [1.0; 4],
+                t0: 0.0,
+                linear: operator,
+                nonlinear: &nonlinear,
+            };
+            let y =
+                solve_semilinear_time_series_iter(semi_def, &t_series, &(), solver.clone(), &0.25)
+                    .unwrap();
+            for (t, y) in t_series.iter().zip(y.iter()) {
+                for i in 0..4 {
+                    let l: f64 = linear[i];
+                    let exact = if l == 0.0 {
+                        1.0 + c[i] * t
+                    } else {
+                        (l * t).exp() + c[i] * (l * t).exp_m1() / l
+                    };
+                    assert_abs_diff_eq!(y[i], exact, epsilon = 1e-10);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn convergence_order() {
+    let diagonal = [-10.0, 0.0, 0.0, -20.0];
+    let orders = [
+        (ExponentialSolvers::Etd1, 1.0),
+        (ExponentialSolvers::Etdrk4, 4.0),
+        (ExponentialSolvers::LawsonRk4, 4.0),
+    ];
+    for (solver, order) in orders {
+        for (operator, matrix) in [
+            (LinearOperator::Diagonal(vec![-10.0, -20.0]), diagonal),
+            (LinearOperator::Dense(L.to_vec()), L),
+        ] {
+            let coarse = manufactured_error(operator.clone(), matrix, solver.clone(), 0.1);
+            let fine = manufactured_error(operator, matrix, solver.clone(), 0.1 / 16.0);
+            let observed = (coarse / fine).log2() / 4.0;
+            assert!(
+                (observed - order).abs() < 0.3,
+                "{solver:?} converges with order {observed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn dense_and_diagonal_operators_agree() {
+    let diagonal = [-10.0, 0.0, 0.0, -20.0];
+    for solver in [
+        ExponentialSolvers::Etd1,
+        ExponentialSolvers::Etdrk4,
+        ExponentialSolvers::LawsonRk4,
+    ] {
+        let from_diagonal = manufactured_error(
+            LinearOperator::Diagonal(vec![-10.0, -20.0]),
+            diagonal,
+            solver.clone(),
+            0.1,
+        );
+        let from_dense = manufactured_error(
+            LinearOperator::Dense(diagonal.to_vec()),
+            diagonal,
+            solver,
+            0.1,
+        );
+        assert_abs_diff_eq!(from_diagonal, from_dense, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn stiff_linear_part() {
+    // Explicit methods would need steps below 2e-4 to remain stable.
+    // The fast component starts close to its slow manifold to avoid an initial layer.
+    let nonlinear = |y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = y[1] * y[1] + t.cos();
+        dy[1] = -y[0] * y[1];
+        Ok(())
+    };
+    let solve = |solver: ExponentialSolvers, dt: f64| {
+        let semi_def = SemilinearDefinition {
+            y0: [2e-4, 1.0],
+            t0: 0.0,
+            linear: LinearOperator::Diagonal(vec![-1e4, -1.0]),
+            nonlinear: &nonlinear,
+        };
+        solve_semilinear_time_series_iter(semi_def, &vec![0.0, 2.0], &(), solver, &dt).unwrap()[1]
+    };
+    let reference = solve(ExponentialSolvers::Etdrk4, 1e-3);
+    let y = solve(ExponentialSolvers::Etdrk4, 0.1);
+    assert_abs_diff_eq!(y[0], reference[0], epsilon = 1e-6);
+    assert_abs_diff_eq!(y[1], reference[1], epsilon = 1e-6);
+
+    // The lower order methods remain stable but are less accurate
+    for solver in [ExponentialSolvers::Etd1, ExponentialSolvers::LawsonRk4] {
+        let y = solve(solver, 0.1);
+        assert_abs_diff_eq!(y[0], reference[0], epsilon = 1e-2);
+        assert_abs_diff_eq!(y[1], reference[1], epsilon = 1e-2);
+    }
+}
+
+#[test]
+fn invalid_problems() {
+    let nonlinear =
+        |_y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()| -> Result<(), CalcError> {
+            *dy = [0.0, 0.0];
+            Ok(())
+        };
+    let solve = |linear: LinearOperator<f64>, t_series: Vec<f64>| {
+        let semi_def = SemilinearDefinition {
+            y0: [1.0, 1.0],
+            t0: 0.0,
+            linear,
+            nonlinear: &nonlinear,
+        };
+        solve_semilinear_time_series_iter(
+            semi_def,
+            &t_series,
+            &(),
+            ExponentialSolvers::Etdrk4,
+            &0.1,
+        )
+    };
+
+    // Operators which do not fit to the state
+    assert!(solve(LinearOperator::Diagonal(vec![-1.0]), vec![0.0, 1.0]).is_err());
+    assert!(solve(LinearOperator::Dense(vec![-1.0, 0.0, 0.0]), vec![0.0, 1.0]).is_err());
+    assert!(solve(
+        LinearOperator::Diagonal(vec![-1.0, f64::NAN]),
+        vec![0.0, 1.0]
+    )
+    .is_err());
+
+    // Time points which are neither increasing nor decreasing
+    assert!(solve(
+        LinearOperator::Diagonal(vec![-1.0, -2.0]),
+        vec![0.0, 1.0, 0.5]
+    )
+    .is_err());
+}
+
+#[test]
+fn backward_integration() {
+    // Exponential integrators are exact for constant nonlinear parts in both directions
+    let linear = [-3.0, 0.5];
+    let c = [1.0, 2.0];
+    let nonlinear =
+        |_y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()| -> Result<(), CalcError> {
+            *dy = c;
+            Ok(())
+        };
+    let t_series = vec![1.0, 0.4, 0.0];
+    for solver in [
+        ExponentialSolvers::Etd1,
+        ExponentialSolvers::Etdrk4,
+        ExponentialSolvers::LawsonRk4,
+    ] {
+        let semi_def = SemilinearDefinition {
+            y0: [1.0; 2],
+            t0: 1.0,
+            linear: LinearOperator::Diagonal(linear.to_vec()),
+            nonlinear: &nonlinear,
+        };
+        let y = solve_semilinear_time_series_iter(semi_def, &t_series, &(), solver.clone(), &0.1)
+            .unwrap();
+        // Lawson methods are only exact up to their order, so compare relative errors
+        let epsilon = match solver {
+            ExponentialSolvers::LawsonRk4 => 1e-5,
+            _ => 1e-12,
+        };
+        for (t, y) in t_series.iter().zip(y.iter()) {
+            for i in 0..2 {
+                let l: f64 = linear[i];
+                let exact = (l * (t - 1.0)).exp() + c[i] * (l * (t - 1.0)).exp_m1() / l;
+                assert_abs_diff_eq!(y[i], exact, epsilon = epsilon * exact.abs());
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Logistic growth \\(y' = p_0 y (1 - y / p_1)\\) and a decaying second component
+fn rhs_logistic(y: &Vec<f64>, dy: &mut Vec<f64>, _t: &f64, p: &Vec<f64>) -> Result<(), CalcError> {
+    dy[0] = p[0] * y[0] * (1.0 - y[0] / p[1]);
+    dy[1] = -p[0] * y[1];
+    Ok(())
+}
+
+fn logistic_definition<'a>() -> SensitivityDefinition<'a, Vec<f64>, f64, Vec<f64>, CalcError> {
+    OdeDefinition {
+        y0: vec![0.1, 1.0],
+        t0: 0.0,
+        func: &rhs_logistic,
+    }
+    .into()
+}
+
+/// Observations of both components generated with the given parameters and perturbed by `noise`
+fn observations(p: &Vec<f64>, noise: &[f64]) -> Observations<f64> {
+    let times: Vec<f64> = (1..9).map(|i| i as f64 * 0.5).collect();
+    let solution = solve_sensitivity_time_series_iter(
+        logistic_definition(),
+        &times,
+        p,
+        SensitivityMethod::Simultaneous,
+        &0.01,
+    )
+    .unwrap();
+    let values = solution
+        .iter()
+        .zip(noise.iter().cycle())
+        .map(|((y, _), e)| vec![y[0] + e, y[1] - e])
+        .collect();
+    Observations {
+        times,
+        components: vec![0, 1],
+        values,
+    }
+}
+
+#[test]
+fn recover_parameters() {
+    let p_true = vec![1.2, 2.0];
+    let obs = observations(&p_true, &[0.0]);
+    let fit = fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![0.5, 1.0],
+        None,
+        &FitSettings::new(0.01),
+    )
+    .unwrap();
+    assert_abs_diff_eq!(fit.parameters[0], p_true[0], epsilon = 1e-6);
+    assert_abs_diff_eq!(fit.parameters[1], p_true[1], epsilon = 1e-6);
+    assert_eq!(fit.residuals.len(), 16);
+    assert!(fit.cost < 1e-12);
+    assert!(fit.iterations > 0);
+}
+
+#[test]
+fn covariance_of_noisy_data() {
+    let obs = observations(&vec![1.2, 2.0], &[0.01, -0.02, 0.015, 0.0, -0.01]);
+    let fit = fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![1.0, 1.5],
+        None,
+        &FitSettings::new(0.01),
+    )
+    .unwrap();
+    assert_abs_diff_eq!(fit.parameters[0], 1.2, epsilon = 0.05);
+    assert_abs_diff_eq!(fit.parameters[1], 2.0, epsilon = 0.05);
+
+    // The residual variance times the inverse of J^T J is symmetric and positive definite
+    let c = fit.covariance.as_ref().unwrap();
+    assert_eq!(c.len(), 4);
+    assert_abs_diff_eq!(c[1], c[2], epsilon = 1e-12);
+    assert!(c[0] > 0.0 && c[3] > 0.0);
+    assert!(c[0] * c[3] - c[1] * c[2] > 0.0);
+    let variance = 2.0 * fit.cost / (16.0 - 2.0);
+    assert!(c[0] < variance * 1e3);
+
+    // Starting from the optimum does not change the result
+    let refit = fit_parameters(
+        logistic_definition(),
+        &obs,
+        &fit.parameters,
+        None,
+        &FitSettings::new(0.01),
+    )
+    .unwrap();
+    assert_abs_diff_eq!(refit.parameters[0], fit.parameters[0], epsilon = 1e-6);
+    assert_abs_diff_eq!(refit.parameters[1], fit.parameters[1], epsilon = 1e-6);
+}
+
+#[test]
+fn parameters_stay_within_bounds() {
+    let obs = observations(&vec![1.2, 2.0], &[0.0]);
+    let bounds = ParameterBounds {
+        lower: vec![0.0, 0.5],
+        upper: vec![1.0, 5.0],
+    };
+    let fit = fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![0.5, 1.0],
+        Some(&bounds),
+        &FitSettings::new(0.01),
+    )
+    .unwrap();
+    assert!(fit.parameters[0] <= 1.0);
+    assert_abs_diff_eq!(fit.parameters[0], 1.0, epsilon = 1e-6);
+    assert!(fit.parameters[1] >= 0.5 && fit.parameters[1] <= 5.0);
+}
+
+#[test]
+fn invalid_input() {
+    let mut obs = observations(&vec![1.2, 2.0], &[0.0]);
+    let settings = FitSettings::new(0.01);
+    let bounds = ParameterBounds {
+        lower: vec![0.0],
+        upper: vec![1.0],
+    };
+    assert!(fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![0.5, 1.0],
+        Some(&bounds),
+        &settings
+    )
+    .is_err());
+
+    obs.components = vec![0, 2];
+    assert!(fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![0.5, 1.0],
+        None,
+        &settings
+    )
+    .is_err());
+
+    obs.components = vec![0];
+    assert!(fit_parameters(
+        logistic_definition(),
+        &obs,
+        &vec![0.5, 1.0],
+        None,
+        &settings
+    )
+    .is_err());
+}
+
+/// Decay \(y' = -p_0 y\) which does not depend on \(p_1\)
+fn rhs_unused_parameter(
+    y: &Vec<f64>,
+    dy: &mut Vec<f64>,
+    _t: &f64,
+    p: &Vec<f64>,
+) -> Result<(), CalcError> {
+    dy[0] = -p[0] * y[0];
+    Ok(())
+}
+
+#[test]
+fn unidentifiable_parameters() {
+    let times: Vec<f64> = (1..5).map(|i| i as f64 * 0.5).collect();
+    let values = times.iter().map(|t| vec![(-0.7 * t).exp()]).collect();
+    let obs = Observations {
+        times,
+        components: vec![0],
+        values,
+    };
+    let ode_def = OdeDefinition {
+        y0: vec![1.0],
+        t0: 0.0,
+        func: &rhs_unused_parameter,
+    };
+    // The fit converges but the covariance of the unused parameter is undefined
+    let fit = fit_parameters(
+        ode_def.into(),
+        &obs,
+        &vec![0.1, 3.0],
+        None,
+        &FitSettings::new(0.01),
+    )
+    .unwrap();
+    assert_abs_diff_eq!(fit.parameters[0], 0.7, epsilon = 1e-3);
+    assert_eq!(fit.parameters[1], 3.0);
+    assert!(fit.covariance.is_none());
+}
@@ -0,0 +1,235 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Room which is heated towards 30 degrees
+fn rhs_heating(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = 30.0 - y[0];
+    Ok(())
+}
+
+/// Room which cools down towards 10 degrees
+fn rhs_cooling(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = 10.0 - y[0];
+    Ok(())
+}
+
+/// Free fall \\(x' = v, v' = -g\\)
+fn rhs_fall(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, g: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -g;
+    Ok(())
+}
+
+/// Free fall \\(x' = v, v' = -g\\) as a vector which can be added
+fn rhs_fall_vec(
+    y: &nalgebra::Vector2<f64>,
+    dy: &mut nalgebra::Vector2<f64>,
+    _t: &f64,
+    g: &f64,
+) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -g;
+    Ok(())
+}
+
+fn tolerances() -> Tolerances<f64> {
+    Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    }
+}
+
+fn settings() -> HybridSettings<f64> {
+    HybridSettings {
+        event_tolerance: 1e-12,
+        max_transitions: 10,
+        window: 0.1,
+    }
+}
+
+#[test]
+fn thermostat() {
+    let too_warm = |y: &[f64; 1], _t: &f64, _p: &()| y[0] - 21.0;
+    let too_cold = |y: &[f64; 1], _t: &f64, _p: &()| 19.0 - y[0];
+    let automaton = HybridAutomaton {
+        y0: [20.0],
+        t0: 0.0,
+        initial_mode: 0,
+        modes: vec![
+            Mode {
+                func: &rhs_heating,
+                transitions: vec![Transition {
+                    target: 1,
+                    guard: &too_warm,
+                    reset: None,
+                }],
+            },
+            Mode {
+                func: &rhs_cooling,
+                transitions: vec![Transition {
+                    target: 0,
+                    guard: &too_cold,
+                    reset: None,
+                }],
+            },
+        ],
+    };
+    let t_series = vec![0.0, 0.1, 0.2, 1.0];
+    let solution = solve_hybrid_time_series_iter(
+        automaton,
+        &t_series,
+        &(),
+        AdaptiveStepSolvers::BulirschStoer,
+        &1.0,
+        &tolerances(),
+        &settings(),
+    )
+    .unwrap();
+
+    // Heating from 20 to 21 degrees takes ln(10/9). Afterwards, cooling from 21 to 19 and
+    // heating from 19 to 21 degrees both take ln(11/9).
+    let t_k = |k: usize| (10.0_f64 / 9.0).ln() + k as f64 * (11.0_f64 / 9.0).ln();
+    assert_eq!(solution.transitions.len(), 5);
+    for (k, transition) in solution.transitions.iter().enumerate() {
+        assert_abs_diff_eq!(transition.t, t_k(k), epsilon = 1e-9);
+        assert_eq!(transition.from, k % 2);
+        assert_eq!(transition.to, (k + 1) % 2);
+    }
+    assert_eq!(solution.modes, vec![0, 0, 1, 1]);
+    assert_eq!(solution.y[0], [20.0]);
+    assert_abs_diff_eq!(
+        solution.y[3][0],
+        10.0 + 11.0 * (t_k(4) - 1.0).exp(),
+        epsilon = 1e-8
+    );
+}
+
+#[test]
+fn bouncing_ball() {
+    let ground = |y: &[f64; 2], _t: &f64, _p: &f64| -y[0];
+    let bounce = |_t: &f64, y: &mut [f64; 2], _p: &f64| y[1] *= -0.5;
+    let automaton = HybridAutomaton {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        initial_mode: 0,
+        modes: vec![Mode {
+            func: &rhs_fall,
+            transitions: vec![Transition {
+                target: 0,
+                guard: &ground,
+                reset: Some(&bounce),
+            }],
+        }],
+    };
+    let g = 9.81;
+    let t_series: Vec<f64> = (0..=10).map(|i| 0.1 * i as f64).collect();
+    let solution = solve_hybrid_time_series_iter(
+        automaton.clone(),
+        &t_series,
+        &g,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+        &settings(),
+    )
+    .unwrap();
+    let t_impact = (2.0 / g).sqrt();
+    let t_second = t_impact + (2.0 * g).sqrt() / g;
+    assert_eq!(solution.transitions.len(), 2);
+    assert_abs_diff_eq!(solution.transitions[0].t, t_impact, epsilon = 1e-9);
+    assert_abs_diff_eq!(solution.transitions[1].t, t_second, epsilon = 1e-8);
+    assert!(solution.y.iter().all(|y| y[0] > -1e-9));
+    assert_eq!(solution.modes, vec![0; 11]);
+
+    // Infinitely many bounces accumulate before t = 3 times the first impact
+    let t_series = vec![0.0, 2.0];
+    let zeno = solve_hybrid_time_series_iter(
+        automaton,
+        &t_series,
+        &g,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+        &settings(),
+    );
+    assert!(format!("{}", zeno.unwrap_err()).contains("Zeno"));
+}
+
+#[test]
+fn invalid_automata() {
+    let guard = |y: &[f64; 1], _t: &f64, _p: &()| y[0];
+    let automaton = |initial_mode, target| HybridAutomaton {
+        y0: [20.0],
+        t0: 0.0,
+        initial_mode,
+        modes: vec![Mode {
+            func: &rhs_heating,
+            transitions: vec![Transition {
+                target,
+                guard: &guard,
+                reset: None,
+            }],
+        }],
+    };
+    let solve = |automaton, settings: &HybridSettings<f64>, t_series: &Vec<f64>| {
+        solve_hybrid_time_series_iter(
+            automaton,
+            t_series,
+            &(),
+            AdaptiveStepSolvers::BulirschStoer,
+            &0.1,
+            &tolerances(),
+            settings,
+        )
+    };
+    let t_series = vec![0.0, 1.0];
+    assert!(solve(automaton(0, 0), &settings(), &t_series).is_ok());
+    assert!(solve(automaton(1, 0), &settings(), &t_series).is_err());
+    assert!(solve(automaton(0, 1), &settings(), &t_series).is_err());
+    let mut no_transitions = settings();
+    no_transitions.max_transitions = 0;
+    assert!(solve(automaton(0, 0), &no_transitions, &t_series).is_err());
+    let mut no_tolerance = settings();
+    no_tolerance.event_tolerance = 0.0;
+    assert!(solve(automaton(0, 0), &no_tolerance, &t_series).is_err());
+    assert!(solve(automaton(0, 0), &settings(), &vec![-1.0, 1.0]).is_err());
+}
+
+#[test]
+fn additive_types() {
+    let ground = |y: &nalgebra::Vector2<f64>, _t: &f64, _p: &f64| -y[0];
+    let bounce = |_t: &f64, y: &mut nalgebra::Vector2<f64>, _p: &f64| y[1] = -y[1];
+    let automaton = HybridAutomaton {
+        y0: nalgebra::Vector2::new(1.0, 0.0),
+        t0: 0.0,
+        initial_mode: 0,
+        modes: vec![Mode {
+            func: &rhs_fall_vec,
+            transitions: vec![Transition {
+                target: 0,
+                guard: &ground,
+                reset: Some(&bounce),
+            }],
+        }],
+    };
+    let g = 9.81;
+    let t_impact = (2.0 / g).sqrt();
+    let t_series = vec![0.0, 2.0 * t_impact];
+    let solution = solve_hybrid_time_series_add(
+        automaton,
+        &t_series,
+        &g,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances(),
+        &settings(),
+    )
+    .unwrap();
+    // An elastic ball returns to its initial height
+    assert_eq!(solution.transitions.len(), 1);
+    assert_abs_diff_eq!(solution.y[1][0], 1.0, epsilon = 1e-8);
+    assert_abs_diff_eq!(solution.y[1][1], 0.0, epsilon = 1e-8);
+}
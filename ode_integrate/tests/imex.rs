@@ -0,0 +1,263 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Diagonal of the stiff linear part of the manufactured problem
+const L: [f64; 2] = [-10.0, -20.0];
+
+/// Exact solution of the manufactured problem
+fn exact(t: f64) -> [f64; 2] {
+    [t.cos(), (2.0 * t).sin()]
+}
+
+/// Explicit part \\(f_E(y, t) = y_e' - L y_e + y^2 - y_e^2\\) with solution \\(y_e\\)
+fn manufactured(y: &[f64; 2], dy: &mut [f64; 2], t: &f64, _p: &()) -> Result<(), CalcError> {
+    let ye = exact(*t);
+    let dye = [-t.sin(), 2.0 * (2.0 * t).cos()];
+    for i in 0..2 {
+        dy[i] = dye[i] - L[i] * ye[i] + y[i] * y[i] - ye[i] * ye[i];
+    }
+    Ok(())
+}
+
+/// Implicit part \\(f_I(y) = L y\\)
+fn linear(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = L[0] * y[0];
+    dy[1] = L[1] * y[1];
+    Ok(())
+}
+
+/// Error at \\(t = 1\\) with constant steps of size `dt` which are all accepted
+fn manufactured_error(solver: ImexSolvers, dt: f64) -> f64 {
+    let add_def = AdditiveDefinition {
+        y0: exact(0.0),
+        t0: 0.0,
+        explicit: &manufactured,
+        implicit: &linear,
+    };
+    let tolerances = Tolerances {
+        rtol: 1e10,
+        atol: 1e10,
+    };
+    let mut stepper = get_imex_stepper(solver, add_def, tolerances);
+    let mut y = exact(0.0);
+    let steps = (1.0 / dt).round() as usize;
+    for i in 0..steps {
+        let err = stepper
+            .do_step_iter(&mut y, &(i as f64 * dt), &dt, &())
+            .unwrap();
+        assert!(err.unwrap() <= 1.0);
+    }
+    let ye = exact(1.0);
+    (y[0] - ye[0]).abs().max((y[1] - ye[1]).abs())
+}
+
+#[test]
+fn convergence_order() {
+    for (solver, order) in [
+        (ImexSolvers::Ark324L2SA, 3.0),
+        (ImexSolvers::Ark436L2SA, 4.0),
+    ] {
+        // Coarser steps are still affected by the stiffness of the linear part
+        let coarse = manufactured_error(solver.clone(), 0.0125);
+        let fine = manufactured_error(solver.clone(), 0.0125 / 8.0);
+        let observed = (coarse / fine).log2() / 3.0;
+        assert!(
+            (observed - order).abs() < 0.3,
+            "{solver:?} converges with order {observed}"
+        );
+    }
+}
+
+/// Number of grid points of the reaction-diffusion equation
+const N: usize = 20;
+
+/// Fisher-KPP reaction \\(u(1 - u)\\)
+fn reaction(u: &Vec<f64>, du: &mut Vec<f64>, _t: &f64, _p: &()) -> Result<(), CalcError> {
+    for (dui, ui) in du.iter_mut().zip(u.iter()) {
+        *dui = ui * (1.0 - ui);
+    }
+    Ok(())
+}
+
+/// Diffusion with homogeneous Dirichlet boundary conditions on the unit interval
+fn diffusion(u: &Vec<f64>, du: &mut Vec<f64>, _t: &f64, _p: &()) -> Result<(), CalcError> {
+    let dx = 1.0 / (N + 1) as f64;
+    for i in 0..N {
+        let left = if i > 0 { u[i - 1] } else { 0.0 };
+        let right = if i + 1 < N { u[i + 1] } else { 0.0 };
+        du[i] = (left - 2.0 * u[i] + right) / (dx * dx);
+    }
+    Ok(())
+}
+
+#[test]
+fn reaction_diffusion() {
+    let u0: Vec<f64> = (1..=N)
+        .map(|i| {
+            let x = i as f64 / (N + 1) as f64;
+            4.0 * x * (1.0 - x)
+        })
+        .collect();
+    let t_series = vec![0.0, 0.05, 0.2];
+
+    let full = |u: &Vec<f64>, du: &mut Vec<f64>, t: &f64, p: &()| -> Result<(), CalcError> {
+        let mut dr = u.clone();
+        reaction(u, &mut dr, t, p)?;
+        diffusion(u, du, t, p)?;
+        du.iter_mut().zip(dr.iter()).for_each(|(d, r)| *d += r);
+        Ok(())
+    };
+    let reference = solve_ode_time_series_adaptive_step_iter(
+        &u0,
+        &t_series,
+        &full,
+        &(),
+        AdaptiveStepSolvers::BulirschStoer,
+        &1e-4,
+        &Tolerances {
+            rtol: 1e-12,
+            atol: 1e-12,
+        },
+    )
+    .unwrap();
+
+    let tolerances = Tolerances {
+        rtol: 1e-7,
+        atol: 1e-9,
+    };
+    for solver in [ImexSolvers::Ark324L2SA, ImexSolvers::Ark436L2SA] {
+        let add_def = AdditiveDefinition {
+            y0: u0.clone(),
+            t0: 0.0,
+            explicit: &reaction,
+            implicit: &diffusion,
+        };
+        // The initial step is far too large and needs to be rejected
+        let u = solve_additive_time_series_iter(add_def, &t_series, &(), solver, &0.1, &tolerances)
+            .unwrap();
+        assert_eq!(u.len(), 3);
+        for (u, u_ref) in u.iter().zip(reference.iter()) {
+            for (ui, ui_ref) in u.iter().zip(u_ref.iter()) {
+                assert_abs_diff_eq!(ui, ui_ref, epsilon = 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn error_control() {
+    // Together, both parts give y' = -y^2 with solution y = y0 / (1 + y0 t)
+    let logistic = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = y[0] * (1.0 - y[0]);
+        Ok(())
+    };
+    let decay = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = -y[0];
+        Ok(())
+    };
+    let t_series = vec![0.0, 1.0, 5.0];
+    for solver in [ImexSolvers::Ark324L2SA, ImexSolvers::Ark436L2SA] {
+        let mut errors = Vec::new();
+        for tol in [1e-4, 1e-8] {
+            let add_def = AdditiveDefinition {
+                y0: [1.0],
+                t0: 0.0,
+                explicit: &logistic,
+                implicit: &decay,
+            };
+            let tolerances = Tolerances {
+                rtol: tol,
+                atol: tol,
+            };
+            let y = solve_additive_time_series_iter(
+                add_def,
+                &t_series,
+                &(),
+                solver.clone(),
+                &0.01,
+                &tolerances,
+            )
+            .unwrap();
+            let error = (y[2][0] - 1.0 / 6.0).abs();
+            assert!(error < 10.0 * tol, "{solver:?} has error {error}");
+            errors.push(error);
+        }
+        assert!(errors[1] < 1e-3 * errors[0]);
+    }
+}
+
+#[test]
+fn invalid_problems() {
+    let failing = |_y: &[f64; 1], _dy: &mut [f64; 1], t: &f64, _p: &()| -> Result<(), CalcError> {
+        if *t > 0.5 {
+            Err(CalcError::from("Evaluated after t = 0.5"))
+        } else {
+            Ok(())
+        }
+    };
+    let decay = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = -y[0];
+        Ok(())
+    };
+    let tolerances = Tolerances {
+        rtol: 1e-6,
+        atol: 1e-6,
+    };
+    let solve = |t_series: Vec<f64>| {
+        let add_def = AdditiveDefinition {
+            y0: [1.0],
+            t0: 0.0,
+            explicit: &failing,
+            implicit: &decay,
+        };
+        solve_additive_time_series_iter(
+            add_def,
+            &t_series,
+            &(),
+            ImexSolvers::Ark436L2SA,
+            &0.1,
+            &tolerances,
+        )
+    };
+
+    // Errors of the RHS end the integration
+    assert!(solve(vec![0.0, 0.4]).is_ok());
+    assert!(solve(vec![0.0, 1.0]).is_err());
+
+    // Time points which are neither increasing nor decreasing
+    assert!(solve(vec![0.0, 0.4, 0.2]).is_err());
+}
+
+#[test]
+fn backward_integration() {
+    // Together, both parts give y' = -y^2 with solution y = 1 / (1 + t) for y(1) = 1/2
+    let logistic = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = y[0] * (1.0 - y[0]);
+        Ok(())
+    };
+    let decay = |y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()| -> Result<(), CalcError> {
+        dy[0] = -y[0];
+        Ok(())
+    };
+    let tolerances = Tolerances {
+        rtol: 1e-8,
+        atol: 1e-8,
+    };
+    let t_series = vec![1.0, 0.5, 0.0];
+    for solver in [ImexSolvers::Ark324L2SA, ImexSolvers::Ark436L2SA] {
+        let add_def = AdditiveDefinition {
+            y0: [0.5],
+            t0: 1.0,
+            explicit: &logistic,
+            implicit: &decay,
+        };
+        let y = solve_additive_time_series_iter(add_def, &t_series, &(), solver, &0.1, &tolerances)
+            .unwrap();
+        for (t, y) in t_series.iter().zip(y.iter()) {
+            assert_abs_diff_eq!(y[0], 1.0 / (1.0 + t), epsilon = 1e-6);
+        }
+    }
+}
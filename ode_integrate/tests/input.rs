@@ -0,0 +1,270 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Integrates the input \\(y' = u(t)\\)
+fn rhs_input(
+    _y: &[f64; 1],
+    dy: &mut [f64; 1],
+    t: &f64,
+    u: &InputSignal<f64>,
+) -> Result<(), CalcError> {
+    dy[0] = u.value(t);
+    Ok(())
+}
+
+fn sine_samples(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let times: Vec<f64> = (0..=n)
+        .map(|i| 2.0 * core::f64::consts::PI * i as f64 / n as f64)
+        .collect();
+    let mut values: Vec<f64> = times.iter().map(|t| t.sin()).collect();
+    values[n] = values[0];
+    (times, values)
+}
+
+#[test]
+fn interpolation() {
+    let times = vec![0.0, 1.0, 2.0, 4.0];
+    let values = vec![1.0, 3.0, 2.0, 0.0];
+    let hold = InputSignal::new(
+        times.clone(),
+        values.clone(),
+        Interpolation::ZeroOrderHold,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    let linear = InputSignal::new(
+        times.clone(),
+        values.clone(),
+        Interpolation::Linear,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    let spline = InputSignal::new(
+        times.clone(),
+        values.clone(),
+        Interpolation::CubicSpline,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    for signal in [&hold, &linear, &spline] {
+        for (t, u) in times.iter().zip(values.iter()) {
+            assert_abs_diff_eq!(signal.value(t), *u, epsilon = 1e-14);
+        }
+        // Values are held outside of the grid
+        assert_eq!(signal.value(&-1.0), 1.0);
+        assert_eq!(signal.value(&10.0), 0.0);
+    }
+    assert_eq!(hold.value(&0.5), 1.0);
+    assert_eq!(hold.value(&3.9), 2.0);
+    assert_eq!(linear.value(&0.5), 2.0);
+    assert_eq!(linear.value(&3.0), 1.0);
+
+    // The natural spline of a sine is accurate away from the ends
+    let (times, values) = sine_samples(32);
+    let spline = InputSignal::new(
+        times,
+        values,
+        Interpolation::CubicSpline,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    for i in 0..100 {
+        let t = 1.0 + 0.04 * i as f64;
+        assert_abs_diff_eq!(spline.value(&t), t.sin(), epsilon = 1e-4);
+    }
+}
+
+#[test]
+fn periodic_extension() {
+    let (times, values) = sine_samples(32);
+    let spline = InputSignal::new(
+        times.clone(),
+        values.clone(),
+        Interpolation::CubicSpline,
+        Extrapolation::Periodic,
+    )
+    .unwrap();
+    let linear = InputSignal::new(
+        times,
+        values,
+        Interpolation::Linear,
+        Extrapolation::Periodic,
+    )
+    .unwrap();
+    for i in 0..200 {
+        let t = -10.0 + 0.1 * i as f64;
+        assert_abs_diff_eq!(spline.value(&t), t.sin(), epsilon = 1e-5);
+        assert_abs_diff_eq!(linear.value(&t), t.sin(), epsilon = 1e-2);
+    }
+
+    // Few samples are solved with the full periodic system
+    for n in [1, 2, 3] {
+        let (times, values) = sine_samples(n);
+        let spline = InputSignal::new(
+            times.clone(),
+            values.clone(),
+            Interpolation::CubicSpline,
+            Extrapolation::Periodic,
+        )
+        .unwrap();
+        for (t, u) in times.iter().zip(values.iter()) {
+            assert_abs_diff_eq!(spline.value(t), *u, epsilon = 1e-12);
+            assert_abs_diff_eq!(spline.value(&(t + times[n])), *u, epsilon = 1e-12);
+        }
+    }
+
+    // Dosing schedule repeated every day
+    let schedule = InputSignal::new(
+        vec![0.0, 8.0, 24.0],
+        vec![1.0, 0.0, 1.0],
+        Interpolation::ZeroOrderHold,
+        Extrapolation::Periodic,
+    )
+    .unwrap();
+    assert_eq!(schedule.value(&50.0), 1.0);
+    assert_eq!(schedule.value(&60.0), 0.0);
+    assert_eq!(schedule.value(&-20.0), 1.0);
+    assert_eq!(
+        schedule.breakpoints(&-10.0, &48.0),
+        vec![0.0, 8.0, 24.0, 32.0, 48.0]
+    );
+}
+
+#[test]
+fn invalid_samples() {
+    let new = |times: Vec<f64>, values: Vec<f64>, extrapolation| {
+        InputSignal::new(times, values, Interpolation::Linear, extrapolation)
+    };
+    assert!(new(vec![0.0, 1.0], vec![1.0], Extrapolation::Hold).is_err());
+    assert!(new(vec![], vec![], Extrapolation::Hold).is_err());
+    assert!(new(vec![0.0, 0.0], vec![1.0, 1.0], Extrapolation::Hold).is_err());
+    assert!(new(vec![0.0, 1.0], vec![1.0, 2.0], Extrapolation::Periodic).is_err());
+    assert!(new(vec![0.0], vec![1.0], Extrapolation::Periodic).is_err());
+    let constant = new(vec![0.0], vec![1.0], Extrapolation::Hold).unwrap();
+    assert_eq!(constant.value(&5.0), 1.0);
+}
+
+#[test]
+fn breakpoints_as_tstops() {
+    let hold = InputSignal::new(
+        vec![0.0, 0.3, 0.7, 1.1],
+        vec![1.0, -1.0, 2.0, 0.0],
+        Interpolation::ZeroOrderHold,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    let spline = InputSignal::new(
+        vec![0.0, 0.5, 0.7, 1.5],
+        vec![0.0, 1.0, 0.0, 1.0],
+        Interpolation::CubicSpline,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    assert_eq!(spline.breakpoints(&0.2, &2.0), vec![1.5]);
+    let tstops: TStops<[f64; 1], f64, InputSignal<f64>> =
+        TStops::from_inputs(&[&hold, &spline], &0.0, &1.2);
+    assert_eq!(tstops.times, vec![0.0, 0.3, 0.7, 1.1]);
+
+    // Euler integrates a zero-order hold exactly when steps end at its breakpoints
+    let t_series = vec![0.0, 0.5, 1.5];
+    let y = solve_ode_time_series_single_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs_input,
+        &hold,
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[1][0], 0.5, epsilon = 1e-14);
+    let tstops = TStops::from_inputs(&[&hold], &0.0, &1.5);
+    let y = solve_ode_time_series_single_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_input,
+        &hold,
+        FixedStepSolvers::Euler,
+        &tstops,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[1][0], 0.3 - 0.2, epsilon = 1e-14);
+    assert_abs_diff_eq!(y[2][0], 0.3 - 0.4 + 0.8, epsilon = 1e-14);
+
+    let tolerances = Tolerances {
+        rtol: 1e-12,
+        atol: 1e-12,
+    };
+    let y = solve_ode_time_series_adaptive_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_input,
+        &hold,
+        AdaptiveStepSolvers::BulirschStoer,
+        &1.0,
+        &tolerances,
+        &tstops,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[2][0], 0.3 - 0.4 + 0.8, epsilon = 1e-9);
+}
+
+#[test]
+fn breakpoints_of_solver_inputs() {
+    let hold = InputSignal::new(
+        vec![0.0, 0.3, 0.7, 1.1],
+        vec![1.0, -1.0, 2.0, 0.0],
+        Interpolation::ZeroOrderHold,
+        Extrapolation::Hold,
+    )
+    .unwrap();
+    let solver = Solver::builder()
+        .fixed_step(FixedStepSolvers::Euler)
+        .input(&hold)
+        .build()
+        .unwrap();
+    assert_eq!(solver.inputs().len(), 1);
+    let y = solver
+        .solve(&[0.0], &vec![0.0, 0.5, 1.5], &rhs_input, &hold)
+        .unwrap()
+        .y;
+    assert_abs_diff_eq!(y[1][0], 0.3 - 0.2, epsilon = 1e-14);
+    assert_abs_diff_eq!(y[2][0], 0.3 - 0.4 + 0.8, epsilon = 1e-14);
+
+    // Breakpoints are ordered backward when integrating backward in time
+    let solver = Solver::builder()
+        .tolerances(Tolerances {
+            rtol: 1e-12,
+            atol: 1e-12,
+        })
+        .input(&hold)
+        .build()
+        .unwrap();
+    let y = solver
+        .solve(&[0.9], &vec![1.5, 0.5], &rhs_input, &hold)
+        .unwrap()
+        .y;
+    assert_abs_diff_eq!(y[1][0], 0.9 - 0.8 + 0.2, epsilon = 1e-9);
+
+    // The state only jumps at the stop times of the tstops
+    let jump = |_t: &f64, y: &mut [f64; 1], _p: &InputSignal<f64>| y[0] += 10.0;
+    let tstops = TStops {
+        times: vec![0.5],
+        jump: Some(&jump),
+    };
+    let solver = Solver::builder()
+        .tolerances(Tolerances {
+            rtol: 1e-12,
+            atol: 1e-12,
+        })
+        .tstops(&tstops)
+        .input(&hold)
+        .build()
+        .unwrap();
+    let y = solver
+        .solve(&[0.0], &vec![0.0, 1.5], &rhs_input, &hold)
+        .unwrap()
+        .y;
+    assert_abs_diff_eq!(y[1][0], 10.0 + 0.3 - 0.4 + 0.8, epsilon = 1e-9);
+}
@@ -0,0 +1,206 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -\omega^2 y_0\\)
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, omega: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -omega * omega * y[0];
+    Ok(())
+}
+
+/// Harmonic oscillator with unit frequency as a vector which can be added
+fn rhs_oscillator_vec(
+    y: &nalgebra::Vector2<f64>,
+    dy: &mut nalgebra::Vector2<f64>,
+    _t: &f64,
+    _p: &(),
+) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+/// Growth \\(y' = y\\) which fails once the solution becomes too large
+fn rhs_blow_up(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    if y[0] > 10.0 {
+        return Err(CalcError::from("Solution too large"));
+    }
+    dy[0] = y[0];
+    Ok(())
+}
+
+fn oscillator<'a>() -> OdeDefinition<'a, [f64; 2], f64, f64, CalcError> {
+    OdeDefinition {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        func: &rhs_oscillator,
+    }
+}
+
+#[test]
+fn fixed_steps_match_driver() {
+    let t_series: Vec<f64> = (0..=16).map(|i| 0.125 * i as f64).collect();
+    let reference = solve_ode_time_series_single_step_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_oscillator,
+        &2.0,
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+
+    let integrator = Integrator::fixed_step_iter(oscillator(), FixedStepSolvers::Euler, 0.125, 2.0)
+        .with_t_end(2.0);
+    let items: Vec<_> = integrator.collect();
+    assert_eq!(items.len(), 16);
+    for ((t, y), (t_ref, y_ref)) in items
+        .iter()
+        .zip(t_series.iter().skip(1).zip(reference.iter().skip(1)))
+    {
+        assert_eq!(t, t_ref);
+        assert_eq!(y, y_ref);
+    }
+}
+
+#[test]
+fn output_times_are_hit_exactly() {
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let output_times = vec![0.0, 0.3, 1.7, 2.0, 5.5];
+    let integrator = Integrator::adaptive_step_iter(
+        oscillator(),
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances,
+        1.0,
+    )
+    .with_output_times(output_times.clone())
+    .with_t_end(3.0);
+    let items: Vec<_> = integrator.collect();
+
+    // Output times after the end are not produced
+    assert_eq!(items.len(), 4);
+    for ((t, y), t_out) in items.iter().zip(output_times.iter()) {
+        assert_eq!(t, t_out);
+        assert_abs_diff_eq!(y[0], t.cos(), epsilon = 1e-8);
+        assert_abs_diff_eq!(y[1], -t.sin(), epsilon = 1e-8);
+    }
+
+    // Fixed steps are shortened to land on the output times
+    let mut integrator =
+        Integrator::fixed_step_iter(oscillator(), FixedStepSolvers::Rk4, 0.25, 1.0)
+            .with_output_times(vec![0.1, 0.6]);
+    assert_eq!(integrator.next().unwrap().0, 0.1);
+    assert_eq!(integrator.statistics().n_accepted, 1);
+    assert_eq!(integrator.next().unwrap().0, 0.6);
+    assert_eq!(integrator.statistics().n_accepted, 3);
+    assert!(integrator.next().is_none());
+    assert!(integrator.is_finished());
+}
+
+#[test]
+fn control_during_iteration() {
+    let tolerances = Tolerances {
+        rtol: 1e-8,
+        atol: 1e-8,
+    };
+    let mut integrator = Integrator::adaptive_step_iter(
+        oscillator(),
+        AdaptiveStepSolvers::BulirschStoer,
+        1.0,
+        tolerances,
+        3.0,
+    );
+    let mut last_t = 0.0;
+    let mut n_items = 0;
+    while let Some((t, y)) = integrator.next() {
+        assert!(t > last_t);
+        if n_items > 0 {
+            assert!(t - last_t <= 0.05 + 1e-15);
+        }
+        n_items += 1;
+        assert_abs_diff_eq!(y[0], (3.0 * t).cos(), epsilon = 1e-6);
+        last_t = t;
+        // Limit the step size manually
+        if *integrator.dt() > 0.05 {
+            integrator.set_dt(0.05);
+        }
+        if t > 1.0 {
+            integrator.stop();
+        }
+    }
+    assert!(last_t > 1.0 && last_t <= 1.05);
+    assert_eq!(*integrator.t(), last_t);
+    assert_eq!(integrator.statistics().n_accepted, n_items);
+    assert!(integrator.error().is_none());
+
+    // Without limit the steps are much larger and may need to be rejected
+    let integrator = Integrator::adaptive_step_iter(
+        oscillator(),
+        AdaptiveStepSolvers::BulirschStoer,
+        1.0,
+        Tolerances {
+            rtol: 1e-8,
+            atol: 1e-8,
+        },
+        3.0,
+    )
+    .with_t_end(1.0);
+    assert!(integrator.count() < n_items);
+}
+
+#[test]
+fn errors_end_the_iteration() {
+    let ode_def = OdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        func: &rhs_blow_up,
+    };
+    let mut integrator =
+        Integrator::fixed_step_iter(ode_def.clone(), FixedStepSolvers::Euler, 0.1, ());
+    let n_items = integrator.by_ref().count();
+    assert!(n_items > 20);
+    assert!(integrator.error().is_some());
+    assert!(integrator.next().is_none());
+
+    let mut integrator = Integrator::fixed_step_iter(ode_def, FixedStepSolvers::Euler, 0.1, ());
+    while let Ok(Some((_, y))) = integrator.step() {
+        assert!(y[0] < 12.0);
+    }
+    assert!(integrator.step().is_err());
+}
+
+#[test]
+fn additive_types() {
+    let ode_def = OdeDefinition {
+        y0: nalgebra::Vector2::new(1.0, 0.0),
+        t0: 0.0,
+        func: &rhs_oscillator_vec,
+    };
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let adaptive = Integrator::adaptive_step_add(
+        ode_def.clone(),
+        AdaptiveStepSolvers::BulirschStoer,
+        0.1,
+        tolerances,
+        (),
+    )
+    .with_t_end(2.0);
+    let (t, y) = adaptive.last().unwrap();
+    assert_eq!(t, 2.0);
+    assert_abs_diff_eq!(y[0], 2.0_f64.cos(), epsilon = 1e-8);
+
+    let fixed = Integrator::fixed_step_add(ode_def, FixedStepSolvers::Rk4, 0.01, ())
+        .with_output_times(vec![1.0, 2.0]);
+    let items: Vec<_> = fixed.collect();
+    assert_eq!(items.len(), 2);
+    assert_abs_diff_eq!(items[1].1[0], 2.0_f64.cos(), epsilon = 1e-8);
+}
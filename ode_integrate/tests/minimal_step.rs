@@ -0,0 +1,151 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+use std::cell::RefCell;
+
+/// Constant growth \\(y' = 1\\) which Euler integrates exactly
+fn rhs_constant(_y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = 1.0;
+    Ok(())
+}
+
+/// Exponential decay \\(y' = -p y\\)
+fn rhs_decay(
+    y: &nalgebra::Vector1<f64>,
+    dy: &mut nalgebra::Vector1<f64>,
+    _t: &f64,
+    p: &f64,
+) -> Result<(), CalcError> {
+    dy[0] = -p * y[0];
+    Ok(())
+}
+
+#[test]
+fn lands_on_output_times() {
+    // None of the output times is a multiple of the step size
+    let t_series = vec![0.0, 0.33, 0.71, 1.0, 2.05];
+    let y = solve_ode_time_series_minimal_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs_constant,
+        &(),
+        FixedStepSolvers::Euler,
+        &0.1,
+    )
+    .unwrap();
+    for (t, y) in t_series.iter().zip(y.iter()) {
+        assert_abs_diff_eq!(y[0], *t, epsilon = 1e-14);
+    }
+    let y = solve_ode_time_series_equidistributed_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs_constant,
+        &(),
+        FixedStepSolvers::Euler,
+        &0.1,
+    )
+    .unwrap();
+    for (t, y) in t_series.iter().zip(y.iter()) {
+        assert_abs_diff_eq!(y[0], *t, epsilon = 1e-14);
+    }
+
+    // The observer sees the truncated last step
+    let mut times = Vec::new();
+    let mut steps = Vec::new();
+    let mut observer = |t: &f64, _y: &mut [f64; 1], dt: &f64, _e: Option<&f64>| {
+        times.push(*t);
+        steps.push(*dt);
+        ObserverAction::Continue
+    };
+    solve_ode_time_series_minimal_step_observed_iter(
+        &[0.0],
+        &vec![0.0, 0.33],
+        &rhs_constant,
+        &(),
+        FixedStepSolvers::Euler,
+        &0.1,
+        &mut observer,
+    )
+    .unwrap();
+    assert_eq!(times.len(), 4);
+    assert_eq!(times[3], 0.33);
+    assert_abs_diff_eq!(steps[3], 0.03, epsilon = 1e-14);
+}
+
+#[test]
+fn analytic_decay() {
+    let p = 2.0;
+    let t_series = vec![0.0, 0.033, 0.25, 0.5, 0.777, 1.3];
+    let y0 = nalgebra::Vector1::new(1.0);
+    let minimal = solve_ode_time_series_minimal_step_add(
+        &y0,
+        &t_series,
+        &rhs_decay,
+        &p,
+        FixedStepSolvers::Rk4,
+        &0.01,
+    )
+    .unwrap();
+    let equidistributed = solve_ode_time_series_equidistributed_step_add(
+        &y0,
+        &t_series,
+        &rhs_decay,
+        &p,
+        FixedStepSolvers::Rk4,
+        &0.01,
+    )
+    .unwrap();
+    for (t, (y_min, y_equi)) in t_series
+        .iter()
+        .zip(minimal.iter().zip(equidistributed.iter()))
+    {
+        let exact = (-p * t).exp();
+        assert_abs_diff_eq!(y_min[0], exact, epsilon = 1e-9);
+        assert_abs_diff_eq!(y_equi[0], exact, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn equidistributed_steps() {
+    // Euler evaluates the RHS once at the start of every step
+    let starts = RefCell::new(Vec::new());
+    let rhs = |_y: &[f64; 1], dy: &mut [f64; 1], t: &f64, _p: &()| -> Result<(), CalcError> {
+        starts.borrow_mut().push(*t);
+        dy[0] = 1.0;
+        Ok(())
+    };
+    let t_series = vec![0.0, 0.33, 0.5];
+    let y = solve_ode_time_series_equidistributed_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs,
+        &(),
+        FixedStepSolvers::Euler,
+        &0.1,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[2][0], 0.5, epsilon = 1e-14);
+
+    // 4 steps of 0.0825 and 2 steps of 0.085
+    let starts = starts.borrow().clone();
+    assert_eq!(starts.len(), 6);
+    for (i, t) in starts[..4].iter().enumerate() {
+        assert_abs_diff_eq!(*t, 0.0825 * i as f64, epsilon = 1e-14);
+    }
+    assert_abs_diff_eq!(starts[4], 0.33, epsilon = 1e-14);
+    assert_abs_diff_eq!(starts[5], 0.415, epsilon = 1e-14);
+
+    // A vanishing step size is rejected
+    assert!(solve_ode_time_series_equidistributed_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs,
+        &(),
+        FixedStepSolvers::Euler,
+        &0.0,
+    )
+    .is_err());
+}
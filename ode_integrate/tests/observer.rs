@@ -0,0 +1,204 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Free fall \\(x' = v, v' = -g\\) of a ball which should not go below the ground
+fn rhs_fall(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, g: &f64) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -g;
+    Ok(())
+}
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -y_0\\)
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -y_0\\) as a vector which can be added
+fn rhs_oscillator_vec(
+    y: &nalgebra::Vector2<f64>,
+    dy: &mut nalgebra::Vector2<f64>,
+    _t: &f64,
+    _p: &(),
+) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+#[test]
+fn observer_sees_every_fixed_step() {
+    let t_series = vec![0.0, 0.5, 1.0];
+    let mut times = Vec::new();
+    let mut observer = |t: &f64, y: &mut [f64; 2], dt: &f64, err: Option<&f64>| {
+        assert!(err.is_none());
+        times.push((*t, *dt, *y));
+        ObserverAction::Continue
+    };
+    let solution = solve_ode_time_series_minimal_step_observed_iter(
+        &[10.0, 0.0],
+        &t_series,
+        &rhs_fall,
+        &9.81,
+        FixedStepSolvers::Euler,
+        &0.125,
+        &mut observer,
+    )
+    .unwrap();
+    assert!(solution.stopped.is_none());
+    assert_eq!(times.len(), 8);
+    assert!(times.iter().all(|(_, dt, _)| *dt == 0.125));
+    assert_eq!(times[3].0, 0.5);
+    assert_eq!(times[3].2, solution.y[1]);
+    assert_eq!(times[7].2, solution.y[2]);
+
+    // Without stopping the solution is identical to the driver without observer
+    let reference = solve_ode_time_series_minimal_step_iter(
+        &[10.0, 0.0],
+        &t_series,
+        &rhs_fall,
+        &9.81,
+        FixedStepSolvers::Euler,
+        &0.125,
+    )
+    .unwrap();
+    assert_eq!(solution.y, reference);
+}
+
+#[test]
+fn stop_and_clamp_fixed_steps() {
+    let t_series: Vec<f64> = (0..=40).map(|i| 0.05 * i as f64).collect();
+
+    // Stop when the ball hits the ground
+    let solution = solve_ode_time_series_single_step_observed_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_fall,
+        &9.81,
+        FixedStepSolvers::Euler,
+        &mut |_t: &f64, y: &mut [f64; 2], _dt: &f64, _err: Option<&f64>| {
+            if y[0] <= 0.0 {
+                ObserverAction::Stop
+            } else {
+                ObserverAction::Continue
+            }
+        },
+    )
+    .unwrap();
+    let (t_stop, y_stop) = solution.stopped.unwrap();
+    assert!(y_stop[0] <= 0.0);
+    assert!(solution.y[..solution.y.len() - 1]
+        .iter()
+        .all(|y| y[0] > 0.0));
+    assert_eq!(t_stop, t_series[solution.y.len() - 1]);
+    assert_abs_diff_eq!(t_stop, (2.0 / 9.81_f64).sqrt(), epsilon = 0.1);
+
+    // Clamp the position at the ground instead
+    let solution = solve_ode_time_series_single_step_observed_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_fall,
+        &9.81,
+        FixedStepSolvers::Euler,
+        &mut |_t: &f64, y: &mut [f64; 2], _dt: &f64, _err: Option<&f64>| {
+            if y[0] < 0.0 {
+                *y = [0.0, 0.0];
+            }
+            ObserverAction::Continue
+        },
+    )
+    .unwrap();
+    assert!(solution.stopped.is_none());
+    assert_eq!(solution.y.len(), t_series.len());
+    assert!(solution.y.iter().all(|y| y[0] >= 0.0));
+    assert_eq!(solution.y.last().unwrap()[0], 0.0);
+}
+
+#[test]
+fn adaptive_observer() {
+    let t_series = vec![0.0, 1.0, 2.0, 3.0];
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let mut last_t = 0.0;
+    let mut n_steps = 0;
+    let mut observer = |t: &f64, y: &mut [f64; 2], dt: &f64, err: Option<&f64>| {
+        assert!(*err.unwrap() <= 1.0);
+        assert_abs_diff_eq!(*t - last_t, *dt, epsilon = 1e-14);
+        last_t = *t;
+        n_steps += 1;
+        if y[0] < 0.0 {
+            ObserverAction::Stop
+        } else {
+            ObserverAction::Continue
+        }
+    };
+    let solution = solve_ode_time_series_adaptive_step_observed_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_oscillator,
+        &(),
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+        &mut observer,
+    )
+    .unwrap();
+    assert!(n_steps > 1);
+
+    // The first zero crossing is at pi/2
+    let (t_stop, y_stop) = solution.stopped.unwrap();
+    assert!(y_stop[0] < 0.0);
+    assert!(t_stop > core::f64::consts::FRAC_PI_2 && t_stop <= 2.0);
+    assert_eq!(t_stop, last_t);
+    assert_abs_diff_eq!(y_stop[0], t_stop.cos(), epsilon = 1e-8);
+    assert_eq!(solution.y.len(), if t_stop == 2.0 { 3 } else { 2 });
+    assert_abs_diff_eq!(solution.y[1][0], 1.0_f64.cos(), epsilon = 1e-8);
+}
+
+#[test]
+fn observers_for_additive_types() {
+    let t_series: Vec<f64> = (0..=10).map(|i| 0.3 * i as f64).collect();
+    let y0 = nalgebra::Vector2::new(1.0, 0.0);
+    let mut renormalize =
+        |_t: &f64, y: &mut nalgebra::Vector2<f64>, _dt: &f64, _err: Option<&f64>| {
+            *y /= y.norm();
+            ObserverAction::Continue
+        };
+    let fixed = solve_ode_time_series_minimal_step_observed_add(
+        &y0,
+        &t_series,
+        &rhs_oscillator_vec,
+        &(),
+        FixedStepSolvers::Rk4,
+        &0.1,
+        &mut renormalize,
+    )
+    .unwrap();
+    let tolerances = Tolerances {
+        rtol: 1e-6,
+        atol: 1e-6,
+    };
+    let adaptive = solve_ode_time_series_adaptive_step_observed_add(
+        &y0,
+        &t_series,
+        &rhs_oscillator_vec,
+        &(),
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+        &mut renormalize,
+    )
+    .unwrap();
+    for ((t, y1), y2) in t_series.iter().zip(fixed.y.iter()).zip(adaptive.y.iter()) {
+        assert_abs_diff_eq!(y1.norm(), 1.0, epsilon = 1e-14);
+        assert_abs_diff_eq!(y2.norm(), 1.0, epsilon = 1e-14);
+        assert_abs_diff_eq!(y1[0], t.cos(), epsilon = 1e-4);
+        assert_abs_diff_eq!(y2[0], t.cos(), epsilon = 1e-4);
+    }
+}
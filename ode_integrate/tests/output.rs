@@ -0,0 +1,241 @@
+#![cfg(feature = "std")]
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+use std::io::Cursor;
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -y_0\\)
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+/// Bitwise CRC-32 as reference for the checksum stored in archives
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Read a little-endian integer of `N` bytes at `offset`
+fn read_le<const N: usize>(bytes: &[u8], offset: usize) -> u64 {
+    (0..N).fold(0, |acc, i| acc | (bytes[offset + i] as u64) << (8 * i))
+}
+
+/// Parse a `.npy` file into its shape and values
+fn parse_npy(bytes: &[u8]) -> (String, Vec<f64>) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let header_len = 10 + read_le::<2>(bytes, 8) as usize;
+    assert_eq!(header_len % 64, 0);
+    let header = String::from_utf8(bytes[10..header_len].to_vec()).unwrap();
+    assert!(header.ends_with('\n'));
+    let shape = header.split("'shape': ").nth(1).unwrap();
+    let shape = shape[..shape.find(')').unwrap() + 1].to_string();
+    let values = bytes[header_len..]
+        .chunks(8)
+        .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    (shape, values)
+}
+
+#[test]
+fn csv_default_header() {
+    let mut csv = CsvWriter::new(Vec::new());
+    csv.write_record(&0.0, &vec![1.0, 2.0, 3.0]).unwrap();
+    csv.write_record(&0.25, &vec![-1.0, 0.5, 1e-10]).unwrap();
+    csv.finish().unwrap();
+    assert_eq!(csv.n_records(), 2);
+    assert_eq!(
+        String::from_utf8(csv.into_inner()).unwrap(),
+        "t,y0,y1,y2\n0,1,2,3\n0.25,-1,0.5,0.0000000001\n"
+    );
+
+    let mut csv = CsvWriter::new(Vec::new()).without_header();
+    csv.write_record(&1.0_f32, &[2.0_f32]).unwrap();
+    assert_eq!(String::from_utf8(csv.into_inner()).unwrap(), "1,2\n");
+
+    // The names have to match the number of columns
+    let mut csv = CsvWriter::new(Vec::new()).with_column_names(&["t", "x"]);
+    assert!(csv.write_record(&0.0, &[1.0, 2.0]).is_err());
+}
+
+#[test]
+fn npy_and_npz_contents() {
+    let records: Vec<(f32, [f32; 2])> = (0..5)
+        .map(|i| (i as f32 * 0.5, [i as f32, -(i as f32) * 0.25]))
+        .collect();
+
+    let mut npy = NpyWriter::new(Cursor::new(Vec::new())).unwrap();
+    for (t, y) in records.iter() {
+        npy.write_record(t, y).unwrap();
+    }
+    npy.finish().unwrap();
+    assert_eq!(npy.n_records(), 5);
+    let npy_bytes = npy.into_inner().into_inner();
+    let (shape, values) = parse_npy(&npy_bytes);
+    assert_eq!(shape, "(5, 3)");
+    for ((t, y), row) in records.iter().zip(values.chunks(3)) {
+        assert_eq!(row, [*t as f64, y[0] as f64, y[1] as f64]);
+    }
+
+    // Records need to have the same length
+    let mut npy = NpyWriter::new(Cursor::new(Vec::new())).unwrap();
+    npy.write_record(&0.0, &vec![1.0]).unwrap();
+    assert!(npy.write_record(&0.0, &vec![1.0, 2.0]).is_err());
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()), "trajectory").unwrap();
+    for (t, y) in records.iter() {
+        npz.write_record(t, y).unwrap();
+    }
+    npz.finish().unwrap();
+    let bytes = npz.into_inner().into_inner();
+
+    // Local file header followed by the uncompressed array
+    assert_eq!(&bytes[..4], b"PK\x03\x04");
+    let crc = read_le::<4>(&bytes, 14) as u32;
+    let size = read_le::<4>(&bytes, 18) as usize;
+    let name_len = read_le::<2>(&bytes, 26) as usize;
+    assert_eq!(&bytes[30..30 + name_len], b"trajectory.npy");
+    let data = &bytes[30 + name_len..30 + name_len + size];
+    assert_eq!(data, &npy_bytes[..]);
+    assert_eq!(crc, crc32(data));
+
+    // End of central directory points to the central directory
+    let eocd = &bytes[bytes.len() - 22..];
+    assert_eq!(&eocd[..4], b"PK\x05\x06");
+    assert_eq!(read_le::<2>(eocd, 10), 1);
+    let directory = read_le::<4>(eocd, 16) as usize;
+    assert_eq!(directory, 30 + name_len + size);
+    assert_eq!(&bytes[directory..directory + 4], b"PK\x01\x02");
+    assert_eq!(read_le::<4>(&bytes, directory + 16) as u32, crc);
+}
+
+#[test]
+fn stream_adaptive_integration() {
+    let t_series: Vec<f64> = (0..=20).map(|i| 0.1 * i as f64).collect();
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let reference = solve_ode_time_series_adaptive_step_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_oscillator,
+        &(),
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+    )
+    .unwrap();
+
+    let ode_def = OdeDefinition {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        func: &rhs_oscillator,
+    };
+    let mut integration =
+        AdaptiveIntegration::new(ode_def, AdaptiveStepSolvers::BulirschStoer, 0.1, tolerances);
+    let mut npy = NpyWriter::new(Cursor::new(Vec::new())).unwrap();
+    integration
+        .write_time_series_iter(&t_series, &(), &mut npy)
+        .unwrap();
+    let (shape, values) = parse_npy(&npy.into_inner().into_inner());
+    assert_eq!(shape, "(21, 3)");
+    for ((t, y), row) in t_series.iter().zip(reference.iter()).zip(values.chunks(3)) {
+        assert_eq!(row, [*t, y[0], y[1]]);
+        assert_abs_diff_eq!(row[1], t.cos(), epsilon = 1e-8);
+    }
+}
+
+#[test]
+fn stream_fixed_step_integration() {
+    let t_series: Vec<f64> = (0..=20).map(|i| 0.1 * i as f64).collect();
+    let reference = solve_ode_time_series_minimal_step_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_oscillator,
+        &(),
+        FixedStepSolvers::Rk4,
+        &0.03,
+    )
+    .unwrap();
+    let mut npy = NpyWriter::new(Cursor::new(Vec::new())).unwrap();
+    write_ode_time_series_minimal_step_iter(
+        &[1.0, 0.0],
+        &t_series,
+        &rhs_oscillator,
+        &(),
+        FixedStepSolvers::Rk4,
+        &0.03,
+        &mut npy,
+    )
+    .unwrap();
+    let (shape, values) = parse_npy(&npy.into_inner().into_inner());
+    assert_eq!(shape, "(21, 3)");
+    for ((t, y), row) in t_series.iter().zip(reference.iter()).zip(values.chunks(3)) {
+        assert_eq!(row, [*t, y[0], y[1]]);
+    }
+
+    // Single steps of types which can be added, backward in time
+    let t_backward: Vec<f64> = t_series.iter().rev().copied().collect();
+    let y0 = nalgebra::Vector2::new(1.0, 0.0);
+    let rhs_vec = |y: &nalgebra::Vector2<f64>,
+                   dy: &mut nalgebra::Vector2<f64>,
+                   _t: &f64,
+                   _p: &()|
+     -> Result<(), CalcError> {
+        *dy = nalgebra::Vector2::new(y[1], -y[0]);
+        Ok(())
+    };
+    let reference = solve_ode_time_series_single_step_add(
+        &y0,
+        &t_backward,
+        &rhs_vec,
+        &(),
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+    let mut csv = CsvWriter::new(Vec::new()).without_header();
+    write_ode_time_series_single_step_add(
+        &y0,
+        &t_backward,
+        &rhs_vec,
+        &(),
+        FixedStepSolvers::Euler,
+        &mut csv,
+    )
+    .unwrap();
+    assert_eq!(csv.n_records(), 21);
+    let expected: String = t_backward
+        .iter()
+        .zip(reference.iter())
+        .map(|(t, y)| format!("{t},{},{}\n", y[0], y[1]))
+        .collect();
+    assert_eq!(String::from_utf8(csv.into_inner()).unwrap(), expected);
+
+    // Errors of the driver are returned before anything is written
+    let mut csv = CsvWriter::new(Vec::new());
+    assert!(write_ode_time_series_single_step_iter(
+        &[1.0, 0.0],
+        &vec![0.0, 1.0, 0.5],
+        &rhs_oscillator,
+        &(),
+        FixedStepSolvers::Euler,
+        &mut csv,
+    )
+    .is_err());
+    assert_eq!(csv.n_records(), 0);
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "plot")]
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use std::path::PathBuf;
+
+/// Lorenz system with parameters \\((\sigma, \rho, \beta)\\)
+fn rhs_lorenz(
+    y: &[f64; 3],
+    dy: &mut [f64; 3],
+    _t: &f64,
+    p: &(f64, f64, f64),
+) -> Result<(), CalcError> {
+    dy[0] = p.0 * (y[1] - y[0]);
+    dy[1] = y[0] * (p.1 - y[2]) - y[1];
+    dy[2] = y[0] * y[1] - p.2 * y[2];
+    Ok(())
+}
+
+/// Time points and solution of the Lorenz system
+fn lorenz_solution() -> (Vec<f64>, Vec<[f64; 3]>) {
+    let t_series: Vec<f64> = (0..=1000).map(|i| 0.01 * i as f64).collect();
+    let tolerances = Tolerances {
+        rtol: 1e-6,
+        atol: 1e-6,
+    };
+    let solution = solve_ode_time_series_adaptive_step_iter(
+        &[1.0, 1.0, 1.0],
+        &t_series,
+        &rhs_lorenz,
+        &(10.0, 28.0, 8.0 / 3.0),
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.01,
+        &tolerances,
+    )
+    .unwrap();
+    (t_series, solution)
+}
+
+/// Path of an output file in a temporary directory
+fn output_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("ode_integrate_plots");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+#[test]
+fn svg_plots() {
+    let (t_series, solution) = lorenz_solution();
+    let mut settings = PlotSettings::new("Lorenz system");
+    settings.component_names = vec!["x".into(), "y".into(), "z".into()];
+
+    let path = output_path("time_series.svg");
+    plot_time_series(&path, &t_series, &solution, &settings).unwrap();
+    let svg = std::fs::read_to_string(&path).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("Lorenz system"));
+    assert!(svg.matches("<polyline").count() >= 3);
+
+    let path = output_path("phase_portrait.svg");
+    plot_phase_portrait(&path, &solution, (0, 2), &settings).unwrap();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("<polyline"));
+
+    let path = output_path("trajectory.svg");
+    plot_trajectory_3d(&path, &solution, (0, 1, 2), &settings).unwrap();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("<polyline"));
+}
+
+#[test]
+fn png_plots() {
+    let (t_series, solution) = lorenz_solution();
+    let mut settings = PlotSettings::new("Lorenz system");
+    settings.width = 400;
+    settings.height = 300;
+
+    let path = output_path("time_series.png");
+    plot_time_series(&path, &t_series, &solution, &settings).unwrap();
+    let png = std::fs::read(&path).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[16..24], [0, 0, 1, 144, 0, 0, 1, 44]);
+
+    let path = output_path("trajectory.png");
+    plot_trajectory_3d(&path, &solution, (0, 1, 2), &settings).unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() > 0);
+}
+
+#[test]
+fn invalid_input() {
+    let (t_series, solution) = lorenz_solution();
+    let settings = PlotSettings::new("Invalid");
+    assert!(plot_time_series(output_path("a.jpg"), &t_series, &solution, &settings).is_err());
+    assert!(plot_time_series(
+        output_path("a.svg"),
+        &t_series[1..].to_vec(),
+        &solution,
+        &settings
+    )
+    .is_err());
+    assert!(plot_phase_portrait(output_path("b.svg"), &solution, (0, 3), &settings).is_err());
+    let empty: Vec<[f64; 3]> = Vec::new();
+    assert!(plot_trajectory_3d(output_path("c.svg"), &empty, (0, 1, 2), &settings).is_err());
+}
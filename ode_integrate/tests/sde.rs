@@ -0,0 +1,277 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Parameters of the geometric Brownian motion
+const MU: f64 = 1.2;
+const SIGMA: f64 = 0.6;
+
+/// Drift \\(f(y, t, p) = p_0 y\\) of the geometric Brownian motion
+fn drift_gbm(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &(f64, f64)) -> Result<(), CalcError> {
+    dy[0] = p.0 * y[0];
+    Ok(())
+}
+
+/// Diffusion \\(g(y, t, p) = p_1 y\\) of the geometric Brownian motion
+fn diffusion_gbm(
+    y: &[f64; 1],
+    dy: &mut [f64; 1],
+    _t: &f64,
+    p: &(f64, f64),
+) -> Result<(), CalcError> {
+    dy[0] = p.1 * y[0];
+    Ok(())
+}
+
+/// Same diffusion but formulated as general noise with a single Wiener process
+fn diffusion_gbm_general(
+    y: &[f64; 1],
+    g: &mut [[f64; 1]],
+    _t: &f64,
+    p: &(f64, f64),
+) -> Result<(), CalcError> {
+    g[0][0] = p.1 * y[0];
+    Ok(())
+}
+
+fn gbm_definition<'a>() -> SdeDefinition<'a, [f64; 1], f64, (f64, f64), CalcError> {
+    SdeDefinition {
+        y0: [1.0],
+        t0: 0.0,
+        drift: &drift_gbm,
+        diffusion: Diffusion::Diagonal(&diffusion_gbm),
+    }
+}
+
+fn standard_normal(rng: &mut ChaCha8Rng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Brownian path on a fine grid given by the increments and the iterated integrals I_(1,0)
+fn fine_path(rng: &mut ChaCha8Rng, n: usize, h: f64) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|_| {
+            let dw = h.sqrt() * standard_normal(rng);
+            let dz = h.sqrt() * standard_normal(rng);
+            (dw, 0.5 * h * (dw + dz / 3f64.sqrt()))
+        })
+        .collect()
+}
+
+/// Combine `ratio` fine steps of size `h` to one coarse step
+fn coarsen(path: &[(f64, f64)], ratio: usize, h: f64) -> Vec<BrownianIncrements<f64>> {
+    path.chunks(ratio)
+        .map(|chunk| {
+            let mut dw = 0.0;
+            let mut i10 = 0.0;
+            for (dw_k, i10_k) in chunk {
+                i10 += i10_k + h * dw;
+                dw += dw_k;
+            }
+            let dt = h * ratio as f64;
+            let dz = 3f64.sqrt() * (2.0 * i10 / dt - dw);
+            BrownianIncrements {
+                dw: vec![dw],
+                dz: vec![dz],
+            }
+        })
+        .collect()
+}
+
+/// Mean absolute error at the final time for different step sizes
+fn strong_errors(solver: fn() -> SdeSolvers, levels: &[usize]) -> Vec<f64> {
+    let n_fine = 1024;
+    let t_end = 1.0;
+    let h = t_end / n_fine as f64;
+    let n_paths = 100;
+    let p = (MU, SIGMA);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut errors = vec![0.0; levels.len()];
+    for _ in 0..n_paths {
+        let path = fine_path(&mut rng, n_fine, h);
+        let w_end: f64 = path.iter().map(|(dw, _)| dw).sum();
+        let exact = ((MU - 0.5 * SIGMA * SIGMA) * t_end + SIGMA * w_end).exp();
+        for (error, n_coarse) in errors.iter_mut().zip(levels) {
+            let ratio = n_fine / n_coarse;
+            let dt = h * ratio as f64;
+            let mut stepper =
+                get_sde_stepper(solver(), gbm_definition(), ChaCha8Rng::seed_from_u64(0)).unwrap();
+            let mut y = [1.0];
+            let mut t = 0.0;
+            for increments in coarsen(&path, ratio, h) {
+                stepper
+                    .do_step_increments_iter(&mut y, &t, &dt, &increments, &p)
+                    .unwrap();
+                t += dt;
+            }
+            *error += (y[0] - exact).abs() / n_paths as f64;
+        }
+    }
+    errors
+}
+
+/// Estimate the order of convergence from errors at step sizes which are halved each time
+fn estimated_order(errors: &[f64]) -> f64 {
+    let n = errors.len() - 1;
+    (errors[0] / errors[n]).log2() / n as f64
+}
+
+#[test]
+fn strong_order_euler_maruyama() {
+    let errors = strong_errors(|| SdeSolvers::EulerMaruyama, &[32, 64, 128, 256]);
+    let order = estimated_order(&errors);
+    assert!(order > 0.35 && order < 0.75, "order {order}");
+}
+
+#[test]
+fn strong_order_milstein() {
+    let errors = strong_errors(|| SdeSolvers::Milstein, &[32, 64, 128, 256]);
+    let order = estimated_order(&errors);
+    assert!(order > 0.8 && order < 1.25, "order {order}");
+}
+
+#[test]
+fn strong_order_sri() {
+    let errors = strong_errors(|| SdeSolvers::Sri, &[16, 32, 64, 128]);
+    let order = estimated_order(&errors);
+    assert!(order > 1.3 && order < 1.8, "order {order}");
+}
+
+#[test]
+fn seeded_runs_are_reproducible() {
+    let t_series: Vec<f64> = (0..21).map(|i| i as f64 * 0.05).collect();
+    let p = (MU, SIGMA);
+    let solve = |seed: u64| {
+        solve_sde_time_series_iter(
+            gbm_definition(),
+            &t_series,
+            &p,
+            SdeSolvers::Milstein,
+            &0.01,
+            ChaCha8Rng::seed_from_u64(seed),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(solve(7), solve(7));
+    assert_ne!(solve(7), solve(8));
+}
+
+#[test]
+fn scalar_general_noise_equals_diagonal() {
+    let t_series = vec![0.0, 0.5, 1.0];
+    let p = (MU, SIGMA);
+    let general = SdeDefinition {
+        diffusion: Diffusion::General {
+            n_noise: 1,
+            func: &diffusion_gbm_general,
+        },
+        ..gbm_definition()
+    };
+    for solver in [
+        || SdeSolvers::EulerMaruyama,
+        || SdeSolvers::Milstein,
+        || SdeSolvers::Sri,
+    ] {
+        let y_diag = solve_sde_time_series_iter(
+            gbm_definition(),
+            &t_series,
+            &p,
+            solver(),
+            &0.01,
+            ChaCha8Rng::seed_from_u64(3),
+        )
+        .unwrap();
+        let y_general = solve_sde_time_series_iter(
+            general.clone(),
+            &t_series,
+            &p,
+            solver(),
+            &0.01,
+            ChaCha8Rng::seed_from_u64(3),
+        )
+        .unwrap();
+        assert_eq!(y_diag, y_general);
+    }
+}
+
+/// Two independent Wiener processes acting on a two-dimensional system with commutative noise
+fn diffusion_commutative(
+    y: &Vec<f64>,
+    g: &mut [Vec<f64>],
+    _t: &f64,
+    p: &(f64, f64),
+) -> Result<(), CalcError> {
+    g[0][0] = p.1 * y[0];
+    g[0][1] = 0.0;
+    g[1][0] = 0.5 * p.1 * y[0];
+    g[1][1] = p.1;
+    Ok(())
+}
+
+fn drift_linear(
+    y: &Vec<f64>,
+    dy: &mut Vec<f64>,
+    _t: &f64,
+    p: &(f64, f64),
+) -> Result<(), CalcError> {
+    dy[0] = p.0 * y[0];
+    dy[1] = -y[1];
+    Ok(())
+}
+
+#[test]
+fn general_noise() {
+    let sde_def = SdeDefinition {
+        y0: vec![1.0, 0.0],
+        t0: 0.0,
+        drift: &drift_linear,
+        diffusion: Diffusion::General {
+            n_noise: 2,
+            func: &diffusion_commutative,
+        },
+    };
+    let t_series = vec![0.0, 0.5, 1.0];
+    let p = (MU, SIGMA);
+
+    // Milstein and Euler-Maruyama should agree closely for small steps on the same path
+    let y_em = solve_sde_time_series_iter(
+        sde_def.clone(),
+        &t_series,
+        &p,
+        SdeSolvers::EulerMaruyama,
+        &1e-4,
+        ChaCha8Rng::seed_from_u64(11),
+    )
+    .unwrap();
+    let y_mil = solve_sde_time_series_iter(
+        sde_def.clone(),
+        &t_series,
+        &p,
+        SdeSolvers::Milstein,
+        &1e-4,
+        ChaCha8Rng::seed_from_u64(11),
+    )
+    .unwrap();
+    for (a, b) in y_em.iter().zip(y_mil.iter()) {
+        assert!((a[0] - b[0]).abs() < 0.1);
+        assert!((a[1] - b[1]).abs() < 0.1);
+    }
+
+    // The SRI method does not support more than one Wiener process
+    assert!(solve_sde_time_series_iter(
+        sde_def,
+        &t_series,
+        &p,
+        SdeSolvers::Sri,
+        &0.01,
+        ChaCha8Rng::seed_from_u64(11),
+    )
+    .is_err());
+}
@@ -0,0 +1,172 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Exponential decay \\(y' = -p_0 y\\)
+fn rhs_decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &[f64; 1]) -> Result<(), CalcError> {
+    dy[0] = -p[0] * y[0];
+    Ok(())
+}
+
+#[test]
+fn exponential_decay() {
+    let ode_def = OdeDefinition {
+        y0: [2.0],
+        t0: 0.0,
+        func: &rhs_decay,
+    };
+    let t_series: Vec<f64> = (0..11).map(|i| i as f64 * 0.2).collect();
+    let p = [0.7];
+    for (method, dt, epsilon) in [
+        (SensitivityMethod::Simultaneous, 0.05, 1e-6),
+        (
+            SensitivityMethod::Staggered(ImplicitSolvers::Sdirk2),
+            0.002,
+            1e-5,
+        ),
+    ] {
+        let res =
+            solve_sensitivity_time_series_iter(ode_def.clone().into(), &t_series, &p, method, &dt)
+                .unwrap();
+        for (t, (y, s)) in t_series.iter().zip(res.iter()) {
+            let exact = 2.0 * (-p[0] * t).exp();
+            assert_abs_diff_eq!(y[0], exact, epsilon = epsilon);
+            assert_abs_diff_eq!(s[0], -t * exact, epsilon = epsilon);
+        }
+    }
+}
+
+/// Logistic growth \\(y' = p_0 y (1 - y / p_1)\\) of two independent populations
+fn rhs_logistic(y: &Vec<f64>, dy: &mut Vec<f64>, _t: &f64, p: &Vec<f64>) -> Result<(), CalcError> {
+    for (yi, dyi) in y.iter().zip(dy.iter_mut()) {
+        *dyi = p[0] * yi * (1.0 - yi / p[1]);
+    }
+    Ok(())
+}
+
+fn jacobian_y_logistic(
+    y: &Vec<f64>,
+    _t: &f64,
+    p: &Vec<f64>,
+    jac: &mut [f64],
+) -> Result<(), CalcError> {
+    let n = y.len();
+    for (i, yi) in y.iter().enumerate() {
+        jac[i * n + i] = p[0] * (1.0 - 2.0 * yi / p[1]);
+    }
+    Ok(())
+}
+
+fn jacobian_p_logistic(
+    y: &Vec<f64>,
+    _t: &f64,
+    p: &Vec<f64>,
+    jac: &mut [f64],
+) -> Result<(), CalcError> {
+    for (i, yi) in y.iter().enumerate() {
+        jac[i * 2] = yi * (1.0 - yi / p[1]);
+        jac[i * 2 + 1] = p[0] * yi * yi / (p[1] * p[1]);
+    }
+    Ok(())
+}
+
+fn logistic_definition<'a>() -> SensitivityDefinition<'a, Vec<f64>, f64, Vec<f64>, CalcError> {
+    SensitivityDefinition {
+        y0: vec![0.1, 0.5],
+        t0: 0.0,
+        func: &rhs_logistic,
+        jacobian_y: Some(&jacobian_y_logistic),
+        jacobian_p: Some(&jacobian_p_logistic),
+    }
+}
+
+#[test]
+fn supplied_and_finite_difference_jacobians_agree() {
+    let t_series = vec![0.0, 1.0, 2.0, 4.0];
+    let p = vec![1.5, 2.0];
+    let finite_differences = SensitivityDefinition {
+        jacobian_y: None,
+        jacobian_p: None,
+        ..logistic_definition()
+    };
+    for method in [
+        || SensitivityMethod::Simultaneous,
+        || SensitivityMethod::Staggered(ImplicitSolvers::ImplicitEuler),
+    ] {
+        let supplied = solve_sensitivity_time_series_iter(
+            logistic_definition(),
+            &t_series,
+            &p,
+            method(),
+            &0.01,
+        )
+        .unwrap();
+        let approximated = solve_sensitivity_time_series_iter(
+            finite_differences.clone(),
+            &t_series,
+            &p,
+            method(),
+            &0.01,
+        )
+        .unwrap();
+        for ((_, s1), (_, s2)) in supplied.iter().zip(approximated.iter()) {
+            assert_eq!(s1.len(), 4);
+            for (a, b) in s1.iter().zip(s2.iter()) {
+                assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+            }
+        }
+    }
+}
+
+#[test]
+fn staggered_is_derivative_of_discrete_solution() {
+    // The staggered sensitivities are the derivative of the numerical solution itself
+    let t_series = vec![0.0, 3.0];
+    let p = vec![1.5, 2.0];
+    let dt = 0.1;
+    let res = solve_sensitivity_time_series_iter(
+        logistic_definition(),
+        &t_series,
+        &p,
+        SensitivityMethod::Staggered(ImplicitSolvers::Sdirk2),
+        &dt,
+    )
+    .unwrap();
+    let s = &res[1].1;
+
+    let solve = |p: &Vec<f64>| {
+        let dae_def = DaeDefinition {
+            y0: vec![0.1, 0.5],
+            t0: 0.0,
+            func: &rhs_logistic,
+            mass: MassMatrix::Identity,
+        };
+        solve_dae_time_series_iter(dae_def, &t_series, p, ImplicitSolvers::Sdirk2, &dt).unwrap()[1]
+            .clone()
+    };
+    let delta = 1e-6;
+    for k in 0..2 {
+        let mut p_plus = p.clone();
+        let mut p_minus = p.clone();
+        p_plus[k] += delta;
+        p_minus[k] -= delta;
+        let (y_plus, y_minus) = (solve(&p_plus), solve(&p_minus));
+        for i in 0..2 {
+            let central = (y_plus[i] - y_minus[i]) / (2.0 * delta);
+            assert_abs_diff_eq!(s[i * 2 + k], central, epsilon = 1e-7);
+        }
+    }
+}
+
+#[test]
+fn wrong_sensitivity_size() {
+    let mut stepper =
+        ForwardSensitivity::new(logistic_definition(), SensitivityMethod::Simultaneous).unwrap();
+    let mut y = vec![0.1, 0.5];
+    let mut s = vec![0.0; 3];
+    assert!(stepper
+        .do_step_iter(&mut y, &mut s, &0.0, &0.1, &vec![1.5, 2.0])
+        .is_err());
+}
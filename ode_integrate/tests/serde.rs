@@ -0,0 +1,100 @@
+#![cfg(feature = "serde")]
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Harmonic oscillator \\(y_0' = y_1, y_1' = -y_0\\)
+fn rhs_oscillator(y: &[f64; 2], dy: &mut [f64; 2], _t: &f64, _p: &()) -> Result<(), CalcError> {
+    dy[0] = y[1];
+    dy[1] = -y[0];
+    Ok(())
+}
+
+/// Serialize to JSON and deserialize again
+fn round_trip<T>(value: &T) -> T
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn configurations_and_errors() {
+    assert!(matches!(
+        round_trip(&FixedStepSolvers::Rk4),
+        FixedStepSolvers::Rk4
+    ));
+    assert!(matches!(
+        round_trip(&SensitivityMethod::Staggered(ImplicitSolvers::Sdirk2)),
+        SensitivityMethod::Staggered(ImplicitSolvers::Sdirk2)
+    ));
+
+    let tolerances = round_trip(&Tolerances {
+        rtol: 1e-6,
+        atol: 1e-9,
+    });
+    assert_eq!(tolerances.rtol, 1e-6);
+    assert_eq!(tolerances.atol, 1e-9);
+
+    let mut settings = FitSettings::new(0.01);
+    settings.max_iterations = 7;
+    let settings = round_trip(&settings);
+    assert_eq!(settings.max_iterations, 7);
+    assert_eq!(settings.dt, 0.01);
+
+    let error = SolvingError::from("Time steps need to be increasing");
+    assert_eq!(format!("{}", round_trip(&error)), format!("{}", error));
+}
+
+#[test]
+fn solutions() {
+    let ensemble = solve_ensemble_single_step_iter(
+        vec![([1.0, 0.0], ()), ([0.0, 1.0], ())],
+        &vec![0.0, 0.1, 0.2],
+        &rhs_oscillator,
+        FixedStepSolvers::Euler,
+    );
+    let stored = round_trip(&ensemble);
+    assert_eq!(stored.results.len(), 2);
+    for (a, b) in stored.results.iter().zip(ensemble.results.iter()) {
+        assert_eq!(a.as_ref().unwrap(), b.as_ref().unwrap());
+    }
+}
+
+#[test]
+fn stepper_states() {
+    let ode_def = OdeDefinition {
+        y0: [1.0, 0.0],
+        t0: 0.0,
+        func: &rhs_oscillator,
+    };
+    let mut rk4 = Rk4::from(ode_def.clone());
+    let mut y = [1.0, 0.0];
+    rk4.do_step_iter(&mut y, &0.0, &0.1, &()).unwrap();
+    let state = round_trip(&rk4.state());
+    assert_eq!(state.dy, rk4.state().dy);
+    assert_eq!(state.k4, rk4.state().k4);
+
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let mut bs = BulirschStoer::new(ode_def.clone(), tolerances.clone());
+    let mut y = [1.0, 0.0];
+    bs.do_step_iter(&mut y, &0.0, &0.2, &()).unwrap();
+
+    // Continuing with a restored stepper gives identical results
+    let mut restored = BulirschStoer::new(ode_def, tolerances).with_state(round_trip(&bs.state()));
+    let dt = bs.suggested_dt().unwrap();
+    assert_eq!(restored.suggested_dt(), Some(dt));
+    let mut y1 = y;
+    let mut y2 = y;
+    bs.do_step_iter(&mut y1, &0.2, &dt, &()).unwrap();
+    restored.do_step_iter(&mut y2, &0.2, &dt, &()).unwrap();
+    assert_eq!(y1, y2);
+    assert_eq!(bs.suggested_dt(), restored.suggested_dt());
+    assert_abs_diff_eq!(y1[0], (0.2 + dt).cos(), epsilon = 1e-9);
+}
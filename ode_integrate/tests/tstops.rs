@@ -0,0 +1,240 @@
+#![allow(clippy::ptr_arg)]
+
+use ode_integrate::*;
+
+use approx::assert_abs_diff_eq;
+
+/// Inflow which is switched off at \\(t = p\\)
+fn rhs_valve(_y: &[f64; 1], dy: &mut [f64; 1], t: &f64, p: &f64) -> Result<(), CalcError> {
+    dy[0] = if *t < *p { 1.0 } else { 0.0 };
+    Ok(())
+}
+
+/// Elimination \\(y' = -p y\\)
+fn rhs_decay(y: &[f64; 1], dy: &mut [f64; 1], _t: &f64, p: &f64) -> Result<(), CalcError> {
+    dy[0] = -p * y[0];
+    Ok(())
+}
+
+/// Elimination \\(y' = -p y\\) as a vector which can be added
+fn rhs_decay_vec(
+    y: &nalgebra::Vector2<f64>,
+    dy: &mut nalgebra::Vector2<f64>,
+    _t: &f64,
+    p: &f64,
+) -> Result<(), CalcError> {
+    *dy = -p * y;
+    Ok(())
+}
+
+/// Add a dose of size one to the first component
+fn dose(_t: &f64, y: &mut [f64; 1], _p: &f64) {
+    y[0] += 1.0;
+}
+
+#[test]
+fn fixed_steps_land_on_stops() {
+    let t_series = vec![0.0, 2.0];
+    let tstops = TStops {
+        times: vec![0.75],
+        jump: None,
+    };
+
+    // A single step crosses the switch
+    let y = solve_ode_time_series_single_step_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.75,
+        FixedStepSolvers::Euler,
+    )
+    .unwrap();
+    assert_eq!(y[1][0], 2.0);
+
+    // Piecewise constant inflow is integrated exactly when steps end at the switch
+    let y = solve_ode_time_series_single_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.75,
+        FixedStepSolvers::Euler,
+        &tstops,
+    )
+    .unwrap();
+    assert_eq!(y[1][0], 0.75);
+
+    let y = solve_ode_time_series_minimal_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.75,
+        FixedStepSolvers::Rk4,
+        &0.25,
+        &tstops,
+    )
+    .unwrap();
+    assert_eq!(y[1][0], 0.75);
+}
+
+#[test]
+fn jumps_at_stops() {
+    // Stops between, at and after the time points
+    let t_series = vec![0.0, 1.0, 2.0];
+    let tstops = TStops {
+        times: vec![0.5, 1.0, 3.0],
+        jump: Some(&dose),
+    };
+    let y = solve_ode_time_series_minimal_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &1.0,
+        FixedStepSolvers::Rk4,
+        &0.01,
+        &tstops,
+    )
+    .unwrap();
+    let y_1 = (-1.0_f64).exp() + (-0.5_f64).exp();
+    let y_2 = (y_1 + 1.0) * (-1.0_f64).exp();
+    // Stored solutions at stop times contain the jump
+    assert_abs_diff_eq!(y[1][0], y_1 + 1.0, epsilon = 1e-2);
+    assert_abs_diff_eq!(y[2][0], y_2, epsilon = 1e-2);
+
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let y_adaptive = solve_ode_time_series_adaptive_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &1.0,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+        &tstops,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y_adaptive[1][0], y_1 + 1.0, epsilon = 1e-8);
+    assert_abs_diff_eq!(y_adaptive[2][0], y_2, epsilon = 1e-8);
+
+    // Stops at or before the initial time are ignored
+    let tstops = TStops {
+        times: vec![-1.0, 0.0],
+        jump: Some(&dose),
+    };
+    let y = solve_ode_time_series_single_step_tstops_iter(
+        &[1.0],
+        &t_series,
+        &rhs_decay,
+        &0.0,
+        FixedStepSolvers::Euler,
+        &tstops,
+    )
+    .unwrap();
+    assert_eq!(y, vec![[1.0]; 3]);
+}
+
+#[test]
+fn adaptive_steps_restart_at_switch() {
+    let t_series = vec![0.0, 1.0, 2.0];
+    let tolerances = Tolerances {
+        rtol: 1e-12,
+        atol: 1e-12,
+    };
+    let tstops = TStops {
+        times: vec![0.3],
+        jump: None,
+    };
+    let y = solve_ode_time_series_adaptive_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.3,
+        AdaptiveStepSolvers::BulirschStoer,
+        &1.0,
+        &tolerances,
+        &tstops,
+    )
+    .unwrap();
+    assert_abs_diff_eq!(y[1][0], 0.3, epsilon = 1e-9);
+    assert_eq!(y[2][0], y[1][0]);
+
+    // Stops need to be sorted
+    let unsorted = TStops {
+        times: vec![0.3, 0.2],
+        jump: None,
+    };
+    assert!(solve_ode_time_series_adaptive_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.3,
+        AdaptiveStepSolvers::BulirschStoer,
+        &1.0,
+        &tolerances,
+        &unsorted,
+    )
+    .is_err());
+    assert!(solve_ode_time_series_minimal_step_tstops_iter(
+        &[0.0],
+        &t_series,
+        &rhs_valve,
+        &0.3,
+        FixedStepSolvers::Euler,
+        &0.1,
+        &unsorted,
+    )
+    .is_err());
+}
+
+#[test]
+fn additive_types() {
+    let t_series = vec![0.0, 1.0, 2.0];
+    let y0 = nalgebra::Vector2::new(1.0, 0.0);
+    let swap = |_t: &f64, y: &mut nalgebra::Vector2<f64>, _p: &f64| y.swap_rows(0, 1);
+    let tstops = TStops {
+        times: vec![1.5],
+        jump: Some(&swap),
+    };
+    let tolerances = Tolerances {
+        rtol: 1e-10,
+        atol: 1e-10,
+    };
+    let fixed = solve_ode_time_series_minimal_step_tstops_add(
+        &y0,
+        &t_series,
+        &rhs_decay_vec,
+        &1.0,
+        FixedStepSolvers::Rk4,
+        &0.01,
+        &tstops,
+    )
+    .unwrap();
+    let adaptive = solve_ode_time_series_adaptive_step_tstops_add(
+        &y0,
+        &t_series,
+        &rhs_decay_vec,
+        &1.0,
+        AdaptiveStepSolvers::BulirschStoer,
+        &0.1,
+        &tolerances,
+        &tstops,
+    )
+    .unwrap();
+    let single = solve_ode_time_series_single_step_tstops_add(
+        &y0,
+        &t_series,
+        &rhs_decay_vec,
+        &1.0,
+        FixedStepSolvers::Rk4,
+        &tstops,
+    )
+    .unwrap();
+    for y in [fixed, adaptive] {
+        assert_eq!(y[2][0], 0.0);
+        assert_abs_diff_eq!(y[2][1], (-2.0_f64).exp(), epsilon = 1e-8);
+    }
+    assert_eq!(single[2][0], 0.0);
+    assert_abs_diff_eq!(single[2][1], (-2.0_f64).exp(), epsilon = 1e-2);
+}